@@ -0,0 +1,144 @@
+//! Measures how fast commands are turned into output.
+//!
+//! Run with `cargo bench --bench commands`. The ANSI benchmarks write into memory, the WinAPI
+//! benchmark (Windows only) needs to be run from a console.
+
+use std::io::Write;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use crossterm::{
+    cursor::MoveTo,
+    queue,
+    style::{
+        style, Attribute, Color, Print, PrintStyledContent, SetAttribute, SetBackgroundColor,
+        SetForegroundColor, Stylize,
+    },
+    terminal::{Clear, ClearType},
+    Result,
+};
+
+const FRAME_SIZES: [(u16, u16); 2] = [(80, 24), (200, 60)];
+
+fn color(column: u16, row: u16) -> Color {
+    Color::Rgb {
+        r: column as u8,
+        g: row as u8,
+        b: (column ^ row) as u8,
+    }
+}
+
+/// Queues a full frame where every cell has its own foreground color.
+fn queue_frame(w: &mut impl Write, columns: u16, rows: u16) -> Result<()> {
+    queue!(w, Clear(ClearType::All))?;
+    for row in 0..rows {
+        queue!(w, MoveTo(0, row))?;
+        for column in 0..columns {
+            queue!(w, SetForegroundColor(color(column, row)), Print('x'))?;
+        }
+    }
+    Ok(())
+}
+
+fn commands(c: &mut Criterion) {
+    let mut group = c.benchmark_group("commands");
+    let mut buffer = Vec::with_capacity(1024);
+
+    group.bench_function("move_to", |b| {
+        b.iter(|| {
+            buffer.clear();
+            queue!(buffer, MoveTo(black_box(120), black_box(40))).unwrap();
+        })
+    });
+
+    group.bench_function("set_colors", |b| {
+        b.iter(|| {
+            buffer.clear();
+            queue!(
+                buffer,
+                SetForegroundColor(black_box(Color::Rgb {
+                    r: 255,
+                    g: 128,
+                    b: 0
+                })),
+                SetBackgroundColor(black_box(Color::AnsiValue(42))),
+            )
+            .unwrap();
+        })
+    });
+
+    group.bench_function("set_attribute", |b| {
+        b.iter(|| {
+            buffer.clear();
+            queue!(buffer, SetAttribute(black_box(Attribute::Bold))).unwrap();
+        })
+    });
+
+    group.bench_function("print_styled_content", |b| {
+        b.iter(|| {
+            buffer.clear();
+            queue!(
+                buffer,
+                PrintStyledContent(style(black_box("crossterm")).red().on_blue().bold())
+            )
+            .unwrap();
+        })
+    });
+
+    group.finish();
+}
+
+fn ansi_frame(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ansi_frame");
+
+    for &(columns, rows) in FRAME_SIZES.iter() {
+        let mut buffer = Vec::new();
+
+        group.throughput(Throughput::Elements(columns as u64 * rows as u64));
+        group.bench_function(
+            BenchmarkId::from_parameter(format!("{}x{}", columns, rows)),
+            |b| {
+                b.iter(|| {
+                    buffer.clear();
+                    queue_frame(&mut buffer, columns, rows).unwrap();
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+#[cfg(windows)]
+fn winapi_frame(c: &mut Criterion) {
+    use crossterm::Command;
+
+    let mut group = c.benchmark_group("winapi_frame");
+
+    for &(columns, rows) in FRAME_SIZES.iter() {
+        group.throughput(Throughput::Elements(columns as u64 * rows as u64));
+        group.bench_function(
+            BenchmarkId::from_parameter(format!("{}x{}", columns, rows)),
+            |b| {
+                b.iter(|| {
+                    for row in 0..rows {
+                        MoveTo(0, row).execute_winapi().unwrap();
+                        for column in 0..columns {
+                            SetForegroundColor(color(column, row))
+                                .execute_winapi()
+                                .unwrap();
+                        }
+                    }
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+#[cfg(not(windows))]
+criterion_group!(benches, commands, ansi_frame);
+#[cfg(windows)]
+criterion_group!(benches, commands, ansi_frame, winapi_frame);
+criterion_main!(benches);
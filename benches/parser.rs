@@ -0,0 +1,63 @@
+//! Measures how fast the bytes read from the TTY are turned into events.
+//!
+//! Run with `cargo bench --bench parser`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+use crossterm::event::{Event, EventParser};
+
+/// Plain characters mixed with arrow and function keys, like a user typing fast.
+fn keyboard_burst() -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for _ in 0..256 {
+        bytes.extend_from_slice(b"hello world\x1b[A\x1b[B\x1bOP\x1b[15~\x7f\r");
+    }
+    bytes
+}
+
+/// SGR mouse moves over the whole screen, like a mouse with any motion tracking enabled.
+fn mouse_storm() -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for row in 1..=50 {
+        for column in 1..=80 {
+            bytes.extend_from_slice(format!("\x1b[<35;{};{}M", column, row).as_bytes());
+        }
+    }
+    bytes
+}
+
+/// A large block of pasted text, including multi-byte UTF-8 characters.
+fn paste() -> Vec<u8> {
+    "Lorem ipsum dolor sit amet, ünïcödé テキスト 🦀\n"
+        .repeat(128)
+        .into_bytes()
+}
+
+/// Parses all the bytes at once, as if they were read from the TTY in a single read.
+fn parse_events(bytes: &[u8]) -> Vec<Event> {
+    let mut parser = EventParser::new();
+    parser.advance(bytes, false);
+    parser.collect()
+}
+
+fn parser(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_events");
+
+    for (name, bytes) in [
+        ("keyboard_burst", keyboard_burst()),
+        ("mouse_storm", mouse_storm()),
+        ("paste", paste()),
+    ]
+    .iter()
+    {
+        group.throughput(Throughput::Bytes(bytes.len() as u64));
+        group.bench_with_input(*name, bytes, |b, bytes| {
+            b.iter(|| parse_events(black_box(bytes)))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, parser);
+criterion_main!(benches);
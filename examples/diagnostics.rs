@@ -0,0 +1,183 @@
+//! Checks which capabilities of the terminal work with crossterm and prints a summary which can
+//! be pasted into an issue.
+//!
+//! cargo run --example diagnostics
+
+use std::io::{stdout, Write};
+
+use crossterm::{
+    debug,
+    event::{read, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    execute, queue,
+    style::{
+        available_color_count, Attribute, Color, Print, ResetColor, SetAttribute,
+        SetBackgroundColor,
+    },
+    terminal::{disable_raw_mode, enable_raw_mode},
+    Result,
+};
+
+const HELP: &str = r#"Events
+ - Press some keys (arrows, function keys, with modifiers...), click, scroll and resize
+ - Check that the events match what you did
+ - Use Esc to finish
+"#;
+
+/// The private modes worth knowing about: mouse reporting, alternate screen, bracketed paste and
+/// synchronized output.
+#[cfg(unix)]
+const MODES: &[(u16, &str)] = &[
+    (1000, "mouse_normal"),
+    (1002, "mouse_button_motion"),
+    (1003, "mouse_any_motion"),
+    (1006, "mouse_sgr"),
+    (1015, "mouse_urxvt"),
+    (1049, "alternate_screen"),
+    (2004, "bracketed_paste"),
+    (2026, "synchronized_output"),
+];
+
+/// The lines of the summary, `key: value`.
+type Summary = Vec<(String, String)>;
+
+fn add(summary: &mut Summary, key: &str, value: impl std::fmt::Debug) {
+    summary.push((key.to_string(), format!("{:?}", value)));
+}
+
+#[cfg(unix)]
+fn query_capabilities(summary: &mut Summary) {
+    use crossterm::terminal::{query_device_attributes, query_mode, query_version};
+
+    add(summary, "version", query_version());
+    add(summary, "device_attributes", query_device_attributes());
+    for (mode, name) in MODES {
+        add(summary, &format!("mode_{}", name), query_mode(*mode));
+    }
+}
+
+#[cfg(windows)]
+fn query_capabilities(summary: &mut Summary) {
+    add(
+        summary,
+        "ansi_support",
+        crossterm::ansi_support::supports_ansi(),
+    );
+}
+
+fn print_colors(summary: &mut Summary) -> Result<()> {
+    let mut stdout = stdout();
+    add(summary, "color_count", available_color_count());
+
+    println!("Colors, the rows must show 16 colors, 216 colors and a smooth gradient");
+    for color in 0..16 {
+        queue!(
+            stdout,
+            SetBackgroundColor(Color::AnsiValue(color)),
+            Print("  ")
+        )?;
+    }
+    queue!(stdout, ResetColor, Print("\n"))?;
+    for color in 16..232 {
+        queue!(
+            stdout,
+            SetBackgroundColor(Color::AnsiValue(color)),
+            Print(" ")
+        )?;
+        if (color - 16) % 72 == 71 {
+            queue!(stdout, ResetColor, Print("\n"))?;
+        }
+    }
+    for column in 0..72u16 {
+        let value = (column * 255 / 71) as u8;
+        queue!(
+            stdout,
+            SetBackgroundColor(Color::Rgb {
+                r: value,
+                g: 128,
+                b: 255 - value
+            }),
+            Print(" ")
+        )?;
+    }
+    queue!(stdout, ResetColor, Print("\n\n"))?;
+
+    println!("Attributes, every word must look like what it says");
+    for attribute in &[
+        Attribute::Bold,
+        Attribute::Dim,
+        Attribute::Italic,
+        Attribute::Underlined,
+        Attribute::SlowBlink,
+        Attribute::Reverse,
+        Attribute::CrossedOut,
+    ] {
+        queue!(
+            stdout,
+            SetAttribute(*attribute),
+            Print(format!("{:?}", attribute)),
+            SetAttribute(Attribute::Reset),
+            Print(" ")
+        )?;
+    }
+    queue!(stdout, Print("\n\n"))?;
+    stdout.flush()
+}
+
+fn read_events(summary: &mut Summary) -> Result<()> {
+    let (mut keys, mut mouse, mut resizes) = (0, 0, 0);
+
+    loop {
+        let event = read()?;
+        println!("{:?}\r", event);
+
+        match event {
+            Event::Key(key) if key.code == KeyCode::Esc => break,
+            Event::Key(_) => keys += 1,
+            Event::Mouse(_) => mouse += 1,
+            Event::Resize(_, _) => resizes += 1,
+            _ => {}
+        }
+    }
+
+    add(summary, "key_events", keys);
+    add(summary, "mouse_events", mouse);
+    add(summary, "resize_events", resizes);
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let state = debug::dump_state();
+    let mut summary = Summary::new();
+
+    println!("{}\n", state);
+
+    enable_raw_mode()?;
+    query_capabilities(&mut summary);
+    disable_raw_mode()?;
+
+    print_colors(&mut summary)?;
+
+    println!("{}", HELP);
+    enable_raw_mode()?;
+    execute!(stdout(), EnableMouseCapture)?;
+    let events = read_events(&mut summary);
+    execute!(stdout(), DisableMouseCapture)?;
+    disable_raw_mode()?;
+    events?;
+
+    // Everything in one place, to paste into an issue
+    println!("\n```");
+    println!("crossterm: {}", env!("CARGO_PKG_VERSION"));
+    println!("os: {}", std::env::consts::OS);
+    for (name, value) in &state.environment {
+        println!("env_{}: {}", name, value);
+    }
+    println!("size: {:?}", state.size);
+    println!("modes: {:?}", state.modes);
+    for (key, value) in &summary {
+        println!("{}: {}", key, value);
+    }
+    println!("```");
+
+    Ok(())
+}
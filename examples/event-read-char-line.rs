@@ -4,7 +4,7 @@
 //! cargo run --example event-read-char-line
 
 use crossterm::{
-    event::{self, Event, KeyCode, KeyEvent},
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
     Result,
 };
 
@@ -12,6 +12,7 @@ pub fn read_char() -> Result<char> {
     loop {
         if let Event::Key(KeyEvent {
             code: KeyCode::Char(c),
+            kind: KeyEventKind::Press,
             ..
         }) = event::read()?
         {
@@ -22,7 +23,11 @@ pub fn read_char() -> Result<char> {
 
 pub fn read_line() -> Result<String> {
     let mut line = String::new();
-    while let Event::Key(KeyEvent { code, .. }) = event::read()? {
+    while let Event::Key(KeyEvent { code, kind, .. }) = event::read()? {
+        if kind == KeyEventKind::Release {
+            continue;
+        }
+
         match code {
             KeyCode::Enter => {
                 break;
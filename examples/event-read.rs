@@ -32,7 +32,7 @@ fn print_events() -> Result<()> {
         }
 
         if let Event::Resize(_, _) = event {
-            let (original_size, new_size) = flush_resize_events(event);
+            let (original_size, new_size) = flush_resize_events(&event);
             println!("Resize from: {:?}, to: {:?}", original_size, new_size);
         }
 
@@ -47,8 +47,8 @@ fn print_events() -> Result<()> {
 // Resize events can occur in batches.
 // With a simple loop they can be flushed.
 // This function will keep the first and last resize event.
-fn flush_resize_events(event: Event) -> ((u16, u16), (u16, u16)) {
-    if let Event::Resize(x, y) = event {
+fn flush_resize_events(event: &Event) -> ((u16, u16), (u16, u16)) {
+    if let Event::Resize(x, y) = *event {
         let mut last_resize = (x, y);
         while let Ok(true) = poll(Duration::from_millis(50)) {
             if let Ok(Event::Resize(x, y)) = read() {
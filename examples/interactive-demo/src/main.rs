@@ -4,7 +4,7 @@ use std::io::{self, Write};
 
 pub use crossterm::{
     cursor,
-    event::{self, Event, KeyCode, KeyEvent},
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
     execute, queue, style,
     terminal::{self, ClearType},
     Command, Result,
@@ -78,6 +78,7 @@ pub fn read_char() -> Result<char> {
     loop {
         if let Ok(Event::Key(KeyEvent {
             code: KeyCode::Char(c),
+            kind: KeyEventKind::Press,
             ..
         })) = event::read()
         {
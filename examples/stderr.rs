@@ -13,7 +13,7 @@ use std::io::{stderr, Write};
 use crossterm::{
     cursor::{Hide, MoveTo, Show},
     event,
-    event::{Event, KeyCode, KeyEvent},
+    event::{Event, KeyCode, KeyEvent, KeyEventKind},
     execute, queue,
     style::Print,
     terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
@@ -56,10 +56,8 @@ where
         Hide                  // hide the cursor
     )?;
 
-    let mut y = 1;
-    for line in TEXT.split('\n') {
+    for (y, line) in (1..).zip(TEXT.split('\n')) {
         queue!(write, MoveTo(1, y), Print(line.to_string()))?;
-        y += 1;
     }
 
     write.flush()?;
@@ -77,6 +75,7 @@ pub fn read_char() -> Result<char> {
     loop {
         if let Event::Key(KeyEvent {
             code: KeyCode::Char(c),
+            kind: KeyEventKind::Press,
             ..
         }) = event::read()?
         {
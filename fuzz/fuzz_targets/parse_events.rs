@@ -0,0 +1,20 @@
+//! Parses the bytes at once, the parser must not panic and must parse the next sequence.
+#![no_main]
+
+use crossterm::event::{Event, EventParser, KeyCode};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut parser = EventParser::new();
+
+    parser.advance(data, false);
+    parser.by_ref().for_each(drop);
+    assert!(parser.pending_len() <= 64 * 1024);
+
+    parser.flush();
+    parser.by_ref().for_each(drop);
+    assert_eq!(parser.pending_len(), 0);
+
+    parser.advance(b"\x1B[A", false);
+    assert_eq!(parser.next(), Some(Event::Key(KeyCode::Up.into())));
+});
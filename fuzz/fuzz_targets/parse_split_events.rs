@@ -0,0 +1,30 @@
+//! Parses the bytes in pieces, like several reads of a terminal. The first byte gives the size of
+//! the pieces, the sequences are truncated and interleaved at any position.
+#![no_main]
+
+use crossterm::event::{Event, EventParser, KeyCode};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let (size, bytes) = match data.split_first() {
+        Some((size, bytes)) => (usize::from(*size % 16) + 1, bytes),
+        None => return,
+    };
+    let mut parser = EventParser::new();
+
+    for (index, chunk) in bytes.chunks(size).enumerate() {
+        // Sometimes the escape timeout expires between two pieces
+        parser.advance(chunk, index % 3 != 0);
+        if index % 5 == 0 {
+            parser.flush();
+        }
+        parser.by_ref().for_each(drop);
+        assert!(parser.pending_len() <= 64 * 1024);
+    }
+
+    parser.flush();
+    parser.by_ref().for_each(drop);
+
+    parser.advance(b"\x1B[A", false);
+    assert_eq!(parser.next(), Some(Event::Key(KeyCode::Up.into())));
+});
@@ -0,0 +1,100 @@
+//! # Accessibility
+//!
+//! The reduced decorations mode helps an application honor the preferences of the users of screen
+//! readers, braille displays or of those who are bothered by colors and motion, with one switch.
+//!
+//! When it's enabled:
+//!
+//! * The commands setting colors and attributes write nothing ([`SetForegroundColor`],
+//!   [`SetBackgroundColor`], [`SetColors`], [`SetAttribute`], [`SetAttributes`]), and
+//!   [`PrintStyledContent`] and `StyledContent` print the content only. The resets
+//!   ([`ResetColor`], `Attribute::Reset`) are still written, they clear the decorations written
+//!   before the mode was enabled.
+//! * [`EnableMouseCapture`] does nothing, the mouse keeps working like in any other program
+//!   (selecting text, ...).
+//! * [`EnableBlinking`] does nothing.
+//!
+//! The mode is enabled with [`set_reduced_decorations`](fn.set_reduced_decorations.html), or by
+//! the user with the `CROSSTERM_REDUCED_DECORATIONS` environment variable (any value but `0`).
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use crossterm::accessibility;
+//!
+//! // A `--plain` command line option of the application
+//! # let plain = true;
+//! if plain {
+//!     accessibility::set_reduced_decorations(true);
+//! }
+//! ```
+//!
+//! [`SetForegroundColor`]: ../style/struct.SetForegroundColor.html
+//! [`SetBackgroundColor`]: ../style/struct.SetBackgroundColor.html
+//! [`SetColors`]: ../style/struct.SetColors.html
+//! [`SetAttribute`]: ../style/struct.SetAttribute.html
+//! [`SetAttributes`]: ../style/struct.SetAttributes.html
+//! [`PrintStyledContent`]: ../style/struct.PrintStyledContent.html
+//! [`ResetColor`]: ../style/struct.ResetColor.html
+//! [`EnableMouseCapture`]: ../event/struct.EnableMouseCapture.html
+//! [`EnableBlinking`]: ../cursor/struct.EnableBlinking.html
+
+use std::{
+    env,
+    ffi::OsStr,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+/// The environment variable enabling the reduced decorations mode.
+const ENVIRONMENT_VARIABLE: &str = "CROSSTERM_REDUCED_DECORATIONS";
+
+/// The mode wasn't set yet, the environment variable is read on first use.
+const UNSET: u8 = 0;
+const DISABLED: u8 = 1;
+const ENABLED: u8 = 2;
+
+static REDUCED_DECORATIONS: AtomicU8 = AtomicU8::new(UNSET);
+
+/// Enables or disables the reduced decorations mode, it overrides the environment variable.
+pub fn set_reduced_decorations(enabled: bool) {
+    let mode = if enabled { ENABLED } else { DISABLED };
+    REDUCED_DECORATIONS.store(mode, Ordering::Relaxed);
+}
+
+/// Returns whether the reduced decorations mode is enabled.
+pub fn reduced_decorations() -> bool {
+    match REDUCED_DECORATIONS.load(Ordering::Relaxed) {
+        UNSET => {
+            let enabled = env::var_os(ENVIRONMENT_VARIABLE).is_some_and(|value| enables(&value));
+            // Unless it was set in the meantime
+            let _ = REDUCED_DECORATIONS.compare_exchange(
+                UNSET,
+                if enabled { ENABLED } else { DISABLED },
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            );
+            reduced_decorations()
+        }
+        mode => mode == ENABLED,
+    }
+}
+
+/// Returns whether the value of the environment variable enables the mode.
+fn enables(value: &OsStr) -> bool {
+    !value.is_empty() && value != "0"
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsStr;
+
+    use super::enables;
+
+    #[test]
+    fn test_environment_variable() {
+        assert!(enables(OsStr::new("1")));
+        assert!(enables(OsStr::new("yes")));
+        assert!(!enables(OsStr::new("0")));
+        assert!(!enables(OsStr::new("")));
+    }
+}
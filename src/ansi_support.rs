@@ -1,10 +1,10 @@
 use std::sync::atomic::{AtomicBool, Ordering};
 
-use crossterm_winapi::{ConsoleMode, Handle};
+use crossterm_winapi::Handle;
 use parking_lot::Once;
 use winapi::um::wincon::ENABLE_VIRTUAL_TERMINAL_PROCESSING;
 
-use crate::Result;
+use crate::{terminal::sys::update_console_mode, Result};
 
 /// Enable virtual terminal processing.
 ///
@@ -18,12 +18,7 @@ use crate::Result;
 fn enable_vt_processing() -> Result<()> {
     let mask = ENABLE_VIRTUAL_TERMINAL_PROCESSING;
 
-    let console_mode = ConsoleMode::from(Handle::current_out_handle()?);
-    let old_mode = console_mode.mode()?;
-
-    if old_mode & mask == 0 {
-        console_mode.set_mode(old_mode | mask)?;
-    }
+    update_console_mode(&Handle::current_out_handle()?, |mode| mode | mask)?;
 
     Ok(())
 }
@@ -38,7 +33,7 @@ pub fn supports_ansi() -> bool {
         // so when we try to enable the ANSI-flag for Windows this won't work.
         // Because of that we should check first if the TERM-variable is set
         // and see if the current terminal is a terminal who does support ANSI.
-        let supported = std::env::var("TERM").map_or(false, |term| term != "dumb")
+        let supported = std::env::var("TERM").is_ok_and(|term| term != "dumb")
             || enable_vt_processing().is_ok();
 
         SUPPORTS_ANSI_ESCAPE_CODES.store(supported, Ordering::SeqCst);
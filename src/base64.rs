@@ -0,0 +1,75 @@
+//! Base64 encoding, used by the sequences transferring binary data.
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub(crate) fn encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (index, byte)| {
+            group | u32::from(*byte) << (16 - 8 * index)
+        });
+
+        for index in 0..4 {
+            if index <= chunk.len() {
+                let sextet = (group >> (18 - 6 * index)) & 0x3F;
+                encoded.push(ALPHABET[sextet as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+/// Decodes base64, the padding is optional. Returns `None` if it's not valid base64.
+pub(crate) fn decode(encoded: &[u8]) -> Option<Vec<u8>> {
+    let unpadded = match encoded.iter().position(|byte| *byte == b'=') {
+        Some(padding) if encoded[padding..].iter().all(|byte| *byte == b'=') => &encoded[..padding],
+        Some(_) => return None,
+        None => encoded,
+    };
+    if unpadded.len() % 4 == 1 {
+        return None;
+    }
+
+    let mut decoded = Vec::with_capacity(unpadded.len() / 4 * 3 + 2);
+    for chunk in unpadded.chunks(4) {
+        let mut group = 0u32;
+        for (index, byte) in chunk.iter().enumerate() {
+            let sextet = ALPHABET.iter().position(|c| c == byte)? as u32;
+            group |= sextet << (18 - 6 * index);
+        }
+        for index in 0..chunk.len() - 1 {
+            decoded.push((group >> (16 - 8 * index)) as u8);
+        }
+    }
+
+    Some(decoded)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::{decode, encode};
+
+    #[test]
+    fn test_encode_and_decode() {
+        for (decoded, encoded) in &[
+            (&b""[..], ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"foob", "Zm9vYg=="),
+            (b"\xFF\x00\xFE", "/wD+"),
+        ] {
+            assert_eq!(encode(decoded), *encoded);
+            assert_eq!(decode(encoded.as_bytes()).as_deref(), Some(*decoded));
+        }
+
+        assert_eq!(decode(b"Zm8").as_deref(), Some(&b"fo"[..]));
+        assert_eq!(decode(b"Zm9vY"), None);
+        assert_eq!(decode(b"Zm=8"), None);
+        assert_eq!(decode(b"Zm9?"), None);
+    }
+}
@@ -0,0 +1,206 @@
+//! # Clipboard
+//!
+//! The `clipboard` module provides functionality to access the clipboard of the terminal emulator
+//! with the OSC 52 sequence. It works over SSH as well, the terminal on the machine of the user
+//! holds the clipboard.
+//!
+//! Not every terminal supports it, some only after it was enabled in their settings.
+//! [`query_support`](fn.query_support.html) asks the terminal on UNIX.
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use std::io::stdout;
+//!
+//! use crossterm::{clipboard::CopyToClipboard, execute, Result};
+//!
+//! fn main() -> Result<()> {
+//!     execute!(stdout(), CopyToClipboard::new("Hello from crossterm")?)
+//! }
+//! ```
+
+use std::{fmt, io};
+
+use crate::{
+    base64, impl_display,
+    terminal::{multiplexer, Multiplexer},
+    Command, ErrorKind, Result,
+};
+
+/// The maximum length of the content copied by [`CopyToClipboard`](struct.CopyToClipboard.html),
+/// in bytes.
+///
+/// Terminals limit the length of the sequence, xterm and others to 100 000 bytes of base64
+/// encoded content.
+pub const MAX_CLIPBOARD_LENGTH: usize = 75_000;
+
+/// The clipboards of the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClipboardSelection {
+    /// The clipboard, pasted with `Ctrl+V` or `Cmd+V`.
+    Clipboard,
+    /// The primary selection of X11, pasted with the middle mouse button.
+    Primary,
+}
+
+impl ClipboardSelection {
+    fn parameter(self) -> char {
+        match self {
+            ClipboardSelection::Clipboard => 'c',
+            ClipboardSelection::Primary => 'p',
+        }
+    }
+}
+
+/// A command that copies the content to the clipboard of the terminal.
+///
+/// # Notes
+///
+/// * Commands must be executed/queued for execution otherwise they do nothing.
+/// * Inside of tmux and screen, the sequence is sent to the terminal with their passthrough
+///   sequence, tmux requires its `allow-passthrough` option. The
+///   [multiplexer](../terminal/fn.multiplexer.html) is detected with the environment variables,
+///   [`passthrough`](#method.passthrough) overrides it.
+/// * The Windows versions without ANSI support don't support it, nothing happens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CopyToClipboard {
+    encoded: String,
+    selection: ClipboardSelection,
+    passthrough: Option<Multiplexer>,
+}
+
+impl CopyToClipboard {
+    /// Creates the command copying the content to the clipboard.
+    ///
+    /// Fails with `io::ErrorKind::InvalidInput` if the content is longer than
+    /// [`MAX_CLIPBOARD_LENGTH`](constant.MAX_CLIPBOARD_LENGTH.html) bytes.
+    pub fn new(content: impl AsRef<[u8]>) -> Result<CopyToClipboard> {
+        let content = content.as_ref();
+
+        if content.len() > MAX_CLIPBOARD_LENGTH {
+            return Err(ErrorKind::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "can't copy {} bytes to the clipboard, the maximum is {}",
+                    content.len(),
+                    MAX_CLIPBOARD_LENGTH
+                ),
+            ));
+        }
+
+        Ok(CopyToClipboard {
+            encoded: base64::encode(content),
+            selection: ClipboardSelection::Clipboard,
+            passthrough: multiplexer(),
+        })
+    }
+
+    /// Copies the content to the given clipboard, instead of `ClipboardSelection::Clipboard`.
+    pub fn selection(mut self, selection: ClipboardSelection) -> CopyToClipboard {
+        self.selection = selection;
+        self
+    }
+
+    /// Sets the multiplexer whose passthrough sequence wraps the sequence, `None` to write it as
+    /// is.
+    pub fn passthrough(mut self, multiplexer: Option<Multiplexer>) -> CopyToClipboard {
+        self.passthrough = multiplexer;
+        self
+    }
+
+    /// Sets whether the sequence is wrapped in the tmux passthrough sequence.
+    pub fn tmux_passthrough(self, passthrough: bool) -> CopyToClipboard {
+        self.passthrough(Some(Multiplexer::Tmux).filter(|_| passthrough))
+    }
+}
+
+impl Command for CopyToClipboard {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        let sequence = format!(
+            "\x1B]52;{};{}\x07",
+            self.selection.parameter(),
+            self.encoded
+        );
+
+        match self.passthrough {
+            Some(multiplexer) => multiplexer.write_passthrough(f, &sequence),
+            None => f.write_str(&sequence),
+        }
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl_display!(for CopyToClipboard);
+
+/// Queries whether the terminal supports the clipboard sequence.
+///
+/// The terminals supporting it report it in their device attributes, see
+/// [`query_device_attributes`](../terminal/fn.query_device_attributes.html). Some terminals
+/// support it without reporting it, or not for the programs running in tmux.
+///
+/// Only available on UNIX.
+#[cfg(unix)]
+pub fn query_support() -> Result<bool> {
+    Ok(crate::terminal::query_device_attributes()?.contains(&52))
+}
+
+/// Queries the content of the clipboard of the terminal.
+///
+/// Returns `None` if the terminal doesn't reply with the content, most terminals don't or only
+/// after asking the user. The content is read like the events, the contents longer than 48 KiB
+/// aren't read. Fails like [`query_device_attributes`](../terminal/fn.query_device_attributes.html).
+///
+/// Only available on UNIX.
+#[cfg(unix)]
+pub fn query_clipboard(selection: ClipboardSelection) -> Result<Option<Vec<u8>>> {
+    crate::terminal::sys::query_clipboard(selection.parameter())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use crate::{terminal::Multiplexer, Command};
+
+    use super::{ClipboardSelection, CopyToClipboard, MAX_CLIPBOARD_LENGTH};
+
+    fn ansi(command: impl Command) -> String {
+        let mut ansi = String::new();
+        command.write_ansi(&mut ansi).unwrap();
+        ansi
+    }
+
+    #[test]
+    fn test_copy_to_clipboard() {
+        let copy = CopyToClipboard::new("foo").unwrap().tmux_passthrough(false);
+
+        assert_eq!(ansi(&copy), "\x1B]52;c;Zm9v\x07");
+        assert_eq!(
+            ansi(copy.clone().selection(ClipboardSelection::Primary)),
+            "\x1B]52;p;Zm9v\x07"
+        );
+        assert_eq!(
+            ansi(copy.clone().tmux_passthrough(true)),
+            "\x1BPtmux;\x1B\x1B]52;c;Zm9v\x07\x1B\\"
+        );
+        assert_eq!(
+            ansi(copy.passthrough(Some(Multiplexer::Screen))),
+            "\x1BP\x1B]52;c;Zm9v\x07\x1B\\"
+        );
+    }
+
+    #[test]
+    fn test_copy_to_clipboard_limits_the_length() {
+        assert!(CopyToClipboard::new(vec![b'a'; MAX_CLIPBOARD_LENGTH]).is_ok());
+        assert_eq!(
+            CopyToClipboard::new(vec![b'a'; MAX_CLIPBOARD_LENGTH + 1])
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::InvalidInput
+        );
+    }
+}
@@ -1,8 +1,15 @@
+use std::cell::RefCell;
 use std::fmt;
 use std::io::{self, Write};
 
 use super::error::Result;
 
+#[cfg(feature = "command-sink")]
+pub use self::sink::CommandSink;
+
+#[cfg(feature = "command-sink")]
+mod sink;
+
 /// An interface for a command that performs an action on the terminal.
 ///
 /// Crossterm provides a set of commands,
@@ -30,10 +37,23 @@ pub trait Command {
     ///
     /// A list of supported ANSI escape codes
     /// can be found [here](https://docs.microsoft.com/en-us/windows/console/console-virtual-terminal-sequences).
+    ///
+    /// This is decided per command, by default based on whether the terminal supports ANSI escape codes.
+    /// If this returns `false` but the WinAPI call fails, the ANSI representation is used
+    /// if the terminal supports ANSI escape codes.
     #[cfg(windows)]
     fn is_ansi_code_supported(&self) -> bool {
         super::ansi_support::supports_ansi()
     }
+
+    /// Checks the parameters of this command against the terminal size `(columns, rows)`.
+    ///
+    /// Returns an `io::ErrorKind::InvalidInput` error for parameters which different terminals
+    /// interpret differently (a position outside of the screen, ...). Commands are checked before
+    /// they're queued if the `validate` feature is enabled, every check queries the terminal size.
+    fn validate(&self, _size: (u16, u16)) -> Result<()> {
+        Ok(())
+    }
 }
 
 impl<T: Command + ?Sized> Command for &T {
@@ -52,8 +72,149 @@ impl<T: Command + ?Sized> Command for &T {
     fn is_ansi_code_supported(&self) -> bool {
         T::is_ansi_code_supported(self)
     }
+
+    #[inline]
+    fn validate(&self, size: (u16, u16)) -> Result<()> {
+        T::validate(self, size)
+    }
+}
+
+impl<T: Command + ?Sized> Command for Box<T> {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        (**self).write_ansi(f)
+    }
+
+    #[inline]
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        T::execute_winapi(self)
+    }
+
+    #[cfg(windows)]
+    #[inline]
+    fn is_ansi_code_supported(&self) -> bool {
+        T::is_ansi_code_supported(self)
+    }
+
+    #[inline]
+    fn validate(&self, size: (u16, u16)) -> Result<()> {
+        T::validate(self, size)
+    }
+}
+
+/// An object-safe version of the [`Command`](./trait.Command.html) trait.
+///
+/// `Command::write_ansi` is generic over its writer, which means `dyn Command` can not exist.
+/// Every `Command` implements `DynCommand` though, and `dyn DynCommand` implements `Command` again.
+/// This allows commands of different types to be collected, stored and executed later on.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::io::{stdout, Write};
+///
+/// use crossterm::{
+///     cursor::MoveTo,
+///     style::Print,
+///     terminal::{Clear, ClearType},
+///     DynCommand, QueueableCommand, Result,
+/// };
+///
+/// fn main() -> Result<()> {
+///     let commands: Vec<Box<dyn DynCommand>> = vec![
+///         Box::new(Clear(ClearType::All)),
+///         Box::new(MoveTo(5, 5)),
+///         Box::new(Print("Hello world")),
+///     ];
+///
+///     let mut stdout = stdout();
+///     for command in &commands {
+///         stdout.queue(command)?;
+///     }
+///     stdout.flush()?;
+///
+///     Ok(())
+/// }
+/// ```
+pub trait DynCommand {
+    /// Write an ANSI representation of this command to the given writer.
+    ///
+    /// See [`Command::write_ansi`](./trait.Command.html#tymethod.write_ansi).
+    fn write_ansi_dyn(&self, f: &mut dyn fmt::Write) -> fmt::Result;
+
+    /// Execute this command.
+    ///
+    /// See [`Command::execute_winapi`](./trait.Command.html#tymethod.execute_winapi).
+    #[cfg(windows)]
+    fn execute_winapi_dyn(&self) -> Result<()>;
+
+    /// Returns whether the ansi code representation of this command is supported by windows.
+    ///
+    /// See [`Command::is_ansi_code_supported`](./trait.Command.html#method.is_ansi_code_supported).
+    #[cfg(windows)]
+    fn is_ansi_code_supported_dyn(&self) -> bool;
+
+    /// Checks the parameters of this command against the terminal size.
+    ///
+    /// See [`Command::validate`](./trait.Command.html#method.validate).
+    fn validate_dyn(&self, size: (u16, u16)) -> Result<()>;
+}
+
+impl<T: Command> DynCommand for T {
+    fn write_ansi_dyn(&self, mut f: &mut dyn fmt::Write) -> fmt::Result {
+        self.write_ansi(&mut f)
+    }
+
+    #[inline]
+    #[cfg(windows)]
+    fn execute_winapi_dyn(&self) -> Result<()> {
+        self.execute_winapi()
+    }
+
+    #[cfg(windows)]
+    #[inline]
+    fn is_ansi_code_supported_dyn(&self) -> bool {
+        self.is_ansi_code_supported()
+    }
+    #[inline]
+    fn validate_dyn(&self, size: (u16, u16)) -> Result<()> {
+        self.validate(size)
+    }
 }
 
+macro_rules! impl_command_for_dyn {
+    ($($t:ty),+) => {
+        $(impl Command for $t {
+            fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+                self.write_ansi_dyn(f)
+            }
+
+            #[inline]
+            #[cfg(windows)]
+            fn execute_winapi(&self) -> Result<()> {
+                self.execute_winapi_dyn()
+            }
+
+            #[cfg(windows)]
+            #[inline]
+            fn is_ansi_code_supported(&self) -> bool {
+                self.is_ansi_code_supported_dyn()
+            }
+
+            #[inline]
+            fn validate(&self, size: (u16, u16)) -> Result<()> {
+                self.validate_dyn(size)
+            }
+        })*
+    };
+}
+
+impl_command_for_dyn!(
+    dyn DynCommand + '_,
+    dyn DynCommand + Send + '_,
+    dyn DynCommand + Send + Sync + '_
+);
+
 /// An interface for types that can queue commands for further execution.
 pub trait QueueableCommand {
     /// Queues the given command for further execution.
@@ -115,19 +276,26 @@ impl<T: Write + ?Sized> QueueableCommand for T {
     ///
     /// * In the case of UNIX and Windows 10, ANSI codes are written to the given 'writer'.
     /// * In case of Windows versions lower than 10, a direct WinAPI call will be made.
-    ///     The reason for this is that Windows versions lower than 10 do not support ANSI codes,
-    ///     and can therefore not be written to the given `writer`.
-    ///     Therefore, there is no difference between [execute](./trait.ExecutableCommand.html)
-    ///     and [queue](./trait.QueueableCommand.html) for those old Windows versions.
+    ///   The reason for this is that Windows versions lower than 10 do not support ANSI codes,
+    ///   and can therefore not be written to the given `writer`.
+    ///   Therefore, there is no difference between [execute](./trait.ExecutableCommand.html)
+    ///   and [queue](./trait.QueueableCommand.html) for those old Windows versions.
+    /// * Every command is written with a single `write_all` call. Threads sharing a writer which
+    ///   locks itself for every write, like `Stdout`, don't interleave the escape sequences. Lock
+    ///   the writer (`stdout().lock()`) to keep several commands together.
     fn queue(&mut self, command: impl Command) -> Result<&mut Self> {
+        #[cfg(feature = "validate")]
+        command.validate(crate::terminal::size()?)?;
+
         #[cfg(windows)]
         if !command.is_ansi_code_supported() {
             // There may be queued commands in this writer, but `execute_winapi` will execute the
             // command immediately. To prevent commands being executed out of order we flush the
             // writer now.
             self.flush()?;
-            command.execute_winapi()?;
-            return Ok(self);
+            if execute_winapi_with_fallback(&command)? {
+                return Ok(self);
+            }
         }
 
         write_command_ansi(self, command)?;
@@ -173,10 +341,10 @@ impl<T: Write + ?Sized> ExecutableCommand for T {
     ///
     /// * In the case of UNIX and Windows 10, ANSI codes are written to the given 'writer'.
     /// * In case of Windows versions lower than 10, a direct WinAPI call will be made.
-    ///     The reason for this is that Windows versions lower than 10 do not support ANSI codes,
-    ///     and can therefore not be written to the given `writer`.
-    ///     Therefore, there is no difference between [execute](./trait.ExecutableCommand.html)
-    ///     and [queue](./trait.QueueableCommand.html) for those old Windows versions.
+    ///   The reason for this is that Windows versions lower than 10 do not support ANSI codes,
+    ///   and can therefore not be written to the given `writer`.
+    ///   Therefore, there is no difference between [execute](./trait.ExecutableCommand.html)
+    ///   and [queue](./trait.QueueableCommand.html) for those old Windows versions.
     fn execute(&mut self, command: impl Command) -> Result<&mut Self> {
         self.queue(command)?;
         self.flush()?;
@@ -184,47 +352,154 @@ impl<T: Write + ?Sized> ExecutableCommand for T {
     }
 }
 
+/// Commands formatted to a larger buffer don't keep the memory around.
+const MAX_RETAINED_ANSI_BUFFER_CAPACITY: usize = 4096;
+
+thread_local! {
+    /// The buffer the commands are formatted to before they are written.
+    static ANSI_BUFFER: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
 /// Writes the ANSI representation of a command to the given writer.
-fn write_command_ansi<C: Command>(
+///
+/// The command is formatted first and written with a single `write_all` call. Writers locking
+/// themselves for every write (`Stdout`, ...) can't interleave the sequence with the output of
+/// another thread.
+pub(crate) fn write_command_ansi<C: Command>(
     io: &mut (impl io::Write + ?Sized),
     command: C,
 ) -> io::Result<()> {
-    struct Adapter<T> {
-        inner: T,
-        res: io::Result<()>,
-    }
-
-    impl<T: Write> fmt::Write for Adapter<T> {
-        fn write_str(&mut self, s: &str) -> fmt::Result {
-            self.inner.write_all(s.as_bytes()).map_err(|e| {
-                self.res = Err(e);
-                fmt::Error
-            })
+    let mut write = |buffer: &mut String| {
+        buffer.clear();
+        if command.write_ansi(buffer).is_err() {
+            panic!(
+                "<{}>::write_ansi incorrectly errored",
+                std::any::type_name::<C>()
+            );
         }
-    }
 
-    let mut adapter = Adapter {
-        inner: io,
-        res: Ok(()),
+        let result = io.write_all(buffer.as_bytes());
+        if buffer.capacity() > MAX_RETAINED_ANSI_BUFFER_CAPACITY {
+            *buffer = String::new();
+        }
+        result
     };
 
-    command
-        .write_ansi(&mut adapter)
-        .map_err(|fmt::Error| match adapter.res {
-            Ok(()) => panic!(
-                "<{}>::write_ansi incorrectly errored",
-                std::any::type_name::<C>()
-            ),
-            Err(e) => e,
-        })
+    match ANSI_BUFFER
+        .try_with(|buffer| buffer.try_borrow_mut().map(|mut buffer| write(&mut buffer)))
+    {
+        Ok(Ok(result)) => result,
+        // A command is written while another one is formatted or the thread is exiting
+        _ => write(&mut String::new()),
+    }
 }
 
 /// Executes the ANSI representation of a command, using the given `fmt::Write`.
 pub(crate) fn execute_fmt(f: &mut impl fmt::Write, command: impl Command) -> fmt::Result {
     #[cfg(windows)]
-    if !command.is_ansi_code_supported() {
-        return command.execute_winapi().map_err(|_| fmt::Error);
+    if !command.is_ansi_code_supported()
+        && execute_winapi_with_fallback(&command).map_err(|_| fmt::Error)?
+    {
+        return Ok(());
     }
 
     command.write_ansi(f)
 }
+
+/// Writes the decimal representation of the given number.
+///
+/// This is used by commands which are written for every cell of a frame (e.g. `MoveTo`), it avoids
+/// the formatting machinery of `write!`.
+pub(crate) fn write_u16(f: &mut impl fmt::Write, mut n: u16) -> fmt::Result {
+    let mut buffer = [0u8; 5];
+    let mut start = buffer.len();
+
+    loop {
+        start -= 1;
+        buffer[start] = b'0' + (n % 10) as u8;
+        n /= 10;
+
+        if n == 0 {
+            break;
+        }
+    }
+
+    f.write_str(std::str::from_utf8(&buffer[start..]).map_err(|_| fmt::Error)?)
+}
+
+/// Executes a command that prefers the WinAPI over its ANSI representation.
+///
+/// A console can support ANSI output while a single command still prefers the WinAPI, or the other
+/// way around (e.g. GitBash supports ANSI codes but has no console screen buffer for the WinAPI).
+/// So instead of deciding on one of both for all commands, the ANSI representation is used as
+/// a fallback when the WinAPI call fails and the terminal supports ANSI escape codes.
+///
+/// Returns `Ok(false)` if the ANSI representation should be written instead.
+#[cfg(windows)]
+fn execute_winapi_with_fallback(command: &impl Command) -> Result<bool> {
+    match command.execute_winapi() {
+        Ok(()) => Ok(true),
+        Err(_) if super::ansi_support::supports_ansi() => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::{write_u16, QueueableCommand};
+    use crate::{cursor::MoveTo, style::Print};
+
+    /// Records every single write.
+    #[derive(Default)]
+    struct Writes(Vec<Vec<u8>>);
+
+    impl io::Write for Writes {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.push(buf.to_vec());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_queue_writes_each_command_at_once() {
+        let mut writes = Writes::default();
+        writes
+            .queue(MoveTo(10, 20))
+            .unwrap()
+            .queue(Print("crossterm"))
+            .unwrap();
+
+        assert_eq!(
+            writes.0,
+            vec![b"\x1B[21;11H".to_vec(), b"crossterm".to_vec()]
+        );
+    }
+
+    #[test]
+    fn test_queue_doesnt_keep_large_buffers() {
+        let content = "x".repeat(2 * super::MAX_RETAINED_ANSI_BUFFER_CAPACITY);
+
+        let mut writes = Writes::default();
+        writes.queue(Print(&content)).unwrap();
+        assert_eq!(writes.0, vec![content.into_bytes()]);
+
+        super::ANSI_BUFFER.with(|buffer| {
+            assert!(buffer.borrow().capacity() <= super::MAX_RETAINED_ANSI_BUFFER_CAPACITY)
+        });
+    }
+
+    #[test]
+    fn test_write_u16() {
+        for n in [0, 1, 9, 10, 99, 100, 1234, 9999, 10000, 54321, u16::MAX].iter() {
+            let mut result = String::new();
+            write_u16(&mut result, *n).unwrap();
+            assert_eq!(result, n.to_string());
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use std::{
+    fmt, io,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_io::AsyncWrite;
+use futures_sink::Sink;
+
+use super::{write_command_ansi, Command, DynCommand};
+
+/// The number of buffered bytes from which the sink writes them before it accepts more commands.
+const DEFAULT_CAPACITY: usize = 8 * 1024;
+
+/// A `futures::Sink` of commands, writing them to an `AsyncWrite`.
+///
+/// The commands are buffered, the buffer is written once it holds more than the capacity
+/// (8 KiB by default) and when the sink is flushed. A task producing the commands waits for the
+/// writer then, the sink applies the backpressure of the writer.
+///
+/// The sink takes one type of commands `C`, commands of different types are sent as
+/// `Box<dyn DynCommand + Send>` (the default) or as an enum implementing `Command`.
+///
+/// The ANSI representation of the commands is written, the WinAPI isn't used on Windows. With the
+/// `validate` feature, the commands are checked against the size of the terminal of this process
+/// like [`queue`](trait.QueueableCommand.html#tymethod.queue) does.
+///
+/// Tokio's `AsyncWrite` is turned into the one of `futures` with
+/// [`tokio_util::compat`](https://docs.rs/tokio-util/latest/tokio_util/compat/index.html).
+///
+/// # Examples
+///
+/// ```no_run
+/// use futures::{io::AllowStdIo, SinkExt};
+///
+/// use crossterm::{cursor::MoveTo, style::Print, CommandSink};
+///
+/// # async fn render() -> std::io::Result<()> {
+/// let mut sink: CommandSink<_> = CommandSink::new(AllowStdIo::new(std::io::stdout()));
+///
+/// sink.feed(Box::new(MoveTo(5, 5))).await?;
+/// sink.feed(Box::new(Print("Hello"))).await?;
+/// // Written when the sink is flushed
+/// sink.flush().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct CommandSink<W, C = Box<dyn DynCommand + Send>> {
+    writer: W,
+    buffer: Vec<u8>,
+    /// The bytes at the beginning of the buffer which were written already.
+    written: usize,
+    capacity: usize,
+    // The sink doesn't own commands, it's `Send` and `Sync` whatever they are
+    command: PhantomData<fn(C)>,
+}
+
+impl<W: AsyncWrite + Unpin, C: Command> CommandSink<W, C> {
+    /// Creates a sink writing the commands to the writer.
+    pub fn new(writer: W) -> CommandSink<W, C> {
+        CommandSink::with_capacity(writer, DEFAULT_CAPACITY)
+    }
+
+    /// Creates a sink buffering up to `capacity` bytes before it waits for the writer.
+    pub fn with_capacity(writer: W, capacity: usize) -> CommandSink<W, C> {
+        CommandSink {
+            writer,
+            buffer: Vec::new(),
+            written: 0,
+            capacity,
+            command: PhantomData,
+        }
+    }
+
+    /// Returns a reference to the writer.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Returns a mutable reference to the writer, writing to it directly can interleave the
+    /// output with the buffered commands.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Returns the writer, the buffered commands which weren't flushed are dropped.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Writes the whole buffer to the writer.
+    fn poll_write_buffer(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while self.written < self.buffer.len() {
+            match Pin::new(&mut self.writer).poll_write(cx, &self.buffer[self.written..]) {
+                Poll::Ready(Ok(0)) => return Poll::Ready(Err(io::ErrorKind::WriteZero.into())),
+                Poll::Ready(Ok(written)) => self.written += written,
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        self.buffer.clear();
+        self.written = 0;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<W: AsyncWrite + Unpin, C: Command> Sink<C> for CommandSink<W, C> {
+    type Error = io::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if this.buffer.len() >= this.capacity {
+            this.poll_write_buffer(cx)
+        } else {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    fn start_send(self: Pin<&mut Self>, command: C) -> io::Result<()> {
+        #[cfg(feature = "validate")]
+        command.validate(crate::terminal::size()?)?;
+
+        write_command_ansi(&mut self.get_mut().buffer, command)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        match this.poll_write_buffer(cx) {
+            Poll::Ready(Ok(())) => Pin::new(&mut this.writer).poll_flush(cx),
+            poll => poll,
+        }
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        match this.poll_write_buffer(cx) {
+            Poll::Ready(Ok(())) => Pin::new(&mut this.writer).poll_close(cx),
+            poll => poll,
+        }
+    }
+}
+
+impl<W: fmt::Debug, C> fmt::Debug for CommandSink<W, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CommandSink")
+            .field("writer", &self.writer)
+            .field("buffered", &(self.buffer.len() - self.written))
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{executor::block_on, io::Cursor, SinkExt};
+
+    use super::CommandSink;
+    use crate::{
+        cursor::{Hide, MoveTo},
+        style::Print,
+    };
+
+    #[test]
+    fn test_send_commands() {
+        let mut sink: CommandSink<_> = CommandSink::new(Cursor::new(Vec::new()));
+
+        block_on(async {
+            sink.feed(Box::new(MoveTo(4, 2))).await.unwrap();
+            sink.feed(Box::new(Print("x"))).await.unwrap();
+            assert!(sink.get_ref().get_ref().is_empty());
+
+            sink.send(Box::new(Hide)).await.unwrap();
+        });
+
+        assert_eq!(sink.into_inner().into_inner(), b"\x1B[3;5Hx\x1B[?25l");
+    }
+
+    #[test]
+    fn test_capacity() {
+        let mut sink: CommandSink<_> = CommandSink::with_capacity(Cursor::new(Vec::new()), 4);
+
+        block_on(async {
+            sink.feed(Box::new(MoveTo(4, 2))).await.unwrap();
+            assert!(sink.get_ref().get_ref().is_empty());
+
+            // The buffer is over the capacity, it's written before the next command is accepted
+            sink.feed(Box::new(Hide)).await.unwrap();
+            assert_eq!(sink.get_ref().get_ref(), b"\x1B[3;5H");
+        });
+    }
+}
@@ -0,0 +1,9 @@
+//! # Compatibility
+//!
+//! The `compat` module eases the migration from other terminal libraries, it mirrors their API on
+//! top of crossterm.
+//!
+//! * [`termion`](termion/index.html) - the modules of termion, with the `termion-compat` feature.
+
+#[cfg(feature = "termion-compat")]
+pub mod termion;
@@ -0,0 +1,102 @@
+//! # Termion
+//!
+//! The `termion` module mirrors the API of [termion](https://crates.io/crates/termion) on top of
+//! crossterm. An application written for termion is ported by replacing `termion::` with
+//! `crossterm::compat::termion::`, and it runs on Windows then.
+//!
+//! The items keep the names and the behavior of termion: the cursor positions start at 1, the
+//! colors are the ones of termion, they're written with `write!` like in termion. They are
+//! crossterm [commands](../../trait.Command.html) too, for [`queue!`](../../macro.queue.html) and
+//! [`execute!`](../../macro.execute.html), which also work with the legacy Windows console.
+//!
+//! What differs from termion:
+//!
+//! * The raw mode is the one of the terminal of this process, whatever the writer
+//!   [`into_raw_mode`](raw/trait.IntoRawMode.html#tymethod.into_raw_mode) is called on.
+//! * The keys and the events are read with [`event::read`](../../event/fn.read.html), only from
+//!   the standard input. The events termion doesn't have, like the resize events, are skipped.
+//!
+//! Only available with the `termion-compat` feature.
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use std::io::{stdin, stdout, Write};
+//!
+//! use crossterm::compat::termion::{
+//!     clear, color, cursor, event::Key, input::TermRead, raw::IntoRawMode, style,
+//! };
+//!
+//! let mut stdout = stdout().into_raw_mode().unwrap();
+//! write!(
+//!     stdout,
+//!     "{}{}{}Press q to exit{}",
+//!     clear::All,
+//!     cursor::Goto(1, 1),
+//!     color::Fg(color::Red),
+//!     style::Reset
+//! )
+//! .unwrap();
+//! stdout.flush().unwrap();
+//!
+//! for key in stdin().keys() {
+//!     if key.unwrap() == Key::Char('q') {
+//!         break;
+//!     }
+//! }
+//! ```
+
+use std::io;
+
+use crate::tty::IsTty;
+
+/// Implements `Command` and `Display` for termion items, with the crossterm command each one
+/// stands for.
+macro_rules! delegate_command {
+    ($($item:ty => |$binding:pat| $command:expr;)*) => {$(
+        impl $crate::Command for $item {
+            fn write_ansi(&self, f: &mut impl ::std::fmt::Write) -> ::std::fmt::Result {
+                let $binding = self;
+                $crate::Command::write_ansi(&$command, f)
+            }
+
+            #[cfg(windows)]
+            fn execute_winapi(&self) -> $crate::Result<()> {
+                let $binding = self;
+                $crate::Command::execute_winapi(&$command)
+            }
+
+            #[cfg(windows)]
+            fn is_ansi_code_supported(&self) -> bool {
+                let $binding = self;
+                $crate::Command::is_ansi_code_supported(&$command)
+            }
+
+            fn validate(&self, size: (u16, u16)) -> $crate::Result<()> {
+                let $binding = self;
+                $crate::Command::validate(&$command, size)
+            }
+        }
+
+        $crate::impl_display!(for $item);
+    )*};
+}
+
+pub mod clear;
+pub mod color;
+pub mod cursor;
+pub mod event;
+pub mod input;
+pub mod raw;
+pub mod screen;
+pub mod style;
+
+/// Returns the size of the terminal `(columns, rows)`.
+pub fn terminal_size() -> io::Result<(u16, u16)> {
+    crate::terminal::size()
+}
+
+/// Returns whether the stream is a terminal.
+pub fn is_tty<T: IsTty>(stream: &T) -> bool {
+    stream.is_tty()
+}
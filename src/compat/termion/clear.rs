@@ -0,0 +1,31 @@
+//! Clearing the screen.
+
+use crate::terminal::{Clear, ClearType};
+
+/// Clears the whole screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct All;
+
+/// Clears from the cursor to the end of the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AfterCursor;
+
+/// Clears from the start of the screen to the cursor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BeforeCursor;
+
+/// Clears the line of the cursor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CurrentLine;
+
+/// Clears from the cursor to the end of the line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UntilNewline;
+
+delegate_command! {
+    All => |_| Clear(ClearType::All);
+    AfterCursor => |_| Clear(ClearType::FromCursorDown);
+    BeforeCursor => |_| Clear(ClearType::FromCursorUp);
+    CurrentLine => |_| Clear(ClearType::CurrentLine);
+    UntilNewline => |_| Clear(ClearType::UntilNewLine);
+}
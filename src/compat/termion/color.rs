@@ -0,0 +1,192 @@
+//! Colors, for the foreground with [`Fg`](struct.Fg.html) and the background with
+//! [`Bg`](struct.Bg.html).
+//!
+//! The 16 colors are the ones of termion: `Red` is the dark red of crossterm, `LightRed` the
+//! bright one.
+
+use std::fmt;
+
+use crate::{
+    command::execute_fmt,
+    style::{self, SetBackgroundColor, SetForegroundColor},
+    Command,
+};
+
+/// A color of termion.
+pub trait Color {
+    /// Returns the crossterm color it stands for.
+    fn to_crossterm(&self) -> style::Color;
+
+    /// Writes the escape sequence setting the foreground color.
+    fn write_fg(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&SetForegroundColor(self.to_crossterm()), f)
+    }
+
+    /// Writes the escape sequence setting the background color.
+    fn write_bg(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&SetBackgroundColor(self.to_crossterm()), f)
+    }
+}
+
+impl<C: Color + ?Sized> Color for &C {
+    fn to_crossterm(&self) -> style::Color {
+        (**self).to_crossterm()
+    }
+}
+
+macro_rules! colors {
+    ($($(#[$meta:meta])* $name:ident => $color:ident,)*) => {$(
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct $name;
+
+        impl Color for $name {
+            fn to_crossterm(&self) -> style::Color {
+                style::Color::$color
+            }
+        }
+    )*};
+}
+
+colors! {
+    /// Black.
+    Black => Black,
+    /// Red.
+    Red => DarkRed,
+    /// Green.
+    Green => DarkGreen,
+    /// Yellow.
+    Yellow => DarkYellow,
+    /// Blue.
+    Blue => DarkBlue,
+    /// Magenta.
+    Magenta => DarkMagenta,
+    /// Cyan.
+    Cyan => DarkCyan,
+    /// White, a light gray.
+    White => Grey,
+    /// Light black, a dark gray.
+    LightBlack => DarkGrey,
+    /// Light red.
+    LightRed => Red,
+    /// Light green.
+    LightGreen => Green,
+    /// Light yellow.
+    LightYellow => Yellow,
+    /// Light blue.
+    LightBlue => Blue,
+    /// Light magenta.
+    LightMagenta => Magenta,
+    /// Light cyan.
+    LightCyan => Cyan,
+    /// Light white, the brightest white.
+    LightWhite => White,
+    /// The default color of the terminal.
+    Reset => Reset,
+}
+
+/// A color of the 256 color palette.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnsiValue(pub u8);
+
+impl AnsiValue {
+    /// Returns the color of the 6x6x6 color cube, every component is between 0 and 5.
+    pub fn rgb(r: u8, g: u8, b: u8) -> AnsiValue {
+        debug_assert!(
+            r <= 5 && g <= 5 && b <= 5,
+            "the components are between 0 and 5"
+        );
+        AnsiValue(16 + 36 * r + 6 * g + b)
+    }
+
+    /// Returns the gray of the grayscale ramp, the shade is between 0 and 23.
+    pub fn grayscale(shade: u8) -> AnsiValue {
+        debug_assert!(shade < 24, "the shade is between 0 and 23");
+        AnsiValue(0xE8 + shade)
+    }
+}
+
+impl Color for AnsiValue {
+    fn to_crossterm(&self) -> style::Color {
+        style::Color::AnsiValue(self.0)
+    }
+}
+
+/// A true color `(red, green, blue)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Color for Rgb {
+    fn to_crossterm(&self) -> style::Color {
+        style::Color::Rgb {
+            r: self.0,
+            g: self.1,
+            b: self.2,
+        }
+    }
+}
+
+/// Sets the foreground color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fg<C: Color>(pub C);
+
+/// Sets the background color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bg<C: Color>(pub C);
+
+impl<C: Color> Command for Fg<C> {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        SetForegroundColor(self.0.to_crossterm()).write_ansi(f)
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> crate::Result<()> {
+        SetForegroundColor(self.0.to_crossterm()).execute_winapi()
+    }
+}
+
+impl<C: Color> Command for Bg<C> {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        SetBackgroundColor(self.0.to_crossterm()).write_ansi(f)
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> crate::Result<()> {
+        SetBackgroundColor(self.0.to_crossterm()).execute_winapi()
+    }
+}
+
+impl<C: Color> fmt::Display for Fg<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        execute_fmt(f, self)
+    }
+}
+
+impl<C: Color> fmt::Display for Bg<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        execute_fmt(f, self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AnsiValue, Bg, Color, Fg, LightWhite, Red, Reset, Rgb};
+
+    #[test]
+    fn test_colors_are_the_ones_of_termion() {
+        assert_eq!(Fg(Red).to_string(), "\x1B[38;5;1m");
+        assert_eq!(Bg(LightWhite).to_string(), "\x1B[48;5;15m");
+        assert_eq!(Fg(Reset).to_string(), "\x1B[39m");
+        assert_eq!(Bg(Reset).to_string(), "\x1B[49m");
+        assert_eq!(Fg(Rgb(1, 2, 3)).to_string(), "\x1B[38;2;1;2;3m");
+        assert_eq!(Fg(AnsiValue::rgb(5, 0, 0)).to_string(), "\x1B[38;5;196m");
+        assert_eq!(Bg(AnsiValue::grayscale(23)).to_string(), "\x1B[48;5;255m");
+    }
+
+    #[test]
+    fn test_dyn_colors() {
+        let colors: [&dyn Color; 2] = [&Red, &Rgb(0, 0, 0)];
+        let written: Vec<String> = colors.iter().map(|color| Fg(color).to_string()).collect();
+        assert_eq!(written, ["\x1B[38;5;1m", "\x1B[38;2;0;0;0m"]);
+    }
+}
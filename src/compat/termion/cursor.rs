@@ -0,0 +1,136 @@
+//! Moving the cursor, changing its visibility and its shape.
+//!
+//! The positions start at 1, `Goto(1, 1)` is the top left cell.
+
+use std::{fmt, io};
+
+use crate::{
+    command::write_u16,
+    csi,
+    cursor::{self, MoveDown, MoveLeft, MoveRight, MoveTo, MoveUp, RestorePosition, SavePosition},
+    impl_display, Command,
+};
+
+/// Moves the cursor to the position `(column, row)`, starting at 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Goto(pub u16, pub u16);
+
+impl Default for Goto {
+    fn default() -> Goto {
+        Goto(1, 1)
+    }
+}
+
+/// Moves the cursor up the given number of rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Up(pub u16);
+
+/// Moves the cursor down the given number of rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Down(pub u16);
+
+/// Moves the cursor left the given number of columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Left(pub u16);
+
+/// Moves the cursor right the given number of columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Right(pub u16);
+
+/// Hides the cursor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hide;
+
+/// Shows the cursor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Show;
+
+/// Saves the position of the cursor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Save;
+
+/// Moves the cursor back to the saved position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Restore;
+
+delegate_command! {
+    Goto => |Goto(column, row)| MoveTo(column.saturating_sub(1), row.saturating_sub(1));
+    Up => |Up(rows)| MoveUp(*rows);
+    Down => |Down(rows)| MoveDown(*rows);
+    Left => |Left(columns)| MoveLeft(*columns);
+    Right => |Right(columns)| MoveRight(*columns);
+    Hide => |_| cursor::Hide;
+    Show => |_| cursor::Show;
+    Save => |_| SavePosition;
+    Restore => |_| RestorePosition;
+}
+
+macro_rules! cursor_shapes {
+    ($($(#[$meta:meta])* $name:ident => $parameter:expr,)*) => {$(
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct $name;
+
+        impl Command for $name {
+            fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+                f.write_str(csi!())?;
+                write_u16(f, $parameter)?;
+                f.write_str(" q")
+            }
+
+            #[cfg(windows)]
+            fn execute_winapi(&self) -> crate::Result<()> {
+                Ok(())
+            }
+        }
+
+        impl_display!(for $name);
+    )*};
+}
+
+cursor_shapes! {
+    /// Shows the cursor as a blinking block.
+    BlinkingBlock => 1,
+    /// Shows the cursor as a steady block.
+    SteadyBlock => 2,
+    /// Shows the cursor as a blinking underline.
+    BlinkingUnderline => 3,
+    /// Shows the cursor as a steady underline.
+    SteadyUnderline => 4,
+    /// Shows the cursor as a blinking bar.
+    BlinkingBar => 5,
+    /// Shows the cursor as a steady bar.
+    SteadyBar => 6,
+}
+
+/// Reads the position of the cursor.
+pub trait DetectCursorPos {
+    /// Returns the position of the cursor `(column, row)`, starting at 1.
+    fn cursor_pos(&mut self) -> io::Result<(u16, u16)>;
+}
+
+impl<W: io::Write> DetectCursorPos for W {
+    fn cursor_pos(&mut self) -> io::Result<(u16, u16)> {
+        let (column, row) = cursor::position()?;
+        Ok((column + 1, row + 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Goto, Hide, Left, SteadyBar};
+
+    #[test]
+    fn test_positions_start_at_one() {
+        assert_eq!(Goto(1, 1).to_string(), "\x1B[1;1H");
+        assert_eq!(Goto(10, 5).to_string(), "\x1B[5;10H");
+        assert_eq!(Goto::default(), Goto(1, 1));
+    }
+
+    #[test]
+    fn test_cursor_items() {
+        assert_eq!(Left(3).to_string(), "\x1B[3D");
+        assert_eq!(Hide.to_string(), "\x1B[?25l");
+        assert_eq!(SteadyBar.to_string(), "\x1B[6 q");
+    }
+}
@@ -0,0 +1,279 @@
+//! The events of termion.
+//!
+//! They are converted from the crossterm events with `TryFrom`, the events termion doesn't have
+//! are handed back as the error.
+
+use std::convert::TryFrom;
+
+use crate::event::{self as crossterm, KeyCode, KeyEventKind, KeyModifiers, MouseEventKind};
+
+/// An event.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Event {
+    /// A key press.
+    Key(Key),
+    /// A mouse event.
+    Mouse(MouseEvent),
+    /// A sequence which isn't supported (the raw bytes).
+    Unsupported(Vec<u8>),
+}
+
+/// A mouse event, the positions `(column, row)` start at 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum MouseEvent {
+    /// A button was pressed, the wheel reports its moves as presses.
+    Press(MouseButton, u16, u16),
+    /// A button was released.
+    Release(u16, u16),
+    /// The mouse was moved with a button held.
+    Hold(u16, u16),
+}
+
+/// A mouse button.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum MouseButton {
+    /// The left button.
+    Left,
+    /// The right button.
+    Right,
+    /// The middle button.
+    Middle,
+    /// The wheel was moved up.
+    WheelUp,
+    /// The wheel was moved down.
+    WheelDown,
+}
+
+/// A key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Key {
+    /// Backspace.
+    Backspace,
+    /// Left arrow.
+    Left,
+    /// Right arrow.
+    Right,
+    /// Up arrow.
+    Up,
+    /// Down arrow.
+    Down,
+    /// Home key.
+    Home,
+    /// End key.
+    End,
+    /// Page up.
+    PageUp,
+    /// Page down.
+    PageDown,
+    /// Shift + Tab.
+    BackTab,
+    /// Delete.
+    Delete,
+    /// Insert.
+    Insert,
+    /// A function key, `F(1)` is F1.
+    F(u8),
+    /// A character, `Char('\n')` is Enter and `Char('\t')` is Tab.
+    Char(char),
+    /// Alt + a character.
+    Alt(char),
+    /// Ctrl + a character, the letters are lowercase.
+    Ctrl(char),
+    /// Null.
+    Null,
+    /// Escape.
+    Esc,
+}
+
+impl TryFrom<crossterm::KeyEvent> for Key {
+    type Error = crossterm::KeyEvent;
+
+    /// Converts the key, termion doesn't report the modifiers of the other keys than the
+    /// characters, they're left out. The releases of the keys aren't reported either.
+    fn try_from(event: crossterm::KeyEvent) -> Result<Key, crossterm::KeyEvent> {
+        if event.kind == KeyEventKind::Release {
+            return Err(event);
+        }
+
+        let alt = event.modifiers.contains(KeyModifiers::ALT);
+        let control = event.modifiers.contains(KeyModifiers::CONTROL);
+
+        Ok(match event.code {
+            KeyCode::Backspace => Key::Backspace,
+            KeyCode::Enter => Key::Char('\n'),
+            KeyCode::Left => Key::Left,
+            KeyCode::Right => Key::Right,
+            KeyCode::Up => Key::Up,
+            KeyCode::Down => Key::Down,
+            KeyCode::Home => Key::Home,
+            KeyCode::End => Key::End,
+            KeyCode::PageUp => Key::PageUp,
+            KeyCode::PageDown => Key::PageDown,
+            KeyCode::Tab => Key::Char('\t'),
+            KeyCode::BackTab => Key::BackTab,
+            KeyCode::Delete => Key::Delete,
+            KeyCode::Insert => Key::Insert,
+            KeyCode::F(n) => Key::F(n),
+            // Termion reads `ESC` and a control character as Alt and the control character
+            KeyCode::Char(c) if alt && control => match control_character(c) {
+                Some(c) => Key::Alt(c),
+                None => return Err(event),
+            },
+            KeyCode::Char(c) if alt => Key::Alt(c),
+            KeyCode::Char(c) if control => Key::Ctrl(c),
+            KeyCode::Char(c) => Key::Char(c),
+            KeyCode::Null => Key::Null,
+            KeyCode::Esc => Key::Esc,
+            KeyCode::Media(_) | KeyCode::Modifier(_) => return Err(event),
+        })
+    }
+}
+
+impl TryFrom<crossterm::MouseEvent> for MouseEvent {
+    type Error = crossterm::MouseEvent;
+
+    fn try_from(event: crossterm::MouseEvent) -> Result<MouseEvent, crossterm::MouseEvent> {
+        let (column, row) = (event.column + 1, event.row + 1);
+
+        Ok(match event.kind {
+            MouseEventKind::Down(button) => MouseEvent::Press(button.into(), column, row),
+            MouseEventKind::ScrollUp => MouseEvent::Press(MouseButton::WheelUp, column, row),
+            MouseEventKind::ScrollDown => MouseEvent::Press(MouseButton::WheelDown, column, row),
+            MouseEventKind::Up(_) => MouseEvent::Release(column, row),
+            MouseEventKind::Drag(_) => MouseEvent::Hold(column, row),
+            // termion doesn't have the horizontal wheel, the presses of the clicks are reported
+            MouseEventKind::Moved
+            | MouseEventKind::ScrollLeft
+            | MouseEventKind::ScrollRight
+            | MouseEventKind::DoubleClick(_)
+            | MouseEventKind::TripleClick(_) => return Err(event),
+        })
+    }
+}
+
+impl From<crossterm::MouseButton> for MouseButton {
+    fn from(button: crossterm::MouseButton) -> MouseButton {
+        match button {
+            crossterm::MouseButton::Left => MouseButton::Left,
+            crossterm::MouseButton::Right => MouseButton::Right,
+            crossterm::MouseButton::Middle => MouseButton::Middle,
+        }
+    }
+}
+
+impl TryFrom<crossterm::Event> for Event {
+    type Error = crossterm::Event;
+
+    fn try_from(event: crossterm::Event) -> Result<Event, crossterm::Event> {
+        match event {
+            crossterm::Event::Key(key) => Key::try_from(key)
+                .map(Event::Key)
+                .map_err(crossterm::Event::Key),
+            crossterm::Event::Mouse(mouse) => MouseEvent::try_from(mouse)
+                .map(Event::Mouse)
+                .map_err(crossterm::Event::Mouse),
+            crossterm::Event::Unsupported(bytes) => Ok(Event::Unsupported(bytes)),
+            event => Err(event),
+        }
+    }
+}
+
+/// Returns the control character Ctrl and the character produce, `None` if there's none.
+fn control_character(c: char) -> Option<char> {
+    match c {
+        'a'..='z' | 'A'..='Z' => Some((c.to_ascii_uppercase() as u8 - b'@') as char),
+        ' ' | '@' => Some('\0'),
+        '[' => Some('\x1B'),
+        '\\' => Some('\x1C'),
+        ']' => Some('\x1D'),
+        '^' => Some('\x1E'),
+        '_' => Some('\x1F'),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::{Event, Key, MouseButton, MouseEvent};
+    use crate::event::{
+        self as crossterm, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseEventKind,
+        ScrollDelta,
+    };
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> Result<Key, KeyEvent> {
+        Key::try_from(KeyEvent::new(code, modifiers))
+    }
+
+    #[test]
+    fn test_keys() {
+        assert_eq!(
+            key(KeyCode::Char('a'), KeyModifiers::NONE),
+            Ok(Key::Char('a'))
+        );
+        assert_eq!(
+            key(KeyCode::Char('A'), KeyModifiers::SHIFT),
+            Ok(Key::Char('A'))
+        );
+        assert_eq!(
+            key(KeyCode::Char('c'), KeyModifiers::CONTROL),
+            Ok(Key::Ctrl('c'))
+        );
+        assert_eq!(
+            key(KeyCode::Char('x'), KeyModifiers::ALT),
+            Ok(Key::Alt('x'))
+        );
+        assert_eq!(
+            key(
+                KeyCode::Char('c'),
+                KeyModifiers::CONTROL | KeyModifiers::ALT
+            ),
+            Ok(Key::Alt('\x03'))
+        );
+        assert_eq!(key(KeyCode::Enter, KeyModifiers::NONE), Ok(Key::Char('\n')));
+        assert_eq!(key(KeyCode::Up, KeyModifiers::SHIFT), Ok(Key::Up));
+        assert_eq!(key(KeyCode::F(5), KeyModifiers::NONE), Ok(Key::F(5)));
+
+        let release = KeyEvent::new_with_kind(
+            KeyCode::Char('a'),
+            KeyModifiers::NONE,
+            KeyEventKind::Release,
+        );
+        assert_eq!(Key::try_from(release), Err(release));
+    }
+
+    #[test]
+    fn test_events() {
+        let mouse = |kind| {
+            crossterm::Event::Mouse(crossterm::MouseEvent {
+                kind,
+                column: 0,
+                row: 9,
+                modifiers: KeyModifiers::NONE,
+                scroll_delta: ScrollDelta::default(),
+            })
+        };
+
+        assert_eq!(
+            Event::try_from(mouse(MouseEventKind::Down(crossterm::MouseButton::Left))),
+            Ok(Event::Mouse(MouseEvent::Press(MouseButton::Left, 1, 10)))
+        );
+        assert_eq!(
+            Event::try_from(mouse(MouseEventKind::ScrollDown)),
+            Ok(Event::Mouse(MouseEvent::Press(
+                MouseButton::WheelDown,
+                1,
+                10
+            )))
+        );
+        assert_eq!(
+            Event::try_from(mouse(MouseEventKind::Moved)),
+            Err(mouse(MouseEventKind::Moved))
+        );
+        assert_eq!(
+            Event::try_from(crossterm::Event::Resize(80, 24)),
+            Err(crossterm::Event::Resize(80, 24))
+        );
+    }
+}
@@ -0,0 +1,130 @@
+//! Reading the keys and the events, and the mouse support.
+
+use std::{
+    convert::TryFrom,
+    io::{self, Write},
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
+
+use super::event::{Event, Key};
+use crate::{
+    event::{self, DisableMouseCapture, EnableMouseCapture},
+    ExecutableCommand,
+};
+
+/// Reads the keys and the events of the terminal.
+///
+/// It's implemented for the standard input, the events are read with
+/// [`event::read`](../../../event/fn.read.html).
+pub trait TermRead: Sized {
+    /// Returns an iterator over the events.
+    fn events(self) -> Events<Self>;
+
+    /// Returns an iterator over the keys, the other events are skipped.
+    fn keys(self) -> Keys<Self>;
+}
+
+impl TermRead for io::Stdin {
+    fn events(self) -> Events<Self> {
+        Events(PhantomData)
+    }
+
+    fn keys(self) -> Keys<Self> {
+        Keys(PhantomData)
+    }
+}
+
+impl<'a> TermRead for io::StdinLock<'a> {
+    fn events(self) -> Events<Self> {
+        Events(PhantomData)
+    }
+
+    fn keys(self) -> Keys<Self> {
+        Keys(PhantomData)
+    }
+}
+
+/// An iterator over the events, it never ends.
+#[derive(Debug)]
+pub struct Events<R>(PhantomData<R>);
+
+impl<R> Iterator for Events<R> {
+    type Item = io::Result<Event>;
+
+    fn next(&mut self) -> Option<io::Result<Event>> {
+        loop {
+            match event::read() {
+                Ok(event) => {
+                    if let Ok(event) = Event::try_from(event) {
+                        return Some(Ok(event));
+                    }
+                }
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// An iterator over the keys, it never ends.
+#[derive(Debug)]
+pub struct Keys<R>(PhantomData<R>);
+
+impl<R> Iterator for Keys<R> {
+    type Item = io::Result<Key>;
+
+    fn next(&mut self) -> Option<io::Result<Key>> {
+        let mut events = Events::<R>(PhantomData);
+        loop {
+            match events.next()? {
+                Ok(Event::Key(key)) => return Some(Ok(key)),
+                Ok(_) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// A writer with the mouse events enabled, they're disabled again when it's dropped.
+#[derive(Debug)]
+pub struct MouseTerminal<W: Write> {
+    output: W,
+}
+
+impl<W: Write> From<W> for MouseTerminal<W> {
+    fn from(mut output: W) -> MouseTerminal<W> {
+        // Termion ignores the errors too
+        let _ = output.execute(EnableMouseCapture);
+        MouseTerminal { output }
+    }
+}
+
+impl<W: Write> Drop for MouseTerminal<W> {
+    fn drop(&mut self) {
+        let _ = self.output.execute(DisableMouseCapture);
+    }
+}
+
+impl<W: Write> Deref for MouseTerminal<W> {
+    type Target = W;
+
+    fn deref(&self) -> &W {
+        &self.output
+    }
+}
+
+impl<W: Write> DerefMut for MouseTerminal<W> {
+    fn deref_mut(&mut self) -> &mut W {
+        &mut self.output
+    }
+}
+
+impl<W: Write> Write for MouseTerminal<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.output.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
@@ -0,0 +1,72 @@
+//! The raw mode.
+//!
+//! The raw mode is the one of the terminal of this process, see
+//! [`terminal::enable_raw_mode`](../../../terminal/fn.enable_raw_mode.html).
+
+use std::{
+    io::{self, Write},
+    ops::{Deref, DerefMut},
+};
+
+use crate::terminal;
+
+/// A writer with the raw mode enabled, it's disabled again when the writer is dropped.
+#[derive(Debug)]
+pub struct RawTerminal<W: Write> {
+    output: W,
+}
+
+impl<W: Write> RawTerminal<W> {
+    /// Disables the raw mode until [`activate_raw_mode`](#method.activate_raw_mode).
+    pub fn suspend_raw_mode(&self) -> io::Result<()> {
+        terminal::disable_raw_mode()
+    }
+
+    /// Enables the raw mode again.
+    pub fn activate_raw_mode(&self) -> io::Result<()> {
+        terminal::enable_raw_mode()
+    }
+}
+
+impl<W: Write> Drop for RawTerminal<W> {
+    fn drop(&mut self) {
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+impl<W: Write> Deref for RawTerminal<W> {
+    type Target = W;
+
+    fn deref(&self) -> &W {
+        &self.output
+    }
+}
+
+impl<W: Write> DerefMut for RawTerminal<W> {
+    fn deref_mut(&mut self) -> &mut W {
+        &mut self.output
+    }
+}
+
+impl<W: Write> Write for RawTerminal<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.output.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
+
+/// Switches to the raw mode for as long as the writer lives.
+pub trait IntoRawMode: Write + Sized {
+    /// Enables the raw mode and returns the writer, the raw mode is disabled when it's dropped.
+    fn into_raw_mode(self) -> io::Result<RawTerminal<Self>>;
+}
+
+impl<W: Write> IntoRawMode for W {
+    fn into_raw_mode(self) -> io::Result<RawTerminal<W>> {
+        terminal::enable_raw_mode()?;
+        Ok(RawTerminal { output: self })
+    }
+}
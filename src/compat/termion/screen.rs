@@ -0,0 +1,82 @@
+//! The alternate screen.
+
+use std::{
+    io::{self, Write},
+    ops::{Deref, DerefMut},
+};
+
+use crate::{
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen},
+    ExecutableCommand,
+};
+
+/// Switches to the alternate screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ToAlternateScreen;
+
+/// Switches back to the main screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ToMainScreen;
+
+delegate_command! {
+    ToAlternateScreen => |_| EnterAlternateScreen;
+    ToMainScreen => |_| LeaveAlternateScreen;
+}
+
+/// A writer showing the alternate screen, the main screen is shown again when it's dropped.
+#[derive(Debug)]
+pub struct AlternateScreen<W: Write> {
+    output: W,
+}
+
+impl<W: Write> From<W> for AlternateScreen<W> {
+    fn from(mut output: W) -> AlternateScreen<W> {
+        // Termion ignores the errors too
+        let _ = output.execute(EnterAlternateScreen);
+        AlternateScreen { output }
+    }
+}
+
+impl<W: Write> Drop for AlternateScreen<W> {
+    fn drop(&mut self) {
+        let _ = self.output.execute(LeaveAlternateScreen);
+    }
+}
+
+impl<W: Write> Deref for AlternateScreen<W> {
+    type Target = W;
+
+    fn deref(&self) -> &W {
+        &self.output
+    }
+}
+
+impl<W: Write> DerefMut for AlternateScreen<W> {
+    fn deref_mut(&mut self) -> &mut W {
+        &mut self.output
+    }
+}
+
+impl<W: Write> Write for AlternateScreen<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.output.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
+
+/// Switches to the alternate screen for as long as the writer lives.
+pub trait IntoAlternateScreen: Write + Sized {
+    /// Shows the alternate screen and returns the writer, the main screen is shown again when
+    /// it's dropped.
+    fn into_alternate_screen(self) -> io::Result<AlternateScreen<Self>>;
+}
+
+impl<W: Write> IntoAlternateScreen for W {
+    fn into_alternate_screen(mut self) -> io::Result<AlternateScreen<W>> {
+        self.execute(EnterAlternateScreen)?;
+        Ok(AlternateScreen { output: self })
+    }
+}
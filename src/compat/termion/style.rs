@@ -0,0 +1,66 @@
+//! Text attributes.
+
+use crate::style::{Attribute, SetAttribute};
+
+macro_rules! styles {
+    ($($(#[$meta:meta])* $name:ident => $attribute:ident,)*) => {
+        $(
+            $(#[$meta])*
+            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            pub struct $name;
+        )*
+
+        delegate_command! {
+            $($name => |_| SetAttribute(Attribute::$attribute);)*
+        }
+    };
+}
+
+styles! {
+    /// Resets all the attributes and the colors.
+    Reset => Reset,
+    /// Bold text.
+    Bold => Bold,
+    /// Faint text.
+    Faint => Dim,
+    /// Italic text.
+    Italic => Italic,
+    /// Underlined text.
+    Underline => Underlined,
+    /// Blinking text.
+    Blink => SlowBlink,
+    /// Inverted colors.
+    Invert => Reverse,
+    /// Crossed out text.
+    CrossedOut => CrossedOut,
+    /// Framed text.
+    Framed => Framed,
+    /// Turns bold off, it's a double underline on some terminals like in termion.
+    NoBold => NoBold,
+    /// Turns bold and faint off.
+    NoFaint => NormalIntensity,
+    /// Turns italic off.
+    NoItalic => NoItalic,
+    /// Turns underline off.
+    NoUnderline => NoUnderline,
+    /// Turns blinking off.
+    NoBlink => NoBlink,
+    /// Turns inverted colors off.
+    NoInvert => NoReverse,
+    /// Turns crossed out off.
+    NoCrossedOut => NotCrossedOut,
+    /// Turns framed off.
+    NoFramed => NotFramedOrEncircled,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Bold, NoFaint, Reset};
+
+    #[test]
+    fn test_styles() {
+        assert_eq!(Bold.to_string(), "\x1B[1m");
+        assert_eq!(NoFaint.to_string(), "\x1B[22m");
+        assert_eq!(Reset.to_string(), "\x1B[0m");
+    }
+}
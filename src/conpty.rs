@@ -0,0 +1,566 @@
+//! # ConPTY
+//!
+//! The `conpty` module hosts console programs in a pseudo console, like a terminal emulator,
+//! a terminal multiplexer or the terminal of an IDE does.
+//!
+//! A [`PseudoConsole`](struct.PseudoConsole.html) runs a program in a console of its own which
+//! isn't displayed: the output of the program comes out as UTF-8 text and escape sequences, to be
+//! drawn by the application, and the input goes in as the bytes a terminal would send. The events
+//! read with [`event::read`](../event/fn.read.html) are forwarded with
+//! [`write_event`](struct.PseudoConsole.html#method.write_event).
+//!
+//! The pseudo consoles need Windows 10 1809 or newer, spawning fails with
+//! `io::ErrorKind::Unsupported` on the older versions.
+//!
+//! Only available on Windows.
+//!
+//! ## Examples
+//!
+//! Runs `cmd.exe` in the current terminal until it exits:
+//!
+//! ```no_run
+//! use std::{
+//!     io::{stdout, Write},
+//!     time::Duration,
+//! };
+//!
+//! use crossterm::{conpty::PseudoConsole, event, terminal, Result};
+//!
+//! fn main() -> Result<()> {
+//!     let (columns, rows) = terminal::size()?;
+//!     let mut console = PseudoConsole::spawn("cmd.exe", columns, rows)?;
+//!
+//!     terminal::enable_raw_mode()?;
+//!     while console.wait(Duration::from_secs(0))?.is_none() {
+//!         while let Some(output) = console.read_output(Some(Duration::from_millis(10)))? {
+//!             stdout().write_all(&output)?;
+//!         }
+//!         stdout().flush()?;
+//!
+//!         if event::poll(Duration::from_millis(10))? {
+//!             console.write_event(&event::read()?)?;
+//!         }
+//!     }
+//!     terminal::disable_raw_mode()
+//! }
+//! ```
+
+use std::{
+    ffi::OsStr,
+    fs::File,
+    io::{self, Read, Write},
+    iter, mem,
+    os::windows::{
+        ffi::OsStrExt,
+        io::{AsRawHandle, FromRawHandle, RawHandle},
+    },
+    ptr,
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    thread,
+    time::Duration,
+};
+
+use crossterm_winapi::{result, Handle};
+use winapi::{
+    shared::{
+        basetsd::SIZE_T,
+        minwindef::{DWORD, FALSE, FARPROC},
+        ntdef::HRESULT,
+        winerror::WAIT_TIMEOUT,
+    },
+    um::{
+        handleapi::INVALID_HANDLE_VALUE,
+        libloaderapi::{GetModuleHandleW, GetProcAddress},
+        namedpipeapi::CreatePipe,
+        processthreadsapi::{
+            CreateProcessW, DeleteProcThreadAttributeList, GetExitCodeProcess,
+            InitializeProcThreadAttributeList, TerminateProcess, UpdateProcThreadAttribute,
+            PROCESS_INFORMATION, PROC_THREAD_ATTRIBUTE_LIST,
+        },
+        synchapi::WaitForSingleObject,
+        winbase::{
+            EXTENDED_STARTUPINFO_PRESENT, INFINITE, STARTF_USESTDHANDLES, STARTUPINFOEXW,
+            WAIT_OBJECT_0,
+        },
+        wincontypes::{COORD, HPCON},
+        winnt::HANDLE,
+    },
+};
+
+use crate::{
+    event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    Result,
+};
+
+/// The attribute giving the pseudo console to `CreateProcessW`, missing from winapi.
+const PROC_THREAD_ATTRIBUTE_PSEUDOCONSOLE: usize = 0x0002_0016;
+
+/// The size of the chunks the output is read in.
+const OUTPUT_BUFFER_SIZE: usize = 4096;
+
+type CreatePseudoConsoleFn =
+    unsafe extern "system" fn(COORD, HANDLE, HANDLE, DWORD, *mut HPCON) -> HRESULT;
+type ResizePseudoConsoleFn = unsafe extern "system" fn(HPCON, COORD) -> HRESULT;
+type ClosePseudoConsoleFn = unsafe extern "system" fn(HPCON);
+
+/// The pseudo console functions of `kernel32.dll`.
+///
+/// They are looked up when a pseudo console is spawned, linking them would keep the applications
+/// from starting on the Windows versions without them.
+#[derive(Debug, Clone, Copy)]
+struct ConPtyApi {
+    create: CreatePseudoConsoleFn,
+    resize: ResizePseudoConsoleFn,
+    close: ClosePseudoConsoleFn,
+}
+
+impl ConPtyApi {
+    fn load() -> Result<ConPtyApi> {
+        let kernel32 = wide("kernel32.dll");
+        let module = unsafe { GetModuleHandleW(kernel32.as_ptr()) };
+        if module.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+
+        let function = |name: &[u8]| {
+            let address = unsafe { GetProcAddress(module, name.as_ptr() as *const i8) };
+            if address.is_null() {
+                Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "Pseudo consoles need Windows 10 1809 or newer",
+                ))
+            } else {
+                Ok(address)
+            }
+        };
+
+        unsafe {
+            Ok(ConPtyApi {
+                create: mem::transmute::<FARPROC, CreatePseudoConsoleFn>(function(
+                    b"CreatePseudoConsole\0",
+                )?),
+                resize: mem::transmute::<FARPROC, ResizePseudoConsoleFn>(function(
+                    b"ResizePseudoConsole\0",
+                )?),
+                close: mem::transmute::<FARPROC, ClosePseudoConsoleFn>(function(
+                    b"ClosePseudoConsole\0",
+                )?),
+            })
+        }
+    }
+}
+
+/// A console program running in a pseudo console.
+///
+/// The output of the program is read with [`read_output`](#method.read_output), its input is
+/// written with [`write`](#method.write) or [`write_event`](#method.write_event).
+///
+/// The pseudo console is closed when the `PseudoConsole` is dropped, which ends the programs
+/// attached to it.
+#[derive(Debug)]
+pub struct PseudoConsole {
+    api: ConPtyApi,
+    console: HPCON,
+    input: File,
+    /// The output read by a thread, reading a pipe can't time out.
+    output: Receiver<Vec<u8>>,
+    process: Handle,
+}
+
+// The pseudo console can be used and closed from any thread
+unsafe impl Send for PseudoConsole {}
+
+impl PseudoConsole {
+    /// Spawns the command line in a new pseudo console with the given size.
+    ///
+    /// The command line is given to `CreateProcessW` as it is, the arguments with spaces must be
+    /// quoted. The program gets the environment and the working directory of this process.
+    pub fn spawn(
+        command_line: impl AsRef<OsStr>,
+        columns: u16,
+        rows: u16,
+    ) -> Result<PseudoConsole> {
+        let api = ConPtyApi::load()?;
+
+        // The pseudo console reads `console_input` and writes `console_output`
+        let (console_input, input) = pipe()?;
+        let (output, console_output) = pipe()?;
+
+        let mut console = ptr::null_mut();
+        hresult(unsafe {
+            (api.create)(
+                coord(columns, rows),
+                console_input.as_raw_handle() as HANDLE,
+                console_output.as_raw_handle() as HANDLE,
+                0,
+                &mut console,
+            )
+        })?;
+
+        let process = match spawn_attached(console, command_line.as_ref()) {
+            Ok(process) => process,
+            Err(e) => {
+                unsafe { (api.close)(console) };
+                return Err(e);
+            }
+        };
+
+        // The pseudo console keeps its own copies of the handles, the output pipe is closed when
+        // it's closed
+        drop((console_input, console_output));
+
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let mut output = output;
+            let mut buffer = [0u8; OUTPUT_BUFFER_SIZE];
+            while let Ok(count) = output.read(&mut buffer) {
+                if count == 0 || sender.send(buffer[..count].to_vec()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(PseudoConsole {
+            api,
+            console,
+            input,
+            output: receiver,
+            process,
+        })
+    }
+
+    /// Writes the bytes to the input of the program, as if they were typed in a terminal.
+    pub fn write(&mut self, input: &[u8]) -> Result<()> {
+        self.input.write_all(input)?;
+        self.input.flush()
+    }
+
+    /// Forwards an event to the program.
+    ///
+    /// The key events are written as the escape sequences of a terminal, a resize event resizes
+    /// the pseudo console. The other events are ignored, the pseudo console doesn't take mouse
+    /// events.
+    pub fn write_event(&mut self, event: &Event) -> Result<()> {
+        match event {
+            Event::Key(key) => match encode_key(key) {
+                Some(input) => self.write(&input),
+                None => Ok(()),
+            },
+            Event::Resize(columns, rows) => self.resize(*columns, *rows),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the output of the program which was written since the last call, UTF-8 text and
+    /// escape sequences.
+    ///
+    /// Waits for the output at most as long as the timeout, forever if it's `None`. Returns
+    /// `None` if there was no output in time.
+    ///
+    /// Fails with `io::ErrorKind::BrokenPipe` once the whole output was read and the pseudo
+    /// console can't write anymore.
+    pub fn read_output(&mut self, timeout: Option<Duration>) -> Result<Option<Vec<u8>>> {
+        let output = match timeout {
+            Some(timeout) => self.output.recv_timeout(timeout),
+            None => self
+                .output
+                .recv()
+                .map_err(|_| RecvTimeoutError::Disconnected),
+        };
+
+        match output {
+            Ok(mut output) => {
+                // Everything which was read already
+                while let Ok(more) = self.output.try_recv() {
+                    output.extend_from_slice(&more);
+                }
+                Ok(Some(output))
+            }
+            Err(RecvTimeoutError::Timeout) => Ok(None),
+            Err(RecvTimeoutError::Disconnected) => Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "The pseudo console was closed",
+            )),
+        }
+    }
+
+    /// Resizes the pseudo console, the program gets a resize event.
+    pub fn resize(&mut self, columns: u16, rows: u16) -> Result<()> {
+        hresult(unsafe { (self.api.resize)(self.console, coord(columns, rows)) })
+    }
+
+    /// Waits at most as long as the timeout for the program to exit.
+    ///
+    /// Returns the exit code of the program, `None` if it's still running.
+    pub fn wait(&mut self, timeout: Duration) -> Result<Option<u32>> {
+        let millis = timeout.as_millis().min(u128::from(INFINITE - 1)) as DWORD;
+
+        match unsafe { WaitForSingleObject(*self.process, millis) } {
+            WAIT_OBJECT_0 => {
+                let mut code = 0;
+                result(unsafe { GetExitCodeProcess(*self.process, &mut code) })?;
+                Ok(Some(code))
+            }
+            WAIT_TIMEOUT => Ok(None),
+            _ => Err(io::Error::last_os_error()),
+        }
+    }
+
+    /// Terminates the program.
+    pub fn kill(&mut self) -> Result<()> {
+        result(unsafe { TerminateProcess(*self.process, 1) })
+    }
+}
+
+impl Drop for PseudoConsole {
+    fn drop(&mut self) {
+        // It waits until the output is read, which the output thread does until the pipe is closed
+        unsafe { (self.api.close)(self.console) };
+    }
+}
+
+/// Starts the command line attached to the pseudo console and returns the process handle.
+fn spawn_attached(console: HPCON, command_line: &OsStr) -> Result<Handle> {
+    // The attribute list is an opaque structure, its size is asked first
+    let mut size: SIZE_T = 0;
+    unsafe { InitializeProcThreadAttributeList(ptr::null_mut(), 1, 0, &mut size) };
+    let mut attributes = vec![0u64; size / mem::size_of::<u64>() + 1];
+    let attribute_list = attributes.as_mut_ptr() as *mut PROC_THREAD_ATTRIBUTE_LIST;
+    result(unsafe { InitializeProcThreadAttributeList(attribute_list, 1, 0, &mut size) })?;
+
+    let spawned = (|| -> Result<Handle> {
+        result(unsafe {
+            UpdateProcThreadAttribute(
+                attribute_list,
+                0,
+                PROC_THREAD_ATTRIBUTE_PSEUDOCONSOLE,
+                console,
+                mem::size_of::<HPCON>(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+            )
+        })?;
+
+        let mut startup_info: STARTUPINFOEXW = unsafe { mem::zeroed() };
+        startup_info.StartupInfo.cb = mem::size_of::<STARTUPINFOEXW>() as DWORD;
+        // Without standard handles of its own, the program would inherit the redirected ones of
+        // this process instead of using the pseudo console
+        startup_info.StartupInfo.dwFlags = STARTF_USESTDHANDLES;
+        startup_info.StartupInfo.hStdInput = INVALID_HANDLE_VALUE;
+        startup_info.StartupInfo.hStdOutput = INVALID_HANDLE_VALUE;
+        startup_info.StartupInfo.hStdError = INVALID_HANDLE_VALUE;
+        startup_info.lpAttributeList = attribute_list;
+
+        // `CreateProcessW` may change the command line
+        let mut command_line: Vec<u16> = command_line.encode_wide().chain(iter::once(0)).collect();
+        let mut process_info: PROCESS_INFORMATION = unsafe { mem::zeroed() };
+        result(unsafe {
+            CreateProcessW(
+                ptr::null(),
+                command_line.as_mut_ptr(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                FALSE,
+                EXTENDED_STARTUPINFO_PRESENT,
+                ptr::null_mut(),
+                ptr::null(),
+                &mut startup_info.StartupInfo,
+                &mut process_info,
+            )
+        })?;
+
+        unsafe {
+            drop(Handle::from_raw(process_info.hThread));
+            Ok(Handle::from_raw(process_info.hProcess))
+        }
+    })();
+
+    unsafe { DeleteProcThreadAttributeList(attribute_list) };
+    spawned
+}
+
+/// Creates an anonymous pipe, returns the ends to read and to write.
+fn pipe() -> Result<(File, File)> {
+    let (mut read, mut write) = (ptr::null_mut(), ptr::null_mut());
+    result(unsafe { CreatePipe(&mut read, &mut write, ptr::null_mut(), 0) })?;
+    unsafe {
+        Ok((
+            File::from_raw_handle(read as RawHandle),
+            File::from_raw_handle(write as RawHandle),
+        ))
+    }
+}
+
+fn hresult(result: HRESULT) -> Result<()> {
+    if result < 0 {
+        Err(io::Error::from_raw_os_error(result))
+    } else {
+        Ok(())
+    }
+}
+
+fn coord(columns: u16, rows: u16) -> COORD {
+    COORD {
+        X: columns.min(i16::MAX as u16) as i16,
+        Y: rows.min(i16::MAX as u16) as i16,
+    }
+}
+
+fn wide(text: &str) -> Vec<u16> {
+    OsStr::new(text)
+        .encode_wide()
+        .chain(iter::once(0))
+        .collect()
+}
+
+/// Returns the escape sequence an xterm compatible terminal sends for the key, `None` if there's
+/// none.
+fn encode_key(key: &KeyEvent) -> Option<Vec<u8>> {
+    if key.kind == KeyEventKind::Release {
+        return None;
+    }
+
+    // The modifier parameter of the sequences, 1 without modifiers
+    let modifiers = 1 + [
+        KeyModifiers::SHIFT,
+        KeyModifiers::ALT,
+        KeyModifiers::CONTROL,
+    ]
+    .iter()
+    .enumerate()
+    .filter(|(_, modifier)| key.modifiers.contains(**modifier))
+    .map(|(bit, _)| 1 << bit)
+    .sum::<u8>();
+    let alt = key.modifiers.contains(KeyModifiers::ALT);
+
+    // `ESC [ <final>` or `ESC [ 1 ; <modifiers> <final>`
+    let cursor_key = |final_byte: char| {
+        if modifiers == 1 {
+            format!("\x1B[{}", final_byte)
+        } else {
+            format!("\x1B[1;{}{}", modifiers, final_byte)
+        }
+    };
+    // `ESC [ <number> ~` or `ESC [ <number> ; <modifiers> ~`
+    let tilde_key = |number: u8| {
+        if modifiers == 1 {
+            format!("\x1B[{}~", number)
+        } else {
+            format!("\x1B[{};{}~", number, modifiers)
+        }
+    };
+    // The other keys get an `ESC` in front with Alt
+    let with_alt = |text: &str| {
+        if alt {
+            format!("\x1B{}", text)
+        } else {
+            text.to_string()
+        }
+    };
+
+    let sequence = match key.code {
+        KeyCode::Up => cursor_key('A'),
+        KeyCode::Down => cursor_key('B'),
+        KeyCode::Right => cursor_key('C'),
+        KeyCode::Left => cursor_key('D'),
+        KeyCode::Home => cursor_key('H'),
+        KeyCode::End => cursor_key('F'),
+        KeyCode::Insert => tilde_key(2),
+        KeyCode::Delete => tilde_key(3),
+        KeyCode::PageUp => tilde_key(5),
+        KeyCode::PageDown => tilde_key(6),
+        KeyCode::F(n @ 1..=4) if modifiers == 1 => format!("\x1BO{}", (b'P' + n - 1) as char),
+        KeyCode::F(n @ 1..=4) => cursor_key((b'P' + n - 1) as char),
+        KeyCode::F(n @ 5..=12) => tilde_key([15, 17, 18, 19, 20, 21, 23, 24][n as usize - 5]),
+        KeyCode::F(_) => return None,
+        KeyCode::Enter => with_alt("\r"),
+        KeyCode::Tab => with_alt("\t"),
+        KeyCode::BackTab => "\x1B[Z".to_string(),
+        KeyCode::Esc => with_alt("\x1B"),
+        KeyCode::Backspace if key.modifiers.contains(KeyModifiers::CONTROL) => with_alt("\x08"),
+        KeyCode::Backspace => with_alt("\x7F"),
+        KeyCode::Null => with_alt("\0"),
+        // No VT sequence without the kitty keyboard protocol
+        KeyCode::Media(_) | KeyCode::Modifier(_) => return None,
+        KeyCode::Char(c) if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            let control = match c {
+                'a'..='z' | 'A'..='Z' => (c.to_ascii_uppercase() as u8 - b'@') as char,
+                ' ' | '@' | '2' => '\0',
+                '[' | '3' => '\x1B',
+                '\\' | '4' => '\x1C',
+                ']' | '5' => '\x1D',
+                '^' | '6' => '\x1E',
+                '_' | '7' => '\x1F',
+                _ => c,
+            };
+            with_alt(control.encode_utf8(&mut [0; 4]))
+        }
+        KeyCode::Char(c) => with_alt(c.encode_utf8(&mut [0; 4])),
+    };
+    Some(sequence.into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+    use super::{encode_key, PseudoConsole};
+
+    fn encoded(code: KeyCode, modifiers: KeyModifiers) -> String {
+        String::from_utf8(encode_key(&KeyEvent::new(code, modifiers)).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_encode_keys() {
+        assert_eq!(encoded(KeyCode::Char('a'), KeyModifiers::NONE), "a");
+        assert_eq!(encoded(KeyCode::Char('é'), KeyModifiers::ALT), "\x1Bé");
+        assert_eq!(encoded(KeyCode::Char('c'), KeyModifiers::CONTROL), "\x03");
+        assert_eq!(
+            encoded(
+                KeyCode::Char('['),
+                KeyModifiers::CONTROL | KeyModifiers::ALT
+            ),
+            "\x1B\x1B"
+        );
+        assert_eq!(encoded(KeyCode::Enter, KeyModifiers::NONE), "\r");
+        assert_eq!(encoded(KeyCode::Backspace, KeyModifiers::NONE), "\x7F");
+        assert_eq!(encoded(KeyCode::BackTab, KeyModifiers::SHIFT), "\x1B[Z");
+        assert!(encode_key(&KeyEvent::new(KeyCode::F(13), KeyModifiers::NONE)).is_none());
+    }
+
+    #[test]
+    fn test_encode_keys_with_modifiers() {
+        assert_eq!(encoded(KeyCode::Up, KeyModifiers::NONE), "\x1B[A");
+        assert_eq!(
+            encoded(KeyCode::Left, KeyModifiers::SHIFT | KeyModifiers::CONTROL),
+            "\x1B[1;6D"
+        );
+        assert_eq!(encoded(KeyCode::Delete, KeyModifiers::ALT), "\x1B[3;3~");
+        assert_eq!(encoded(KeyCode::F(1), KeyModifiers::NONE), "\x1BOP");
+        assert_eq!(encoded(KeyCode::F(4), KeyModifiers::SHIFT), "\x1B[1;2S");
+        assert_eq!(encoded(KeyCode::F(12), KeyModifiers::CONTROL), "\x1B[24;5~");
+    }
+
+    #[test]
+    fn test_spawn_reads_output_and_exit_code() {
+        let mut console =
+            PseudoConsole::spawn("cmd.exe /c echo crossterm & exit 3", 80, 24).unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut output = Vec::new();
+        while !String::from_utf8_lossy(&output).contains("crossterm") {
+            assert!(Instant::now() < deadline, "no output: {:?}", output);
+            if let Some(more) = console
+                .read_output(Some(Duration::from_millis(100)))
+                .unwrap()
+            {
+                output.extend_from_slice(&more);
+            }
+        }
+
+        console.write_event(&Event::Resize(100, 30)).unwrap();
+        assert_eq!(console.wait(Duration::from_secs(10)).unwrap(), Some(3));
+    }
+}
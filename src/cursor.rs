@@ -42,11 +42,9 @@
 //!
 //! For manual execution control check out [crossterm::queue](../macro.queue.html).
 
-use std::fmt;
+use std::{fmt, io};
 
-#[cfg(windows)]
-use crate::Result;
-use crate::{csi, impl_display, Command};
+use crate::{command::write_u16, csi, impl_display, Command, ErrorKind, Result};
 
 pub use sys::position;
 
@@ -63,13 +61,30 @@ pub struct MoveTo(pub u16, pub u16);
 
 impl Command for MoveTo {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
-        write!(f, csi!("{};{}H"), self.1 + 1, self.0 + 1)
+        f.write_str(csi!())?;
+        write_u16(f, self.1.saturating_add(1))?;
+        f.write_str(";")?;
+        write_u16(f, self.0.saturating_add(1))?;
+        f.write_str("H")
     }
 
     #[cfg(windows)]
     fn execute_winapi(&self) -> Result<()> {
         sys::move_to(self.0, self.1)
     }
+
+    fn validate(&self, (columns, rows): (u16, u16)) -> Result<()> {
+        if self.0 >= columns || self.1 >= rows {
+            return Err(ErrorKind::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "cursor position {},{} is outside of the {}x{} terminal",
+                    self.0, self.1, columns, rows
+                ),
+            ));
+        }
+        Ok(())
+    }
 }
 
 /// A command that moves the terminal cursor down the given number of lines,
@@ -83,7 +98,9 @@ pub struct MoveToNextLine(pub u16);
 
 impl Command for MoveToNextLine {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
-        write!(f, csi!("{}E"), self.0)
+        f.write_str(csi!())?;
+        write_u16(f, self.0)?;
+        f.write_str("E")
     }
 
     #[cfg(windows)]
@@ -103,7 +120,9 @@ pub struct MoveToPreviousLine(pub u16);
 
 impl Command for MoveToPreviousLine {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
-        write!(f, csi!("{}F"), self.0)
+        f.write_str(csi!())?;
+        write_u16(f, self.0)?;
+        f.write_str("F")
     }
 
     #[cfg(windows)]
@@ -122,7 +141,9 @@ pub struct MoveToColumn(pub u16);
 
 impl Command for MoveToColumn {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
-        write!(f, csi!("{}G"), self.0)
+        f.write_str(csi!())?;
+        write_u16(f, self.0)?;
+        f.write_str("G")
     }
 
     #[cfg(windows)]
@@ -141,7 +162,9 @@ pub struct MoveToRow(pub u16);
 
 impl Command for MoveToRow {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
-        write!(f, csi!("{}d"), self.0)
+        f.write_str(csi!())?;
+        write_u16(f, self.0)?;
+        f.write_str("d")
     }
 
     #[cfg(windows)]
@@ -161,7 +184,9 @@ pub struct MoveUp(pub u16);
 impl Command for MoveUp {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
         if self.0 != 0 {
-            write!(f, csi!("{}A"), self.0)?;
+            f.write_str(csi!())?;
+            write_u16(f, self.0)?;
+            f.write_str("A")?;
         }
         Ok(())
     }
@@ -183,7 +208,9 @@ pub struct MoveRight(pub u16);
 impl Command for MoveRight {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
         if self.0 != 0 {
-            write!(f, csi!("{}C"), self.0)?;
+            f.write_str(csi!())?;
+            write_u16(f, self.0)?;
+            f.write_str("C")?;
         }
         Ok(())
     }
@@ -205,7 +232,9 @@ pub struct MoveDown(pub u16);
 impl Command for MoveDown {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
         if self.0 != 0 {
-            write!(f, csi!("{}B"), self.0)?;
+            f.write_str(csi!())?;
+            write_u16(f, self.0)?;
+            f.write_str("B")?;
         }
         Ok(())
     }
@@ -227,7 +256,9 @@ pub struct MoveLeft(pub u16);
 impl Command for MoveLeft {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
         if self.0 != 0 {
-            write!(f, csi!("{}D"), self.0)?;
+            f.write_str(csi!())?;
+            write_u16(f, self.0)?;
+            f.write_str("D")?;
         }
         Ok(())
     }
@@ -325,12 +356,17 @@ impl Command for Show {
 /// # Notes
 ///
 /// - Windows versions lower than Windows 10 do not support this functionality.
+/// - It does nothing in the [reduced decorations](../accessibility/index.html) mode.
 /// - Commands must be executed/queued for execution otherwise they do nothing.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct EnableBlinking;
 
 impl Command for EnableBlinking {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        if crate::accessibility::reduced_decorations() {
+            return Ok(());
+        }
+
         f.write_str(csi!("?12h"))
     }
 
@@ -417,12 +453,35 @@ impl_display!(for SetCursorShape);
 mod tests {
     use std::io::{self, stdout};
 
-    use crate::execute;
+    use crate::{execute, Command};
 
     use super::{
-        position, MoveDown, MoveLeft, MoveRight, MoveTo, MoveUp, RestorePosition, SavePosition,
+        position, MoveDown, MoveLeft, MoveRight, MoveTo, MoveToColumn, MoveUp, RestorePosition,
+        SavePosition,
     };
 
+    #[test]
+    fn test_move_to_ansi() {
+        let mut result = String::new();
+        MoveTo(4, 9).write_ansi(&mut result).unwrap();
+        MoveToColumn(120).write_ansi(&mut result).unwrap();
+        MoveUp(0).write_ansi(&mut result).unwrap();
+        MoveUp(65535).write_ansi(&mut result).unwrap();
+        assert_eq!(result, "\x1B[10;5H\x1B[120G\x1B[65535A");
+    }
+
+    #[test]
+    fn test_move_to_validate() {
+        assert!(MoveTo(79, 23).validate((80, 24)).is_ok());
+        assert!(MoveTo(80, 0).validate((80, 24)).is_err());
+        assert!(MoveTo(0, 24).validate((80, 24)).is_err());
+
+        // Still written without overflowing if it's not validated
+        let mut result = String::new();
+        MoveTo(u16::MAX, u16::MAX).write_ansi(&mut result).unwrap();
+        assert_eq!(result, "\x1B[65535;65535H");
+    }
+
     // Test is disabled, because it's failing on Travis
     #[test]
     #[ignore]
@@ -1,10 +1,15 @@
 use std::{
-    io::{self, Error, ErrorKind, Write},
+    io::{self, Error, Write},
     time::Duration,
 };
 
 use crate::{
-    event::{filter::CursorPositionFilter, poll_internal, read_internal, InternalEvent},
+    event::{
+        filter::{AwaitedReply, CursorPositionFilter},
+        poll_internal, read_internal,
+        timeout::PollTimeout,
+        InternalEvent,
+    },
     terminal::{disable_raw_mode, enable_raw_mode, sys::is_raw_mode_enabled},
     Result,
 };
@@ -28,13 +33,20 @@ fn read_position() -> Result<(u16, u16)> {
 }
 
 fn read_position_raw() -> Result<(u16, u16)> {
+    // Another thread blocked on the events hands the reader over when the reply is read
+    let _awaited = AwaitedReply::cursor_position();
+
     // Use `ESC [ 6 n` to and retrieve the cursor position.
     let mut stdout = io::stdout();
     stdout.write_all(b"\x1B[6n")?;
     stdout.flush()?;
 
+    // Events the user triggers in the meantime are skipped by the filter. They stay in the
+    // queue and are returned by the next `read` of the application.
+    let timeout = PollTimeout::new(Some(Duration::from_millis(2000)));
+
     loop {
-        match poll_internal(Some(Duration::from_millis(2000)), &CursorPositionFilter) {
+        match poll_internal(timeout.leftover(), &CursorPositionFilter) {
             Ok(true) => {
                 if let Ok(InternalEvent::CursorPosition(x, y)) =
                     read_internal(&CursorPositionFilter)
@@ -42,13 +54,16 @@ fn read_position_raw() -> Result<(u16, u16)> {
                     return Ok((x, y));
                 }
             }
+            // Handed over by another thread, the reply can't be far
+            Ok(false) if !timeout.elapsed() => {}
             Ok(false) => {
-                return Err(Error::new(
-                    ErrorKind::Other,
+                return Err(Error::other(
                     "The cursor position could not be read within a normal duration",
                 ));
             }
-            Err(_) => {}
+            // The poll was woken up (`EventStream`, ...), keep waiting
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
         }
     }
 }
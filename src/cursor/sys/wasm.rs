@@ -0,0 +1,14 @@
+use std::io;
+
+use crate::Result;
+
+/// Returns the cursor position (column, row).
+///
+/// It can't be read in the browser, the reply of the terminal isn't delivered while waiting for
+/// it.
+pub fn position() -> Result<(u16, u16)> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "The cursor position can't be read in the browser",
+    ))
+}
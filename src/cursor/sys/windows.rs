@@ -4,13 +4,13 @@ use std::convert::TryFrom;
 use std::io;
 use std::sync::atomic::{AtomicU64, Ordering};
 
-use crossterm_winapi::{result, Coord, Handle, HandleType, ScreenBuffer};
+use crossterm_winapi::{result, Coord, Handle, ScreenBuffer};
 use winapi::{
     shared::minwindef::{FALSE, TRUE},
     um::wincon::{SetConsoleCursorInfo, SetConsoleCursorPosition, CONSOLE_CURSOR_INFO, COORD},
 };
 
-use crate::Result;
+use crate::{terminal::sys::current_out_handle, Result};
 
 /// The position of the cursor, written when you save the cursor's position.
 ///
@@ -22,7 +22,7 @@ static SAVED_CURSOR_POS: AtomicU64 = AtomicU64::new(u64::MAX);
 // We can calculate the relative cursor position by subtracting the top position of the terminal window from the y position.
 // This results in an 1-based coord zo subtract 1 to make cursor position 0-based.
 pub fn parse_relative_y(y: i16) -> Result<i16> {
-    let window = ScreenBuffer::current()?.info()?;
+    let window = ScreenBuffer::from(current_out_handle()?).info()?;
 
     let window_size = window.terminal_window();
     let screen_size = window.terminal_size();
@@ -47,7 +47,7 @@ pub fn position() -> Result<(u16, u16)> {
 }
 
 pub(crate) fn show_cursor(show_cursor: bool) -> Result<()> {
-    ScreenBufferCursor::from(Handle::current_out_handle()?).set_visibility(show_cursor)
+    ScreenBufferCursor::from(current_out_handle()?).set_visibility(show_cursor)
 }
 
 pub(crate) fn move_to(column: u16, row: u16) -> Result<()> {
@@ -122,7 +122,7 @@ struct ScreenBufferCursor {
 impl ScreenBufferCursor {
     fn output() -> Result<ScreenBufferCursor> {
         Ok(ScreenBufferCursor {
-            screen_buffer: ScreenBuffer::from(Handle::new(HandleType::CurrentOutputHandle)?),
+            screen_buffer: ScreenBuffer::from(current_out_handle()?),
         })
     }
 
@@ -132,23 +132,17 @@ impl ScreenBufferCursor {
 
     fn move_to(&self, x: i16, y: i16) -> Result<()> {
         if x < 0 {
-            return Err(io::Error::new(
-                io::ErrorKind::Other,
-                format!(
-                    "Argument Out of Range Exception when setting cursor position to X: {}",
-                    x
-                ),
-            ));
+            return Err(io::Error::other(format!(
+                "Argument Out of Range Exception when setting cursor position to X: {}",
+                x
+            )));
         }
 
         if y < 0 {
-            return Err(io::Error::new(
-                io::ErrorKind::Other,
-                format!(
-                    "Argument Out of Range Exception when setting cursor position to Y: {}",
-                    y
-                ),
-            ));
+            return Err(io::Error::other(format!(
+                "Argument Out of Range Exception when setting cursor position to Y: {}",
+                y
+            )));
         }
 
         let position = COORD { X: x, Y: y };
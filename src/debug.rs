@@ -0,0 +1,211 @@
+//! # Debug
+//!
+//! The `debug` module helps to find out why a program behaves differently in some terminals.
+//!
+//! [`dump_state`](fn.dump_state.html) collects what crossterm knows about the terminal: the modes
+//! of the terminal driver, the ANSI support, the color depth, the variables identifying the
+//! terminal... The report is meant to be attached to bug reports.
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use crossterm::debug;
+//!
+//! eprintln!("{}", debug::dump_state());
+//! ```
+
+use std::{env, fmt};
+
+use crate::{event, style, terminal, tty::IsTty};
+
+pub(crate) use self::input_log::log_input;
+#[cfg(windows)]
+pub(crate) use self::input_log::log_input_record;
+pub use self::input_log::{read_input_log, start_input_log, stop_input_log, LoggedInput};
+
+mod input_log;
+
+/// The environment variables identifying the terminal, the multiplexer and the locale.
+const ENVIRONMENT_VARIABLES: &[&str] = &[
+    "TERM",
+    "COLORTERM",
+    "TERM_PROGRAM",
+    "TERM_PROGRAM_VERSION",
+    "WT_SESSION",
+    "ConEmuANSI",
+    "TMUX",
+    "STY",
+    "ZELLIJ",
+    "LANG",
+    "LC_ALL",
+];
+
+/// The state of the terminal, returned by [`dump_state`](fn.dump_state.html).
+///
+/// The `Display` implementation writes a readable report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TerminalState {
+    /// Whether the standard input is a terminal.
+    pub stdin_is_tty: bool,
+    /// Whether the standard output is a terminal.
+    pub stdout_is_tty: bool,
+    /// Whether the standard error is a terminal.
+    pub stderr_is_tty: bool,
+    /// The terminal size `(columns, rows)`, or why it couldn't be read.
+    pub size: Result<(u16, u16), String>,
+    /// Whether the raw mode is enabled.
+    ///
+    /// On UNIX, only the raw mode enabled by [`enable_raw_mode`](../terminal/fn.enable_raw_mode.html)
+    /// is reported. On Windows, it's read from the console input mode.
+    pub raw_mode: bool,
+    /// Whether the alternate screen is active, as tracked by the
+    /// [`EnterAlternateScreen`](../terminal/struct.EnterAlternateScreen.html) and
+    /// [`LeaveAlternateScreen`](../terminal/struct.LeaveAlternateScreen.html) commands.
+    ///
+    /// The sequences written without the commands aren't seen.
+    pub alternate_screen: bool,
+    /// Whether the mouse capture is enabled, as tracked by the
+    /// [`EnableMouseCapture`](../event/struct.EnableMouseCapture.html) and
+    /// [`DisableMouseCapture`](../event/struct.DisableMouseCapture.html) commands.
+    ///
+    /// The sequences written without the commands aren't seen.
+    pub mouse_capture: bool,
+    /// Whether the terminal supports the ANSI escape sequences, always `true` except on Windows.
+    pub ansi_support: bool,
+    /// The number of colors, see [`available_color_count`](../style/fn.available_color_count.html).
+    pub color_count: u16,
+    /// The flags of the terminal driver and whether they are set, or why they couldn't be read.
+    ///
+    /// The `termios` flags on UNIX, the console input and output mode flags on Windows.
+    pub modes: Result<Vec<(&'static str, bool)>, String>,
+    /// The environment variables identifying the terminal which are set, `TERM` for example.
+    pub environment: Vec<(String, String)>,
+}
+
+/// Returns the state of the terminal.
+///
+/// It never fails, what can't be read is reported as an error in the state. Nothing is written
+/// to the terminal.
+pub fn dump_state() -> TerminalState {
+    TerminalState {
+        stdin_is_tty: std::io::stdin().is_tty(),
+        stdout_is_tty: std::io::stdout().is_tty(),
+        stderr_is_tty: std::io::stderr().is_tty(),
+        size: terminal::size().map_err(|e| e.to_string()),
+        raw_mode: terminal::sys::is_raw_mode_enabled(),
+        alternate_screen: terminal::is_alternate_screen_active(),
+        mouse_capture: event::is_mouse_capture_enabled(),
+        #[cfg(windows)]
+        ansi_support: crate::ansi_support::supports_ansi(),
+        #[cfg(not(windows))]
+        ansi_support: true,
+        color_count: style::available_color_count(),
+        modes: terminal::sys::terminal_modes().map_err(|e| e.to_string()),
+        environment: ENVIRONMENT_VARIABLES
+            .iter()
+            .filter_map(|name| Some((name.to_string(), env::var(name).ok()?)))
+            .collect(),
+    }
+}
+
+impl fmt::Display for TerminalState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "crossterm {}", env!("CARGO_PKG_VERSION"))?;
+        writeln!(
+            f,
+            "tty: stdin {}, stdout {}, stderr {}",
+            self.stdin_is_tty, self.stdout_is_tty, self.stderr_is_tty
+        )?;
+        match &self.size {
+            Ok((columns, rows)) => writeln!(f, "size: {}x{}", columns, rows)?,
+            Err(e) => writeln!(f, "size: error: {}", e)?,
+        }
+        writeln!(f, "raw mode: {}", self.raw_mode)?;
+        writeln!(f, "alternate screen: {}", self.alternate_screen)?;
+        writeln!(f, "mouse capture: {}", self.mouse_capture)?;
+        writeln!(f, "ansi support: {}", self.ansi_support)?;
+        writeln!(f, "colors: {}", self.color_count)?;
+
+        // Like `stty`, the flags which aren't set start with `-`
+        f.write_str("modes:")?;
+        match &self.modes {
+            Ok(modes) => {
+                for (name, set) in modes {
+                    write!(f, " {}{}", if *set { "" } else { "-" }, name)?;
+                }
+                writeln!(f)?;
+            }
+            Err(e) => writeln!(f, " error: {}", e)?,
+        }
+
+        f.write_str("environment:")?;
+        if self.environment.is_empty() {
+            f.write_str(" (none)")?;
+        }
+        for (name, value) in &self.environment {
+            write!(f, "\n  {}={}", name, value)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TerminalState;
+
+    #[test]
+    fn test_display_state() {
+        let state = TerminalState {
+            stdin_is_tty: true,
+            stdout_is_tty: true,
+            stderr_is_tty: false,
+            size: Ok((80, 24)),
+            raw_mode: true,
+            alternate_screen: false,
+            mouse_capture: true,
+            ansi_support: true,
+            color_count: 256,
+            modes: Ok(vec![("ECHO", false), ("ISIG", true)]),
+            environment: vec![("TERM".to_string(), "xterm-256color".to_string())],
+        };
+
+        assert_eq!(
+            state.to_string(),
+            format!(
+                "crossterm {}\n\
+                 tty: stdin true, stdout true, stderr false\n\
+                 size: 80x24\n\
+                 raw mode: true\n\
+                 alternate screen: false\n\
+                 mouse capture: true\n\
+                 ansi support: true\n\
+                 colors: 256\n\
+                 modes: -ECHO ISIG\n\
+                 environment:\n  TERM=xterm-256color",
+                env!("CARGO_PKG_VERSION")
+            )
+        );
+    }
+
+    #[test]
+    fn test_display_errors() {
+        let state = TerminalState {
+            stdin_is_tty: false,
+            stdout_is_tty: false,
+            stderr_is_tty: false,
+            size: Err("not a terminal".to_string()),
+            raw_mode: false,
+            alternate_screen: false,
+            mouse_capture: false,
+            ansi_support: true,
+            color_count: 8,
+            modes: Err("not a terminal".to_string()),
+            environment: Vec::new(),
+        };
+
+        let report = state.to_string();
+        assert!(report.contains("size: error: not a terminal\n"));
+        assert!(report.contains("modes: error: not a terminal\n"));
+        assert!(report.ends_with("environment: (none)"));
+    }
+}
@@ -0,0 +1,290 @@
+use std::{
+    env,
+    fmt::Write as _,
+    fs::File,
+    io::{self, Write},
+    path::Path,
+    sync::Once,
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+
+use crate::Result;
+
+/// The environment variable with the path of the input log, read when the input is read first.
+const LOG_VAR: &str = "CROSSTERM_INPUT_LOG";
+
+/// The log the input is written to, if any.
+static INPUT_LOG: Mutex<Option<InputLog>> = parking_lot::const_mutex(None);
+static LOG_VAR_READ: Once = Once::new();
+
+struct InputLog {
+    file: File,
+    start: Instant,
+}
+
+impl InputLog {
+    fn create(path: &Path) -> Result<InputLog> {
+        let mut file = File::create(path)?;
+        writeln!(
+            file,
+            "# crossterm {} input log, the seconds since the start and the bytes read",
+            env!("CARGO_PKG_VERSION")
+        )?;
+
+        Ok(InputLog {
+            file,
+            start: Instant::now(),
+        })
+    }
+}
+
+/// Starts writing the input read from the terminal to the file, the file is truncated.
+///
+/// Every read is written on a line: the seconds since the log started, then the bytes, the
+/// control characters, the backslash and the bytes which aren't printable ASCII are escaped like
+/// `\x1B`. [`read_input_log`](fn.read_input_log.html) reads the log back, to replay the input
+/// through an [`EventParser`](../event/struct.EventParser.html) for example.
+///
+/// The log can be started without changing the program as well, by setting the
+/// `CROSSTERM_INPUT_LOG` environment variable to the path of the file.
+///
+/// On Windows, the console input records are written as comments, they are not bytes.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm::{debug, event};
+///
+/// fn main() -> crossterm::Result<()> {
+///     debug::start_input_log("input.log")?;
+///     let event = event::read()?;
+///     debug::stop_input_log();
+///     Ok(())
+/// }
+/// ```
+pub fn start_input_log(path: impl AsRef<Path>) -> Result<()> {
+    let log = InputLog::create(path.as_ref())?;
+    // The environment variable doesn't replace the log later
+    LOG_VAR_READ.call_once(|| {});
+    *INPUT_LOG.lock() = Some(log);
+    Ok(())
+}
+
+/// Stops writing the input to the log started by [`start_input_log`](fn.start_input_log.html)
+/// or the `CROSSTERM_INPUT_LOG` environment variable.
+pub fn stop_input_log() {
+    LOG_VAR_READ.call_once(|| {});
+    *INPUT_LOG.lock() = None;
+}
+
+/// Writes the bytes read from the terminal to the input log, if there's one.
+///
+/// Logging never makes reading fail, the log stops at the first write error.
+pub(crate) fn log_input(bytes: &[u8]) {
+    write_log(|line| escape_bytes(line, bytes));
+}
+
+/// Writes an input which isn't made of bytes to the input log as a comment, if there's one.
+#[cfg(windows)]
+pub(crate) fn log_input_record(record: &dyn std::fmt::Debug) {
+    write_log(|line| {
+        line.insert_str(0, "# ");
+        let _ = write!(line, "{:?}", record);
+    });
+}
+
+fn write_log(write_input: impl FnOnce(&mut String)) {
+    LOG_VAR_READ.call_once(|| {
+        if let Some(path) = env::var_os(LOG_VAR) {
+            *INPUT_LOG.lock() = InputLog::create(Path::new(&path)).ok();
+        }
+    });
+
+    let mut log = INPUT_LOG.lock();
+    if let Some(input_log) = log.as_mut() {
+        let elapsed = input_log.start.elapsed();
+        let mut line = format!("{}.{:06}", elapsed.as_secs(), elapsed.subsec_micros());
+        line.push(' ');
+
+        write_input(&mut line);
+        line.push('\n');
+
+        if input_log.file.write_all(line.as_bytes()).is_err() {
+            *log = None;
+        }
+    }
+}
+
+fn escape_bytes(escaped: &mut String, bytes: &[u8]) {
+    for &byte in bytes {
+        match byte {
+            b'\\' => escaped.push_str("\\\\"),
+            0x20..=0x7E => escaped.push(byte as char),
+            _ => {
+                let _ = write!(escaped, "\\x{:02X}", byte);
+            }
+        }
+    }
+}
+
+/// An input read from the terminal, read back from the input log by
+/// [`read_input_log`](fn.read_input_log.html).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoggedInput {
+    /// When the input was read, since the log started.
+    pub time: Duration,
+    /// The bytes read.
+    pub bytes: Vec<u8>,
+}
+
+/// Reads the content of an input log written by [`start_input_log`](fn.start_input_log.html).
+///
+/// The comments, the lines starting with `#`, and the empty lines are skipped. Fails with
+/// `io::ErrorKind::InvalidData` if a line is malformed.
+///
+/// # Examples
+///
+/// ```
+/// use crossterm::{
+///     debug::read_input_log,
+///     event::{Event, EventParser, KeyCode},
+/// };
+///
+/// let log = "# crossterm input log\n0.000000 \\x1B[\n0.001000 A\n";
+///
+/// # #[cfg(unix)]
+/// # {
+/// let mut parser = EventParser::new();
+/// for input in read_input_log(log).unwrap() {
+///     parser.advance(&input.bytes, true);
+/// }
+/// assert_eq!(parser.next(), Some(Event::Key(KeyCode::Up.into())));
+/// # }
+/// ```
+pub fn read_input_log(log: &str) -> Result<Vec<LoggedInput>> {
+    log.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(index, line)| {
+            parse_line(line).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Malformed input log line {}: {:?}", index + 1, line),
+                )
+            })
+        })
+        .collect()
+}
+
+fn parse_line(line: &str) -> Option<LoggedInput> {
+    let mut parts = line.splitn(2, ' ');
+    let (time, escaped) = (parts.next()?, parts.next()?);
+
+    let mut time_parts = time.splitn(2, '.');
+    let (seconds, micros) = (time_parts.next()?, time_parts.next()?);
+    if seconds.is_empty()
+        || micros.len() != 6
+        || !seconds
+            .bytes()
+            .chain(micros.bytes())
+            .all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+    let time =
+        Duration::from_secs(seconds.parse().ok()?) + Duration::from_micros(micros.parse().ok()?);
+
+    let mut bytes = Vec::with_capacity(escaped.len());
+    let mut rest = escaped.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        rest = tail;
+        if byte != b'\\' {
+            bytes.push(byte);
+            continue;
+        }
+        match rest {
+            [b'\\', tail @ ..] => {
+                bytes.push(b'\\');
+                rest = tail;
+            }
+            [b'x', high, low, tail @ ..] if high.is_ascii_hexdigit() && low.is_ascii_hexdigit() => {
+                let hex = [*high, *low];
+                bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+                rest = tail;
+            }
+            _ => return None,
+        }
+    }
+
+    Some(LoggedInput { time, bytes })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, time::Duration};
+
+    use super::{
+        escape_bytes, log_input, read_input_log, start_input_log, stop_input_log, LoggedInput,
+    };
+
+    #[test]
+    fn test_escaped_bytes_are_read_back() {
+        let bytes = b"a \\\x1B[A\x00\xC3\xA9\x7F~".to_vec();
+
+        let mut line = "1.000002 ".to_string();
+        escape_bytes(&mut line, &bytes);
+        assert_eq!(line, "1.000002 a \\\\\\x1B[A\\x00\\xC3\\xA9\\x7F~");
+
+        assert_eq!(
+            read_input_log(&line).unwrap(),
+            vec![LoggedInput {
+                time: Duration::from_micros(1_000_002),
+                bytes
+            }]
+        );
+    }
+
+    #[test]
+    fn test_read_malformed_log() {
+        for log in &[
+            "0.000000",
+            "0 a",
+            "0.0 a",
+            "x.000000 a",
+            "0.000000 \\",
+            "0.000000 \\x1",
+            "0.000000 \\xZZ",
+            "0.000000 \\x+1",
+            "+0.000000 a",
+            "0.000000 \\n",
+        ] {
+            let error = read_input_log(&format!("# comment\n{}", log)).unwrap_err();
+            assert_eq!(error.kind(), std::io::ErrorKind::InvalidData, "{}", log);
+            assert!(error.to_string().contains("line 2"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn test_input_is_logged() {
+        let path = std::env::temp_dir().join(format!("crossterm-input-{}.log", std::process::id()));
+
+        start_input_log(&path).unwrap();
+        log_input(b"\x1B[");
+        log_input(b"A");
+        stop_input_log();
+        log_input(b"not logged");
+
+        let log = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        // The event sources of other tests may log their input in the meantime
+        let inputs = read_input_log(&log).unwrap();
+        let position = |bytes: &[u8]| inputs.iter().position(|input| input.bytes == bytes);
+        let (first, second) = (position(b"\x1B[").unwrap(), position(b"A").unwrap());
+        assert!(first < second);
+        assert!(inputs[first].time <= inputs[second].time);
+        assert_eq!(position(b"not logged"), None);
+    }
+}
@@ -9,10 +9,14 @@
 //! within the given period of time. In other words - if subsequent call to the [`read`](fn.read.html)
 //! function will block or not.
 //!
+//! * The [`read_where`](fn.read_where.html) and [`poll_where`](fn.poll_where.html) functions
+//! only look for the events a predicate accepts, the other events stay queued for later.
+//!
 //! It's **not allowed** to call these functions from different threads or combine them with the
 //! [`EventStream`](struct.EventStream.html). You're allowed to either:
 //!
-//! * use the [`read`](fn.read.html) & [`poll`](fn.poll.html) functions on any, but same, thread
+//! * use the [`read`](fn.read.html) & [`poll`](fn.poll.html) functions (and their `_where`
+//!   variants) on any, but same, thread
 //! * or the [`EventStream`](struct.EventStream.html).
 //!
 //! **Make sure to enable raw mode in order for keyboard events to work properly**
@@ -23,6 +27,20 @@
 //! [`EnableMouseCapture`](struct.EnableMouseCapture.html) command. See [Command API](../index.html#command-api)
 //! for more information.
 //!
+//! ## Focus Events
+//!
+//! Focus events are not enabled by default either, the
+//! [`EnableFocusChange`](struct.EnableFocusChange.html) command enables them. The same goes for
+//! the pasted text, which is read as key events until
+//! [`EnableBracketedPaste`](struct.EnableBracketedPaste.html) is executed.
+//!
+//! ## Event Sources
+//!
+//! The input is read from the terminal, unless [`set_event_source`](fn.set_event_source.html)
+//! set another [`EventSource`](source/trait.EventSource.html): the bytes of an SSH channel for
+//! example. On UNIX, [`set_event_source_tty`](fn.set_event_source_tty.html) reads from another
+//! terminal.
+//!
 //! ## Examples
 //!
 //! Blocking read:
@@ -37,6 +55,13 @@
 //!             Event::Key(event) => println!("{:?}", event),
 //!             Event::Mouse(event) => println!("{:?}", event),
 //!             Event::Resize(width, height) => println!("New size {}x{}", width, height),
+//!             Event::FocusGained => println!("Focus gained"),
+//!             Event::FocusLost => println!("Focus lost"),
+//!             Event::Paste(text) => println!("Pasted {:?}", text),
+//!             Event::Unsupported(bytes) => println!("Unsupported {:?}", bytes),
+//!             Event::Closed => break,
+//!             Event::Resumed => println!("Resumed"),
+//!             Event::Signal(kind) => println!("Signal {:?}", kind),
 //!         }
 //!     }
 //!     Ok(())
@@ -60,6 +85,13 @@
 //!                 Event::Key(event) => println!("{:?}", event),
 //!                 Event::Mouse(event) => println!("{:?}", event),
 //!                 Event::Resize(width, height) => println!("New size {}x{}", width, height),
+//!                 Event::FocusGained => println!("Focus gained"),
+//!                 Event::FocusLost => println!("Focus lost"),
+//!                 Event::Paste(text) => println!("Pasted {:?}", text),
+//!                 Event::Unsupported(bytes) => println!("Unsupported {:?}", bytes),
+//!                 Event::Closed => break,
+//!                 Event::Resumed => println!("Resumed"),
+//!                 Event::Signal(kind) => println!("Signal {:?}", kind),
 //!             }
 //!         } else {
 //!             // Timeout expired and no `Event` is available
@@ -74,6 +106,9 @@
 
 use std::fmt;
 use std::hash::{Hash, Hasher};
+#[cfg(unix)]
+use std::os::unix::io::{IntoRawFd, OwnedFd};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::time::Duration;
 
 use bitflags::bitflags;
@@ -82,29 +117,123 @@ use parking_lot::{MappedMutexGuard, Mutex, MutexGuard};
 use serde::{Deserialize, Serialize};
 
 use crate::{csi, Command, Result};
-use filter::{EventFilter, Filter};
+use filter::{EventFilter, EventPredicateFilter, Filter};
 use read::InternalEventReader;
 #[cfg(feature = "event-stream")]
 pub use stream::EventStream;
 use timeout::PollTimeout;
 
+pub use self::parser::EventParser;
+
+/// Reading the events with the async-std runtime.
+#[cfg(feature = "async-std")]
+pub mod async_std;
+#[cfg(any(feature = "tokio", feature = "async-std"))]
+mod async_wait;
+mod click;
 pub(crate) mod filter;
+mod parser;
 mod read;
-mod source;
+pub mod source;
 #[cfg(feature = "event-stream")]
 mod stream;
 pub(crate) mod sys;
-mod timeout;
+pub(crate) mod timeout;
+/// Reading the events with the Tokio runtime.
+#[cfg(feature = "tokio")]
+pub mod tokio;
 
 /// Static instance of `InternalEventReader`.
 /// This needs to be static because there can be one event reader.
 static INTERNAL_EVENT_READER: Mutex<Option<InternalEventReader>> = parking_lot::const_mutex(None);
 
+/// The timeout set with `set_escape_timeout`, in milliseconds. `UNSET_ESCAPE_TIMEOUT` until it's
+/// set or used, the default depends on the multiplexer.
+static ESCAPE_TIMEOUT_MILLIS: AtomicU64 = AtomicU64::new(UNSET_ESCAPE_TIMEOUT);
+
+const UNSET_ESCAPE_TIMEOUT: u64 = u64::MAX;
+
+/// The interval set with `set_double_click_interval`, in milliseconds. `UNSET_DOUBLE_CLICK_INTERVAL`
+/// until it's set, the default is the one of the system on Windows.
+static DOUBLE_CLICK_INTERVAL_MILLIS: AtomicU64 = AtomicU64::new(UNSET_DOUBLE_CLICK_INTERVAL);
+
+const UNSET_DOUBLE_CLICK_INTERVAL: u64 = u64::MAX;
+
+/// Whether `read` recognizes the 8-bit C1 controls, set with `set_c1_controls`.
+static C1_CONTROLS: AtomicBool = AtomicBool::new(false);
+
+/// Whether the raw mode makes the Windows console send the keys as escape sequences, set with
+/// `set_virtual_terminal_input`.
+static VIRTUAL_TERMINAL_INPUT: AtomicBool = AtomicBool::new(false);
+
+/// Whether the last mouse capture command written was `EnableMouseCapture`.
+static MOUSE_CAPTURE: AtomicBool = AtomicBool::new(false);
+
+/// Whether the last focus change command written was `EnableFocusChange`.
+static FOCUS_CHANGE: AtomicBool = AtomicBool::new(false);
+
 fn lock_internal_event_reader() -> MappedMutexGuard<'static, InternalEventReader> {
     MutexGuard::map(INTERNAL_EVENT_READER.lock(), |reader| {
         reader.get_or_insert_with(InternalEventReader::default)
     })
 }
+
+/// Replaces the source of the events, `None` reads from the terminal again.
+///
+/// The events which weren't read yet are dropped.
+pub(crate) fn replace_event_source(source: Option<Box<dyn source::InternalEventSource>>) {
+    *INTERNAL_EVENT_READER.lock() = source.map(InternalEventReader::with_source);
+}
+
+/// Makes [`read`](fn.read.html), [`poll`](fn.poll.html) and the `EventStream` read the input
+/// of the source instead of the terminal, `None` reads from the terminal again.
+///
+/// The events which weren't read yet are thrown away. Set it before the `EventStream` is
+/// created, one created before keeps waiting for the previous source.
+///
+/// See [`EventSource`](source/trait.EventSource.html) for an example of a source.
+pub fn set_event_source(source: Option<Box<dyn source::EventSource>>) -> Result<()> {
+    let source = match source {
+        Some(source) => Some(Box::new(source::custom::CustomEventSource::new(source)?)
+            as Box<dyn source::InternalEventSource>),
+        None => None,
+    };
+    replace_event_source(source);
+    Ok(())
+}
+
+/// Makes [`read`](fn.read.html), [`poll`](fn.poll.html) and the `EventStream` read the input of
+/// another terminal than the one of this process: a secondary terminal (`/dev/pts/N`) or the
+/// master side of a PTY. [`set_event_source`](fn.set_event_source.html) with `None` reads from
+/// the terminal of this process again.
+///
+/// The terminal is closed when another source is set. Switch it to the raw mode before,
+/// [`enable_raw_mode`](../terminal/fn.enable_raw_mode.html) only switches the terminal of this
+/// process. The resize events are reported when this process is resized (`SIGWINCH`), with the
+/// size of the given terminal.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::fs::OpenOptions;
+///
+/// use crossterm::{event, Result};
+///
+/// fn main() -> Result<()> {
+///     let tty = OpenOptions::new().read(true).write(true).open("/dev/pts/3")?;
+///     event::set_event_source_tty(tty.into())?;
+///     println!("{:?}", event::read()?);
+///     Ok(())
+/// }
+/// ```
+#[cfg(unix)]
+pub fn set_event_source_tty(tty: OwnedFd) -> Result<()> {
+    let tty = sys::unix::file_descriptor::FileDesc::new(tty.into_raw_fd(), true);
+    let source = source::unix::UnixInternalEventSource::from_file_descriptor(tty)?;
+    replace_event_source(Some(Box::new(source)));
+    Ok(())
+}
+
 fn try_lock_internal_event_reader_for(
     duration: Duration,
 ) -> Option<MappedMutexGuard<'static, InternalEventReader>> {
@@ -200,11 +329,322 @@ pub fn poll(timeout: Duration) -> Result<bool> {
 pub fn read() -> Result<Event> {
     match read_internal(&EventFilter)? {
         InternalEvent::Event(event) => Ok(event),
-        #[cfg(unix)]
         _ => unreachable!(),
     }
 }
 
+/// Checks if there is an [`Event`](enum.Event.html) available the predicate returns `true` for.
+///
+/// It works like [`poll`](fn.poll.html), the other events are left in the queue for the next
+/// reads. `Ok(true)` guarantees that a subsequent call to [`read_where`](fn.read_where.html)
+/// with the same predicate won't block.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use crossterm::{
+///     event::{poll_where, Event},
+///     Result,
+/// };
+///
+/// fn is_key_available() -> Result<bool> {
+///     poll_where(Duration::from_millis(100), |event| matches!(event, Event::Key(_)))
+/// }
+/// ```
+pub fn poll_where<P>(timeout: Duration, predicate: P) -> Result<bool>
+where
+    P: Fn(&Event) -> bool,
+{
+    poll_internal(Some(timeout), &EventPredicateFilter(predicate))
+}
+
+/// Reads the first [`Event`](enum.Event.html) the predicate returns `true` for.
+///
+/// It blocks until there's such an event, like [`read`](fn.read.html). The events the predicate
+/// returns `false` for are left in the queue, in order, and returned by the next reads.
+///
+/// # Examples
+///
+/// Waiting for a key while the mouse moves:
+///
+/// ```no_run
+/// use crossterm::{
+///     event::{read_where, Event},
+///     Result,
+/// };
+///
+/// fn wait_for_key() -> Result<()> {
+///     let key = read_where(|event| matches!(event, Event::Key(_)))?;
+///     println!("{:?}", key);
+///     Ok(())
+/// }
+/// ```
+pub fn read_where<P>(predicate: P) -> Result<Event>
+where
+    P: Fn(&Event) -> bool,
+{
+    match read_internal(&EventPredicateFilter(predicate))? {
+        InternalEvent::Event(event) => Ok(event),
+        _ => unreachable!(),
+    }
+}
+
+/// Sets how long to wait for the rest of an escape sequence after an `ESC` byte.
+///
+/// Pressing Esc sends a lone `ESC` byte, while most other keys (arrows, function keys, ...) and
+/// the mouse send a sequence starting with it. If the rest of a sequence doesn't arrive within
+/// this timeout, the `ESC` is reported as the Esc key. The default is 50ms, 100ms inside of a
+/// [multiplexer](../terminal/fn.multiplexer.html). Increase it over slow links (SSH, serial
+/// lines, ...) where the sequences can be split.
+///
+/// **Note** that it's only used on UNIX, the Windows console reports the keys directly.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use crossterm::event::set_escape_timeout;
+///
+/// set_escape_timeout(Duration::from_millis(200));
+/// ```
+pub fn set_escape_timeout(timeout: Duration) {
+    let millis = timeout.as_millis().min(UNSET_ESCAPE_TIMEOUT as u128 - 1) as u64;
+    ESCAPE_TIMEOUT_MILLIS.store(millis, Ordering::Relaxed);
+}
+
+/// Returns the timeout set with `set_escape_timeout`.
+pub(crate) fn escape_timeout() -> Duration {
+    let mut millis = ESCAPE_TIMEOUT_MILLIS.load(Ordering::Relaxed);
+    if millis == UNSET_ESCAPE_TIMEOUT {
+        let default = if crate::terminal::multiplexer().is_some() {
+            100
+        } else {
+            50
+        };
+        // Unless it was set in the meantime
+        millis = match ESCAPE_TIMEOUT_MILLIS.compare_exchange(
+            UNSET_ESCAPE_TIMEOUT,
+            default,
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+        ) {
+            Ok(_) => default,
+            Err(set) => set,
+        };
+    }
+    Duration::from_millis(millis)
+}
+
+/// Sets the longest time between the presses of a double or a triple click.
+///
+/// The second press of the same mouse button in the same cell within the interval is reported as
+/// `MouseEventKind::DoubleClick` right after its `MouseEventKind::Down` event, the third as
+/// `MouseEventKind::TripleClick`. The default is 500ms, the double click time of the system on
+/// Windows.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use crossterm::event::set_double_click_interval;
+///
+/// set_double_click_interval(Duration::from_millis(300));
+/// ```
+pub fn set_double_click_interval(interval: Duration) {
+    let millis = interval
+        .as_millis()
+        .min(UNSET_DOUBLE_CLICK_INTERVAL as u128 - 1) as u64;
+    DOUBLE_CLICK_INTERVAL_MILLIS.store(millis, Ordering::Relaxed);
+}
+
+/// Returns the interval set with `set_double_click_interval`.
+pub(crate) fn double_click_interval() -> Duration {
+    let millis = DOUBLE_CLICK_INTERVAL_MILLIS.load(Ordering::Relaxed);
+    if millis != UNSET_DOUBLE_CLICK_INTERVAL {
+        return Duration::from_millis(millis);
+    }
+
+    #[cfg(windows)]
+    {
+        let millis = unsafe { winapi::um::winuser::GetDoubleClickTime() };
+        Duration::from_millis(millis.into())
+    }
+
+    #[cfg(not(windows))]
+    {
+        Duration::from_millis(500)
+    }
+}
+
+/// Sets whether the 8-bit C1 controls are recognized in the input, in addition to their 7-bit
+/// form starting with `ESC`.
+///
+/// The VT220 and the later terminals, and some serial devices, can send `CSI` as the single byte
+/// 0x9B instead of `ESC [`, `SS3` as 0x8F, `OSC` as 0x9D and end the strings with `ST` as 0x9C
+/// (`DCS` 0x90 and `APC` 0x9F as well). Those bytes are continuation bytes of UTF-8, the mode is
+/// disabled by default: the UTF-8 characters are still parsed in the mode, but the 8-bit
+/// characters of a terminal which doesn't use UTF-8 (Latin-1, ...) aren't.
+///
+/// [`EventParser::set_c1_controls`](struct.EventParser.html#method.set_c1_controls) enables it
+/// for a parser of its own.
+///
+/// **Note** that it's used on UNIX, by an [`EventSource`](source/trait.EventSource.html) and on
+/// Windows with [`set_virtual_terminal_input`](fn.set_virtual_terminal_input.html), the Windows
+/// console reports the keys directly otherwise.
+pub fn set_c1_controls(enabled: bool) {
+    C1_CONTROLS.store(enabled, Ordering::Relaxed);
+}
+
+/// Sets whether the Windows console sends the keys as the escape sequences of a VT terminal,
+/// parsed like the input of a UNIX terminal.
+///
+/// The consoles of Windows 10 and later send them with `ENABLE_VIRTUAL_TERMINAL_INPUT`, which is
+/// set while the [raw mode](../terminal/fn.enable_raw_mode.html) is enabled: call it before. The
+/// keys are reported exactly as on UNIX, the releases of the keys aren't reported. The older
+/// consoles don't support the mode, the keys are read from the input records as usual then. The
+/// mouse, resize and focus events are always read from the input records.
+///
+/// **Note** that it's only used on Windows, it's disabled by default.
+pub fn set_virtual_terminal_input(enabled: bool) {
+    VIRTUAL_TERMINAL_INPUT.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns whether the mode of `set_virtual_terminal_input` is requested.
+#[cfg(windows)]
+pub(crate) fn virtual_terminal_input_requested() -> bool {
+    VIRTUAL_TERMINAL_INPUT.load(Ordering::Relaxed)
+}
+
+/// Sets whether the signals asking the process to stop are reported as
+/// [`Event::Signal`](enum.Event.html#variant.Signal) events, instead of terminating the process.
+///
+/// The signals are `SIGINT`, `SIGTERM` and `SIGHUP` on UNIX, and the `CTRL_C_EVENT` and
+/// `CTRL_BREAK_EVENT` of the console on Windows. Disabling it unregisters the handlers of
+/// crossterm, the ones of the application run again. They are read with the input of the
+/// terminal, not with the one of a custom [`EventSource`](source/trait.EventSource.html).
+///
+/// **Note** that signal-hook can't restore the default action on UNIX, a signal without any
+/// other handler is ignored once it was enabled and disabled again.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm::{
+///     event::{self, Event},
+///     Result,
+/// };
+///
+/// fn main() -> Result<()> {
+///     event::set_signal_events(true)?;
+///     loop {
+///         if let Event::Signal(kind) = event::read()? {
+///             println!("Stopped by {:?}", kind);
+///             return Ok(());
+///         }
+///     }
+/// }
+/// ```
+pub fn set_signal_events(enabled: bool) -> Result<()> {
+    #[cfg(unix)]
+    {
+        sys::unix::signal::set_signal_events(enabled)
+    }
+
+    #[cfg(windows)]
+    {
+        sys::windows::signal::set_signal_events(enabled)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        let _ = enabled;
+        Ok(())
+    }
+}
+
+/// Returns whether the mode of `set_c1_controls` is enabled.
+pub(crate) fn c1_controls_enabled() -> bool {
+    C1_CONTROLS.load(Ordering::Relaxed)
+}
+
+/// Loads the key sequences of the terminal from its terminfo entry (the `TERM` environment
+/// variable).
+///
+/// The arrows, the navigation and the function keys (F1-F12) are recognized then on the
+/// terminals which don't send the sequences of xterm, like the Linux and the Sun consoles. The
+/// sequences of terminfo take precedence over the built-in ones and the keys are reported without
+/// modifiers. They're used by [`EventParser`](struct.EventParser.html) as well.
+///
+/// It returns a `NotFound` error if `TERM` isn't set or the terminal has no terminfo entry.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm::event::load_terminfo_keys;
+///
+/// // The built-in sequences are used without an entry
+/// let _ = load_terminfo_keys();
+/// ```
+#[cfg(unix)]
+pub fn load_terminfo_keys() -> Result<()> {
+    let term = std::env::var("TERM")
+        .map_err(|_| std::io::Error::new(std::io::ErrorKind::NotFound, "TERM is not set"))?;
+
+    sys::unix::parse::set_key_sequences(sys::unix::terminfo::key_sequences(&term)?);
+    Ok(())
+}
+
+/// Reads the mouse events from gpm, the mouse server of the Linux virtual console.
+///
+/// The virtual console doesn't report the mouse like the terminal emulators do, gpm reports the
+/// motion, the drags, the wheel and the modifiers as [`Event::Mouse`](enum.Event.html) events.
+/// Returns `false` if the standard streams aren't a virtual console (`/dev/ttyN`) or gpm doesn't
+/// run, the mouse events aren't reported then.
+///
+/// The connection is picked up by the next [`read`](fn.read.html)/[`poll`](fn.poll.html), gpm
+/// keeps drawing the pointer.
+///
+/// Only available on Linux, with the `gpm` feature.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm::{event, execute};
+///
+/// if !event::enable_gpm()? {
+///     execute!(std::io::stdout(), event::EnableMouseCapture)?;
+/// }
+/// # crossterm::Result::Ok(())
+/// ```
+#[cfg(all(target_os = "linux", feature = "gpm"))]
+pub fn enable_gpm() -> Result<bool> {
+    sys::unix::gpm::connect()
+}
+
+/// Closes the connection opened by [`enable_gpm`](fn.enable_gpm.html), gpm handles the mouse on
+/// its own again (selecting text, ...).
+///
+/// Only available on Linux, with the `gpm` feature.
+#[cfg(all(target_os = "linux", feature = "gpm"))]
+pub fn disable_gpm() {
+    sys::unix::gpm::disconnect()
+}
+
+/// Returns whether the mouse capture is enabled, as tracked by the mouse capture commands.
+pub(crate) fn is_mouse_capture_enabled() -> bool {
+    MOUSE_CAPTURE.load(Ordering::Relaxed)
+}
+
+/// Returns whether the focus events are enabled, as tracked by the focus change commands.
+#[cfg(windows)]
+pub(crate) fn is_focus_change_enabled() -> bool {
+    FOCUS_CHANGE.load(Ordering::Relaxed)
+}
+
 /// Polls to check if there are any `InternalEvent`s that can be read within the given duration.
 pub(crate) fn poll_internal<F>(timeout: Option<Duration>, filter: &F) -> Result<bool>
 where
@@ -220,7 +660,10 @@ where
     } else {
         (lock_internal_event_reader(), None)
     };
-    reader.poll(timeout, filter)
+    let result = reader.poll(timeout, filter);
+    // A thread waiting for a reply gets the reader before this one can lock it again
+    MappedMutexGuard::unlock_fair(reader);
+    result
 }
 
 /// Reads a single `InternalEvent`.
@@ -228,18 +671,31 @@ pub(crate) fn read_internal<F>(filter: &F) -> Result<InternalEvent>
 where
     F: Filter,
 {
-    let mut reader = lock_internal_event_reader();
-    reader.read(filter)
+    loop {
+        let mut reader = lock_internal_event_reader();
+        if let Some(event) = reader.try_read(filter)? {
+            return Ok(event);
+        }
+        // Hands the reader over to the thread waiting for the reply
+        MappedMutexGuard::unlock_fair(reader);
+    }
 }
 
 /// A command that enables mouse event capturing.
 ///
 /// Mouse events can be captured with [read](./fn.read.html)/[poll](./fn.poll.html).
+///
+/// It does nothing in the [reduced decorations](../accessibility/index.html) mode.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct EnableMouseCapture;
 
 impl Command for EnableMouseCapture {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        if crate::accessibility::reduced_decorations() {
+            return Ok(());
+        }
+
+        MOUSE_CAPTURE.store(true, Ordering::Relaxed);
         f.write_str(concat!(
             // Normal tracking: Send mouse X & Y on button press and release
             csi!("?1000h"),
@@ -256,7 +712,13 @@ impl Command for EnableMouseCapture {
 
     #[cfg(windows)]
     fn execute_winapi(&self) -> Result<()> {
-        sys::windows::enable_mouse_capture()
+        if crate::accessibility::reduced_decorations() {
+            return Ok(());
+        }
+
+        sys::windows::enable_mouse_capture()?;
+        MOUSE_CAPTURE.store(true, Ordering::Relaxed);
+        Ok(())
     }
 
     #[cfg(windows)]
@@ -273,6 +735,7 @@ pub struct DisableMouseCapture;
 
 impl Command for DisableMouseCapture {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        MOUSE_CAPTURE.store(false, Ordering::Relaxed);
         f.write_str(concat!(
             // The inverse commands of EnableMouseCapture, in reverse order.
             csi!("?1006l"),
@@ -285,7 +748,209 @@ impl Command for DisableMouseCapture {
 
     #[cfg(windows)]
     fn execute_winapi(&self) -> Result<()> {
-        sys::windows::disable_mouse_capture()
+        sys::windows::disable_mouse_capture()?;
+        MOUSE_CAPTURE.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+
+    #[cfg(windows)]
+    fn is_ansi_code_supported(&self) -> bool {
+        false
+    }
+}
+
+/// A command that enables the focus events, `Event::FocusGained` and `Event::FocusLost` are sent
+/// when the terminal gains or loses the focus.
+///
+/// Focus events can be read with [read](./fn.read.html)/[poll](./fn.poll.html).
+///
+/// **Note** that the Windows console always reports the focus, the events are only sent while
+/// they're enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnableFocusChange;
+
+impl Command for EnableFocusChange {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        FOCUS_CHANGE.store(true, Ordering::Relaxed);
+        f.write_str(csi!("?1004h"))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        FOCUS_CHANGE.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    #[cfg(windows)]
+    fn is_ansi_code_supported(&self) -> bool {
+        false
+    }
+}
+
+/// A command that disables the focus events.
+///
+/// Focus events can be read with [read](./fn.read.html)/[poll](./fn.poll.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisableFocusChange;
+
+impl Command for DisableFocusChange {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        FOCUS_CHANGE.store(false, Ordering::Relaxed);
+        f.write_str(csi!("?1004l"))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        FOCUS_CHANGE.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+
+    #[cfg(windows)]
+    fn is_ansi_code_supported(&self) -> bool {
+        false
+    }
+}
+
+/// A command that enables the bracketed paste mode, the pasted text is sent as a single
+/// `Event::Paste` instead of a key event for every character.
+///
+/// The pasted text can be read with [read](./fn.read.html)/[poll](./fn.poll.html).
+///
+/// **Note** that the Windows console has no WinAPI equivalent. The escape sequence is written if
+/// the console supports the virtual terminal sequences (Windows 10+, Windows Terminal), it fails
+/// with `io::ErrorKind::Unsupported` otherwise. The paste is only read as a single event if the
+/// console sends the input as escape sequences, see
+/// [`set_virtual_terminal_input`](fn.set_virtual_terminal_input.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnableBracketedPaste;
+
+impl Command for EnableBracketedPaste {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str(csi!("?2004h"))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "The bracketed paste isn't supported by the Windows console",
+        ))
+    }
+
+    #[cfg(windows)]
+    fn is_ansi_code_supported(&self) -> bool {
+        false
+    }
+}
+
+/// A command that disables the bracketed paste mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisableBracketedPaste;
+
+impl Command for DisableBracketedPaste {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str(csi!("?2004l"))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+
+    #[cfg(windows)]
+    fn is_ansi_code_supported(&self) -> bool {
+        false
+    }
+}
+
+bitflags! {
+    /// The enhancements of the [kitty keyboard protocol](https://sw.kovidgoyal.net/kitty/keyboard-protocol/)
+    /// pushed with [`PushKeyboardEnhancementFlags`](struct.PushKeyboardEnhancementFlags.html).
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct KeyboardEnhancementFlags: u8 {
+        /// Sends the keys which are ambiguous in the other encodings as escape sequences: Esc,
+        /// the Alt and Ctrl combinations (Ctrl+I isn't Tab, Alt+[ doesn't start a sequence, ...).
+        const DISAMBIGUATE_ESCAPE_CODES = 0b0000_0001;
+        /// Sends the repeats and the releases of the keys as well, see
+        /// [`KeyEventKind`](enum.KeyEventKind.html).
+        const REPORT_EVENT_TYPES = 0b0000_0010;
+        /// Sends the key with Shift along with the key, `Shift+1` is read as `!` with Shift.
+        const REPORT_ALTERNATE_KEYS = 0b0000_0100;
+        /// Sends all the keys as escape sequences, the text keys and Enter, Tab and Backspace as
+        /// well.
+        const REPORT_ALL_KEYS_AS_ESCAPE_CODES = 0b0000_1000;
+    }
+}
+
+/// A command that enables the given enhancements of the
+/// [kitty keyboard protocol](https://sw.kovidgoyal.net/kitty/keyboard-protocol/), supported by
+/// kitty, foot, WezTerm and others.
+///
+/// The flags are pushed on a stack of the terminal, pop them with
+/// [`PopKeyboardEnhancementFlags`](struct.PopKeyboardEnhancementFlags.html) before the program
+/// exits. The terminals which don't support the protocol ignore it and keep sending the keys as
+/// before.
+///
+/// **Note** that the Windows console has no WinAPI equivalent. The escape sequence is written if
+/// the console supports the virtual terminal sequences (Windows 10+, Windows Terminal), it fails
+/// with `io::ErrorKind::Unsupported` otherwise. The enhanced keys are only read if the console
+/// sends the input as escape sequences, see
+/// [`set_virtual_terminal_input`](fn.set_virtual_terminal_input.html).
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::{stdout, Write};
+///
+/// use crossterm::{
+///     event::{KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags},
+///     execute, Result,
+/// };
+///
+/// fn main() -> Result<()> {
+///     execute!(
+///         stdout(),
+///         PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
+///     )?;
+///     // Esc is read without waiting for the escape timeout
+///     execute!(stdout(), PopKeyboardEnhancementFlags)
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PushKeyboardEnhancementFlags(pub KeyboardEnhancementFlags);
+
+impl Command for PushKeyboardEnhancementFlags {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        write!(f, csi!(">{}u"), self.0.bits())
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "The kitty keyboard protocol isn't supported by the Windows console",
+        ))
+    }
+
+    #[cfg(windows)]
+    fn is_ansi_code_supported(&self) -> bool {
+        false
+    }
+}
+
+/// A command that disables the enhancements pushed last with
+/// [`PushKeyboardEnhancementFlags`](struct.PushKeyboardEnhancementFlags.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PopKeyboardEnhancementFlags;
+
+impl Command for PopKeyboardEnhancementFlags {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str(csi!("<1u"))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
     }
 
     #[cfg(windows)]
@@ -296,7 +961,7 @@ impl Command for DisableMouseCapture {
 
 /// Represents an event.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, PartialOrd, PartialEq, Eq, Clone, Copy, Hash)]
+#[derive(Debug, PartialOrd, PartialEq, Eq, Clone, Hash)]
 pub enum Event {
     /// A single key event with additional pressed modifiers.
     Key(KeyEvent),
@@ -305,6 +970,61 @@ pub enum Event {
     /// An resize event with new dimensions after resize (columns, rows).
     /// **Note** that resize events can be occur in batches.
     Resize(u16, u16),
+    /// The terminal gained the focus, sent after [`EnableFocusChange`](struct.EnableFocusChange.html).
+    FocusGained,
+    /// The terminal lost the focus, sent after [`EnableFocusChange`](struct.EnableFocusChange.html).
+    FocusLost,
+    /// Text pasted into the terminal, sent after
+    /// [`EnableBracketedPaste`](struct.EnableBracketedPaste.html).
+    ///
+    /// The text is sent as it was pasted, with the line breaks and the escape sequences it
+    /// contains, instead of a key event for every character.
+    ///
+    /// **Note** that it's only sent on UNIX, and on Windows with
+    /// [`set_virtual_terminal_input`](fn.set_virtual_terminal_input.html).
+    Paste(String),
+    /// A complete escape sequence which crossterm doesn't know (the raw bytes).
+    ///
+    /// It allows to log or handle the sequences which aren't supported yet.
+    ///
+    /// **Note** that it's only sent on Windows with
+    /// [`set_virtual_terminal_input`](fn.set_virtual_terminal_input.html), the console reports
+    /// the events as input records otherwise.
+    Unsupported(Vec<u8>),
+    /// The terminal was closed (hangup, the other end of the PTY was closed, ...).
+    ///
+    /// It's the last event, reading or polling for events fails afterwards.
+    ///
+    /// **Note** that it's only sent on UNIX, the process is terminated when the console closes on
+    /// Windows.
+    Closed,
+    /// The process was continued after it was stopped (`SIGCONT`).
+    ///
+    /// The raw mode is enabled again if it was enabled before, the shell restores its own mode
+    /// while the process is stopped. Everything else (alternate screen, mouse capture, ...) is up
+    /// to the application.
+    ///
+    /// **Note** that it's only sent on UNIX.
+    Resumed,
+    /// The process received a signal, sent after [`set_signal_events`](fn.set_signal_events.html).
+    Signal(SignalKind),
+}
+
+/// A signal reported as an [`Event::Signal`](enum.Event.html#variant.Signal).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialOrd, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum SignalKind {
+    /// `SIGINT` on UNIX, `CTRL_C_EVENT` on Windows.
+    ///
+    /// **Note** that the Ctrl+C key is read as a key event in the raw mode, the signal is sent by
+    /// another process then.
+    Interrupt,
+    /// `SIGTERM`, only sent on UNIX.
+    Terminate,
+    /// `SIGHUP`, only sent on UNIX.
+    Hangup,
+    /// `CTRL_BREAK_EVENT`, only sent on Windows.
+    Break,
 }
 
 /// Represents a mouse event.
@@ -333,6 +1053,8 @@ pub struct MouseEvent {
     pub row: u16,
     /// The key modifiers active when the event occurred.
     pub modifiers: KeyModifiers,
+    /// The distance the wheel scrolled for the scroll events, zero for the other ones.
+    pub scroll_delta: ScrollDelta,
 }
 
 /// A mouse event kind.
@@ -359,6 +1081,15 @@ pub enum MouseEventKind {
     ScrollDown,
     /// Scrolled mouse wheel upwards (away from the user).
     ScrollUp,
+    /// Scrolled mouse wheel to the left.
+    ScrollLeft,
+    /// Scrolled mouse wheel to the right.
+    ScrollRight,
+    /// Pressed the contained mouse button a second time, reported after its `Down` event. See
+    /// [`set_double_click_interval`](fn.set_double_click_interval.html).
+    DoubleClick(MouseButton),
+    /// Pressed the contained mouse button a third time, reported after its `Down` event.
+    TripleClick(MouseButton),
 }
 
 /// Represents a mouse button.
@@ -373,17 +1104,114 @@ pub enum MouseButton {
     Middle,
 }
 
+/// The distance a mouse wheel scrolled, in 120ths of a notch.
+///
+/// The terminals report whole notches, [`ScrollDelta::NOTCH`](#associatedconstant.NOTCH) per
+/// event. The Windows console reports the precise distance of the touchpads and of the high
+/// resolution wheels, a fraction of a notch per event, to scroll smoothly.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Default, PartialOrd, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct ScrollDelta {
+    /// The horizontal distance, positive to the right.
+    pub x: i16,
+    /// The vertical distance, positive upwards (away from the user).
+    pub y: i16,
+}
+
+impl ScrollDelta {
+    /// The distance of a notch of the wheel, `WHEEL_DELTA` on Windows.
+    pub const NOTCH: i16 = 120;
+
+    /// Returns the distance of one notch in the direction of the scroll event, zero for the other
+    /// events.
+    pub(crate) fn notch(kind: MouseEventKind) -> ScrollDelta {
+        let (x, y) = match kind {
+            MouseEventKind::ScrollUp => (0, ScrollDelta::NOTCH),
+            MouseEventKind::ScrollDown => (0, -ScrollDelta::NOTCH),
+            MouseEventKind::ScrollLeft => (-ScrollDelta::NOTCH, 0),
+            MouseEventKind::ScrollRight => (ScrollDelta::NOTCH, 0),
+            _ => (0, 0),
+        };
+        ScrollDelta { x, y }
+    }
+}
+
 bitflags! {
     /// Represents key modifiers (shift, control, alt).
+    ///
+    /// Super, Hyper and Meta are only reported with the
+    /// [kitty keyboard protocol](struct.PushKeyboardEnhancementFlags.html), the other encodings
+    /// report Meta as Alt.
     #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     pub struct KeyModifiers: u8 {
         const SHIFT = 0b0000_0001;
         const CONTROL = 0b0000_0010;
         const ALT = 0b0000_0100;
+        const SUPER = 0b0000_1000;
+        const HYPER = 0b0001_0000;
+        const META = 0b0010_0000;
+        const NONE = 0b0000_0000;
+    }
+}
+
+bitflags! {
+    /// Additional information about a key, see [`KeyEvent::state`](struct.KeyEvent.html#structfield.state).
+    ///
+    /// The lock keys are left out when the key events are compared, a key read with Caps Lock on
+    /// is equal to the same key without it.
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    #[derive(Default)]
+    pub struct KeyEventState: u8 {
+        /// The key is on the numeric keypad: the digits, the operators and Enter, and the
+        /// navigation keys of the keypad with Num Lock off.
+        ///
+        /// Reported on Windows, with the kitty keyboard protocol, and by the terminals in the
+        /// application keypad mode (`ESC =`).
+        const KEYPAD = 0b0000_0001;
+        /// Caps Lock was on when the key was pressed.
+        ///
+        /// Reported on Windows and with the kitty keyboard protocol.
+        const CAPS_LOCK = 0b0000_0010;
+        /// Num Lock was on when the key was pressed.
+        ///
+        /// Reported on Windows and with the kitty keyboard protocol.
+        const NUM_LOCK = 0b0000_0100;
+        /// Scroll Lock was on when the key was pressed.
+        ///
+        /// Only reported on Windows.
+        const SCROLL_LOCK = 0b0000_1000;
         const NONE = 0b0000_0000;
     }
 }
 
+impl KeyEventState {
+    /// Returns the state without the lock keys, which don't change which key was pressed.
+    fn without_locks(self) -> KeyEventState {
+        self - (KeyEventState::CAPS_LOCK | KeyEventState::NUM_LOCK | KeyEventState::SCROLL_LOCK)
+    }
+}
+
+/// Represents whether a key was pressed, held down or released.
+///
+/// Windows reports the releases of the keys, the repeats of a held key are reported as presses.
+/// On UNIX the keys are only pressed, unless the terminal supports the
+/// [`KeyboardEnhancementFlags::REPORT_EVENT_TYPES`](struct.KeyboardEnhancementFlags.html) of the
+/// kitty keyboard protocol.
+///
+/// **Note** that the program can start reading before the key which launched it is released,
+/// skip the releases of the keys which weren't pressed.
+#[derive(Debug, Default, PartialOrd, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum KeyEventKind {
+    /// The key was pressed.
+    #[default]
+    Press,
+    /// The key is held down and repeated.
+    Repeat,
+    /// The key was released.
+    Release,
+}
+
 /// Represents a key event.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, PartialOrd, Clone, Copy)]
@@ -392,11 +1220,42 @@ pub struct KeyEvent {
     pub code: KeyCode,
     /// Additional key modifiers.
     pub modifiers: KeyModifiers,
+    /// Whether the key was pressed, repeated or released.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub kind: KeyEventKind,
+    /// Additional information about the key, whether it's on the keypad or Caps Lock is on for
+    /// example.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub state: KeyEventState,
 }
 
 impl KeyEvent {
+    /// Creates the event of a key press.
     pub fn new(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
-        KeyEvent { code, modifiers }
+        KeyEvent::new_with_kind(code, modifiers, KeyEventKind::Press)
+    }
+
+    /// Creates the event of a key press, repeat or release.
+    pub fn new_with_kind(code: KeyCode, modifiers: KeyModifiers, kind: KeyEventKind) -> KeyEvent {
+        KeyEvent::new_with_kind_and_state(code, modifiers, kind, KeyEventState::NONE)
+    }
+
+    /// Creates the event of a key press, repeat or release with the given state.
+    ///
+    /// The lock keys of the state (`CAPS_LOCK`, `NUM_LOCK`, `SCROLL_LOCK`) are ignored when the
+    /// events are compared or hashed, only `KEYPAD` tells two events apart.
+    pub fn new_with_kind_and_state(
+        code: KeyCode,
+        modifiers: KeyModifiers,
+        kind: KeyEventKind,
+        state: KeyEventState,
+    ) -> KeyEvent {
+        KeyEvent {
+            code,
+            modifiers,
+            kind,
+            state,
+        }
     }
 
     // modifies the KeyEvent,
@@ -419,10 +1278,7 @@ impl KeyEvent {
 
 impl From<KeyCode> for KeyEvent {
     fn from(code: KeyCode) -> Self {
-        KeyEvent {
-            code,
-            modifiers: KeyModifiers::empty(),
-        }
+        KeyEvent::new(code, KeyModifiers::empty())
     }
 }
 
@@ -431,12 +1287,19 @@ impl PartialEq for KeyEvent {
         let KeyEvent {
             code: lhs_code,
             modifiers: lhs_modifiers,
+            kind: lhs_kind,
+            state: lhs_state,
         } = self.normalize_case();
         let KeyEvent {
             code: rhs_code,
             modifiers: rhs_modifiers,
+            kind: rhs_kind,
+            state: rhs_state,
         } = other.normalize_case();
-        (lhs_code == rhs_code) && (lhs_modifiers == rhs_modifiers)
+        (lhs_code == rhs_code)
+            && (lhs_modifiers == rhs_modifiers)
+            && (lhs_kind == rhs_kind)
+            && (lhs_state.without_locks() == rhs_state.without_locks())
     }
 }
 
@@ -444,9 +1307,16 @@ impl Eq for KeyEvent {}
 
 impl Hash for KeyEvent {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        let KeyEvent { code, modifiers } = self.normalize_case();
+        let KeyEvent {
+            code,
+            modifiers,
+            kind,
+            state: key_state,
+        } = self.normalize_case();
         code.hash(state);
         modifiers.hash(state);
+        kind.hash(state);
+        key_state.without_locks().hash(state);
     }
 }
 
@@ -494,6 +1364,83 @@ pub enum KeyCode {
     Null,
     /// Escape key.
     Esc,
+    /// A media key.
+    ///
+    /// Reported on Windows, and with the kitty keyboard protocol with
+    /// [`KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES`](struct.KeyboardEnhancementFlags.html).
+    Media(MediaKeyCode),
+    /// A modifier key pressed or released alone.
+    ///
+    /// Only reported with the kitty keyboard protocol with
+    /// [`KeyboardEnhancementFlags::REPORT_ALL_KEYS_AS_ESCAPE_CODES`](struct.KeyboardEnhancementFlags.html),
+    /// the modifiers are reported along with the other keys otherwise.
+    Modifier(ModifierKeyCode),
+}
+
+/// Represents a media key, see [`KeyCode::Media`](enum.KeyCode.html#variant.Media).
+#[derive(Debug, PartialOrd, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MediaKeyCode {
+    /// Play media key.
+    Play,
+    /// Pause media key.
+    Pause,
+    /// Play/Pause media key.
+    PlayPause,
+    /// Reverse media key.
+    Reverse,
+    /// Stop media key.
+    Stop,
+    /// Fast-forward media key.
+    FastForward,
+    /// Rewind media key.
+    Rewind,
+    /// Next-track media key.
+    TrackNext,
+    /// Previous-track media key.
+    TrackPrevious,
+    /// Record media key.
+    Record,
+    /// Lower-volume media key.
+    LowerVolume,
+    /// Raise-volume media key.
+    RaiseVolume,
+    /// Mute media key.
+    MuteVolume,
+}
+
+/// Represents a modifier key, see [`KeyCode::Modifier`](enum.KeyCode.html#variant.Modifier).
+#[derive(Debug, PartialOrd, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ModifierKeyCode {
+    /// Left Shift key.
+    LeftShift,
+    /// Left Control key.
+    LeftControl,
+    /// Left Alt key.
+    LeftAlt,
+    /// Left Super key.
+    LeftSuper,
+    /// Left Hyper key.
+    LeftHyper,
+    /// Left Meta key.
+    LeftMeta,
+    /// Right Shift key.
+    RightShift,
+    /// Right Control key.
+    RightControl,
+    /// Right Alt key.
+    RightAlt,
+    /// Right Super key.
+    RightSuper,
+    /// Right Hyper key.
+    RightHyper,
+    /// Right Meta key.
+    RightMeta,
+    /// Iso Level3 Shift key, AltGr on most layouts.
+    IsoLevel3Shift,
+    /// Iso Level5 Shift key.
+    IsoLevel5Shift,
 }
 
 /// An internal event.
@@ -501,12 +1448,28 @@ pub enum KeyCode {
 /// Encapsulates publicly available `Event` with additional internal
 /// events that shouldn't be publicly available to the crate users.
 #[derive(Debug, PartialOrd, PartialEq, Hash, Clone, Eq)]
+// The data of the replies is boxed to keep the events small, see `test_event_size`
+#[allow(clippy::box_collection)]
 pub(crate) enum InternalEvent {
     /// An event.
     Event(Event),
     /// A cursor position (`col`, `row`).
-    #[cfg(unix)]
     CursorPosition(u16, u16),
+    /// The primary device attributes, the reply every terminal sends to `ESC [ c`.
+    PrimaryDeviceAttributes(Box<Vec<u16>>),
+    /// The status of a private mode (`mode`, `status`).
+    ModeReport(u16, u8),
+    /// The name and version of the terminal.
+    TerminalVersion(Box<String>),
+    /// The content of the clipboard.
+    ClipboardContent(Box<Vec<u8>>),
+    /// The message of a reply to a kitty graphics command, `OK` if it succeeded.
+    KittyGraphics(Box<String>),
+    /// The name and value of a terminal capability, `None` if the terminal doesn't know the
+    /// capability it was asked for.
+    Capability(Box<Option<(String, String)>>),
+    /// The enhancements of the kitty keyboard protocol currently enabled, the reply to `ESC [ ? u`.
+    KeyboardEnhancementFlags(KeyboardEnhancementFlags),
 }
 
 #[cfg(test)]
@@ -514,7 +1477,9 @@ mod tests {
     use std::collections::hash_map::DefaultHasher;
     use std::hash::{Hash, Hasher};
 
-    use super::{KeyCode, KeyEvent, KeyModifiers};
+    use super::{
+        Event, InternalEvent, KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers,
+    };
 
     #[test]
     fn test_equality() {
@@ -525,6 +1490,25 @@ mod tests {
         assert_eq!(uppercase_d, uppercase_d_with_shift);
     }
 
+    #[test]
+    fn test_equality_ignores_the_lock_keys() {
+        let a = KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE);
+        let a_with_caps_lock = KeyEvent::new_with_kind_and_state(
+            KeyCode::Char('a'),
+            KeyModifiers::NONE,
+            KeyEventKind::Press,
+            KeyEventState::CAPS_LOCK | KeyEventState::NUM_LOCK,
+        );
+        let keypad_a = KeyEvent::new_with_kind_and_state(
+            KeyCode::Char('a'),
+            KeyModifiers::NONE,
+            KeyEventKind::Press,
+            KeyEventState::KEYPAD,
+        );
+        assert_eq!(a, a_with_caps_lock);
+        assert_ne!(a, keypad_a);
+    }
+
     #[test]
     fn test_hash() {
         let lowercase_d_with_shift_hash = {
@@ -545,4 +1529,12 @@ mod tests {
         assert_eq!(lowercase_d_with_shift_hash, uppercase_d_with_shift_hash);
         assert_eq!(uppercase_d_hash, uppercase_d_with_shift_hash);
     }
+
+    #[test]
+    fn test_event_size() {
+        // `Event`s are queued by the hundreds during mouse-move floods. A `String` or a `Vec` is
+        // the largest data a variant may hold, larger data has to be boxed.
+        assert!(std::mem::size_of::<Event>() <= 32);
+        assert!(std::mem::size_of::<InternalEvent>() <= 32);
+    }
 }
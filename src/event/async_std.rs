@@ -0,0 +1,77 @@
+#[cfg(unix)]
+use std::{os::unix::io::RawFd, time::Duration};
+
+#[cfg(unix)]
+use async_io::Async;
+
+use crate::Result;
+
+#[cfg(unix)]
+use super::async_wait::{readiness, SourceFd};
+use super::{
+    async_wait::{try_read, wait_blocking},
+    Event,
+};
+
+/// Reads a single [`Event`](../enum.Event.html) without blocking the thread of the executor.
+///
+/// **This function is not available by default. You have to use the `async-std` feature flag to
+/// make it available.**
+///
+/// It's the async counterpart of [`event::read`](../fn.read.html) for the
+/// [async-std](https://crates.io/crates/async-std) runtime. On UNIX the terminal is waited for in
+/// the reactor of async-std, on Windows and with the `poll(2)` event source (without the
+/// `mio-poll` feature) a thread of the blocking pool waits for the events.
+///
+/// The future can be dropped at any time, in `async_std::future::timeout` for example, without
+/// losing an event. The same rules as for `read` apply: don't combine it with `read`, `poll` or
+/// the [`EventStream`](../struct.EventStream.html) on another thread.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use crossterm::{event, Result};
+///
+/// async fn print_events() -> Result<()> {
+///     loop {
+///         match async_std::future::timeout(Duration::from_secs(1), event::async_std::read()).await {
+///             Ok(event) => println!("{:?}", event?),
+///             Err(_) => println!("No event within a second"),
+///         }
+///     }
+/// }
+/// ```
+pub async fn read() -> Result<Event> {
+    loop {
+        if let Some(event) = try_read()? {
+            return Ok(event);
+        }
+
+        #[cfg(unix)]
+        {
+            if let Some((fd, escape_timeout)) = readiness() {
+                wait_readable(fd, escape_timeout).await?;
+                continue;
+            }
+        }
+
+        wait_blocking(|poll| async_std::task::spawn_blocking(move || poll.run())).await?;
+    }
+}
+
+/// Waits until the file descriptor of the event source is readable, or the escape timeout of the
+/// ambiguous bytes elapsed.
+#[cfg(unix)]
+async fn wait_readable(fd: RawFd, escape_timeout: Option<Duration>) -> Result<()> {
+    // The event source doesn't rely on the mode of the file descriptor, it's left as it is
+    let fd = Async::new_nonblocking(SourceFd(fd))?;
+    match escape_timeout {
+        Some(timeout) => match async_std::future::timeout(timeout, fd.readable()).await {
+            Ok(readable) => readable,
+            Err(_) => Ok(()),
+        },
+        None => fd.readable().await,
+    }
+}
@@ -0,0 +1,110 @@
+#[cfg(all(unix, feature = "async-std"))]
+use std::os::unix::io::{AsFd, BorrowedFd};
+#[cfg(unix)]
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use crate::Result;
+
+use super::{filter::EventFilter, lock_internal_event_reader, poll_internal, sys::Waker, Event};
+
+/// Reads an event if one is available, without waiting for it.
+///
+/// The event is read right away, there's no await point where the future could be dropped.
+pub(super) fn try_read() -> Result<Option<Event>> {
+    if poll_internal(Some(Duration::from_secs(0)), &EventFilter)? {
+        return super::read().map(Some);
+    }
+    Ok(None)
+}
+
+/// Returns the file descriptor which becomes readable when there's new input, and how long to
+/// wait at most for the rest of the ambiguous bytes. `None` if the event source has to be waited
+/// for in a thread.
+#[cfg(unix)]
+pub(super) fn readiness() -> Option<(RawFd, Option<Duration>)> {
+    lock_internal_event_reader().readiness()
+}
+
+/// A file descriptor of the event source, which isn't closed when it's dropped.
+#[cfg(unix)]
+pub(super) struct SourceFd(pub(super) RawFd);
+
+#[cfg(unix)]
+impl AsRawFd for SourceFd {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0
+    }
+}
+
+#[cfg(all(unix, feature = "async-std"))]
+impl AsFd for SourceFd {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        // The event source outlives the wait
+        unsafe { BorrowedFd::borrow_raw(self.0) }
+    }
+}
+
+/// Waits for an event in a thread of the blocking pool of the runtime.
+///
+/// The `spawn_blocking` closure runs [`BlockingPoll::run`] in the pool. The thread is woken when
+/// the future is dropped.
+pub(super) async fn wait_blocking<S, F>(spawn_blocking: S) -> Result<()>
+where
+    S: FnOnce(BlockingPoll) -> F,
+    F: Future<Output = Result<()>>,
+{
+    // The thread holds the lock of the reader while it waits
+    let waker = lock_internal_event_reader().waker();
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let mut guard = CancelOnDrop {
+        cancelled: cancelled.clone(),
+        waker,
+        done: false,
+    };
+
+    let result = spawn_blocking(BlockingPoll { cancelled }).await;
+    guard.done = true;
+    result
+}
+
+/// The wait of `wait_blocking` running in the blocking pool.
+pub(super) struct BlockingPoll {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl BlockingPoll {
+    /// Blocks until an event is available or the future waiting for it was dropped.
+    pub(super) fn run(self) -> Result<()> {
+        loop {
+            match poll_internal(None, &EventFilter) {
+                // Woken by another reader
+                Ok(false) if !self.cancelled.load(Ordering::SeqCst) => continue,
+                result => return result.map(drop),
+            }
+        }
+    }
+}
+
+/// Wakes the thread waiting for an event if the future is dropped.
+struct CancelOnDrop {
+    cancelled: Arc<AtomicBool>,
+    waker: Waker,
+    done: bool,
+}
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        if !self.done {
+            self.cancelled.store(true, Ordering::SeqCst);
+            let _ = self.waker.wake();
+        }
+    }
+}
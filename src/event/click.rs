@@ -0,0 +1,175 @@
+use std::time::Instant;
+
+use super::{timeout, Event, InternalEvent, MouseButton, MouseEvent, MouseEventKind};
+
+/// Counts the consecutive presses of a mouse button, to report the double and the triple clicks.
+///
+/// A press is the next click of the previous one if it's the same button, in the same cell, within
+/// the [double click interval](../fn.set_double_click_interval.html) of the previous press. The
+/// count starts over after a triple click.
+#[derive(Debug, Default)]
+pub(crate) struct ClickTracker {
+    last: Option<LastClick>,
+    /// The double or triple click to report after the press which was just read.
+    pending: Option<InternalEvent>,
+}
+
+#[derive(Debug)]
+struct LastClick {
+    button: MouseButton,
+    column: u16,
+    row: u16,
+    time: Instant,
+    count: u8,
+}
+
+impl ClickTracker {
+    /// Takes the double or triple click which follows the last press.
+    pub(crate) fn take_pending(&mut self) -> Option<InternalEvent> {
+        self.pending.take()
+    }
+
+    /// Looks at an event read from the source, a double or triple click is pending after it if
+    /// it's the second or third click.
+    pub(crate) fn track(&mut self, event: &InternalEvent) {
+        // The clicks can't be timed without a clock (in the browser)
+        if let Some(now) = timeout::now() {
+            self.track_at(event, now);
+        }
+    }
+
+    fn track_at(&mut self, event: &InternalEvent, now: Instant) {
+        let mouse = match event {
+            InternalEvent::Event(Event::Mouse(mouse)) => *mouse,
+            _ => return,
+        };
+        let button = match mouse.kind {
+            MouseEventKind::Down(button) => button,
+            _ => return,
+        };
+
+        let interval = super::double_click_interval();
+        let count = match &self.last {
+            Some(last)
+                if last.count < 3
+                    && last.button == button
+                    && (last.column, last.row) == (mouse.column, mouse.row)
+                    && now.saturating_duration_since(last.time) <= interval =>
+            {
+                last.count + 1
+            }
+            _ => 1,
+        };
+
+        self.last = Some(LastClick {
+            button,
+            column: mouse.column,
+            row: mouse.row,
+            time: now,
+            count,
+        });
+
+        let kind = match count {
+            2 => MouseEventKind::DoubleClick(button),
+            3 => MouseEventKind::TripleClick(button),
+            _ => return,
+        };
+        self.pending = Some(InternalEvent::Event(Event::Mouse(MouseEvent {
+            kind,
+            ..mouse
+        })));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{
+        super::{Event, InternalEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind},
+        ClickTracker,
+    };
+
+    fn mouse(kind: MouseEventKind, column: u16) -> InternalEvent {
+        InternalEvent::Event(Event::Mouse(MouseEvent {
+            kind,
+            column,
+            row: 3,
+            modifiers: KeyModifiers::NONE,
+            scroll_delta: Default::default(),
+        }))
+    }
+
+    /// Presses the button at the times in milliseconds, returns the clicks reported.
+    fn clicks(presses: &[(MouseButton, u16, u64)]) -> Vec<Option<MouseEventKind>> {
+        let start = Instant::now();
+        let mut tracker = ClickTracker::default();
+        presses
+            .iter()
+            .map(|(button, column, millis)| {
+                let at = start + Duration::from_millis(*millis);
+                tracker.track_at(&mouse(MouseEventKind::Down(*button), *column), at);
+                tracker.track_at(&mouse(MouseEventKind::Up(*button), *column), at);
+                tracker.take_pending().map(|event| match event {
+                    InternalEvent::Event(Event::Mouse(mouse)) => mouse.kind,
+                    event => panic!("{:?} is not a mouse event", event),
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_double_and_triple_clicks() {
+        use MouseButton::*;
+
+        assert_eq!(
+            clicks(&[(Left, 5, 0), (Left, 5, 200), (Left, 5, 400), (Left, 5, 600)]),
+            vec![
+                None,
+                Some(MouseEventKind::DoubleClick(Left)),
+                Some(MouseEventKind::TripleClick(Left)),
+                None
+            ]
+        );
+        assert_eq!(
+            clicks(&[(Right, 5, 0), (Right, 5, 100)]),
+            vec![None, Some(MouseEventKind::DoubleClick(Right))]
+        );
+    }
+
+    #[test]
+    fn test_clicks_start_over() {
+        use MouseButton::*;
+
+        // Too late, elsewhere, another button
+        assert_eq!(clicks(&[(Left, 5, 0), (Left, 5, 2000)]), vec![None, None]);
+        assert_eq!(clicks(&[(Left, 5, 0), (Left, 6, 100)]), vec![None, None]);
+        assert_eq!(clicks(&[(Left, 5, 0), (Middle, 5, 100)]), vec![None, None]);
+    }
+
+    #[test]
+    fn test_double_click_keeps_the_modifiers() {
+        let mut tracker = ClickTracker::default();
+        let press = InternalEvent::Event(Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: 1,
+            row: 2,
+            modifiers: KeyModifiers::SHIFT,
+            scroll_delta: Default::default(),
+        }));
+        let now = Instant::now();
+        tracker.track_at(&press, now);
+        tracker.track_at(&press, now);
+
+        assert_eq!(
+            tracker.take_pending(),
+            Some(InternalEvent::Event(Event::Mouse(MouseEvent {
+                kind: MouseEventKind::DoubleClick(MouseButton::Left),
+                column: 1,
+                row: 2,
+                modifiers: KeyModifiers::SHIFT,
+                scroll_delta: Default::default(),
+            })))
+        );
+    }
+}
@@ -1,7 +1,18 @@
-use crate::event::InternalEvent;
+#[cfg(unix)]
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::event::{Event, InternalEvent};
+
+/// The number of threads waiting for a cursor position.
+#[cfg(unix)]
+static AWAITED_CURSOR_POSITIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// The number of threads waiting for a reply accepted by `QueryReplyFilter`.
+#[cfg(unix)]
+static AWAITED_QUERY_REPLIES: AtomicUsize = AtomicUsize::new(0);
 
 /// Interface for filtering an `InternalEvent`.
-pub(crate) trait Filter: Send + Sync + 'static {
+pub(crate) trait Filter {
     /// Returns whether the given event fulfills the filter.
     fn eval(&self, event: &InternalEvent) -> bool;
 }
@@ -17,24 +28,103 @@ impl Filter for CursorPositionFilter {
     }
 }
 
+/// Accepts the replies of the terminal to the queries, except the cursor position.
+#[cfg(unix)]
+#[derive(Debug, Clone)]
+pub(crate) struct QueryReplyFilter;
+
+#[cfg(unix)]
+impl Filter for QueryReplyFilter {
+    fn eval(&self, event: &InternalEvent) -> bool {
+        matches!(
+            *event,
+            InternalEvent::PrimaryDeviceAttributes(_)
+                | InternalEvent::ModeReport(_, _)
+                | InternalEvent::TerminalVersion(_)
+                | InternalEvent::ClipboardContent(_)
+                | InternalEvent::KittyGraphics(_)
+                | InternalEvent::Capability(_)
+                | InternalEvent::KeyboardEnhancementFlags(_)
+        )
+    }
+}
+
+/// Tells the polls of the other threads that a reply of the terminal is awaited, until it's
+/// dropped.
+///
+/// A poll holding the event reader (the one of an `EventStream`, ...) returns when it reads the
+/// reply, the waiting thread gets the reader instead of timing out.
+#[cfg(unix)]
+#[derive(Debug)]
+pub(crate) struct AwaitedReply(&'static AtomicUsize);
+
+#[cfg(unix)]
+impl AwaitedReply {
+    /// Awaits a reply accepted by `CursorPositionFilter`.
+    pub(crate) fn cursor_position() -> AwaitedReply {
+        AwaitedReply::new(&AWAITED_CURSOR_POSITIONS)
+    }
+
+    /// Awaits a reply accepted by `QueryReplyFilter`.
+    pub(crate) fn query_reply() -> AwaitedReply {
+        AwaitedReply::new(&AWAITED_QUERY_REPLIES)
+    }
+
+    fn new(count: &'static AtomicUsize) -> AwaitedReply {
+        count.fetch_add(1, Ordering::SeqCst);
+        AwaitedReply(count)
+    }
+}
+
+#[cfg(unix)]
+impl Drop for AwaitedReply {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Returns whether another thread waits for the reply, see `AwaitedReply`.
+#[cfg(unix)]
+pub(crate) fn is_awaited_reply(event: &InternalEvent) -> bool {
+    (CursorPositionFilter.eval(event) && AWAITED_CURSOR_POSITIONS.load(Ordering::SeqCst) > 0)
+        || (QueryReplyFilter.eval(event) && AWAITED_QUERY_REPLIES.load(Ordering::SeqCst) > 0)
+}
+
+#[cfg(not(unix))]
+pub(crate) fn is_awaited_reply(_: &InternalEvent) -> bool {
+    false
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct EventFilter;
 
 impl Filter for EventFilter {
-    #[cfg(unix)]
     fn eval(&self, event: &InternalEvent) -> bool {
         matches!(*event, InternalEvent::Event(_))
     }
+}
 
-    #[cfg(windows)]
-    fn eval(&self, _: &InternalEvent) -> bool {
-        true
+/// Accepts the events the predicate of `poll_where` and `read_where` returns `true` for.
+#[derive(Debug, Clone)]
+pub(crate) struct EventPredicateFilter<P>(pub(crate) P);
+
+impl<P> Filter for EventPredicateFilter<P>
+where
+    P: Fn(&Event) -> bool,
+{
+    fn eval(&self, event: &InternalEvent) -> bool {
+        match event {
+            InternalEvent::Event(event) => (self.0)(event),
+            _ => false,
+        }
     }
 }
 
+#[cfg(test)]
 #[derive(Debug, Clone)]
 pub(crate) struct InternalEventFilter;
 
+#[cfg(test)]
 impl Filter for InternalEventFilter {
     fn eval(&self, _: &InternalEvent) -> bool {
         true
@@ -45,7 +135,9 @@ impl Filter for InternalEventFilter {
 #[cfg(unix)]
 mod tests {
     use super::{
-        super::Event, CursorPositionFilter, EventFilter, Filter, InternalEvent, InternalEventFilter,
+        super::{Event, KeyCode, KeyboardEnhancementFlags},
+        CursorPositionFilter, EventFilter, EventPredicateFilter, Filter, InternalEvent,
+        InternalEventFilter, QueryReplyFilter,
     };
 
     #[test]
@@ -54,12 +146,32 @@ mod tests {
         assert!(CursorPositionFilter.eval(&InternalEvent::CursorPosition(0, 0)));
     }
 
+    #[test]
+    fn test_query_reply_filter_filters_query_replies() {
+        assert!(!QueryReplyFilter.eval(&InternalEvent::Event(Event::Resize(10, 10))));
+        assert!(!QueryReplyFilter.eval(&InternalEvent::CursorPosition(0, 0)));
+        assert!(QueryReplyFilter.eval(&InternalEvent::ModeReport(1049, 1)));
+        assert!(
+            QueryReplyFilter.eval(&InternalEvent::KeyboardEnhancementFlags(
+                KeyboardEnhancementFlags::empty()
+            ))
+        );
+    }
+
     #[test]
     fn test_event_filter_filters_events() {
         assert!(EventFilter.eval(&InternalEvent::Event(Event::Resize(10, 10))));
         assert!(!EventFilter.eval(&InternalEvent::CursorPosition(0, 0)));
     }
 
+    #[test]
+    fn test_event_predicate_filter_filters_events() {
+        let keys = EventPredicateFilter(|event: &Event| matches!(event, Event::Key(_)));
+        assert!(keys.eval(&InternalEvent::Event(Event::Key(KeyCode::Esc.into()))));
+        assert!(!keys.eval(&InternalEvent::Event(Event::Resize(10, 10))));
+        assert!(!keys.eval(&InternalEvent::CursorPosition(0, 0)));
+    }
+
     #[test]
     fn test_event_filter_filters_internal_events() {
         assert!(InternalEventFilter.eval(&InternalEvent::Event(Event::Resize(10, 10))));
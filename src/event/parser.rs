@@ -0,0 +1,229 @@
+use super::{source::unix::Parser, Event, InternalEvent};
+
+/// Parses the bytes of a terminal input into events.
+///
+/// It's the parser [`read`](fn.read.html) uses on UNIX, for the input coming from elsewhere (a
+/// socket, a serial line, a recording...). It never panics, whatever the bytes are:
+///
+/// * The sequences it doesn't know are returned as `Event::Unsupported`.
+/// * A sequence longer than 64 KiB is thrown away, the memory used by the parser stays bounded.
+/// * The invalid bytes are skipped, the next valid sequence is parsed as usual.
+///
+/// # Examples
+///
+/// ```
+/// use crossterm::event::{Event, EventParser, KeyCode};
+///
+/// let mut parser = EventParser::new();
+///
+/// // The sequence of the up arrow, split across two reads
+/// parser.advance(b"\x1B", true);
+/// assert_eq!(parser.next(), None);
+/// parser.advance(b"[A", true);
+/// assert_eq!(parser.next(), Some(Event::Key(KeyCode::Up.into())));
+///
+/// // A lone `ESC` is the Esc key once no more bytes follow
+/// parser.advance(b"\x1B", true);
+/// parser.flush();
+/// assert_eq!(parser.next(), Some(Event::Key(KeyCode::Esc.into())));
+/// ```
+#[derive(Debug, Default)]
+pub struct EventParser {
+    parser: Parser,
+}
+
+impl EventParser {
+    /// Creates a parser.
+    pub fn new() -> EventParser {
+        EventParser::default()
+    }
+
+    /// Parses the bytes, the events are returned by [`next`](#method.next).
+    ///
+    /// `more` says whether more bytes can follow right away, like when the bytes were read from a
+    /// terminal with more input available. An `ESC` at the end is either the Esc key or the start
+    /// of a sequence then: it stays pending until the next bytes or [`flush`](#method.flush).
+    pub fn advance(&mut self, bytes: &[u8], more: bool) {
+        self.parser.advance(bytes, more);
+    }
+
+    /// Resolves the pending `ESC` as the Esc key and throws away an incomplete sequence.
+    ///
+    /// [`read`](fn.read.html) does it when no more bytes arrived within the
+    /// [escape timeout](fn.set_escape_timeout.html).
+    pub fn flush(&mut self) {
+        self.parser.flush();
+    }
+
+    /// Returns the number of buffered bytes of an incomplete sequence.
+    pub fn pending_len(&self) -> usize {
+        self.parser.pending_len()
+    }
+
+    /// Sets whether the 8-bit C1 controls (`CSI` 0x9B, ...) are recognized, like
+    /// [`set_c1_controls`](fn.set_c1_controls.html) for [`read`](fn.read.html).
+    ///
+    /// ```
+    /// use crossterm::event::{Event, EventParser, KeyCode};
+    ///
+    /// let mut parser = EventParser::new();
+    /// parser.set_c1_controls(true);
+    ///
+    /// // The up arrow of a VT220 in the 8-bit mode
+    /// parser.advance(b"\x9BA", false);
+    /// assert_eq!(parser.next(), Some(Event::Key(KeyCode::Up.into())));
+    /// ```
+    pub fn set_c1_controls(&mut self, enabled: bool) {
+        self.parser.set_c1_controls(enabled);
+    }
+}
+
+impl Iterator for EventParser {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Event> {
+        loop {
+            match self.parser.next()? {
+                InternalEvent::Event(event) => return Some(event),
+                // Replies to the queries of this crate
+                _ => continue,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+    use super::EventParser;
+
+    fn up() -> Option<Event> {
+        Some(Event::Key(KeyCode::Up.into()))
+    }
+
+    /// Random bytes, mostly the ones found in sequences. A small xorshift generator keeps the
+    /// tests deterministic.
+    fn random_bytes(seed: &mut u64, len: usize) -> Vec<u8> {
+        const BYTES: &[u8] = b"\x1B[O]<M;0123456789~ABCDPQRSHFZmu$^@\x07\\\xE2\x82\xAC\xFFa";
+
+        (0..len)
+            .map(|_| {
+                *seed ^= *seed << 13;
+                *seed ^= *seed >> 7;
+                *seed ^= *seed << 17;
+                BYTES[(*seed % BYTES.len() as u64) as usize]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parser_skips_cursor_positions() {
+        let mut parser = EventParser::new();
+
+        parser.advance(b"\x1B[2;3R\x1B[A", false);
+
+        assert_eq!(parser.next(), up());
+        assert_eq!(parser.next(), None);
+    }
+
+    #[test]
+    fn test_parser_bounds_sequences() {
+        let mut parser = EventParser::new();
+
+        parser.advance(b"\x1B[", true);
+        for _ in 0..200 {
+            parser.advance(&[b'1'; 1024], true);
+        }
+        assert_eq!(parser.pending_len(), 64 * 1024);
+
+        // The end of the overlong sequence is thrown away with it
+        parser.advance(b"A\x1B[A", false);
+        assert_eq!(parser.next(), up());
+        assert_eq!(parser.next(), None);
+        assert_eq!(parser.pending_len(), 0);
+
+        parser.advance(b"\x1B]", true);
+        for _ in 0..200 {
+            parser.advance(&[b'x'; 1024], true);
+        }
+        assert_eq!(parser.pending_len(), 64 * 1024);
+        parser.advance(b"\x07a", false);
+        assert_eq!(parser.next(), Some(Event::Key(KeyCode::Char('a').into())));
+    }
+
+    #[test]
+    fn test_parser_recovers_from_truncated_sequences() {
+        let mut parser = EventParser::new();
+
+        for truncated in &[
+            &b"\x1B[1;"[..],
+            b"\x1B[<0;1",
+            b"\x1B]0;title",
+            b"\x1BO",
+            b"\xE2\x82",
+        ] {
+            parser.advance(truncated, true);
+            parser.flush();
+            parser.by_ref().for_each(drop);
+
+            assert_eq!(parser.pending_len(), 0);
+            parser.advance(b"\x1B[A", false);
+            assert_eq!(parser.next(), up());
+        }
+    }
+
+    #[test]
+    fn test_parser_c1_controls() {
+        let mut parser = EventParser::new();
+
+        // Without the mode, 0x9B is an invalid UTF-8 byte
+        parser.advance(b"\x9BA", false);
+        assert_eq!(parser.next(), Some(Event::Key(KeyCode::Char('A').into())));
+
+        parser.set_c1_controls(true);
+        parser.advance(b"\x9B1;5A\x8FP", false);
+        assert_eq!(
+            parser.next(),
+            Some(Event::Key(KeyEvent::new(
+                KeyCode::Up,
+                KeyModifiers::CONTROL
+            )))
+        );
+        assert_eq!(parser.next(), Some(Event::Key(KeyCode::F(1).into())));
+
+        // An OSC string ended by ST, and a UTF-8 character containing 0x9B (U+271B)
+        parser.advance(b"\x9D0;title\x9C\xE2\x9C\x9B", false);
+        assert_eq!(parser.next(), Some(Event::Key(KeyCode::Char('✛').into())));
+        assert_eq!(parser.next(), None);
+    }
+
+    #[test]
+    fn test_parser_survives_random_input() {
+        let mut seed = 0x2545_F491_4F6C_DD1D;
+
+        for round in 0..20_000 {
+            let bytes = random_bytes(&mut seed, round % 64);
+            let mut parser = EventParser::new();
+
+            // In pieces of random sizes, some sequences are split
+            for chunk in bytes.chunks(1 + round % 7) {
+                parser.advance(chunk, true);
+                parser.by_ref().for_each(drop);
+            }
+            parser.flush();
+            parser.by_ref().for_each(drop);
+
+            parser.advance(b"\x1B[1;5A", false);
+            assert_eq!(
+                parser.next(),
+                Some(Event::Key(KeyEvent::new(
+                    KeyCode::Up,
+                    KeyModifiers::CONTROL
+                ))),
+                "input {:?}",
+                bytes
+            );
+        }
+    }
+}
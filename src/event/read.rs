@@ -1,17 +1,32 @@
+#[cfg(all(unix, any(feature = "tokio", feature = "async-std")))]
+use std::os::unix::io::RawFd;
 use std::{collections::vec_deque::VecDeque, io, time::Duration};
 
 #[cfg(unix)]
 use super::source::unix::UnixInternalEventSource;
+#[cfg(target_arch = "wasm32")]
+use super::source::wasm::WasmEventSource;
 #[cfg(windows)]
 use super::source::windows::WindowsEventSource;
 #[cfg(feature = "event-stream")]
 use super::sys::Waker;
-use super::{filter::Filter, source::EventSource, timeout::PollTimeout, InternalEvent, Result};
+use super::{
+    click::ClickTracker,
+    filter::{is_awaited_reply, Filter},
+    source::InternalEventSource,
+    timeout::PollTimeout,
+    Event, InternalEvent, MouseEvent, MouseEventKind, Result,
+};
+
+/// The number of events a queue holds at most, see `push_event`.
+const MAX_QUEUED_EVENTS: usize = 1024;
+
 /// Can be used to read `InternalEvent`s.
 pub(crate) struct InternalEventReader {
     events: VecDeque<InternalEvent>,
-    source: Option<Box<dyn EventSource>>,
-    skipped_events: Vec<InternalEvent>,
+    source: Option<Box<dyn InternalEventSource>>,
+    skipped_events: VecDeque<InternalEvent>,
+    clicks: ClickTracker,
 }
 
 impl Default for InternalEventReader {
@@ -20,54 +35,102 @@ impl Default for InternalEventReader {
         let source = WindowsEventSource::new();
         #[cfg(unix)]
         let source = UnixInternalEventSource::new();
+        #[cfg(target_arch = "wasm32")]
+        let source = WasmEventSource::new();
 
-        let source = source.ok().map(|x| Box::new(x) as Box<dyn EventSource>);
+        let source = source
+            .ok()
+            .map(|x| Box::new(x) as Box<dyn InternalEventSource>);
 
         InternalEventReader {
             source,
             events: VecDeque::with_capacity(32),
-            skipped_events: Vec::with_capacity(32),
+            skipped_events: VecDeque::with_capacity(32),
+            clicks: ClickTracker::default(),
         }
     }
 }
 
 impl InternalEventReader {
+    /// Creates a reader reading from the given source instead of the terminal.
+    pub(crate) fn with_source(source: Box<dyn InternalEventSource>) -> Self {
+        InternalEventReader {
+            source: Some(source),
+            events: VecDeque::with_capacity(32),
+            skipped_events: VecDeque::with_capacity(32),
+            clicks: ClickTracker::default(),
+        }
+    }
+
     /// Returns a `Waker` allowing to wake/force the `poll` method to return `Ok(false)`.
     #[cfg(feature = "event-stream")]
     pub(crate) fn waker(&self) -> Waker {
         self.source.as_ref().expect("reader source not set").waker()
     }
 
+    /// Returns the file descriptor which becomes readable when there's new input, and how long to
+    /// wait at most for the rest of the ambiguous bytes.
+    #[cfg(all(unix, any(feature = "tokio", feature = "async-std")))]
+    pub(crate) fn readiness(&self) -> Option<(RawFd, Option<Duration>)> {
+        let source = self.source.as_ref()?;
+        Some((source.readiness_fd()?, source.escape_timeout()))
+    }
+
     pub(crate) fn poll<F>(&mut self, timeout: Option<Duration>, filter: &F) -> Result<bool>
     where
         F: Filter,
     {
         for event in &self.events {
-            if filter.eval(&event) {
+            if filter.eval(event) {
                 return Ok(true);
             }
         }
 
         let event_source = match self.source.as_mut() {
             Some(source) => source,
-            None => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    "Failed to initialize input reader",
-                ))
-            }
+            None => return Err(std::io::Error::other("Failed to initialize input reader")),
         };
 
+        // Fast path, events which were already read from the input don't need the OS poller. A
+        // double click follows its press before anything else.
+        while let Some(event) = self
+            .clicks
+            .take_pending()
+            .or_else(|| event_source.try_read_buffered())
+        {
+            self.clicks.track(&event);
+            let matches = filter.eval(&event);
+            make_room(&mut self.events, &mut self.skipped_events, &event)?;
+            self.events.push_back(event);
+
+            if matches {
+                return Ok(true);
+            }
+        }
+
+        // Another thread waits for a reply which was read already, it gets the reader instead
+        if self.events.iter().any(is_awaited_reply) {
+            return Ok(false);
+        }
+
         let poll_timeout = PollTimeout::new(timeout);
 
         loop {
-            let maybe_event = match event_source.try_read(poll_timeout.leftover()) {
+            let mut hand_over = false;
+            let read = match self.clicks.take_pending() {
+                Some(event) => Ok(Some(event)),
+                None => event_source.try_read(poll_timeout.leftover()),
+            };
+            let maybe_event = match read {
                 Ok(None) => None,
                 Ok(Some(event)) => {
+                    self.clicks.track(&event);
                     if filter.eval(&event) {
                         Some(event)
                     } else {
-                        self.skipped_events.push(event);
+                        hand_over = is_awaited_reply(&event);
+                        make_room(&mut self.events, &mut self.skipped_events, &event)?;
+                        self.skipped_events.push_back(event);
                         None
                     }
                 }
@@ -80,7 +143,8 @@ impl InternalEventReader {
                 }
             };
 
-            if poll_timeout.elapsed() || maybe_event.is_some() {
+            if poll_timeout.elapsed() || maybe_event.is_some() || hand_over {
+                // Both queues together hold `MAX_QUEUED_EVENTS` at most, nothing is dropped here
                 self.events.extend(self.skipped_events.drain(..));
 
                 if let Some(event) = maybe_event {
@@ -93,7 +157,9 @@ impl InternalEventReader {
         }
     }
 
-    pub(crate) fn read<F>(&mut self, filter: &F) -> Result<InternalEvent>
+    /// Reads an event, `None` if another thread waits for a reply which was read already. The
+    /// reader has to be handed over to it then.
+    pub(crate) fn try_read<F>(&mut self, filter: &F) -> Result<Option<InternalEvent>>
     where
         F: Filter,
     {
@@ -102,11 +168,12 @@ impl InternalEventReader {
         loop {
             while let Some(event) = self.events.pop_front() {
                 if filter.eval(&event) {
-                    while let Some(event) = skipped_events.pop_front() {
-                        self.events.push_back(event);
+                    // In front of the events which were read after them
+                    while let Some(event) = skipped_events.pop_back() {
+                        self.events.push_front(event);
                     }
 
-                    return Ok(event);
+                    return Ok(Some(event));
                 } else {
                     // We can not directly write events back to `self.events`.
                     // If we did, we would put our self's into an endless loop
@@ -119,9 +186,103 @@ impl InternalEventReader {
                 }
             }
 
+            if skipped_events.iter().any(is_awaited_reply) {
+                self.events = skipped_events;
+                return Ok(None);
+            }
+
             let _ = self.poll(None, filter)?;
         }
     }
+
+    #[cfg(test)]
+    pub(crate) fn read<F>(&mut self, filter: &F) -> Result<InternalEvent>
+    where
+        F: Filter,
+    {
+        loop {
+            if let Some(event) = self.try_read(filter)? {
+                return Ok(event);
+            }
+        }
+    }
+}
+
+/// Returns `true` if the `later` event makes the `earlier` one useless: both are resizes, or
+/// both are mouse moves which only differ by their position.
+fn supersedes(later: &InternalEvent, earlier: &InternalEvent) -> bool {
+    match (later, earlier) {
+        (InternalEvent::Event(Event::Resize(_, _)), InternalEvent::Event(Event::Resize(_, _))) => {
+            true
+        }
+        (
+            InternalEvent::Event(Event::Mouse(MouseEvent {
+                kind: later_kind,
+                modifiers: later_modifiers,
+                ..
+            })),
+            InternalEvent::Event(Event::Mouse(MouseEvent {
+                kind: earlier_kind,
+                modifiers: earlier_modifiers,
+                ..
+            })),
+        ) => {
+            later_kind == earlier_kind
+                && later_modifiers == earlier_modifiers
+                && matches!(later_kind, MouseEventKind::Moved | MouseEventKind::Drag(_))
+        }
+        _ => false,
+    }
+}
+
+/// Makes room for the `incoming` event if the queued and the skipped events together reach
+/// `MAX_QUEUED_EVENTS`.
+///
+/// Terminals report every cell the mouse passes, so events which are not read for a while
+/// (e.g. while waiting for the cursor position) would otherwise grow the queues without a limit.
+/// The oldest mouse move, drag or resize which is followed by another one of the same kind,
+/// queued or incoming, is dropped. If there's none, nothing is dropped from the queues and an
+/// error is returned, the `incoming` event is lost then.
+fn make_room(
+    events: &mut VecDeque<InternalEvent>,
+    skipped_events: &mut VecDeque<InternalEvent>,
+    incoming: &InternalEvent,
+) -> Result<()> {
+    let len = events.len() + skipped_events.len();
+    if len < MAX_QUEUED_EVENTS {
+        return Ok(());
+    }
+
+    // The skipped events were read after the queued ones
+    let mut later = vec![incoming];
+    let mut superseded = None;
+    for (index, event) in (0..len)
+        .rev()
+        .zip(events.iter().chain(skipped_events.iter()).rev())
+    {
+        if later.iter().any(|later| supersedes(later, event)) {
+            superseded = Some(index);
+        } else if supersedes(event, event) {
+            // Only the mouse moves, the drags and the resizes supersede anything
+            later.push(event);
+        }
+    }
+
+    match superseded {
+        Some(index) if index < events.len() => {
+            events.remove(index);
+        }
+        Some(index) => {
+            skipped_events.remove(index - events.len());
+        }
+        None => {
+            return Err(io::Error::other(format!(
+                "More than {} events weren't read, the latest one was dropped",
+                MAX_QUEUED_EVENTS
+            )))
+        }
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -132,10 +293,14 @@ mod tests {
     use crate::ErrorKind;
 
     #[cfg(unix)]
-    use super::super::filter::CursorPositionFilter;
+    use super::super::filter::{AwaitedReply, CursorPositionFilter};
     use super::{
-        super::{filter::InternalEventFilter, Event},
-        EventSource, InternalEvent, InternalEventReader,
+        super::{
+            filter::{EventFilter, InternalEventFilter},
+            Event, KeyCode, KeyModifiers, MouseButton, MouseEvent, MouseEventKind, ScrollDelta,
+        },
+        make_room, ClickTracker, InternalEvent, InternalEventReader, InternalEventSource,
+        MAX_QUEUED_EVENTS,
     };
 
     #[test]
@@ -143,7 +308,8 @@ mod tests {
         let mut reader = InternalEventReader {
             events: VecDeque::new(),
             source: None,
-            skipped_events: Vec::with_capacity(32),
+            skipped_events: VecDeque::with_capacity(32),
+            clicks: ClickTracker::default(),
         };
 
         assert!(reader.poll(None, &InternalEventFilter).is_err());
@@ -160,7 +326,8 @@ mod tests {
         let mut reader = InternalEventReader {
             events: vec![InternalEvent::Event(Event::Resize(10, 10))].into(),
             source: None,
-            skipped_events: Vec::with_capacity(32),
+            skipped_events: VecDeque::with_capacity(32),
+            clicks: ClickTracker::default(),
         };
 
         assert!(reader.poll(None, &InternalEventFilter).unwrap());
@@ -176,7 +343,8 @@ mod tests {
             ]
             .into(),
             source: None,
-            skipped_events: Vec::with_capacity(32),
+            skipped_events: VecDeque::with_capacity(32),
+            clicks: ClickTracker::default(),
         };
 
         assert!(reader.poll(None, &CursorPositionFilter).unwrap());
@@ -189,7 +357,8 @@ mod tests {
         let mut reader = InternalEventReader {
             events: vec![EVENT].into(),
             source: None,
-            skipped_events: Vec::with_capacity(32),
+            skipped_events: VecDeque::with_capacity(32),
+            clicks: ClickTracker::default(),
         };
 
         assert_eq!(reader.read(&InternalEventFilter).unwrap(), EVENT);
@@ -203,7 +372,8 @@ mod tests {
         let mut reader = InternalEventReader {
             events: vec![InternalEvent::Event(Event::Resize(10, 10)), CURSOR_EVENT].into(),
             source: None,
-            skipped_events: Vec::with_capacity(32),
+            skipped_events: VecDeque::with_capacity(32),
+            clicks: ClickTracker::default(),
         };
 
         assert_eq!(reader.read(&CursorPositionFilter).unwrap(), CURSOR_EVENT);
@@ -218,7 +388,8 @@ mod tests {
         let mut reader = InternalEventReader {
             events: vec![SKIPPED_EVENT, CURSOR_EVENT].into(),
             source: None,
-            skipped_events: Vec::with_capacity(32),
+            skipped_events: VecDeque::with_capacity(32),
+            clicks: ClickTracker::default(),
         };
 
         assert_eq!(reader.read(&CursorPositionFilter).unwrap(), CURSOR_EVENT);
@@ -232,7 +403,8 @@ mod tests {
         let mut reader = InternalEventReader {
             events: VecDeque::new(),
             source: Some(Box::new(source)),
-            skipped_events: Vec::with_capacity(32),
+            skipped_events: VecDeque::with_capacity(32),
+            clicks: ClickTracker::default(),
         };
 
         assert!(!reader
@@ -247,7 +419,8 @@ mod tests {
         let mut reader = InternalEventReader {
             events: VecDeque::new(),
             source: Some(Box::new(source)),
-            skipped_events: Vec::with_capacity(32),
+            skipped_events: VecDeque::with_capacity(32),
+            clicks: ClickTracker::default(),
         };
 
         assert!(reader.poll(None, &InternalEventFilter).unwrap());
@@ -265,7 +438,8 @@ mod tests {
         let mut reader = InternalEventReader {
             events: VecDeque::new(),
             source: Some(Box::new(source)),
-            skipped_events: Vec::with_capacity(32),
+            skipped_events: VecDeque::with_capacity(32),
+            clicks: ClickTracker::default(),
         };
 
         assert_eq!(reader.read(&InternalEventFilter).unwrap(), EVENT);
@@ -280,7 +454,8 @@ mod tests {
         let mut reader = InternalEventReader {
             events: VecDeque::new(),
             source: Some(Box::new(source)),
-            skipped_events: Vec::with_capacity(32),
+            skipped_events: VecDeque::with_capacity(32),
+            clicks: ClickTracker::default(),
         };
 
         assert_eq!(reader.read(&InternalEventFilter).unwrap(), EVENT);
@@ -297,7 +472,8 @@ mod tests {
         let mut reader = InternalEventReader {
             events: VecDeque::new(),
             source: Some(Box::new(source)),
-            skipped_events: Vec::with_capacity(32),
+            skipped_events: VecDeque::with_capacity(32),
+            clicks: ClickTracker::default(),
         };
 
         assert_eq!(reader.read(&InternalEventFilter).unwrap(), EVENT);
@@ -315,7 +491,8 @@ mod tests {
         let mut reader = InternalEventReader {
             events: VecDeque::new(),
             source: Some(Box::new(source)),
-            skipped_events: Vec::with_capacity(32),
+            skipped_events: VecDeque::with_capacity(32),
+            clicks: ClickTracker::default(),
         };
 
         assert_eq!(
@@ -334,7 +511,8 @@ mod tests {
         let mut reader = InternalEventReader {
             events: VecDeque::new(),
             source: Some(Box::new(source)),
-            skipped_events: Vec::with_capacity(32),
+            skipped_events: VecDeque::with_capacity(32),
+            clicks: ClickTracker::default(),
         };
 
         assert_eq!(
@@ -355,7 +533,8 @@ mod tests {
         let mut reader = InternalEventReader {
             events: VecDeque::new(),
             source: Some(Box::new(source)),
-            skipped_events: Vec::with_capacity(32),
+            skipped_events: VecDeque::with_capacity(32),
+            clicks: ClickTracker::default(),
         };
 
         assert_eq!(reader.read(&InternalEventFilter).unwrap(), EVENT);
@@ -374,7 +553,8 @@ mod tests {
         let mut reader = InternalEventReader {
             events: VecDeque::new(),
             source: Some(Box::new(source)),
-            skipped_events: Vec::with_capacity(32),
+            skipped_events: VecDeque::with_capacity(32),
+            clicks: ClickTracker::default(),
         };
 
         assert_eq!(reader.read(&InternalEventFilter).unwrap(), EVENT);
@@ -382,9 +562,269 @@ mod tests {
         assert_eq!(reader.read(&InternalEventFilter).unwrap(), EVENT);
     }
 
+    #[test]
+    fn test_read_takes_buffered_events_without_polling() {
+        const EVENT: InternalEvent = InternalEvent::Event(Event::Resize(10, 10));
+
+        // Polling the source would fail
+        let mut source = FakeSource::with_error(ErrorKind::from(io::ErrorKind::Other));
+        source.buffered_events.push_back(EVENT);
+
+        let mut reader = InternalEventReader {
+            events: VecDeque::new(),
+            source: Some(Box::new(source)),
+            skipped_events: VecDeque::with_capacity(32),
+            clicks: ClickTracker::default(),
+        };
+
+        assert!(reader
+            .poll(Some(Duration::from_secs(0)), &InternalEventFilter)
+            .unwrap());
+        assert_eq!(reader.read(&InternalEventFilter).unwrap(), EVENT);
+        assert!(reader.read(&InternalEventFilter).is_err());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_read_keeps_skipped_buffered_events() {
+        const SKIPPED_EVENT: InternalEvent = InternalEvent::Event(Event::Resize(10, 10));
+        const CURSOR_EVENT: InternalEvent = InternalEvent::CursorPosition(10, 20);
+
+        let source = FakeSource {
+            buffered_events: vec![SKIPPED_EVENT, CURSOR_EVENT].into(),
+            ..FakeSource::default()
+        };
+
+        let mut reader = InternalEventReader {
+            events: VecDeque::new(),
+            source: Some(Box::new(source)),
+            skipped_events: VecDeque::with_capacity(32),
+            clicks: ClickTracker::default(),
+        };
+
+        assert_eq!(reader.read(&CursorPositionFilter).unwrap(), CURSOR_EVENT);
+        assert_eq!(reader.read(&InternalEventFilter).unwrap(), SKIPPED_EVENT);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_events_during_cursor_position_query_are_kept() {
+        let typed_before = InternalEvent::Event(Event::Key(KeyCode::Char('a').into()));
+        let typed_after = InternalEvent::Event(Event::Key(KeyCode::Char('b').into()));
+        const CURSOR_EVENT: InternalEvent = InternalEvent::CursorPosition(10, 20);
+
+        let source =
+            FakeSource::with_events(&[typed_before.clone(), CURSOR_EVENT, typed_after.clone()]);
+
+        let mut reader = InternalEventReader {
+            events: VecDeque::new(),
+            source: Some(Box::new(source)),
+            skipped_events: VecDeque::with_capacity(32),
+            clicks: ClickTracker::default(),
+        };
+
+        assert!(reader.poll(None, &CursorPositionFilter).unwrap());
+        assert_eq!(reader.read(&CursorPositionFilter).unwrap(), CURSOR_EVENT);
+
+        assert_eq!(reader.read(&EventFilter).unwrap(), typed_before);
+        assert_eq!(reader.read(&EventFilter).unwrap(), typed_after);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_awaited_cursor_position_hands_the_reader_over() {
+        let typed = InternalEvent::Event(Event::Key(KeyCode::Char('a').into()));
+        const CURSOR_EVENT: InternalEvent = InternalEvent::CursorPosition(10, 20);
+
+        let source = FakeSource::with_events(&[CURSOR_EVENT, typed.clone()]);
+
+        let mut reader = InternalEventReader {
+            events: VecDeque::new(),
+            source: Some(Box::new(source)),
+            skipped_events: VecDeque::with_capacity(32),
+            clicks: ClickTracker::default(),
+        };
+
+        let awaited = AwaitedReply::cursor_position();
+        assert!(!reader.poll(None, &EventFilter).unwrap());
+        assert_eq!(reader.try_read(&EventFilter).unwrap(), None);
+        assert_eq!(reader.events, vec![CURSOR_EVENT]);
+
+        assert_eq!(reader.read(&CursorPositionFilter).unwrap(), CURSOR_EVENT);
+        drop(awaited);
+        assert_eq!(reader.read(&EventFilter).unwrap(), typed);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_poll_coalesces_skipped_mouse_moves_when_queue_is_full() {
+        let mouse_move = |column| {
+            InternalEvent::Event(Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Moved,
+                column,
+                row: 0,
+                modifiers: KeyModifiers::NONE,
+                scroll_delta: ScrollDelta::default(),
+            }))
+        };
+
+        let mut events: Vec<_> = (0..MAX_QUEUED_EVENTS as u16 * 2).map(mouse_move).collect();
+        events.push(InternalEvent::CursorPosition(10, 20));
+
+        let mut reader = InternalEventReader {
+            events: VecDeque::new(),
+            source: Some(Box::new(FakeSource::with_events(&events))),
+            skipped_events: VecDeque::with_capacity(32),
+            clicks: ClickTracker::default(),
+        };
+
+        assert_eq!(
+            reader.read(&CursorPositionFilter).unwrap(),
+            InternalEvent::CursorPosition(10, 20)
+        );
+        assert_eq!(reader.events.len(), MAX_QUEUED_EVENTS);
+        assert_eq!(
+            reader.events.back(),
+            Some(&mouse_move(MAX_QUEUED_EVENTS as u16 * 2 - 1))
+        );
+    }
+
+    #[test]
+    fn test_double_click_follows_its_press() {
+        let mouse = |kind| {
+            InternalEvent::Event(Event::Mouse(MouseEvent {
+                kind,
+                column: 4,
+                row: 2,
+                modifiers: KeyModifiers::NONE,
+                scroll_delta: ScrollDelta::default(),
+            }))
+        };
+        let press = mouse(MouseEventKind::Down(MouseButton::Left));
+        let release = mouse(MouseEventKind::Up(MouseButton::Left));
+
+        let source = FakeSource::with_events(&[
+            press.clone(),
+            release.clone(),
+            press.clone(),
+            release.clone(),
+        ]);
+        let mut reader = InternalEventReader {
+            events: VecDeque::new(),
+            source: Some(Box::new(source)),
+            skipped_events: VecDeque::with_capacity(32),
+            clicks: ClickTracker::default(),
+        };
+
+        assert_eq!(reader.read(&EventFilter).unwrap(), press);
+        assert_eq!(reader.read(&EventFilter).unwrap(), release);
+        assert_eq!(reader.read(&EventFilter).unwrap(), press);
+        assert_eq!(
+            reader.read(&EventFilter).unwrap(),
+            mouse(MouseEventKind::DoubleClick(MouseButton::Left))
+        );
+        assert_eq!(reader.read(&EventFilter).unwrap(), release);
+    }
+
+    #[test]
+    fn test_make_room_drops_only_superseded_events() {
+        let key = InternalEvent::Event(Event::Key(KeyCode::Char('a').into()));
+        let mouse_move = |column| {
+            InternalEvent::Event(Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Moved,
+                column,
+                row: 0,
+                modifiers: KeyModifiers::NONE,
+                scroll_delta: ScrollDelta::default(),
+            }))
+        };
+
+        let mut events = VecDeque::new();
+        events.push_back(InternalEvent::Event(Event::Resize(10, 10)));
+        events.push_back(mouse_move(1));
+        events.push_back(InternalEvent::Event(Event::Resize(15, 15)));
+        let mut skipped_events: VecDeque<_> = vec![key.clone(); MAX_QUEUED_EVENTS - 3].into();
+
+        // Followed by a later resize
+        make_room(&mut events, &mut skipped_events, &key).unwrap();
+        assert_eq!(
+            events,
+            vec![mouse_move(1), InternalEvent::Event(Event::Resize(15, 15))]
+        );
+        skipped_events.push_back(key.clone());
+
+        // Neither the lone mouse move nor the latest resize is dropped
+        assert!(make_room(&mut events, &mut skipped_events, &key).is_err());
+        assert_eq!(events.len() + skipped_events.len(), MAX_QUEUED_EVENTS);
+
+        // Followed by the incoming event
+        make_room(&mut events, &mut skipped_events, &mouse_move(2)).unwrap();
+        assert_eq!(events, vec![InternalEvent::Event(Event::Resize(15, 15))]);
+    }
+
+    #[test]
+    fn test_make_room_never_drops_keys_and_replies() {
+        let key = InternalEvent::Event(Event::Key(KeyCode::Char('a').into()));
+
+        let mut events: VecDeque<_> = vec![key.clone(); MAX_QUEUED_EVENTS - 1].into();
+        events.push_front(InternalEvent::CursorPosition(1, 2));
+        let mut skipped_events = VecDeque::new();
+
+        assert!(make_room(&mut events, &mut skipped_events, &key).is_err());
+        assert!(make_room(
+            &mut events,
+            &mut skipped_events,
+            &InternalEvent::Event(Event::Resize(10, 10))
+        )
+        .is_err());
+        assert_eq!(events.len(), MAX_QUEUED_EVENTS);
+        assert_eq!(events.front(), Some(&InternalEvent::CursorPosition(1, 2)));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_poll_keeps_the_skipped_events_until_the_queue_is_full() {
+        let key = |c| InternalEvent::Event(Event::Key(KeyCode::Char(c).into()));
+        const CURSOR_EVENT: InternalEvent = InternalEvent::CursorPosition(10, 20);
+
+        let source = FakeSource::with_events(&[key('b'), key('c'), CURSOR_EVENT]);
+        let mut reader = InternalEventReader {
+            events: vec![key('a'); MAX_QUEUED_EVENTS - 2].into(),
+            source: Some(Box::new(source)),
+            skipped_events: VecDeque::with_capacity(32),
+            clicks: ClickTracker::default(),
+        };
+
+        assert_eq!(reader.read(&CursorPositionFilter).unwrap(), CURSOR_EVENT);
+        assert_eq!(reader.events.len(), MAX_QUEUED_EVENTS);
+        assert_eq!(
+            reader
+                .events
+                .iter()
+                .skip(MAX_QUEUED_EVENTS - 3)
+                .collect::<Vec<_>>(),
+            vec![&key('a'), &key('b'), &key('c')]
+        );
+
+        let source = FakeSource::with_events(&[key('b'), key('c'), CURSOR_EVENT]);
+        let mut reader = InternalEventReader {
+            events: vec![key('a'); MAX_QUEUED_EVENTS - 1].into(),
+            source: Some(Box::new(source)),
+            skipped_events: VecDeque::with_capacity(32),
+            clicks: ClickTracker::default(),
+        };
+
+        // The overflow is reported, the events read before are kept
+        assert!(reader.poll(None, &CursorPositionFilter).is_err());
+        assert_eq!(reader.read(&CursorPositionFilter).unwrap(), CURSOR_EVENT);
+        assert_eq!(reader.events.len(), MAX_QUEUED_EVENTS);
+        assert_eq!(reader.events.back(), Some(&key('b')));
+    }
+
     #[derive(Default)]
     struct FakeSource {
         events: VecDeque<InternalEvent>,
+        buffered_events: VecDeque<InternalEvent>,
         error: Option<ErrorKind>,
     }
 
@@ -392,6 +832,7 @@ mod tests {
         fn new(events: &[InternalEvent], error: ErrorKind) -> FakeSource {
             FakeSource {
                 events: events.to_vec().into(),
+                buffered_events: VecDeque::new(),
                 error: Some(error),
             }
         }
@@ -399,6 +840,7 @@ mod tests {
         fn with_events(events: &[InternalEvent]) -> FakeSource {
             FakeSource {
                 events: events.to_vec().into(),
+                buffered_events: VecDeque::new(),
                 error: None,
             }
         }
@@ -406,12 +848,13 @@ mod tests {
         fn with_error(error: ErrorKind) -> FakeSource {
             FakeSource {
                 events: VecDeque::new(),
+                buffered_events: VecDeque::new(),
                 error: Some(error),
             }
         }
     }
 
-    impl EventSource for FakeSource {
+    impl InternalEventSource for FakeSource {
         fn try_read(
             &mut self,
             _timeout: Option<Duration>,
@@ -437,6 +880,10 @@ mod tests {
             Ok(None)
         }
 
+        fn try_read_buffered(&mut self) -> Option<InternalEvent> {
+            self.buffered_events.pop_front()
+        }
+
         #[cfg(feature = "event-stream")]
         fn waker(&self) -> super::super::sys::Waker {
             unimplemented!();
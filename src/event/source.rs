@@ -1,25 +1,109 @@
+#[cfg(all(unix, any(feature = "tokio", feature = "async-std")))]
+use std::os::unix::io::RawFd;
 use std::time::Duration;
 
+use crate::Result;
+
 #[cfg(feature = "event-stream")]
 use super::sys::Waker;
 use super::InternalEvent;
 
-#[cfg(unix)]
+pub(crate) mod custom;
 pub(crate) mod unix;
+#[cfg(feature = "event-stream")]
+pub(crate) mod wake_signal;
+#[cfg(target_arch = "wasm32")]
+pub(crate) mod wasm;
 #[cfg(windows)]
 pub(crate) mod windows;
 
+/// A source of the input, read by [`read`](../fn.read.html), [`poll`](../fn.poll.html) and the
+/// `EventStream` instead of the terminal once it's set with
+/// [`set_event_source`](../fn.set_event_source.html).
+///
+/// The source provides the bytes a terminal would send, the ones of an SSH channel for example.
+/// They are parsed like the input of a UNIX terminal on every platform: the keys, the mouse and
+/// the replies to the queries (`cursor::position`, ...) are recognized.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::{
+///     sync::mpsc::{Receiver, RecvTimeoutError},
+///     time::Duration,
+/// };
+///
+/// use crossterm::{event::source::EventSource, Result};
+///
+/// /// The bytes received by another thread.
+/// struct ChannelSource(Receiver<u8>);
+///
+/// impl EventSource for ChannelSource {
+///     fn read(&mut self, buffer: &mut [u8], timeout: Option<Duration>) -> Result<Option<usize>> {
+///         let first = match timeout {
+///             Some(timeout) => self.0.recv_timeout(timeout),
+///             None => self.0.recv().map_err(RecvTimeoutError::from),
+///         };
+///         buffer[0] = match first {
+///             Ok(byte) => byte,
+///             Err(RecvTimeoutError::Timeout) => return Ok(None),
+///             Err(RecvTimeoutError::Disconnected) => return Ok(Some(0)),
+///         };
+///
+///         // The rest of a sequence is read at once
+///         let mut count = 1;
+///         while count < buffer.len() {
+///             match self.0.try_recv() {
+///                 Ok(byte) => buffer[count] = byte,
+///                 Err(_) => break,
+///             }
+///             count += 1;
+///         }
+///         Ok(Some(count))
+///     }
+/// }
+/// ```
+pub trait EventSource: Send {
+    /// Reads the input into the buffer, waits at most for the timeout or until there's input
+    /// with `None`. A zero timeout only checks whether there's input.
+    ///
+    /// Returns the number of bytes read, `Ok(None)` if nothing arrived within the timeout and
+    /// `Ok(Some(0))` at the end of the input, which is reported as `Event::Closed`.
+    fn read(&mut self, buffer: &mut [u8], timeout: Option<Duration>) -> Result<Option<usize>>;
+}
+
 /// An interface for trying to read an `InternalEvent` within an optional `Duration`.
-pub(crate) trait EventSource: Sync + Send {
+pub(crate) trait InternalEventSource: Send {
     /// Tries to read an `InternalEvent` within the given duration.
     ///
     /// # Arguments
     ///
     /// * `timeout` - `None` block indefinitely until an event is available, `Some(duration)` blocks
-    ///               for the given timeout
+    ///   for the given timeout
     ///
     /// Returns `Ok(None)` if there's no event available and timeout expires.
-    fn try_read(&mut self, timeout: Option<Duration>) -> crate::Result<Option<InternalEvent>>;
+    fn try_read(&mut self, timeout: Option<Duration>) -> Result<Option<InternalEvent>>;
+
+    /// Returns an `InternalEvent` which was already read from the input, if there's any.
+    ///
+    /// It never waits for new input, it's the fast path used before falling back to `try_read`.
+    fn try_read_buffered(&mut self) -> Option<InternalEvent> {
+        None
+    }
+
+    /// Returns a file descriptor which becomes readable when `try_read` has new input, the async
+    /// readers wait for it in their reactor instead of blocking in `try_read`.
+    #[cfg(all(unix, any(feature = "tokio", feature = "async-std")))]
+    fn readiness_fd(&self) -> Option<RawFd> {
+        None
+    }
+
+    /// Returns how long `try_read` waits at most for the rest of the ambiguous bytes, `None` if
+    /// there aren't any.
+    #[cfg(all(unix, any(feature = "tokio", feature = "async-std")))]
+    fn escape_timeout(&self) -> Option<Duration> {
+        None
+    }
 
     /// Returns a `Waker` allowing to wake/force the `try_read` method to return `Ok(None)`.
     #[cfg(feature = "event-stream")]
@@ -0,0 +1,156 @@
+use std::{io, time::Duration};
+
+use crate::Result;
+
+#[cfg(feature = "event-stream")]
+use super::super::sys::Waker;
+#[cfg(feature = "event-stream")]
+use super::wake_signal::{WakeSignal, WAKE_CHECK_INTERVAL};
+use super::{
+    super::{timeout::PollTimeout, Event, InternalEvent},
+    unix::Parser,
+    EventSource, InternalEventSource,
+};
+
+/// The size of the buffer the input of a custom source is read into.
+const BUFFER_SIZE: usize = 1_024;
+
+/// Parses the input of an `EventSource` set by the application.
+pub(crate) struct CustomEventSource {
+    source: Box<dyn EventSource>,
+    parser: Parser,
+    buffer: [u8; BUFFER_SIZE],
+    /// The source reported the end of the input.
+    ended: bool,
+    /// `Event::Closed` was returned, the source fails from now on.
+    closed: bool,
+    #[cfg(feature = "event-stream")]
+    wake_signal: WakeSignal,
+}
+
+impl CustomEventSource {
+    pub(crate) fn new(source: Box<dyn EventSource>) -> Result<CustomEventSource> {
+        Ok(CustomEventSource {
+            source,
+            parser: Parser::default(),
+            buffer: [0u8; BUFFER_SIZE],
+            ended: false,
+            closed: false,
+            #[cfg(feature = "event-stream")]
+            wake_signal: WakeSignal::new()?,
+        })
+    }
+}
+
+impl InternalEventSource for CustomEventSource {
+    fn try_read(&mut self, timeout: Option<Duration>) -> Result<Option<InternalEvent>> {
+        let timeout = PollTimeout::new(timeout);
+
+        loop {
+            self.parser.flush_expired();
+            if let Some(event) = self.parser.next() {
+                return Ok(Some(event));
+            }
+
+            if self.ended {
+                if self.closed {
+                    return Err(io::Error::new(
+                        io::ErrorKind::BrokenPipe,
+                        "The input of the event source ended",
+                    ));
+                }
+                self.closed = true;
+                return Ok(Some(InternalEvent::Event(Event::Closed)));
+            }
+
+            #[cfg(feature = "event-stream")]
+            if self.wake_signal.take() {
+                return Err(io::Error::new(
+                    io::ErrorKind::Interrupted,
+                    "Poll operation was woken up by `Waker::wake`",
+                ));
+            }
+
+            let wait = self.parser.poll_timeout(timeout.leftover());
+            #[cfg(feature = "event-stream")]
+            let wait = Some(wait.map_or(WAKE_CHECK_INTERVAL, |wait| wait.min(WAKE_CHECK_INTERVAL)));
+
+            match self.source.read(&mut self.buffer, wait)? {
+                Some(0) => {
+                    // Nothing follows the ambiguous bytes anymore
+                    self.parser.flush();
+                    self.ended = true;
+                    continue;
+                }
+                Some(read_count) => {
+                    let read_count = read_count.min(BUFFER_SIZE);
+                    crate::debug::log_input(&self.buffer[..read_count]);
+                    self.parser
+                        .set_c1_controls(crate::event::c1_controls_enabled());
+                    self.parser.advance(&self.buffer[..read_count], true);
+                }
+                None => {}
+            }
+
+            if timeout.elapsed() {
+                self.parser.flush_expired();
+                return Ok(self.parser.next());
+            }
+        }
+    }
+
+    fn try_read_buffered(&mut self) -> Option<InternalEvent> {
+        self.parser.next()
+    }
+
+    #[cfg(feature = "event-stream")]
+    fn waker(&self) -> Waker {
+        self.wake_signal.waker.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::VecDeque, time::Duration};
+
+    use crate::{
+        event::{Event, InternalEvent, KeyCode},
+        Result,
+    };
+
+    use super::{CustomEventSource, EventSource, InternalEventSource};
+
+    /// Returns the chunks of input, then the end of the input.
+    struct ChunkSource(VecDeque<&'static [u8]>);
+
+    impl EventSource for ChunkSource {
+        fn read(&mut self, buffer: &mut [u8], _: Option<Duration>) -> Result<Option<usize>> {
+            let chunk = self.0.pop_front().unwrap_or_default();
+            buffer[..chunk.len()].copy_from_slice(chunk);
+            Ok(Some(chunk.len()))
+        }
+    }
+
+    #[test]
+    fn test_parses_the_input_until_it_ends() {
+        let chunks = VecDeque::from(vec![&b"a\x1B["[..], &b"A"[..], &b"\x1B"[..]]);
+        let mut source = CustomEventSource::new(Box::new(ChunkSource(chunks))).unwrap();
+        let mut read = || source.try_read(Some(Duration::from_secs(1))).unwrap();
+
+        assert_eq!(
+            read(),
+            Some(InternalEvent::Event(Event::Key(KeyCode::Char('a').into())))
+        );
+        assert_eq!(
+            read(),
+            Some(InternalEvent::Event(Event::Key(KeyCode::Up.into())))
+        );
+        // The end of the input resolves the ambiguous `ESC`
+        assert_eq!(
+            read(),
+            Some(InternalEvent::Event(Event::Key(KeyCode::Esc.into())))
+        );
+        assert_eq!(read(), Some(InternalEvent::Event(Event::Closed)));
+        assert!(source.try_read(None).is_err());
+    }
+}
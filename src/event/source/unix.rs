@@ -1,173 +1,142 @@
-use std::{collections::VecDeque, io, time::Duration};
+#[cfg(unix)]
+use std::io;
+use std::{collections::VecDeque, sync::Arc, time::Duration};
 
-use mio::{unix::SourceFd, Events, Interest, Poll, Token};
-use signal_hook_mio::v0_7::Signals;
-
-use crate::Result;
+#[cfg(unix)]
+use crate::{
+    terminal::sys::{reapply_raw_mode, size_from_fd},
+    Result,
+};
 
-#[cfg(feature = "event-stream")]
-use super::super::sys::Waker;
+#[cfg(unix)]
+use super::super::sys::unix::file_descriptor::FileDesc;
 use super::super::{
-    source::EventSource,
-    sys::unix::{
-        file_descriptor::{tty_fd, FileDesc},
-        parse::parse_event,
-    },
-    timeout::PollTimeout,
+    sys::unix::parse::parse_event,
+    timeout::{Clock, PollTimeout},
     Event, InternalEvent,
 };
 
-// Tokens to identify file descriptor
-const TTY_TOKEN: Token = Token(0);
-const SIGNAL_TOKEN: Token = Token(1);
-#[cfg(feature = "event-stream")]
-const WAKE_TOKEN: Token = Token(2);
+#[cfg(all(unix, feature = "mio-poll", not(target_os = "redox")))]
+mod mio;
+#[cfg(all(unix, any(not(feature = "mio-poll"), target_os = "redox")))]
+mod tty;
+
+#[cfg(all(unix, feature = "mio-poll", not(target_os = "redox")))]
+pub(crate) use self::mio::UnixInternalEventSource;
+#[cfg(all(unix, any(not(feature = "mio-poll"), target_os = "redox")))]
+pub(crate) use self::tty::UnixInternalEventSource;
 
 // I (@zrzka) wasn't able to read more than 1_022 bytes when testing
 // reading on macOS/Linux -> we don't need bigger buffer and 1k of bytes
 // is enough.
+#[cfg(unix)]
 const TTY_BUFFER_SIZE: usize = 1_204;
 
-pub(crate) struct UnixInternalEventSource {
-    poll: Poll,
-    events: Events,
-    parser: Parser,
-    tty_buffer: [u8; TTY_BUFFER_SIZE],
-    tty_fd: FileDesc,
-    signals: Signals,
-    #[cfg(feature = "event-stream")]
-    waker: Waker,
-}
+/// The number of bytes of a sequence which are buffered at most, the rest of a longer one is
+/// thrown away. It bounds the memory used by a sequence which never ends.
+const MAX_SEQUENCE_LENGTH: usize = 64 * 1024;
 
-impl UnixInternalEventSource {
-    pub fn new() -> Result<Self> {
-        UnixInternalEventSource::from_file_descriptor(tty_fd()?)
-    }
+/// The sequence starting the pasted text, without the final byte.
+const PASTE_START: &[u8] = b"\x1B[200";
 
-    pub(crate) fn from_file_descriptor(input_fd: FileDesc) -> Result<Self> {
-        let poll = Poll::new()?;
-        let registry = poll.registry();
+/// The sequence ending the pasted text.
+const PASTE_END: &[u8] = b"\x1B[201~";
 
-        let tty_raw_fd = input_fd.raw_fd();
-        let mut tty_ev = SourceFd(&tty_raw_fd);
-        registry.register(&mut tty_ev, TTY_TOKEN, Interest::READABLE)?;
+/// Returns whether the TTY read failed because the terminal was closed.
+#[cfg(unix)]
+fn is_hangup_error(error: &io::Error) -> bool {
+    // The other end of the PTY was closed
+    error.raw_os_error() == Some(libc::EIO)
+}
 
-        let mut signals = Signals::new(&[signal_hook::consts::SIGWINCH])?;
-        registry.register(&mut signals, SIGNAL_TOKEN, Interest::READABLE)?;
+/// The error returned when reading from a terminal which was closed.
+#[cfg(unix)]
+fn closed_error() -> io::Error {
+    io::Error::new(io::ErrorKind::BrokenPipe, "The terminal was closed")
+}
 
-        #[cfg(feature = "event-stream")]
-        let waker = Waker::new(registry, WAKE_TOKEN)?;
+/// Restores the raw mode after the process was continued (`SIGCONT`) and produces the event.
+#[cfg(unix)]
+fn resumed_event() -> Result<InternalEvent> {
+    reapply_raw_mode()?;
+    Ok(InternalEvent::Event(Event::Resumed))
+}
 
-        Ok(UnixInternalEventSource {
-            poll,
-            events: Events::with_capacity(3),
-            parser: Parser::default(),
-            tty_buffer: [0u8; TTY_BUFFER_SIZE],
-            tty_fd: input_fd,
-            signals,
-            #[cfg(feature = "event-stream")]
-            waker,
-        })
-    }
+/// Keeps track of the terminal size to produce resize events.
+///
+/// The size is retrieved with an `ioctl` on the TTY only, `terminal::size` can launch a new
+/// process (`tput`) which stalls the poll loop and the async executors polling `EventStream`.
+/// If the `ioctl` fails, the last known size is used.
+#[cfg(unix)]
+#[derive(Debug)]
+struct TerminalSize {
+    last_known: Option<(u16, u16)>,
 }
 
-impl EventSource for UnixInternalEventSource {
-    fn try_read(&mut self, timeout: Option<Duration>) -> Result<Option<InternalEvent>> {
-        if let Some(event) = self.parser.next() {
-            return Ok(Some(event));
+#[cfg(unix)]
+impl TerminalSize {
+    fn new(tty: &FileDesc) -> TerminalSize {
+        TerminalSize {
+            last_known: size_from_fd(tty.raw_fd()).ok(),
         }
+    }
 
-        let timeout = PollTimeout::new(timeout);
-
-        loop {
-            if let Err(e) = self.poll.poll(&mut self.events, timeout.leftover()) {
-                // Mio will throw an interrupted error in case of cursor position retrieval. We need to retry until it succeeds.
-                // Previous versions of Mio (< 0.7) would automatically retry the poll call if it was interrupted (if EINTR was returned).
-                // https://docs.rs/mio/0.7.0/mio/struct.Poll.html#notes
-                if e.kind() == io::ErrorKind::Interrupted {
-                    continue;
-                } else {
-                    return Err(e);
-                }
-            };
-
-            if self.events.is_empty() {
-                // No readiness events = timeout
-                return Ok(None);
-            }
-
-            for token in self.events.iter().map(|x| x.token()) {
-                match token {
-                    TTY_TOKEN => {
-                        loop {
-                            match self.tty_fd.read(&mut self.tty_buffer, TTY_BUFFER_SIZE) {
-                                Ok(read_count) => {
-                                    if read_count > 0 {
-                                        self.parser.advance(
-                                            &self.tty_buffer[..read_count],
-                                            read_count == TTY_BUFFER_SIZE,
-                                        );
-                                    }
-                                }
-                                Err(e) => {
-                                    // No more data to read at the moment. We will receive another event
-                                    if e.kind() == io::ErrorKind::WouldBlock {
-                                        break;
-                                    }
-                                    // once more data is available to read.
-                                    else if e.kind() == io::ErrorKind::Interrupted {
-                                        continue;
-                                    }
-                                }
-                            };
-
-                            if let Some(event) = self.parser.next() {
-                                return Ok(Some(event));
-                            }
-                        }
-                    }
-                    SIGNAL_TOKEN => {
-                        for signal in self.signals.pending() {
-                            match signal {
-                                signal_hook::consts::SIGWINCH => {
-                                    // TODO Should we remove tput?
-                                    //
-                                    // This can take a really long time, because terminal::size can
-                                    // launch new process (tput) and then it parses its output. It's
-                                    // not a really long time from the absolute time point of view, but
-                                    // it's a really long time from the mio, async-std/tokio executor, ...
-                                    // point of view.
-                                    let new_size = crate::terminal::size()?;
-                                    return Ok(Some(InternalEvent::Event(Event::Resize(
-                                        new_size.0, new_size.1,
-                                    ))));
-                                }
-                                _ => unreachable!("Synchronize signal registration & handling"),
-                            };
-                        }
-                    }
-                    #[cfg(feature = "event-stream")]
-                    WAKE_TOKEN => {
-                        return Err(std::io::Error::new(
-                            std::io::ErrorKind::Interrupted,
-                            "Poll operation was woken up by `Waker::wake`",
-                        ));
-                    }
-                    _ => unreachable!("Synchronize Evented handle registration & token handling"),
-                }
-            }
+    fn resize_event(&mut self, tty: &FileDesc) -> Result<InternalEvent> {
+        let (columns, rows) = match (size_from_fd(tty.raw_fd()), self.last_known) {
+            (Ok(size), _) => size,
+            (Err(_), Some(size)) => size,
+            (Err(e), None) => return Err(e),
+        };
+        self.last_known = Some((columns, rows));
 
-            // Processing above can take some time, check if timeout expired
-            if timeout.elapsed() {
-                return Ok(None);
-            }
-        }
+        Ok(InternalEvent::Event(Event::Resize(columns, rows)))
     }
+}
 
-    #[cfg(feature = "event-stream")]
-    fn waker(&self) -> Waker {
-        self.waker.clone()
-    }
+/// The states of the `Parser`.
+///
+/// A simplified version of the DEC compatible state machine described at
+/// <https://vt100.net/emu/dec_ansi_parser>, limited to the sequences a terminal sends as input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Waiting for the first byte of a new sequence.
+    Ground,
+    /// Waiting for the given number of UTF-8 continuation bytes.
+    Utf8(u8),
+    /// `ESC` was received.
+    Escape,
+    /// `ESC [` was received.
+    CsiEntry,
+    /// Collecting the parameters and intermediates of a CSI sequence.
+    CsiParam,
+    /// `ESC O` was received, collecting the modifiers some terminals send before the key
+    /// (`ESC O 5 P`).
+    Ss3,
+    /// Collecting the given number of bytes which can have any value (`ESC [ M Cb Cx Cy`).
+    Raw(u8),
+    /// Collecting an OSC string (`ESC ] ... BEL` or `ESC ] ... ESC \`).
+    Osc,
+    /// `ESC` was received inside of an OSC string.
+    OscEscape,
+    /// Collecting a DCS or APC string (`ESC P ... ESC \`, `ESC _ ... ESC \`).
+    Dcs,
+    /// `ESC` was received inside of a DCS or APC string.
+    DcsEscape,
+    /// Collecting the text pasted between `ESC [ 200 ~` and `ESC [ 201 ~`.
+    Paste,
+}
+
+/// What to do with the buffered bytes after a byte was processed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    /// The sequence isn't complete yet, keep the bytes and move to the given state.
+    Wait(State),
+    /// The sequence is complete, turn it into an event.
+    Dispatch,
+    /// The sequence is complete, but we don't produce events for it.
+    Ignore,
+    /// The byte can't be part of the sequence. Throw the sequence away and process the byte again.
+    Abort,
 }
 
 //
@@ -176,10 +145,22 @@ impl EventSource for UnixInternalEventSource {
 //  * mimick anes Parser interface
 //  * move the advancing, parsing, ... stuff out of the `try_read` method
 //
+// The `Parser` only finds out where the sequences start and end, every complete sequence is
+// turned into an event by the `parse_event` function exactly once.
+//
 #[derive(Debug)]
-struct Parser {
+pub(crate) struct Parser {
     buffer: Vec<u8>,
+    /// The sequence in the buffer is longer than `MAX_SEQUENCE_LENGTH`, its end isn't buffered.
+    truncated: bool,
     internal_events: VecDeque<InternalEvent>,
+    state: State,
+    /// Started when the buffered bytes became ambiguous, see `Parser::is_ambiguous`.
+    escape_timeout: Option<PollTimeout>,
+    /// The clock of the escape timeout, `None` is the one of `PollTimeout::new`.
+    clock: Option<Arc<dyn Clock>>,
+    /// Whether the 8-bit C1 controls (`CSI` 0x9B, ...) are recognized.
+    c1_controls: bool,
 }
 
 impl Default for Parser {
@@ -187,11 +168,10 @@ impl Default for Parser {
         Parser {
             // This buffer is used for -> 1 <- ANSI escape sequence. Are we
             // aware of any ANSI escape sequence that is bigger? Can we make
-            // it smaller?
-            //
-            // Probably not worth spending more time on this as "there's a plan"
-            // to use the anes crate parser.
+            // it smaller? OSC strings can be bigger, but the buffer will simply
+            // grow in this case.
             buffer: Vec::with_capacity(256),
+            truncated: false,
             // TTY_BUFFER_SIZE is 1_024 bytes. How many ANSI escape sequences can
             // fit? What is an average sequence length? Let's guess here
             // and say that the average ANSI escape sequence length is 8 bytes. Thus
@@ -202,33 +182,291 @@ impl Default for Parser {
             // method implementation, all events are consumed before the next TTY_BUFFER
             // is processed -> events pushed.
             internal_events: VecDeque::with_capacity(128),
+            state: State::Ground,
+            escape_timeout: None,
+            clock: None,
+            c1_controls: false,
         }
     }
 }
 
+/// Returns the number of continuation bytes which follow the given UTF-8 leading byte.
+fn utf8_continuation_bytes(byte: u8) -> Option<u8> {
+    match byte {
+        0xC2..=0xDF => Some(1),
+        0xE0..=0xEF => Some(2),
+        0xF0..=0xF4 => Some(3),
+        _ => None,
+    }
+}
+
+/// Returns the byte following `ESC` in the 7-bit form of the 8-bit C1 control.
+fn c1_control_equivalent(byte: u8) -> Option<u8> {
+    match byte {
+        // SS3
+        0x8F => Some(b'O'),
+        // DCS
+        0x90 => Some(b'P'),
+        // CSI
+        0x9B => Some(b'['),
+        // ST
+        0x9C => Some(b'\\'),
+        // OSC
+        0x9D => Some(b']'),
+        // APC
+        0x9F => Some(b'_'),
+        _ => None,
+    }
+}
+
 impl Parser {
-    fn advance(&mut self, buffer: &[u8], more: bool) {
+    /// Processes the bytes, `more` says whether more bytes can follow the last one.
+    ///
+    /// If they can, an `ESC` at the end stays buffered until the next bytes arrive or
+    /// `flush_expired` resolves it as the Esc key.
+    pub(crate) fn advance(&mut self, buffer: &[u8], more: bool) {
         for (idx, byte) in buffer.iter().enumerate() {
             let more = idx + 1 < buffer.len() || more;
 
-            self.buffer.push(*byte);
+            self.advance_byte(*byte, more);
+        }
 
-            match parse_event(&self.buffer, more) {
-                Ok(Some(ie)) => {
-                    self.internal_events.push_back(ie);
-                    self.buffer.clear();
-                }
-                Ok(None) => {
-                    // Event can't be parsed, because we don't have enough bytes for
-                    // the current sequence. Keep the buffer and process next bytes.
+        // Measured from the last byte, a slow link can deliver a sequence in several pieces
+        self.escape_timeout = if self.is_ambiguous() {
+            let timeout = Some(crate::event::escape_timeout());
+            Some(match &self.clock {
+                Some(clock) => PollTimeout::with_clock(timeout, clock.clone()),
+                None => PollTimeout::new(timeout),
+            })
+        } else {
+            None
+        };
+    }
+
+    /// Returns whether the buffered bytes are a complete key on their own, but can be the start
+    /// of a sequence as well (`ESC` is the Esc key or the start of `ESC [ A`).
+    fn is_ambiguous(&self) -> bool {
+        match self.state {
+            State::Escape => true,
+            State::Osc => self.buffer == b"\x1B]",
+            State::Dcs => self.buffer == b"\x1BP" || self.buffer == b"\x1B_",
+            _ => false,
+        }
+    }
+
+    /// Returns the timeout to poll with, shortened to the escape timeout if the buffered bytes
+    /// are ambiguous.
+    pub(crate) fn poll_timeout(&self, leftover: Option<Duration>) -> Option<Duration> {
+        match (
+            leftover,
+            self.escape_timeout.as_ref().and_then(PollTimeout::leftover),
+        ) {
+            (Some(leftover), Some(escape)) => Some(leftover.min(escape)),
+            (leftover, None) => leftover,
+            (None, escape) => escape,
+        }
+    }
+
+    /// Resolves the ambiguous bytes as keys if the rest didn't arrive within the escape timeout.
+    pub(crate) fn flush_expired(&mut self) {
+        if self
+            .escape_timeout
+            .as_ref()
+            .is_some_and(PollTimeout::elapsed)
+        {
+            self.flush();
+        }
+    }
+
+    /// Resolves the ambiguous bytes as keys and throws away an incomplete sequence.
+    pub(crate) fn flush(&mut self) {
+        let pending = if self.is_ambiguous() {
+            self.buffer.clone()
+        } else {
+            Vec::new()
+        };
+        self.buffer.clear();
+        self.truncated = false;
+        self.state = State::Ground;
+        self.advance(&pending, false);
+    }
+
+    /// Returns the number of bytes of an incomplete sequence which are buffered.
+    pub(crate) fn pending_len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Sets whether the 8-bit C1 controls are recognized, they are continuation bytes of UTF-8
+    /// otherwise.
+    pub(crate) fn set_c1_controls(&mut self, enabled: bool) {
+        self.c1_controls = enabled;
+    }
+
+    fn advance_byte(&mut self, byte: u8, more: bool) {
+        // The pasted text is taken as it is, only the end of the paste is looked for
+        if self.state == State::Paste {
+            self.buffer.push(byte);
+            if let Some(text) = self.buffer.strip_suffix(PASTE_END) {
+                let text = String::from_utf8_lossy(text).into_owned();
+                self.internal_events
+                    .push_back(InternalEvent::Event(Event::Paste(text)));
+                self.buffer.clear();
+                self.state = State::Ground;
+            }
+            return;
+        }
+
+        if self.c1_controls {
+            if let Some(equivalent) = self.c1_control(byte) {
+                // Parsed in the 7-bit form, `ESC` is always followed by the second byte
+                self.advance_byte(b'\x1B', true);
+                return self.advance_byte(equivalent, more);
+            }
+        }
+
+        match self.action(byte, more) {
+            Action::Wait(state) => {
+                if self.buffer.len() < MAX_SEQUENCE_LENGTH {
+                    self.buffer.push(byte);
+                } else {
+                    self.truncated = true;
                 }
-                Err(_) => {
-                    // Event can't be parsed (not enough parameters, parameter is not a number, ...).
-                    // Clear the buffer and continue with another sequence.
-                    self.buffer.clear();
+                self.state = state;
+                return;
+            }
+            // The beginning of the sequence isn't enough to know what it is, throw it away
+            Action::Dispatch if self.truncated => {}
+            Action::Dispatch if byte == b'~' && self.buffer == PASTE_START => {
+                self.buffer.clear();
+                self.state = State::Paste;
+                return;
+            }
+            Action::Dispatch => {
+                self.buffer.push(byte);
+
+                let ie = match parse_event(&self.buffer, false) {
+                    Ok(Some(ie)) => ie,
+                    // The sequence is complete, but it can't be parsed (unknown sequence, not
+                    // enough parameters, parameter is not a number, ...). Hand it over as it is.
+                    _ => InternalEvent::Event(Event::Unsupported(self.buffer.clone())),
+                };
+                self.internal_events.push_back(ie);
+            }
+            Action::Ignore => {}
+            Action::Abort => {
+                self.buffer.clear();
+                self.truncated = false;
+
+                if self.state == State::OscEscape || self.state == State::DcsEscape {
+                    // The `ESC` which interrupted the string starts a new sequence
+                    self.buffer.push(b'\x1B');
+                    self.state = State::Escape;
+                } else {
+                    self.state = State::Ground;
                 }
+
+                return self.advance_byte(byte, more);
             }
         }
+
+        self.buffer.clear();
+        self.truncated = false;
+        self.state = State::Ground;
+    }
+
+    /// Returns the byte following `ESC` if the byte is a C1 control in the current state.
+    fn c1_control(&self, byte: u8) -> Option<u8> {
+        match self.state {
+            // The bytes of a UTF-8 character or of a normal mouse sequence can have any value
+            State::Utf8(_) | State::Raw(_) => None,
+            // ST only ends a string, it's ignored elsewhere
+            State::Osc | State::Dcs => c1_control_equivalent(byte),
+            _ if byte == 0x9C => None,
+            _ => c1_control_equivalent(byte),
+        }
+    }
+
+    /// The OSC strings are ignored, except the replies to the queries of this crate.
+    fn osc_string_end(&self) -> Action {
+        if self.buffer.starts_with(b"\x1B]52;") {
+            Action::Dispatch
+        } else {
+            Action::Ignore
+        }
+    }
+
+    fn action(&self, byte: u8, more: bool) -> Action {
+        match self.state {
+            State::Ground => match byte {
+                // A lone `ESC` is the Esc key, otherwise it starts a sequence
+                b'\x1B' if more => Action::Wait(State::Escape),
+                0x00..=0x7F => Action::Dispatch,
+                _ => utf8_continuation_bytes(byte)
+                    .map(|count| Action::Wait(State::Utf8(count)))
+                    .unwrap_or(Action::Ignore),
+            },
+            State::Utf8(count) => match byte {
+                0x80..=0xBF if count > 1 => Action::Wait(State::Utf8(count - 1)),
+                0x80..=0xBF => Action::Dispatch,
+                _ => Action::Abort,
+            },
+            State::Escape => match byte {
+                b'[' => Action::Wait(State::CsiEntry),
+                b'O' => Action::Wait(State::Ss3),
+                // rxvt prefixes a sequence with `ESC` for Alt (`ESC ESC [ A`)
+                b'\x1B' if more => Action::Wait(State::Escape),
+                // `ESC ]` is the Alt+] key as well, it only starts an OSC string if it's
+                // immediately followed by more bytes
+                b']' if more => Action::Wait(State::Osc),
+                // Same for `ESC P`, the Alt+Shift+P key, and DCS strings, and for `ESC _` and
+                // APC strings
+                b'P' | b'_' if more => Action::Wait(State::Dcs),
+                0x00..=0x7F => Action::Dispatch,
+                _ => utf8_continuation_bytes(byte)
+                    .map(|count| Action::Wait(State::Utf8(count)))
+                    .unwrap_or(Action::Ignore),
+            },
+            State::CsiEntry => match byte {
+                // Normal mouse encoding, three bytes with any value follow
+                b'M' => Action::Wait(State::Raw(3)),
+                // Linux console function keys, one more byte follows
+                b'[' => Action::Wait(State::Raw(1)),
+                0x20..=0x3F => Action::Wait(State::CsiParam),
+                0x40..=0x7E => Action::Dispatch,
+                _ => Action::Abort,
+            },
+            State::CsiParam => match byte {
+                // rxvt Shift+<special key>, `$` is an intermediate byte otherwise
+                b'$' if self.buffer[2..].iter().all(u8::is_ascii_digit) => Action::Dispatch,
+                0x20..=0x3F => Action::Wait(State::CsiParam),
+                0x40..=0x7E => Action::Dispatch,
+                _ => Action::Abort,
+            },
+            State::Ss3 => match byte {
+                b'0'..=b'9' | b';' => Action::Wait(State::Ss3),
+                _ => Action::Dispatch,
+            },
+            State::Raw(count) if count > 1 => Action::Wait(State::Raw(count - 1)),
+            State::Raw(_) => Action::Dispatch,
+            State::Osc => match byte {
+                b'\x07' => self.osc_string_end(),
+                b'\x1B' => Action::Wait(State::OscEscape),
+                _ => Action::Wait(State::Osc),
+            },
+            State::OscEscape => match byte {
+                b'\\' => self.osc_string_end(),
+                _ => Action::Abort,
+            },
+            State::Dcs => match byte {
+                b'\x1B' => Action::Wait(State::DcsEscape),
+                _ => Action::Wait(State::Dcs),
+            },
+            State::DcsEscape => match byte {
+                b'\\' => Action::Dispatch,
+                _ => Action::Abort,
+            },
+            State::Paste => unreachable!("The pasted text is collected in `advance_byte`"),
+        }
     }
 }
 
@@ -239,3 +477,468 @@ impl Iterator for Parser {
         self.internal_events.pop_front()
     }
 }
+
+// The tests of the event source need a UNIX terminal
+#[cfg(all(test, unix))]
+mod tests {
+    use std::{
+        io::Write,
+        os::unix::{io::AsRawFd, net::UnixStream},
+        ptr,
+        time::Duration,
+    };
+
+    use crate::event::{
+        timeout::ManualClock, KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind,
+        ScrollDelta,
+    };
+
+    use super::{
+        super::{super::sys::unix::file_descriptor::FileDesc, InternalEventSource},
+        Event, InternalEvent, Parser, TerminalSize, UnixInternalEventSource,
+    };
+
+    /// Signals are delivered to every source, the tests reading from one can't run at once.
+    static SOURCE_LOCK: parking_lot::Mutex<()> = parking_lot::const_mutex(());
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> InternalEvent {
+        InternalEvent::Event(Event::Key(KeyEvent::new(code, modifiers)))
+    }
+
+    fn parse(chunks: &[&[u8]]) -> Vec<InternalEvent> {
+        let mut parser = Parser::default();
+        for chunk in chunks {
+            parser.advance(chunk, false);
+        }
+        parser.collect()
+    }
+
+    #[test]
+    fn test_resize_event_uses_last_known_size() {
+        // Not a TTY, the ioctl fails and no other way to get the size may be used
+        let (stream, _other) = UnixStream::pair().unwrap();
+        let fd = FileDesc::new(stream.as_raw_fd(), false);
+
+        let mut size = TerminalSize::new(&fd);
+        assert_eq!(size.last_known, None);
+        assert!(size.resize_event(&fd).is_err());
+
+        size.last_known = Some((80, 24));
+        assert_eq!(
+            size.resize_event(&fd).unwrap(),
+            InternalEvent::Event(Event::Resize(80, 24))
+        );
+    }
+
+    #[test]
+    fn test_source_reports_closed_terminal() {
+        let _lock = SOURCE_LOCK.lock();
+        let (mut master, mut slave) = (0, 0);
+        assert_eq!(
+            unsafe {
+                libc::openpty(
+                    &mut master,
+                    &mut slave,
+                    ptr::null_mut(),
+                    ptr::null(),
+                    ptr::null(),
+                )
+            },
+            0
+        );
+
+        // Don't wait for a whole line
+        unsafe {
+            let mut termios = std::mem::zeroed();
+            libc::tcgetattr(slave, &mut termios);
+            crate::terminal::sys::raw_terminal_attr(&mut termios);
+            libc::tcsetattr(slave, libc::TCSANOW, &termios);
+        }
+
+        let mut source =
+            UnixInternalEventSource::from_file_descriptor(FileDesc::new(slave, true)).unwrap();
+        let timeout = Some(Duration::from_secs(1));
+
+        assert_eq!(
+            unsafe { libc::write(master, b"a".as_ptr() as *const libc::c_void, 1) },
+            1
+        );
+        assert_eq!(
+            source.try_read(timeout).unwrap(),
+            Some(key(KeyCode::Char('a'), KeyModifiers::NONE))
+        );
+
+        unsafe { libc::close(master) };
+        assert_eq!(
+            source.try_read(timeout).unwrap(),
+            Some(InternalEvent::Event(Event::Closed))
+        );
+        assert!(source.try_read(timeout).is_err());
+    }
+
+    // mio can't register a regular file or `/dev/null`
+    #[cfg(any(not(feature = "mio-poll"), target_os = "redox"))]
+    #[test]
+    fn test_source_reports_the_end_of_a_file_as_closed() {
+        let _lock = SOURCE_LOCK.lock();
+
+        let file = std::fs::File::open("/dev/null").unwrap();
+        let mut source =
+            UnixInternalEventSource::from_file_descriptor(FileDesc::new(file.as_raw_fd(), false))
+                .unwrap();
+
+        assert_eq!(
+            source.try_read(None).unwrap(),
+            Some(InternalEvent::Event(Event::Closed))
+        );
+        assert!(source.try_read(None).is_err());
+    }
+
+    #[test]
+    fn test_source_reports_resumed_process() {
+        let _lock = SOURCE_LOCK.lock();
+
+        let (stream, _other) = UnixStream::pair().unwrap();
+        let mut source =
+            UnixInternalEventSource::from_file_descriptor(FileDesc::new(stream.as_raw_fd(), false))
+                .unwrap();
+        let timeout = Some(Duration::from_secs(1));
+
+        // The process doesn't have to be stopped to receive it
+        assert_eq!(unsafe { libc::raise(libc::SIGCONT) }, 0);
+        assert_eq!(
+            source.try_read(timeout).unwrap(),
+            Some(InternalEvent::Event(Event::Resumed))
+        );
+        assert_eq!(
+            source.try_read(Some(Duration::from_millis(10))).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_source_reads_from_a_non_blocking_descriptor() {
+        let _lock = SOURCE_LOCK.lock();
+
+        // Other libraries can set `O_NONBLOCK` on the standard input we share with them
+        let (stream, mut other) = UnixStream::pair().unwrap();
+        stream.set_nonblocking(true).unwrap();
+        let mut source =
+            UnixInternalEventSource::from_file_descriptor(FileDesc::new(stream.as_raw_fd(), false))
+                .unwrap();
+
+        assert_eq!(
+            source.try_read(Some(Duration::from_millis(10))).unwrap(),
+            None
+        );
+        other.write_all(b"a\x1B[").unwrap();
+        assert_eq!(
+            source.try_read(Some(Duration::from_secs(1))).unwrap(),
+            Some(key(KeyCode::Char('a'), KeyModifiers::NONE))
+        );
+        assert_eq!(
+            source.try_read(Some(Duration::from_millis(10))).unwrap(),
+            None
+        );
+        other.write_all(b"C").unwrap();
+        assert_eq!(
+            source.try_read(Some(Duration::from_secs(1))).unwrap(),
+            Some(key(KeyCode::Right, KeyModifiers::NONE))
+        );
+    }
+
+    #[test]
+    fn test_parser_handles_sequences_split_at_any_position() {
+        let bytes = "a\x1B[A\x1B[<35;10;5M\x1B[15;5~\x1BO5Pü\x1Bc".as_bytes();
+        let expected = vec![
+            key(KeyCode::Char('a'), KeyModifiers::NONE),
+            key(KeyCode::Up, KeyModifiers::NONE),
+            InternalEvent::Event(Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Moved,
+                column: 9,
+                row: 4,
+                modifiers: KeyModifiers::NONE,
+                scroll_delta: ScrollDelta::default(),
+            })),
+            key(KeyCode::F(5), KeyModifiers::CONTROL),
+            key(KeyCode::F(1), KeyModifiers::CONTROL),
+            key(KeyCode::Char('ü'), KeyModifiers::NONE),
+            key(KeyCode::Char('c'), KeyModifiers::ALT),
+        ];
+
+        assert_eq!(parse(&[bytes]), expected);
+
+        for split in 1..bytes.len() {
+            // A lone ESC at the end of the input is the Esc key
+            if bytes[split - 1] == b'\x1B' {
+                continue;
+            }
+
+            assert_eq!(
+                parse(&[&bytes[..split], &bytes[split..]]),
+                expected,
+                "split at {}",
+                split
+            );
+        }
+    }
+
+    /// Parses the chunks like the TTY reads which filled the whole buffer, except for the last one.
+    fn parse_full_reads(chunks: &[&[u8]]) -> Vec<InternalEvent> {
+        let mut parser = Parser::default();
+        for (idx, chunk) in chunks.iter().enumerate() {
+            parser.advance(chunk, idx + 1 < chunks.len());
+        }
+        parser.collect()
+    }
+
+    #[test]
+    fn test_parser_handles_utf8_split_at_any_position() {
+        let text = "aü€🦀\x1Bé";
+        let bytes = text.as_bytes();
+        let expected = vec![
+            key(KeyCode::Char('a'), KeyModifiers::NONE),
+            key(KeyCode::Char('ü'), KeyModifiers::NONE),
+            key(KeyCode::Char('€'), KeyModifiers::NONE),
+            key(KeyCode::Char('🦀'), KeyModifiers::NONE),
+            key(KeyCode::Char('é'), KeyModifiers::ALT),
+        ];
+
+        for first in 1..bytes.len() {
+            for second in first..bytes.len() {
+                assert_eq!(
+                    parse_full_reads(&[&bytes[..first], &bytes[first..second], &bytes[second..]]),
+                    expected,
+                    "split at {} and {}",
+                    first,
+                    second
+                );
+            }
+        }
+
+        let single_bytes: Vec<&[u8]> = bytes.chunks(1).collect();
+        assert_eq!(parse_full_reads(&single_bytes), expected);
+    }
+
+    #[test]
+    fn test_parser_esc_key() {
+        assert_eq!(
+            parse(&[b"\x1B"]),
+            vec![key(KeyCode::Esc, KeyModifiers::NONE)]
+        );
+        assert_eq!(
+            parse(&[b"\x1B\x1B"]),
+            vec![key(KeyCode::Esc, KeyModifiers::NONE)]
+        );
+    }
+
+    #[test]
+    fn test_parser_reads_pasted_text() {
+        assert_eq!(
+            parse(&[b"a\x1B[200~foo\x1B[A\r", b"bar\x1B[2", b"01~b"]),
+            vec![
+                key(KeyCode::Char('a'), KeyModifiers::NONE),
+                InternalEvent::Event(Event::Paste("foo\x1B[A\rbar".to_string())),
+                key(KeyCode::Char('b'), KeyModifiers::NONE),
+            ]
+        );
+        assert_eq!(
+            parse(&[b"\x1B[200~\x1B[201~"]),
+            vec![InternalEvent::Event(Event::Paste(String::new()))]
+        );
+    }
+
+    #[test]
+    fn test_parser_ignores_osc_strings() {
+        assert_eq!(
+            parse(&[b"\x1B]11;rgb:0000/0000/0000\x07a\x1B]10;rgb:ffff/ffff/ffff\x1B\\b"]),
+            vec![
+                key(KeyCode::Char('a'), KeyModifiers::NONE),
+                key(KeyCode::Char('b'), KeyModifiers::NONE),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parser_reads_clipboard_contents() {
+        assert_eq!(
+            parse(&[b"\x1B]52;c;Zm", b"9v\x07a\x1B]52;c;\x1B\\"]),
+            vec![
+                InternalEvent::ClipboardContent(Box::new(b"foo".to_vec())),
+                key(KeyCode::Char('a'), KeyModifiers::NONE),
+                InternalEvent::ClipboardContent(Box::default()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parser_reads_dcs_strings() {
+        assert_eq!(
+            parse(&[b"\x1BP>|term", b"inal 1.0\x1B", b"\\a"]),
+            vec![
+                InternalEvent::TerminalVersion(Box::new("terminal 1.0".to_string())),
+                key(KeyCode::Char('a'), KeyModifiers::NONE),
+            ]
+        );
+        // The `ESC` which interrupts the string starts a new sequence
+        assert_eq!(
+            parse(&[b"\x1BP>|term\x1B[A"]),
+            vec![key(KeyCode::Up, KeyModifiers::NONE)]
+        );
+    }
+
+    #[test]
+    fn test_parser_reads_apc_strings() {
+        assert_eq!(
+            parse(&[b"\x1B_Gi=31;OK\x1B", b"\\\x1B_", b"a"]),
+            vec![
+                InternalEvent::KittyGraphics(Box::new("OK".to_string())),
+                key(KeyCode::Char('_'), KeyModifiers::ALT),
+                key(KeyCode::Char('a'), KeyModifiers::NONE),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parser_alt_shift_p_is_not_a_dcs_string() {
+        assert_eq!(
+            parse(&[b"\x1BP", b"a"]),
+            vec![
+                key(KeyCode::Char('P'), KeyModifiers::ALT | KeyModifiers::SHIFT),
+                key(KeyCode::Char('a'), KeyModifiers::NONE),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parser_alt_right_bracket_is_not_an_osc_string() {
+        assert_eq!(
+            parse(&[b"\x1B]", b"a"]),
+            vec![
+                key(KeyCode::Char(']'), KeyModifiers::ALT),
+                key(KeyCode::Char('a'), KeyModifiers::NONE),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parser_hands_over_unsupported_sequences() {
+        assert_eq!(
+            parse(&[b"\x1B[>1;2ca\x1B[5;1Z"]),
+            vec![
+                InternalEvent::Event(Event::Unsupported(b"\x1B[>1;2c".to_vec())),
+                key(KeyCode::Char('a'), KeyModifiers::NONE),
+                InternalEvent::Event(Event::Unsupported(b"\x1B[5;1Z".to_vec())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parser_reassembles_sequences_split_after_esc() {
+        let mut parser = Parser::default();
+
+        parser.advance(b"\x1B", true);
+        assert_eq!(parser.next(), None);
+        parser.advance(b"[A\x1B]", true);
+        assert_eq!(parser.next(), Some(key(KeyCode::Up, KeyModifiers::NONE)));
+        assert_eq!(parser.next(), None);
+        parser.advance(b"0;title\x07", true);
+        assert_eq!(parser.next(), None);
+    }
+
+    #[test]
+    fn test_parser_flushes_esc_after_the_escape_timeout() {
+        let clock = ManualClock::new();
+        let mut parser = Parser {
+            clock: Some(clock.clone()),
+            ..Parser::default()
+        };
+
+        parser.advance(b"\x1B", true);
+        parser.flush_expired();
+        assert_eq!(parser.next(), None);
+        assert_eq!(
+            parser.poll_timeout(None),
+            Some(crate::event::escape_timeout())
+        );
+
+        // Not yet expired one nanosecond before the end
+        clock.advance(crate::event::escape_timeout() - Duration::from_nanos(1));
+        parser.flush_expired();
+        assert_eq!(parser.next(), None);
+
+        clock.advance(Duration::from_nanos(1));
+        parser.flush_expired();
+        assert_eq!(parser.next(), Some(key(KeyCode::Esc, KeyModifiers::NONE)));
+        assert_eq!(parser.poll_timeout(None), None);
+
+        parser.advance(b"\x1B]", true);
+        clock.advance(crate::event::escape_timeout());
+        parser.flush_expired();
+        assert_eq!(
+            parser.next(),
+            Some(key(KeyCode::Char(']'), KeyModifiers::ALT))
+        );
+    }
+
+    #[test]
+    fn test_source_waits_for_the_rest_of_a_sequence() {
+        let _lock = SOURCE_LOCK.lock();
+
+        let (stream, mut other) = UnixStream::pair().unwrap();
+        let mut source =
+            UnixInternalEventSource::from_file_descriptor(FileDesc::new(stream.as_raw_fd(), false))
+                .unwrap();
+
+        other.write_all(b"\x1B").unwrap();
+        assert_eq!(
+            source.try_read(Some(Duration::from_millis(0))).unwrap(),
+            None
+        );
+        other.write_all(b"[B").unwrap();
+        assert_eq!(
+            source.try_read(Some(Duration::from_secs(1))).unwrap(),
+            Some(key(KeyCode::Down, KeyModifiers::NONE))
+        );
+
+        // Nothing follows, it's the Esc key once the escape timeout elapsed
+        other.write_all(b"\x1B").unwrap();
+        assert_eq!(
+            source.try_read(Some(Duration::from_secs(1))).unwrap(),
+            Some(key(KeyCode::Esc, KeyModifiers::NONE))
+        );
+    }
+
+    #[test]
+    fn test_parser_restarts_on_interrupted_sequence() {
+        assert_eq!(
+            parse(&[b"\x1B[1\x1B[A"]),
+            vec![key(KeyCode::Up, KeyModifiers::NONE)]
+        );
+        assert_eq!(
+            parse(&[b"\x1B]0;title\x1B[B"]),
+            vec![key(KeyCode::Down, KeyModifiers::NONE)]
+        );
+        assert_eq!(
+            parse(&[b"\xC3a"]),
+            vec![key(KeyCode::Char('a'), KeyModifiers::NONE)]
+        );
+    }
+
+    #[test]
+    fn test_parser_finds_the_end_of_rxvt_sequences() {
+        assert_eq!(
+            parse(&[b"\x1B[3$\x1B\x1B[Aa\x1B[?1;2$y"]),
+            vec![
+                key(KeyCode::Delete, KeyModifiers::SHIFT),
+                key(KeyCode::Up, KeyModifiers::ALT),
+                key(KeyCode::Char('a'), KeyModifiers::NONE),
+                InternalEvent::ModeReport(1, 2),
+            ]
+        );
+        assert_eq!(
+            parse(&[b"\x1B\x1B"]),
+            vec![key(KeyCode::Esc, KeyModifiers::NONE)]
+        );
+    }
+}
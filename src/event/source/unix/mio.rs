@@ -0,0 +1,287 @@
+#[cfg(any(feature = "tokio", feature = "async-std"))]
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::{collections::VecDeque, io, time::Duration};
+
+use mio::{unix::SourceFd, Events, Interest, Poll, Token};
+use signal_hook_mio::v0_7::Signals;
+
+use crate::Result;
+
+#[cfg(all(target_os = "linux", feature = "gpm"))]
+use super::super::super::sys::unix::gpm::GpmReader;
+#[cfg(feature = "event-stream")]
+use super::super::super::sys::Waker;
+use super::super::super::{
+    source::InternalEventSource,
+    sys::unix::{
+        file_descriptor::{tty_fd, FileDesc},
+        signal::{read_signal_events, signal_fd},
+    },
+    timeout::PollTimeout,
+    Event, InternalEvent,
+};
+use super::{closed_error, is_hangup_error, resumed_event, Parser, TerminalSize, TTY_BUFFER_SIZE};
+
+// Tokens to identify file descriptor
+const TTY_TOKEN: Token = Token(0);
+const SIGNAL_TOKEN: Token = Token(1);
+const CONT_SIGNAL_TOKEN: Token = Token(2);
+#[cfg(feature = "event-stream")]
+const WAKE_TOKEN: Token = Token(3);
+#[cfg(all(target_os = "linux", feature = "gpm"))]
+const GPM_TOKEN: Token = Token(4);
+const SIGNAL_EVENTS_TOKEN: Token = Token(5);
+
+pub(crate) struct UnixInternalEventSource {
+    poll: Poll,
+    events: Events,
+    parser: Parser,
+    tty_buffer: [u8; TTY_BUFFER_SIZE],
+    tty_fd: FileDesc,
+    closed: bool,
+    size: TerminalSize,
+    signals: Signals,
+    cont_signals: Signals,
+    /// The signals reported as events, see `event::set_signal_events`.
+    signal_events: VecDeque<InternalEvent>,
+    #[cfg(feature = "event-stream")]
+    waker: Waker,
+    #[cfg(all(target_os = "linux", feature = "gpm"))]
+    gpm: GpmReader,
+}
+
+impl UnixInternalEventSource {
+    pub fn new() -> Result<Self> {
+        UnixInternalEventSource::from_file_descriptor(tty_fd()?)
+    }
+
+    pub(crate) fn from_file_descriptor(input_fd: FileDesc) -> Result<Self> {
+        let poll = Poll::new()?;
+        let registry = poll.registry();
+
+        let tty_raw_fd = input_fd.raw_fd();
+        let mut tty_ev = SourceFd(&tty_raw_fd);
+        registry.register(&mut tty_ev, TTY_TOKEN, Interest::READABLE)?;
+
+        let mut signals = Signals::new([signal_hook::consts::SIGWINCH])?;
+        registry.register(&mut signals, SIGNAL_TOKEN, Interest::READABLE)?;
+
+        // Registered on its own, a resize right before it can't swallow it
+        let mut cont_signals = Signals::new([signal_hook::consts::SIGCONT])?;
+        registry.register(&mut cont_signals, CONT_SIGNAL_TOKEN, Interest::READABLE)?;
+
+        // The signals reported as events, see `event::set_signal_events`
+        registry.register(
+            &mut SourceFd(&signal_fd()?),
+            SIGNAL_EVENTS_TOKEN,
+            Interest::READABLE,
+        )?;
+
+        #[cfg(feature = "event-stream")]
+        let waker = Waker::new(registry, WAKE_TOKEN)?;
+
+        Ok(UnixInternalEventSource {
+            poll,
+            events: Events::with_capacity(4),
+            parser: Parser::default(),
+            tty_buffer: [0u8; TTY_BUFFER_SIZE],
+            size: TerminalSize::new(&input_fd),
+            tty_fd: input_fd,
+            closed: false,
+            signals,
+            cont_signals,
+            signal_events: VecDeque::new(),
+            #[cfg(feature = "event-stream")]
+            waker,
+            #[cfg(all(target_os = "linux", feature = "gpm"))]
+            gpm: GpmReader::default(),
+        })
+    }
+
+    /// Registers the gpm connection opened or closed since the last read.
+    #[cfg(all(target_os = "linux", feature = "gpm"))]
+    fn update_gpm(&mut self) -> Result<()> {
+        if !self.gpm.is_outdated() {
+            return Ok(());
+        }
+
+        let registry = self.poll.registry();
+        if let Some(fd) = self.gpm.raw_fd() {
+            registry.deregister(&mut SourceFd(&fd))?;
+        }
+        self.gpm.reconnect()?;
+        if let Some(fd) = self.gpm.raw_fd() {
+            registry.register(&mut SourceFd(&fd), GPM_TOKEN, Interest::READABLE)?;
+        }
+        Ok(())
+    }
+}
+
+impl InternalEventSource for UnixInternalEventSource {
+    fn try_read(&mut self, timeout: Option<Duration>) -> Result<Option<InternalEvent>> {
+        if let Some(event) = self.signal_events.pop_front() {
+            return Ok(Some(event));
+        }
+
+        self.parser.flush_expired();
+        if let Some(event) = self.parser.next() {
+            return Ok(Some(event));
+        }
+
+        #[cfg(all(target_os = "linux", feature = "gpm"))]
+        {
+            if let Some(event) = self.gpm.next() {
+                return Ok(Some(event));
+            }
+            self.update_gpm()?;
+        }
+
+        if self.closed {
+            return Err(closed_error());
+        }
+
+        let timeout = PollTimeout::new(timeout);
+
+        loop {
+            if let Err(e) = self.poll.poll(
+                &mut self.events,
+                self.parser.poll_timeout(timeout.leftover()),
+            ) {
+                // Mio will throw an interrupted error in case of cursor position retrieval. We need to retry until it succeeds.
+                // Previous versions of Mio (< 0.7) would automatically retry the poll call if it was interrupted (if EINTR was returned).
+                // https://docs.rs/mio/0.7.0/mio/struct.Poll.html#notes
+                if e.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                } else {
+                    return Err(e);
+                }
+            };
+
+            if self.events.is_empty() {
+                // No readiness events = timeout, the escape timeout or the one we were given
+                self.parser.flush_expired();
+                if let Some(event) = self.parser.next() {
+                    return Ok(Some(event));
+                }
+
+                if timeout.elapsed() {
+                    return Ok(None);
+                }
+                continue;
+            }
+
+            for (token, read_closed) in self.events.iter().map(|x| (x.token(), x.is_read_closed()))
+            {
+                match token {
+                    TTY_TOKEN => {
+                        loop {
+                            let (hangup, drained) =
+                                match self.tty_fd.read(&mut self.tty_buffer, TTY_BUFFER_SIZE) {
+                                    // Nothing to read after a hangup, the terminal was closed
+                                    Ok(0) => (read_closed, true),
+                                    Ok(read_count) => {
+                                        crate::debug::log_input(&self.tty_buffer[..read_count]);
+                                        self.parser
+                                            .set_c1_controls(crate::event::c1_controls_enabled());
+                                        // More bytes can always follow, see `Parser::flush_expired`
+                                        self.parser.advance(&self.tty_buffer[..read_count], true);
+                                        (false, read_count < TTY_BUFFER_SIZE)
+                                    }
+                                    // No more data to read at the moment. We will receive another event
+                                    // once more data is available to read.
+                                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                                    Err(e) if is_hangup_error(&e) => (true, true),
+                                    Err(e) => return Err(e),
+                                };
+
+                            if let Some(event) = self.parser.next() {
+                                return Ok(Some(event));
+                            }
+
+                            if hangup {
+                                self.closed = true;
+                                return Ok(Some(InternalEvent::Event(Event::Closed)));
+                            }
+
+                            // Everything was read, another read would block until the next key
+                            // press and the escape timeout couldn't elapse in the meantime
+                            if drained {
+                                break;
+                            }
+                        }
+                    }
+                    SIGNAL_TOKEN => {
+                        if let Some(signal) = self.signals.pending().next() {
+                            match signal {
+                                signal_hook::consts::SIGWINCH => {
+                                    return self.size.resize_event(&self.tty_fd).map(Some);
+                                }
+                                _ => unreachable!("Synchronize signal registration & handling"),
+                            };
+                        }
+                    }
+                    CONT_SIGNAL_TOKEN => {
+                        if self.cont_signals.pending().next().is_some() {
+                            return resumed_event().map(Some);
+                        }
+                    }
+                    SIGNAL_EVENTS_TOKEN => {
+                        read_signal_events(&mut self.signal_events)?;
+                        if let Some(event) = self.signal_events.pop_front() {
+                            return Ok(Some(event));
+                        }
+                    }
+                    #[cfg(all(target_os = "linux", feature = "gpm"))]
+                    GPM_TOKEN => {
+                        self.gpm.read()?;
+                        if let Some(event) = self.gpm.next() {
+                            return Ok(Some(event));
+                        }
+                    }
+                    #[cfg(feature = "event-stream")]
+                    WAKE_TOKEN => {
+                        return Err(std::io::Error::new(
+                            std::io::ErrorKind::Interrupted,
+                            "Poll operation was woken up by `Waker::wake`",
+                        ));
+                    }
+                    _ => unreachable!("Synchronize Evented handle registration & token handling"),
+                }
+            }
+
+            // Processing above can take some time, check if timeout expired
+            if timeout.elapsed() {
+                return Ok(None);
+            }
+        }
+    }
+
+    fn try_read_buffered(&mut self) -> Option<InternalEvent> {
+        if let Some(event) = self.signal_events.pop_front() {
+            return Some(event);
+        }
+
+        #[cfg(all(target_os = "linux", feature = "gpm"))]
+        if let Some(event) = self.gpm.next() {
+            return Some(event);
+        }
+
+        self.parser.next()
+    }
+
+    #[cfg(any(feature = "tokio", feature = "async-std"))]
+    fn readiness_fd(&self) -> Option<RawFd> {
+        // Readable when one of the registered sources is
+        Some(self.poll.as_raw_fd())
+    }
+
+    #[cfg(any(feature = "tokio", feature = "async-std"))]
+    fn escape_timeout(&self) -> Option<Duration> {
+        self.parser.poll_timeout(None)
+    }
+
+    #[cfg(feature = "event-stream")]
+    fn waker(&self) -> Waker {
+        self.waker.clone()
+    }
+}
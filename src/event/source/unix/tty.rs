@@ -0,0 +1,336 @@
+use std::{
+    collections::VecDeque,
+    io::{self, Read},
+    os::unix::{
+        io::{AsRawFd, RawFd},
+        net::UnixStream,
+    },
+    time::Duration,
+};
+
+use signal_hook::{low_level::pipe, SigId};
+
+use crate::Result;
+
+#[cfg(all(target_os = "linux", feature = "gpm"))]
+use super::super::super::sys::unix::gpm::GpmReader;
+#[cfg(feature = "event-stream")]
+use super::super::super::sys::Waker;
+use super::super::super::{
+    source::InternalEventSource,
+    sys::unix::{
+        file_descriptor::{tty_fd, FileDesc},
+        signal::{read_signal_events, signal_fd},
+    },
+    timeout::PollTimeout,
+    Event, InternalEvent,
+};
+use super::{closed_error, is_hangup_error, resumed_event, Parser, TerminalSize, TTY_BUFFER_SIZE};
+
+/// Holds the `Waker` handed out by this source and the receiver we poll on.
+#[cfg(feature = "event-stream")]
+struct WakePipe {
+    receiver: UnixStream,
+    waker: Waker,
+}
+
+#[cfg(feature = "event-stream")]
+impl WakePipe {
+    fn new() -> Result<Self> {
+        let (receiver, sender) = nonblocking_unix_pair()?;
+        Ok(WakePipe {
+            receiver,
+            waker: Waker::new(sender),
+        })
+    }
+}
+
+/// A signal delivered through a socket pair registered with signal-hook.
+struct SignalPipe {
+    receiver: UnixStream,
+    id: SigId,
+}
+
+impl SignalPipe {
+    fn register(signal: libc::c_int) -> Result<Self> {
+        let (receiver, sender) = nonblocking_unix_pair()?;
+        Ok(SignalPipe {
+            receiver,
+            id: pipe::register(signal, sender)?,
+        })
+    }
+}
+
+impl Drop for SignalPipe {
+    fn drop(&mut self) {
+        signal_hook::low_level::unregister(self.id);
+    }
+}
+
+/// An `InternalEventSource` polling the TTY with `poll(2)` instead of mio.
+///
+/// Signals are delivered through a socket pair registered with signal-hook, so only the
+/// system `poll` is needed to wait for the TTY, signals and the waker at once.
+pub(crate) struct UnixInternalEventSource {
+    parser: Parser,
+    tty_buffer: [u8; TTY_BUFFER_SIZE],
+    tty: FileDesc,
+    closed: bool,
+    size: TerminalSize,
+    winch_signal: SignalPipe,
+    cont_signal: SignalPipe,
+    /// Readable when a signal is reported as an event, see `event::set_signal_events`.
+    signal_events_fd: RawFd,
+    signal_events: VecDeque<InternalEvent>,
+    #[cfg(feature = "event-stream")]
+    wake_pipe: WakePipe,
+    #[cfg(all(target_os = "linux", feature = "gpm"))]
+    gpm: GpmReader,
+}
+
+fn nonblocking_unix_pair() -> Result<(UnixStream, UnixStream)> {
+    let (receiver, sender) = UnixStream::pair()?;
+    receiver.set_nonblocking(true)?;
+    sender.set_nonblocking(true)?;
+    Ok((receiver, sender))
+}
+
+impl UnixInternalEventSource {
+    pub fn new() -> Result<Self> {
+        UnixInternalEventSource::from_file_descriptor(tty_fd()?)
+    }
+
+    pub(crate) fn from_file_descriptor(input_fd: FileDesc) -> Result<Self> {
+        Ok(UnixInternalEventSource {
+            parser: Parser::default(),
+            tty_buffer: [0u8; TTY_BUFFER_SIZE],
+            size: TerminalSize::new(&input_fd),
+            tty: input_fd,
+            closed: false,
+            winch_signal: SignalPipe::register(signal_hook::consts::SIGWINCH)?,
+            cont_signal: SignalPipe::register(signal_hook::consts::SIGCONT)?,
+            signal_events_fd: signal_fd()?,
+            signal_events: VecDeque::new(),
+            #[cfg(feature = "event-stream")]
+            wake_pipe: WakePipe::new()?,
+            #[cfg(all(target_os = "linux", feature = "gpm"))]
+            gpm: GpmReader::default(),
+        })
+    }
+}
+
+/// Reads everything that is available from the given non-blocking stream and throws it away.
+fn drain(mut stream: &UnixStream) -> Result<()> {
+    let mut buffer = [0u8; 1024];
+    loop {
+        match stream.read(&mut buffer) {
+            Ok(0) => return Ok(()),
+            Ok(_) => continue,
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+fn pollfd(fd: RawFd) -> libc::pollfd {
+    libc::pollfd {
+        fd,
+        events: libc::POLLIN,
+        revents: 0,
+    }
+}
+
+/// Converts the poll timeout to milliseconds, rounded up so we never wake up too early.
+fn poll_timeout_millis(timeout: Option<Duration>) -> libc::c_int {
+    match timeout {
+        None => -1,
+        Some(timeout) => {
+            let millis = timeout.as_nanos().div_ceil(1_000_000);
+            millis.min(libc::c_int::MAX as u128) as libc::c_int
+        }
+    }
+}
+
+impl InternalEventSource for UnixInternalEventSource {
+    fn try_read(&mut self, timeout: Option<Duration>) -> Result<Option<InternalEvent>> {
+        if let Some(event) = self.signal_events.pop_front() {
+            return Ok(Some(event));
+        }
+
+        self.parser.flush_expired();
+        if let Some(event) = self.parser.next() {
+            return Ok(Some(event));
+        }
+
+        #[cfg(all(target_os = "linux", feature = "gpm"))]
+        {
+            if let Some(event) = self.gpm.next() {
+                return Ok(Some(event));
+            }
+            if self.gpm.is_outdated() {
+                self.gpm.reconnect()?;
+            }
+        }
+
+        if self.closed {
+            return Err(closed_error());
+        }
+
+        let timeout = PollTimeout::new(timeout);
+
+        #[cfg(not(feature = "event-stream"))]
+        let mut fds = vec![
+            pollfd(self.tty.raw_fd()),
+            pollfd(self.winch_signal.receiver.as_raw_fd()),
+            pollfd(self.cont_signal.receiver.as_raw_fd()),
+            pollfd(self.signal_events_fd),
+        ];
+
+        #[cfg(feature = "event-stream")]
+        let mut fds = vec![
+            pollfd(self.tty.raw_fd()),
+            pollfd(self.winch_signal.receiver.as_raw_fd()),
+            pollfd(self.cont_signal.receiver.as_raw_fd()),
+            pollfd(self.signal_events_fd),
+            pollfd(self.wake_pipe.receiver.as_raw_fd()),
+        ];
+
+        // The last one, `poll` ignores the negative descriptors
+        #[cfg(all(target_os = "linux", feature = "gpm"))]
+        fds.push(pollfd(self.gpm.raw_fd().unwrap_or(-1)));
+
+        loop {
+            let result = unsafe {
+                libc::poll(
+                    fds.as_mut_ptr(),
+                    fds.len() as libc::nfds_t,
+                    poll_timeout_millis(self.parser.poll_timeout(timeout.leftover())),
+                )
+            };
+
+            if result < 0 {
+                let e = io::Error::last_os_error();
+                // The poll call is interrupted by signals (SIGWINCH, ...). We need to retry until it succeeds.
+                if e.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                } else {
+                    return Err(e);
+                }
+            }
+
+            if result == 0 {
+                // No readiness events = timeout, the escape timeout or the one we were given
+                self.parser.flush_expired();
+                if let Some(event) = self.parser.next() {
+                    return Ok(Some(event));
+                }
+
+                if timeout.elapsed() {
+                    return Ok(None);
+                }
+                continue;
+            }
+
+            let tty_revents = fds[0].revents;
+            if tty_revents & libc::POLLNVAL != 0 {
+                return Err(io::Error::from_raw_os_error(libc::EBADF));
+            }
+
+            if tty_revents & (libc::POLLIN | libc::POLLHUP | libc::POLLERR) != 0 {
+                let hangup = match self.tty.read(&mut self.tty_buffer, TTY_BUFFER_SIZE) {
+                    // The terminal was closed, or the source is a file (`/dev/null`, ...) which
+                    // reached its end and stays readable forever
+                    Ok(0) => true,
+                    Ok(read_count) => {
+                        crate::debug::log_input(&self.tty_buffer[..read_count]);
+                        self.parser
+                            .set_c1_controls(crate::event::c1_controls_enabled());
+                        // More bytes can always follow, see `Parser::flush_expired`
+                        self.parser.advance(&self.tty_buffer[..read_count], true);
+                        false
+                    }
+                    // The descriptor is non-blocking (`O_NONBLOCK` set by another library) and
+                    // the data is gone already, poll again
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => false,
+                    Err(e) if is_hangup_error(&e) => true,
+                    Err(e) => return Err(e),
+                };
+
+                if let Some(event) = self.parser.next() {
+                    return Ok(Some(event));
+                }
+
+                if hangup {
+                    self.closed = true;
+                    return Ok(Some(InternalEvent::Event(Event::Closed)));
+                }
+
+                // The read didn't fail, but polling again would report the same error forever
+                if tty_revents & libc::POLLERR != 0 {
+                    return Err(io::Error::other("Polling the terminal failed"));
+                }
+            }
+
+            if fds[1].revents & libc::POLLIN != 0 {
+                drain(&self.winch_signal.receiver)?;
+
+                return self.size.resize_event(&self.tty).map(Some);
+            }
+
+            if fds[2].revents & libc::POLLIN != 0 {
+                drain(&self.cont_signal.receiver)?;
+
+                return resumed_event().map(Some);
+            }
+
+            if fds[3].revents & libc::POLLIN != 0 {
+                read_signal_events(&mut self.signal_events)?;
+                if let Some(event) = self.signal_events.pop_front() {
+                    return Ok(Some(event));
+                }
+            }
+
+            #[cfg(all(target_os = "linux", feature = "gpm"))]
+            if fds[fds.len() - 1].revents & libc::POLLIN != 0 {
+                self.gpm.read()?;
+                if let Some(event) = self.gpm.next() {
+                    return Ok(Some(event));
+                }
+            }
+
+            #[cfg(feature = "event-stream")]
+            if fds[4].revents & libc::POLLIN != 0 {
+                drain(&self.wake_pipe.receiver)?;
+
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Interrupted,
+                    "Poll operation was woken up by `Waker::wake`",
+                ));
+            }
+
+            // Processing above can take some time, check if timeout expired
+            if timeout.elapsed() {
+                return Ok(None);
+            }
+        }
+    }
+
+    fn try_read_buffered(&mut self) -> Option<InternalEvent> {
+        if let Some(event) = self.signal_events.pop_front() {
+            return Some(event);
+        }
+
+        #[cfg(all(target_os = "linux", feature = "gpm"))]
+        if let Some(event) = self.gpm.next() {
+            return Some(event);
+        }
+
+        self.parser.next()
+    }
+
+    #[cfg(feature = "event-stream")]
+    fn waker(&self) -> Waker {
+        self.wake_pipe.waker.clone()
+    }
+}
@@ -0,0 +1,92 @@
+use std::time::Duration;
+
+#[cfg(windows)]
+use winapi::um::{synchapi::WaitForSingleObject, winbase::WAIT_OBJECT_0};
+
+use crate::Result;
+
+use super::super::sys::Waker;
+
+/// How long to wait for an event at once, the waker is checked in between.
+pub(crate) const WAKE_CHECK_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The `Waker` handed out by an event source which can't wait for it, and what it wakes.
+///
+/// The `Waker` is the one of the platform, it can't interrupt what the source waits on (a
+/// condition variable, a custom source, ...). The source checks whether it was woken up in
+/// between instead.
+pub(crate) struct WakeSignal {
+    pub(crate) waker: Waker,
+    #[cfg(all(unix, feature = "mio-poll", not(target_os = "redox")))]
+    poll: mio::Poll,
+    #[cfg(all(unix, feature = "mio-poll", not(target_os = "redox")))]
+    events: mio::Events,
+    #[cfg(all(unix, any(not(feature = "mio-poll"), target_os = "redox")))]
+    receiver: std::os::unix::net::UnixStream,
+}
+
+impl WakeSignal {
+    #[cfg(all(unix, feature = "mio-poll", not(target_os = "redox")))]
+    pub(crate) fn new() -> Result<WakeSignal> {
+        let poll = mio::Poll::new()?;
+        Ok(WakeSignal {
+            waker: Waker::new(poll.registry(), mio::Token(0))?,
+            poll,
+            events: mio::Events::with_capacity(1),
+        })
+    }
+
+    #[cfg(all(unix, any(not(feature = "mio-poll"), target_os = "redox")))]
+    pub(crate) fn new() -> Result<WakeSignal> {
+        let (receiver, sender) = std::os::unix::net::UnixStream::pair()?;
+        receiver.set_nonblocking(true)?;
+        sender.set_nonblocking(true)?;
+        Ok(WakeSignal {
+            waker: Waker::new(sender),
+            receiver,
+        })
+    }
+
+    #[cfg(windows)]
+    pub(crate) fn new() -> Result<WakeSignal> {
+        Ok(WakeSignal {
+            waker: Waker::new()?,
+        })
+    }
+
+    /// Returns whether the `Waker` was used since the last call.
+    #[cfg(all(unix, feature = "mio-poll", not(target_os = "redox")))]
+    pub(crate) fn take(&mut self) -> bool {
+        self.poll
+            .poll(&mut self.events, Some(Duration::from_secs(0)))
+            .is_ok()
+            && !self.events.is_empty()
+    }
+
+    /// Returns whether the `Waker` was used since the last call.
+    #[cfg(all(unix, any(not(feature = "mio-poll"), target_os = "redox")))]
+    pub(crate) fn take(&mut self) -> bool {
+        use std::io::Read;
+
+        let mut woken = false;
+        let mut buffer = [0u8; 32];
+        while let Ok(count) = self.receiver.read(&mut buffer) {
+            if count == 0 {
+                break;
+            }
+            woken = true;
+        }
+        woken
+    }
+
+    /// Returns whether the `Waker` was used since the last call.
+    #[cfg(windows)]
+    pub(crate) fn take(&mut self) -> bool {
+        let semaphore = self.waker.semaphore();
+        let woken = unsafe { WaitForSingleObject(**semaphore.handle(), 0) } == WAIT_OBJECT_0;
+        if woken {
+            let _ = self.waker.reset();
+        }
+        woken
+    }
+}
@@ -0,0 +1,60 @@
+use std::{io, time::Duration};
+
+use crate::{wasm, Result};
+
+use super::super::{source::InternalEventSource, Event, InternalEvent};
+use super::unix::Parser;
+
+/// Reads the input given to `wasm::push_input`.
+///
+/// The input is pushed by the browser on the thread reading the events, it never arrives while
+/// the source waits. The source doesn't wait: without events, it interrupts the polling.
+pub(crate) struct WasmEventSource {
+    parser: Parser,
+}
+
+impl WasmEventSource {
+    pub fn new() -> Result<WasmEventSource> {
+        Ok(WasmEventSource {
+            parser: Parser::default(),
+        })
+    }
+}
+
+impl InternalEventSource for WasmEventSource {
+    fn try_read(&mut self, timeout: Option<Duration>) -> Result<Option<InternalEvent>> {
+        if let Some(event) = self.try_read_buffered() {
+            return Ok(Some(event));
+        }
+
+        if let Some((columns, rows)) = wasm::take_resize() {
+            return Ok(Some(InternalEvent::Event(Event::Resize(columns, rows))));
+        }
+
+        let input = wasm::take_input();
+        if !input.is_empty() {
+            crate::debug::log_input(&input);
+            // A sequence isn't split between two inputs, see `wasm::push_input`
+            self.parser.advance(&input, false);
+        }
+        if let Some(event) = self.parser.next() {
+            return Ok(Some(event));
+        }
+
+        match timeout {
+            // `poll` returns `false` without waiting
+            Some(_) => Err(io::Error::new(
+                io::ErrorKind::Interrupted,
+                "No input is pending",
+            )),
+            None => Err(io::Error::new(
+                io::ErrorKind::WouldBlock,
+                "Can't wait for the input in the browser, poll the events when the input is pushed",
+            )),
+        }
+    }
+
+    fn try_read_buffered(&mut self) -> Option<InternalEvent> {
+        self.parser.next()
+    }
+}
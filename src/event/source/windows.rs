@@ -1,21 +1,33 @@
 use std::time::Duration;
 
-use crossterm_winapi::{Console, Handle, InputRecord};
+use crossterm_winapi::{Console, Handle, InputRecord, KeyEventRecord};
 
-use crate::event::{sys::windows::poll::WinApiPoll, Event};
+use crate::{
+    event::{is_focus_change_enabled, sys::windows::poll::WinApiPoll, Event},
+    terminal::sys::is_virtual_terminal_input_enabled,
+};
 
 #[cfg(feature = "event-stream")]
 use super::super::sys::Waker;
 use super::super::{
-    source::EventSource,
-    sys::windows::parse::{handle_key_event, handle_mouse_event},
+    source::InternalEventSource,
+    sys::windows::{
+        coordinates::{window_size, ConsoleWindow},
+        parse::{handle_key_event, handle_mouse_event, SurrogateBuffers},
+        signal::take_signal_event,
+    },
     timeout::PollTimeout,
     InternalEvent, Result,
 };
+use super::unix::Parser;
 
 pub(crate) struct WindowsEventSource {
     console: Console,
     poll: WinApiPoll,
+    surrogate_buffers: SurrogateBuffers,
+    window: ConsoleWindow,
+    /// Parses the keys sent as escape sequences, see `event::set_virtual_terminal_input`.
+    parser: Parser,
 }
 
 impl WindowsEventSource {
@@ -28,23 +40,75 @@ impl WindowsEventSource {
             poll: WinApiPoll::new(),
             #[cfg(feature = "event-stream")]
             poll: WinApiPoll::new()?,
+
+            surrogate_buffers: SurrogateBuffers::default(),
+            window: ConsoleWindow::default(),
+            parser: Parser::default(),
         })
     }
+
+    /// Gives the character of a key record to the parser, the console sends the escape sequences
+    /// a character per record.
+    fn advance_parser(&mut self, record: KeyEventRecord) {
+        if !record.key_down || record.u_char == 0 {
+            return;
+        }
+
+        if let Some(character) = self.surrogate_buffers.char_from_utf16(record.u_char, true) {
+            let mut bytes = [0u8; 4];
+            self.parser
+                .set_c1_controls(crate::event::c1_controls_enabled());
+            // More bytes can always follow, see `Parser::flush_expired`
+            self.parser
+                .advance(character.encode_utf8(&mut bytes).as_bytes(), true);
+        }
+    }
 }
 
-impl EventSource for WindowsEventSource {
+impl InternalEventSource for WindowsEventSource {
     fn try_read(&mut self, timeout: Option<Duration>) -> Result<Option<InternalEvent>> {
         let poll_timeout = PollTimeout::new(timeout);
 
         loop {
-            if let Some(event_ready) = self.poll.poll(poll_timeout.leftover())? {
+            self.parser.flush_expired();
+            if let Some(event) = self.parser.next() {
+                return Ok(Some(event));
+            }
+
+            let timeout = self.parser.poll_timeout(poll_timeout.leftover());
+            if let Some(event_ready) = self.poll.poll(timeout)? {
                 let number = self.console.number_of_console_input_events()?;
                 if event_ready && number != 0 {
-                    let event = match self.console.read_single_input_event()? {
-                        InputRecord::KeyEvent(record) => handle_key_event(record),
-                        InputRecord::MouseEvent(record) => handle_mouse_event(record),
+                    let record = self.console.read_single_input_event()?;
+                    crate::debug::log_input_record(&record);
+
+                    let event = match record {
+                        InputRecord::KeyEvent(record) if is_virtual_terminal_input_enabled() => {
+                            self.advance_parser(record);
+                            None
+                        }
+                        InputRecord::KeyEvent(record) => {
+                            handle_key_event(record, &mut self.surrogate_buffers)
+                        }
+                        InputRecord::MouseEvent(record) => {
+                            handle_mouse_event(record, &mut self.window)
+                        }
                         InputRecord::WindowBufferSizeEvent(record) => {
-                            Some(Event::Resize(record.size.x as u16, record.size.y as u16))
+                            // The record holds the size of the screen buffer, not of the window.
+                            // It's the best guess if the window can't be retrieved.
+                            let (columns, rows) = match self.window.current() {
+                                Some(window) => window_size(window),
+                                None => (record.size.x as u16, record.size.y as u16),
+                            };
+                            Some(Event::Resize(columns, rows))
+                        }
+                        InputRecord::MenuEvent(_) => take_signal_event(),
+                        InputRecord::FocusEvent(record) if is_focus_change_enabled() => {
+                            Some(if record.set_focus {
+                                Event::FocusGained
+                            } else {
+                                Event::FocusLost
+                            })
                         }
                         _ => None,
                     };
@@ -56,11 +120,16 @@ impl EventSource for WindowsEventSource {
             }
 
             if poll_timeout.elapsed() {
-                return Ok(None);
+                self.parser.flush_expired();
+                return Ok(self.parser.next());
             }
         }
     }
 
+    fn try_read_buffered(&mut self) -> Option<InternalEvent> {
+        self.parser.next()
+    }
+
     #[cfg(feature = "event-stream")]
     fn waker(&self) -> Waker {
         self.poll.waker()
@@ -6,7 +6,7 @@ use std::{
         Arc,
     },
     task::{Context, Poll},
-    thread,
+    thread::{self, JoinHandle},
     time::Duration,
 };
 
@@ -19,6 +19,10 @@ use super::{
     Event, InternalEvent,
 };
 
+/// How long the task thread waits for the reader or an event before it checks whether the
+/// stream was dropped.
+const SHUTDOWN_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
 /// A stream of `Result<Event>`.
 ///
 /// **This type is not available by default. You have to use the `event-stream` feature flag
@@ -35,21 +39,20 @@ pub struct EventStream {
     poll_internal_waker: Waker,
     stream_wake_task_executed: Arc<AtomicBool>,
     stream_wake_task_should_shutdown: Arc<AtomicBool>,
-    task_sender: SyncSender<Task>,
+    task_sender: Option<SyncSender<Task>>,
+    task_thread: Option<JoinHandle<()>>,
 }
 
 impl Default for EventStream {
     fn default() -> Self {
         let (task_sender, receiver) = mpsc::sync_channel::<Task>(1);
 
-        thread::spawn(move || {
+        let task_thread = thread::spawn(move || {
             while let Ok(task) = receiver.recv() {
-                loop {
-                    if let Ok(true) = poll_internal(None, &EventFilter) {
-                        break;
-                    }
-
-                    if task.stream_wake_task_should_shutdown.load(Ordering::SeqCst) {
+                // Never blocks for long, another thread can hold the reader in `event::read` or
+                // take the wake up meant for this one, and the shutdown must still be noticed
+                while !task.stream_wake_task_should_shutdown.load(Ordering::SeqCst) {
+                    if let Ok(true) = poll_internal(Some(SHUTDOWN_CHECK_INTERVAL), &EventFilter) {
                         break;
                     }
                 }
@@ -63,7 +66,8 @@ impl Default for EventStream {
             poll_internal_waker: lock_internal_event_reader().waker(),
             stream_wake_task_executed: Arc::new(AtomicBool::new(false)),
             stream_wake_task_should_shutdown: Arc::new(AtomicBool::new(false)),
-            task_sender,
+            task_sender: Some(task_sender),
+            task_thread: Some(task_thread),
         }
     }
 }
@@ -107,7 +111,6 @@ impl Stream for EventStream {
             Ok(true) => match read_internal(&EventFilter) {
                 Ok(InternalEvent::Event(event)) => Poll::Ready(Some(Ok(event))),
                 Err(e) => Poll::Ready(Some(Err(e))),
-                #[cfg(unix)]
                 _ => unreachable!(),
             },
             Ok(false) => {
@@ -124,11 +127,13 @@ impl Stream for EventStream {
 
                     stream_wake_task_should_shutdown.store(false, Ordering::SeqCst);
 
-                    let _ = self.task_sender.send(Task {
-                        stream_waker,
-                        stream_wake_task_executed,
-                        stream_wake_task_should_shutdown,
-                    });
+                    if let Some(task_sender) = &self.task_sender {
+                        let _ = task_sender.send(Task {
+                            stream_waker,
+                            stream_wake_task_executed,
+                            stream_wake_task_should_shutdown,
+                        });
+                    }
                 }
                 Poll::Pending
             }
@@ -142,6 +147,15 @@ impl Drop for EventStream {
     fn drop(&mut self) {
         self.stream_wake_task_should_shutdown
             .store(true, Ordering::SeqCst);
-        let _ = self.poll_internal_waker.wake();
+        // Without the sender, the thread quits instead of waiting for the next task
+        self.task_sender.take();
+
+        if let Some(task_thread) = self.task_thread.take() {
+            // Only speeds the shutdown up, the thread notices it within `SHUTDOWN_CHECK_INTERVAL`
+            // anyway. The waker interrupts whichever thread sits in the `poll_internal` and that
+            // doesn't have to be ours if there're several streams.
+            let _ = self.poll_internal_waker.wake();
+            let _ = task_thread.join();
+        }
     }
 }
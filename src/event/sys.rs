@@ -3,7 +3,6 @@ pub(crate) use unix::waker::Waker;
 #[cfg(all(windows, feature = "event-stream"))]
 pub(crate) use windows::waker::Waker;
 
-#[cfg(unix)]
 pub(crate) mod unix;
 #[cfg(windows)]
 pub(crate) mod windows;
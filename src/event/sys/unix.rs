@@ -1,5 +1,12 @@
-#[cfg(feature = "event-stream")]
+#[cfg(all(unix, feature = "event-stream"))]
 pub(crate) mod waker;
 
+#[cfg(unix)]
 pub(crate) mod file_descriptor;
+#[cfg(all(target_os = "linux", feature = "gpm"))]
+pub(crate) mod gpm;
 pub(crate) mod parse;
+#[cfg(unix)]
+pub(crate) mod signal;
+#[cfg(unix)]
+pub(crate) mod terminfo;
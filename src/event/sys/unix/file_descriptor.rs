@@ -28,19 +28,31 @@ impl FileDesc {
         FileDesc { fd, close_on_drop }
     }
 
+    /// Reads up to `size` bytes into the buffer.
+    ///
+    /// The read is retried if it's interrupted by a signal before any data was read.
+    ///
+    /// The descriptor can be in the non-blocking mode, other libraries sharing the standard input
+    /// set `O_NONBLOCK` on it and we don't change it back. The read fails with
+    /// `io::ErrorKind::WouldBlock` then, poll the descriptor before reading to wait for data.
     pub fn read(&self, buffer: &mut [u8], size: usize) -> Result<usize> {
-        let result = unsafe {
-            libc::read(
-                self.fd,
-                buffer.as_mut_ptr() as *mut libc::c_void,
-                size as size_t,
-            ) as isize
-        };
-
-        if result < 0 {
-            Err(io::Error::last_os_error())
-        } else {
-            Ok(result as usize)
+        loop {
+            let result = unsafe {
+                libc::read(
+                    self.fd,
+                    buffer.as_mut_ptr() as *mut libc::c_void,
+                    size as size_t,
+                ) as isize
+            };
+
+            if result >= 0 {
+                return Ok(result as usize);
+            }
+
+            let e = io::Error::last_os_error();
+            if e.kind() != io::ErrorKind::Interrupted {
+                return Err(e);
+            }
         }
     }
 
@@ -64,19 +76,72 @@ impl Drop for FileDesc {
 }
 
 /// Creates a file descriptor pointing to the standard input or `/dev/tty`.
+///
+/// The standard input is used if it's a terminal, `/dev/tty` can't be opened in some containers
+/// and chroots even though there's a terminal. Otherwise `/dev/tty` is opened, which works when the
+/// standard input is redirected.
+///
+/// Fails with `io::ErrorKind::NotFound` if there's no terminal at all.
 pub fn tty_fd() -> Result<FileDesc> {
-    let (fd, close_on_drop) = if unsafe { libc::isatty(libc::STDIN_FILENO) == 1 } {
-        (libc::STDIN_FILENO, false)
-    } else {
-        (
-            fs::OpenOptions::new()
-                .read(true)
-                .write(true)
-                .open("/dev/tty")?
-                .into_raw_fd(),
-            true,
-        )
+    if unsafe { libc::isatty(libc::STDIN_FILENO) == 1 } {
+        return Ok(FileDesc::new(libc::STDIN_FILENO, false));
+    }
+
+    match fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+    {
+        Ok(file) => Ok(FileDesc::new(file.into_raw_fd(), true)),
+        Err(e) => Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!(
+                "No terminal available, the standard input isn't a terminal and /dev/tty can't be opened: {}",
+                e
+            ),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Write,
+        mem,
+        os::unix::{io::AsRawFd, net::UnixStream, thread::JoinHandleExt},
+        ptr, thread,
+        time::Duration,
     };
 
-    Ok(FileDesc::new(fd, close_on_drop))
+    use super::FileDesc;
+
+    extern "C" fn ignore_signal(_: libc::c_int) {}
+
+    #[test]
+    fn test_read_is_retried_when_interrupted() {
+        let (stream, mut other) = UnixStream::pair().unwrap();
+
+        // Without `SA_RESTART` the blocked read fails with `EINTR` when the signal arrives
+        unsafe {
+            let mut action: libc::sigaction = mem::zeroed();
+            action.sa_sigaction = ignore_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            libc::sigaction(libc::SIGUSR2, &action, ptr::null_mut());
+        }
+
+        let reader = thread::spawn(move || {
+            let fd = FileDesc::new(stream.as_raw_fd(), false);
+            let mut buffer = [0u8; 8];
+            let count = fd.read(&mut buffer, 8).unwrap();
+            buffer[..count].to_vec()
+        });
+
+        thread::sleep(Duration::from_millis(50));
+        unsafe {
+            libc::pthread_kill(reader.as_pthread_t(), libc::SIGUSR2);
+        }
+        thread::sleep(Duration::from_millis(50));
+        other.write_all(b"abc").unwrap();
+
+        assert_eq!(reader.join().unwrap(), b"abc");
+    }
 }
@@ -0,0 +1,325 @@
+//! A client of gpm, the mouse server of the Linux virtual console.
+//!
+//! The protocol of `libgpm` is spoken directly over the `/dev/gpmctl` socket: the client sends a
+//! `Gpm_Connect` structure and receives `Gpm_Event` structures, both in the native byte order.
+
+use std::{
+    collections::VecDeque,
+    io::{self, Read, Write},
+    mem,
+    os::unix::{
+        io::{AsRawFd, RawFd},
+        net::UnixStream,
+    },
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use parking_lot::Mutex;
+
+use crate::{
+    event::{
+        Event, InternalEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind, ScrollDelta,
+    },
+    Result,
+};
+
+/// The socket of the gpm server.
+const SOCKET_PATH: &str = "/dev/gpmctl";
+
+/// The major device number of the virtual consoles, `/dev/tty1` to `/dev/tty63`.
+const VIRTUAL_CONSOLE_MAJOR: libc::c_uint = 4;
+
+/// The size of `Gpm_Event`, with the wheel deltas of gpm 1.20.7.
+const EVENT_SIZE: usize = 28;
+
+// The event types of `Gpm_Event`
+const GPM_MOVE: i32 = 1;
+const GPM_DRAG: i32 = 2;
+const GPM_DOWN: i32 = 4;
+const GPM_UP: i32 = 8;
+const GPM_HARD: u16 = 256;
+
+// The buttons of `Gpm_Event`
+const GPM_B_RIGHT: u8 = 1;
+const GPM_B_MIDDLE: u8 = 2;
+const GPM_B_LEFT: u8 = 4;
+const GPM_B_UP: u8 = 16;
+const GPM_B_DOWN: u8 = 32;
+
+// The modifiers of `Gpm_Event`, the bits of the kernel keyboard shift states
+const KG_SHIFT: u8 = 1 << 0;
+const KG_CTRL: u8 = 1 << 2;
+const KG_ALT: u8 = 1 << 3;
+
+/// The connection opened by `enable_gpm`.
+static CONNECTION: Mutex<Option<UnixStream>> = parking_lot::const_mutex(None);
+
+/// Incremented every time the connection is opened or closed, the event sources pick it up then.
+static GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+/// Connects to gpm, returns `false` if the terminal isn't a virtual console or gpm doesn't run.
+pub(crate) fn connect() -> Result<bool> {
+    let console = match virtual_console() {
+        Some(console) => console,
+        None => return Ok(false),
+    };
+
+    let mut stream = match UnixStream::connect(SOCKET_PATH) {
+        Ok(stream) => stream,
+        Err(e)
+            if e.kind() == io::ErrorKind::NotFound
+                || e.kind() == io::ErrorKind::ConnectionRefused =>
+        {
+            return Ok(false)
+        }
+        Err(e) => return Err(e),
+    };
+    stream.write_all(&connect_message(process::id() as i32, console))?;
+    stream.set_nonblocking(true)?;
+
+    *CONNECTION.lock() = Some(stream);
+    GENERATION.fetch_add(1, Ordering::Relaxed);
+    Ok(true)
+}
+
+/// Closes the connection to gpm, gpm handles the mouse on its own again.
+pub(crate) fn disconnect() {
+    if CONNECTION.lock().take().is_some() {
+        GENERATION.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Returns the number of the virtual console of the standard streams, the one gpm reports.
+fn virtual_console() -> Option<i32> {
+    [libc::STDIN_FILENO, libc::STDOUT_FILENO, libc::STDERR_FILENO]
+        .iter()
+        .find_map(|fd| {
+            let mut stat: libc::stat = unsafe { mem::zeroed() };
+            if unsafe { libc::fstat(*fd, &mut stat) } != 0
+                || stat.st_mode & libc::S_IFMT != libc::S_IFCHR
+                || libc::major(stat.st_rdev) != VIRTUAL_CONSOLE_MAJOR
+            {
+                return None;
+            }
+
+            // `/dev/tty0` is the current console, not a console of its own
+            match libc::minor(stat.st_rdev) {
+                0 => None,
+                console => Some(console as i32),
+            }
+        })
+}
+
+/// Returns the `Gpm_Connect` structure asking for all the events of the console.
+fn connect_message(pid: i32, console: i32) -> Vec<u8> {
+    let mut message = Vec::with_capacity(16);
+    // The events we want
+    message.extend_from_slice(&u16::MAX.to_ne_bytes());
+    // The events gpm handles as well, it keeps drawing the pointer
+    message.extend_from_slice(&(GPM_MOVE as u16 | GPM_HARD).to_ne_bytes());
+    // The events with any modifiers
+    message.extend_from_slice(&0u16.to_ne_bytes());
+    message.extend_from_slice(&u16::MAX.to_ne_bytes());
+    message.extend_from_slice(&pid.to_ne_bytes());
+    message.extend_from_slice(&console.to_ne_bytes());
+    message
+}
+
+/// Reads the events of the connection opened by `enable_gpm`, owned by an event source.
+#[derive(Debug, Default)]
+pub(crate) struct GpmReader {
+    generation: usize,
+    stream: Option<UnixStream>,
+    /// The beginning of an event which wasn't read entirely.
+    buffer: Vec<u8>,
+    events: VecDeque<InternalEvent>,
+}
+
+impl GpmReader {
+    /// Returns whether the connection was opened or closed since the reader last picked it up.
+    pub(crate) fn is_outdated(&self) -> bool {
+        self.generation != GENERATION.load(Ordering::Relaxed)
+    }
+
+    /// Picks up the current connection.
+    pub(crate) fn reconnect(&mut self) -> Result<()> {
+        let connection = CONNECTION.lock();
+        self.generation = GENERATION.load(Ordering::Relaxed);
+        self.stream = connection.as_ref().map(UnixStream::try_clone).transpose()?;
+        self.buffer.clear();
+        Ok(())
+    }
+
+    /// Returns the descriptor to poll, if connected.
+    pub(crate) fn raw_fd(&self) -> Option<RawFd> {
+        self.stream.as_ref().map(AsRawFd::as_raw_fd)
+    }
+
+    /// Reads the available events, the descriptor is non-blocking.
+    pub(crate) fn read(&mut self) -> Result<()> {
+        let mut buffer = [0u8; EVENT_SIZE * 16];
+        while let Some(stream) = &mut self.stream {
+            match stream.read(&mut buffer) {
+                // gpm exited
+                Ok(0) => self.stream = None,
+                Ok(read_count) => self.buffer.extend_from_slice(&buffer[..read_count]),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+
+        let complete = self.buffer.len() - self.buffer.len() % EVENT_SIZE;
+        let events: Vec<u8> = self.buffer.drain(..complete).collect();
+        self.events.extend(
+            events
+                .chunks(EVENT_SIZE)
+                .filter_map(parse_event)
+                .map(|event| InternalEvent::Event(Event::Mouse(event))),
+        );
+        Ok(())
+    }
+
+    pub(crate) fn next(&mut self) -> Option<InternalEvent> {
+        self.events.pop_front()
+    }
+}
+
+/// Turns a `Gpm_Event` into a mouse event.
+fn parse_event(event: &[u8]) -> Option<MouseEvent> {
+    let short = |offset: usize| i16::from_ne_bytes([event[offset], event[offset + 1]]);
+    let buttons = event[0];
+    let modifiers = event[1];
+    let (x, y) = (short(8), short(10));
+    let kind = i32::from_ne_bytes([event[12], event[13], event[14], event[15]]);
+    let (wheel_x, wheel_y) = (short(24), short(26));
+
+    let button = if buttons & GPM_B_LEFT != 0 {
+        Some(MouseButton::Left)
+    } else if buttons & GPM_B_RIGHT != 0 {
+        Some(MouseButton::Right)
+    } else if buttons & GPM_B_MIDDLE != 0 {
+        Some(MouseButton::Middle)
+    } else {
+        None
+    };
+
+    let kind = if kind & GPM_DOWN != 0 {
+        if buttons & GPM_B_UP != 0 {
+            MouseEventKind::ScrollUp
+        } else if buttons & GPM_B_DOWN != 0 {
+            MouseEventKind::ScrollDown
+        } else {
+            MouseEventKind::Down(button?)
+        }
+    } else if kind & GPM_UP != 0 {
+        MouseEventKind::Up(button?)
+    } else if kind & GPM_DRAG != 0 {
+        MouseEventKind::Drag(button?)
+    } else if kind & GPM_MOVE != 0 {
+        match (wheel_x.signum(), wheel_y.signum()) {
+            (_, 1) => MouseEventKind::ScrollUp,
+            (_, -1) => MouseEventKind::ScrollDown,
+            (1, _) => MouseEventKind::ScrollRight,
+            (-1, _) => MouseEventKind::ScrollLeft,
+            _ => MouseEventKind::Moved,
+        }
+    } else {
+        return None;
+    };
+
+    let mut key_modifiers = KeyModifiers::empty();
+    if modifiers & KG_SHIFT != 0 {
+        key_modifiers |= KeyModifiers::SHIFT;
+    }
+    if modifiers & KG_CTRL != 0 {
+        key_modifiers |= KeyModifiers::CONTROL;
+    }
+    if modifiers & KG_ALT != 0 {
+        key_modifiers |= KeyModifiers::ALT;
+    }
+
+    let scroll_delta = if wheel_x != 0 || wheel_y != 0 {
+        // gpm counts the notches
+        ScrollDelta {
+            x: wheel_x.saturating_mul(ScrollDelta::NOTCH),
+            y: wheel_y.saturating_mul(ScrollDelta::NOTCH),
+        }
+    } else {
+        ScrollDelta::notch(kind)
+    };
+
+    Some(MouseEvent {
+        kind,
+        // gpm starts at 1
+        column: x.max(1) as u16 - 1,
+        row: y.max(1) as u16 - 1,
+        modifiers: key_modifiers,
+        scroll_delta,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::event::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind, ScrollDelta};
+
+    use super::{connect_message, parse_event, GPM_B_LEFT, GPM_DOWN, GPM_DRAG, GPM_MOVE, KG_CTRL};
+
+    fn event(buttons: u8, modifiers: u8, x: i16, y: i16, kind: i32, wheel: i16) -> Vec<u8> {
+        let mut event = vec![buttons, modifiers, 1, 0];
+        // dx, dy
+        event.extend_from_slice(&[0; 4]);
+        event.extend_from_slice(&x.to_ne_bytes());
+        event.extend_from_slice(&y.to_ne_bytes());
+        event.extend_from_slice(&kind.to_ne_bytes());
+        // clicks, margin, wdx
+        event.extend_from_slice(&[0; 10]);
+        event.extend_from_slice(&wheel.to_ne_bytes());
+        event
+    }
+
+    #[test]
+    fn test_connect_message() {
+        let message = connect_message(42, 3);
+
+        assert_eq!(message.len(), 16);
+        assert_eq!(&message[8..12], &42i32.to_ne_bytes());
+        assert_eq!(&message[12..], &3i32.to_ne_bytes());
+    }
+
+    #[test]
+    fn test_parse_event() {
+        assert_eq!(
+            parse_event(&event(GPM_B_LEFT, KG_CTRL, 10, 5, GPM_DOWN, 0)),
+            Some(MouseEvent {
+                kind: MouseEventKind::Down(MouseButton::Left),
+                column: 9,
+                row: 4,
+                modifiers: KeyModifiers::CONTROL,
+                scroll_delta: ScrollDelta::default(),
+            })
+        );
+        assert_eq!(
+            parse_event(&event(GPM_B_LEFT, 0, 1, 1, GPM_DRAG, 0)).map(|event| event.kind),
+            Some(MouseEventKind::Drag(MouseButton::Left))
+        );
+        assert_eq!(
+            parse_event(&event(0, 0, 1, 1, GPM_MOVE, 0)).map(|event| event.kind),
+            Some(MouseEventKind::Moved)
+        );
+        assert_eq!(
+            parse_event(&event(0, 0, 1, 1, GPM_MOVE, -1))
+                .map(|event| (event.kind, event.scroll_delta)),
+            Some((
+                MouseEventKind::ScrollDown,
+                ScrollDelta {
+                    x: 0,
+                    y: -ScrollDelta::NOTCH
+                }
+            ))
+        );
+        // A press without a button
+        assert_eq!(parse_event(&event(0, 0, 1, 1, GPM_DOWN, 0)), None);
+    }
+}
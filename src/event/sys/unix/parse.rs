@@ -1,7 +1,13 @@
 use std::io;
 
+use parking_lot::RwLock;
+
 use crate::{
-    event::{Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind},
+    event::{
+        Event, KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers,
+        KeyboardEnhancementFlags, MediaKeyCode, ModifierKeyCode, MouseButton, MouseEvent,
+        MouseEventKind, ScrollDelta,
+    },
     ErrorKind, Result,
 };
 
@@ -20,7 +26,25 @@ use super::super::super::InternalEvent;
 //
 
 fn could_not_parse_event_error() -> ErrorKind {
-    io::Error::new(io::ErrorKind::Other, "Could not parse an event.")
+    io::Error::other("Could not parse an event.")
+}
+
+/// The key sequences loaded from terminfo, they take precedence over the built-in ones.
+static KEY_SEQUENCES: RwLock<Vec<(Vec<u8>, KeyEvent)>> = parking_lot::const_rwlock(Vec::new());
+
+/// Replaces the key sequences looked up before the built-in ones.
+#[cfg(any(unix, test))]
+pub(crate) fn set_key_sequences(sequences: Vec<(Vec<u8>, KeyEvent)>) {
+    *KEY_SEQUENCES.write() = sequences;
+}
+
+/// Returns the key of a sequence loaded from terminfo.
+fn lookup_key_sequence(buffer: &[u8]) -> Option<KeyEvent> {
+    KEY_SEQUENCES
+        .read()
+        .iter()
+        .find(|(sequence, _)| sequence == buffer)
+        .map(|(_, key)| *key)
 }
 
 pub(crate) fn parse_event(buffer: &[u8], input_available: bool) -> Result<Option<InternalEvent>> {
@@ -37,23 +61,27 @@ pub(crate) fn parse_event(buffer: &[u8], input_available: bool) -> Result<Option
                 } else {
                     Ok(Some(InternalEvent::Event(Event::Key(KeyCode::Esc.into()))))
                 }
+            } else if let Some(key_event) = lookup_key_sequence(buffer) {
+                Ok(Some(InternalEvent::Event(Event::Key(key_event))))
             } else {
                 match buffer[1] {
                     b'O' => {
                         if buffer.len() == 2 {
                             Ok(None)
                         } else {
-                            match buffer[2] {
-                                // F1-F4
-                                val @ b'P'..=b'S' => Ok(Some(InternalEvent::Event(Event::Key(
-                                    KeyCode::F(1 + val - b'P').into(),
-                                )))),
-                                _ => Err(could_not_parse_event_error()),
-                            }
+                            parse_ss3(buffer)
                         }
                     }
                     b'[' => parse_csi(buffer),
-                    b'\x1B' => Ok(Some(InternalEvent::Event(Event::Key(KeyCode::Esc.into())))),
+                    // A lone `ESC P` is Alt+Shift+P, `ESC _` is Alt+_, `ESC ]` is Alt+]
+                    b'P' if buffer.len() > 2 => parse_dcs(buffer),
+                    b'_' if buffer.len() > 2 => parse_apc(buffer),
+                    b']' if buffer.len() > 2 => parse_osc(buffer),
+                    b'\x1B' if buffer.len() == 2 => {
+                        Ok(Some(InternalEvent::Event(Event::Key(KeyCode::Esc.into()))))
+                    }
+                    // Anything else prefixed with `ESC` is the same key with Alt, this includes
+                    // the rxvt Alt+arrows `ESC ESC [ A`
                     _ => parse_event(&buffer[1..], input_available).map(|event_option| {
                         event_option.map(|event| {
                             if let InternalEvent::Event(Event::Key(key_event)) = event {
@@ -83,11 +111,11 @@ pub(crate) fn parse_event(buffer: &[u8], input_available: bool) -> Result<Option
             KeyCode::Backspace.into(),
         )))),
         c @ b'\x01'..=b'\x1A' => Ok(Some(InternalEvent::Event(Event::Key(KeyEvent::new(
-            KeyCode::Char((c as u8 - 0x1 + b'a') as char),
+            KeyCode::Char((c - 0x1 + b'a') as char),
             KeyModifiers::CONTROL,
         ))))),
         c @ b'\x1C'..=b'\x1F' => Ok(Some(InternalEvent::Event(Event::Key(KeyEvent::new(
-            KeyCode::Char((c as u8 - 0x1C + b'4') as char),
+            KeyCode::Char((c - 0x1C + b'4') as char),
             KeyModifiers::CONTROL,
         ))))),
         b'\0' => Ok(Some(InternalEvent::Event(Event::Key(KeyEvent::new(
@@ -113,8 +141,85 @@ fn char_code_to_event(code: KeyCode) -> KeyEvent {
     KeyEvent::new(code, modifiers)
 }
 
+// Terminals don't agree on how the arrow keys, Home, End and F1-F4 are sent once modifiers are
+// involved. All of them end up as the same `KeyEvent` that xterm's `ESC [ 1 ; <modifiers> <key>`
+// would produce:
+//
+// | Key             | xterm, kitty, tmux, ... | Application mode | rxvt          |
+// |-----------------|-------------------------|------------------|---------------|
+// | Up              | `ESC [ A`               | `ESC O A`        | `ESC [ A`     |
+// | Shift+Up        | `ESC [ 1 ; 2 A`         |                  | `ESC [ a`     |
+// | Ctrl+Up         | `ESC [ 1 ; 5 A`         |                  | `ESC O a`     |
+// | Alt+Up          | `ESC [ 1 ; 3 A`         |                  | `ESC ESC [ A` |
+// | Ctrl+Delete     | `ESC [ 3 ; 5 ~`         |                  | `ESC [ 3 ^`   |
+// | Shift+Delete    | `ESC [ 3 ; 2 ~`         |                  | `ESC [ 3 $`   |
+// | Ctrl+Shift+Del. | `ESC [ 3 ; 6 ~`         |                  | `ESC [ 3 @`   |
+//
+// The Meta modifier (`ESC [ 1 ; 9 A`) is reported as Alt, terminals configured to send Meta for
+// the Alt key use it instead.
+
+/// Returns the arrow key sent as `A`-`D`, rxvt sends them as `a`-`d` along with a modifier.
+fn parse_arrow_key_code(byte: u8) -> Option<KeyCode> {
+    match byte.to_ascii_uppercase() {
+        b'A' => Some(KeyCode::Up),
+        b'B' => Some(KeyCode::Down),
+        b'C' => Some(KeyCode::Right),
+        b'D' => Some(KeyCode::Left),
+        _ => None,
+    }
+}
+
+pub(crate) fn parse_ss3(buffer: &[u8]) -> Result<Option<InternalEvent>> {
+    // ESC O <key> or ESC O <modifiers> <key>
+    //   Some terminals (Konsole, older xterm versions, ...) send the modifiers of F1-F4, Home and
+    //   End in the SS3 sequence, older ones as ESC O 1 ; <modifiers> <key>
+    assert!(buffer.starts_with(b"\x1BO")); // ESC O
+
+    if buffer.len() == 2 {
+        return Ok(None);
+    }
+
+    let key = buffer[buffer.len() - 1];
+    let mut modifiers = KeyModifiers::NONE;
+    if buffer.len() > 3 {
+        let s = std::str::from_utf8(&buffer[2..buffer.len() - 1])
+            .map_err(|_| could_not_parse_event_error())?;
+        modifiers = parse_modifiers(next_parsed::<u16>(&mut s.rsplit(';'))?);
+    }
+
+    let mut state = KeyEventState::NONE;
+    let keycode = match key {
+        // Application cursor keys mode
+        b'A'..=b'D' => parse_arrow_key_code(key).unwrap(),
+        b'H' => KeyCode::Home,
+        b'F' => KeyCode::End,
+        // F1-F4
+        b'P'..=b'S' => KeyCode::F(1 + key - b'P'),
+        // rxvt Ctrl+arrows
+        b'a'..=b'd' => {
+            modifiers |= KeyModifiers::CONTROL;
+            parse_arrow_key_code(key).unwrap()
+        }
+        // Application keypad mode, the digits, the operators and Enter
+        b'M' | b'X' | b'j'..=b'y' => {
+            state |= KeyEventState::KEYPAD;
+            match key {
+                b'M' => KeyCode::Enter,
+                b'X' => KeyCode::Char('='),
+                // `ESC O j` is `*`, the next keys follow the ASCII order: `+ , - . / 0 ... 9`
+                _ => KeyCode::Char((key - b'j' + b'*') as char),
+            }
+        }
+        _ => return Err(could_not_parse_event_error()),
+    };
+
+    Ok(Some(InternalEvent::Event(Event::Key(
+        KeyEvent::new_with_kind_and_state(keycode, modifiers, KeyEventKind::Press, state),
+    ))))
+}
+
 pub(crate) fn parse_csi(buffer: &[u8]) -> Result<Option<InternalEvent>> {
-    assert!(buffer.starts_with(&[b'\x1B', b'['])); // ESC [
+    assert!(buffer.starts_with(b"\x1B[")); // ESC [
 
     if buffer.len() == 2 {
         return Ok(None);
@@ -137,14 +242,32 @@ pub(crate) fn parse_csi(buffer: &[u8]) -> Result<Option<InternalEvent>> {
         b'C' => Some(Event::Key(KeyCode::Right.into())),
         b'A' => Some(Event::Key(KeyCode::Up.into())),
         b'B' => Some(Event::Key(KeyCode::Down.into())),
+        // rxvt Shift+arrows
+        val @ b'a'..=b'd' => Some(Event::Key(KeyEvent::new(
+            parse_arrow_key_code(val).unwrap(),
+            KeyModifiers::SHIFT,
+        ))),
         b'H' => Some(Event::Key(KeyCode::Home.into())),
         b'F' => Some(Event::Key(KeyCode::End.into())),
-        b'Z' => Some(Event::Key(KeyEvent {
-            code: KeyCode::BackTab,
-            modifiers: KeyModifiers::SHIFT,
-        })),
+        b'I' => Some(Event::FocusGained),
+        b'O' => Some(Event::FocusLost),
+        b'Z' => Some(Event::Key(KeyEvent::new(
+            KeyCode::BackTab,
+            KeyModifiers::SHIFT,
+        ))),
         b'M' => return parse_csi_normal_mouse(buffer),
         b'<' => return parse_csi_sgr_mouse(buffer),
+        b'?' => {
+            if buffer.ends_with(b"c") {
+                return parse_csi_primary_device_attributes(buffer);
+            } else if buffer.ends_with(b"$y") {
+                return parse_csi_mode_report(buffer);
+            } else if buffer.ends_with(b"u") {
+                return parse_csi_keyboard_enhancement_flags(buffer);
+            } else {
+                return Err(could_not_parse_event_error());
+            }
+        }
         b'0'..=b'9' => {
             // Numbered escape code.
             if buffer.len() == 3 {
@@ -153,13 +276,16 @@ pub(crate) fn parse_csi(buffer: &[u8]) -> Result<Option<InternalEvent>> {
                 // The final byte of a CSI sequence can be in the range 64-126, so
                 // let's keep reading anything else.
                 let last_byte = *buffer.last().unwrap();
-                if !(64..=126).contains(&last_byte) {
+                if is_rxvt_shift_special_key(buffer) {
+                    return parse_csi_special_key_code(buffer);
+                } else if !(64..=126).contains(&last_byte) {
                     None
                 } else {
                     match buffer[buffer.len() - 1] {
                         b'M' => return parse_csi_rxvt_mouse(buffer),
-                        b'~' => return parse_csi_special_key_code(buffer),
+                        b'~' | b'^' | b'@' => return parse_csi_special_key_code(buffer),
                         b'R' => return parse_csi_cursor_position(buffer),
+                        b'u' => return parse_csi_u_encoded_key_code(buffer),
                         _ => return parse_csi_modifier_key_code(buffer),
                     }
                 }
@@ -171,6 +297,17 @@ pub(crate) fn parse_csi(buffer: &[u8]) -> Result<Option<InternalEvent>> {
     Ok(input_event.map(InternalEvent::Event))
 }
 
+/// Returns whether the buffer is a special key with Shift sent by rxvt (`ESC [ 3 $`).
+///
+/// `$` is an intermediate byte in other CSI sequences, it can only end the sequence if nothing but
+/// the key number precedes it.
+fn is_rxvt_shift_special_key(buffer: &[u8]) -> bool {
+    buffer.len() > 3
+        && buffer.starts_with(b"\x1B[")
+        && buffer.ends_with(b"$")
+        && buffer[2..buffer.len() - 1].iter().all(u8::is_ascii_digit)
+}
+
 pub(crate) fn next_parsed<T>(iter: &mut dyn Iterator<Item = &str>) -> Result<T>
 where
     T: std::str::FromStr,
@@ -185,21 +322,175 @@ pub(crate) fn parse_csi_cursor_position(buffer: &[u8]) -> Result<Option<Internal
     // ESC [ Cy ; Cx R
     //   Cy - cursor row number (starting from 1)
     //   Cx - cursor column number (starting from 1)
-    assert!(buffer.starts_with(&[b'\x1B', b'['])); // ESC [
-    assert!(buffer.ends_with(&[b'R']));
+    assert!(buffer.starts_with(b"\x1B[")); // ESC [
+    assert!(buffer.ends_with(b"R"));
 
     let s = std::str::from_utf8(&buffer[2..buffer.len() - 1])
         .map_err(|_| could_not_parse_event_error())?;
 
     let mut split = s.split(';');
 
-    let y = next_parsed::<u16>(&mut split)? - 1;
-    let x = next_parsed::<u16>(&mut split)? - 1;
+    let y = next_parsed::<u16>(&mut split)?.saturating_sub(1);
+    let x = next_parsed::<u16>(&mut split)?.saturating_sub(1);
 
     Ok(Some(InternalEvent::CursorPosition(x, y)))
 }
 
-fn parse_modifiers(mask: u8) -> KeyModifiers {
+pub(crate) fn parse_csi_primary_device_attributes(buffer: &[u8]) -> Result<Option<InternalEvent>> {
+    // ESC [ ? Pc ; Pa ; ... c
+    //   Pc - the conformance level
+    //   Pa - the supported features
+    assert!(buffer.starts_with(b"\x1B[?")); // ESC [ ?
+    assert!(buffer.ends_with(b"c"));
+
+    let s = std::str::from_utf8(&buffer[3..buffer.len() - 1])
+        .map_err(|_| could_not_parse_event_error())?;
+
+    let attributes = s
+        .split(';')
+        .map(|attribute| attribute.parse::<u16>())
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|_| could_not_parse_event_error())?;
+
+    Ok(Some(InternalEvent::PrimaryDeviceAttributes(Box::new(
+        attributes,
+    ))))
+}
+
+pub(crate) fn parse_csi_mode_report(buffer: &[u8]) -> Result<Option<InternalEvent>> {
+    // ESC [ ? Pd ; Ps $ y
+    //   Pd - the private mode
+    //   Ps - 0 not recognized, 1 set, 2 reset, 3 permanently set, 4 permanently reset
+    assert!(buffer.starts_with(b"\x1B[?")); // ESC [ ?
+    assert!(buffer.ends_with(b"$y"));
+
+    let s = std::str::from_utf8(&buffer[3..buffer.len() - 2])
+        .map_err(|_| could_not_parse_event_error())?;
+
+    let mut split = s.split(';');
+
+    let mode = next_parsed::<u16>(&mut split)?;
+    let status = next_parsed::<u8>(&mut split)?;
+
+    Ok(Some(InternalEvent::ModeReport(mode, status)))
+}
+
+pub(crate) fn parse_csi_keyboard_enhancement_flags(buffer: &[u8]) -> Result<Option<InternalEvent>> {
+    // ESC [ ? flags u
+    //   flags - the enhancements of the kitty keyboard protocol enabled
+    assert!(buffer.starts_with(b"\x1B[?")); // ESC [ ?
+    assert!(buffer.ends_with(b"u"));
+
+    let flags = std::str::from_utf8(&buffer[3..buffer.len() - 1])
+        .map_err(|_| could_not_parse_event_error())?
+        .parse::<u8>()
+        .map_err(|_| could_not_parse_event_error())?;
+
+    Ok(Some(InternalEvent::KeyboardEnhancementFlags(
+        KeyboardEnhancementFlags::from_bits_truncate(flags),
+    )))
+}
+
+pub(crate) fn parse_dcs(buffer: &[u8]) -> Result<Option<InternalEvent>> {
+    // ESC P ... ESC \
+    assert!(buffer.starts_with(b"\x1BP")); // ESC P
+
+    if !buffer.ends_with(b"\x1B\\") {
+        return Ok(None);
+    }
+    let data = &buffer[2..buffer.len() - 2];
+
+    // The XTVERSION reply, ESC P > | name ESC \
+    if let Some(version) = data.strip_prefix(b">|") {
+        return Ok(Some(InternalEvent::TerminalVersion(Box::new(
+            String::from_utf8_lossy(version).into_owned(),
+        ))));
+    }
+
+    // The XTGETTCAP reply, ESC P 1 + r Pn = Pv ESC \ or ESC P 0 + r Pn ESC \
+    //   Pn - the name of the capability encoded in hexadecimal
+    //   Pv - the value encoded in hexadecimal, missing for the boolean capabilities
+    if data.starts_with(b"0+r") {
+        return Ok(Some(InternalEvent::Capability(Box::new(None))));
+    }
+    let capability = data
+        .strip_prefix(b"1+r")
+        .ok_or_else(could_not_parse_event_error)?;
+    let mut split = capability.splitn(2, |byte| *byte == b'=');
+    let name = decode_hex(split.next().unwrap_or_default())?;
+    let value = decode_hex(split.next().unwrap_or_default())?;
+    Ok(Some(InternalEvent::Capability(Box::new(Some((
+        name, value,
+    ))))))
+}
+
+/// Decodes the hexadecimal string of the XTGETTCAP replies.
+fn decode_hex(hex: &[u8]) -> Result<String> {
+    if !hex.len().is_multiple_of(2) {
+        return Err(could_not_parse_event_error());
+    }
+    let bytes = hex
+        .chunks(2)
+        .map(|digits| {
+            std::str::from_utf8(digits)
+                .ok()
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                .ok_or_else(could_not_parse_event_error)
+        })
+        .collect::<Result<Vec<u8>>>()?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+pub(crate) fn parse_apc(buffer: &[u8]) -> Result<Option<InternalEvent>> {
+    // ESC _ ... ESC \
+    assert!(buffer.starts_with(b"\x1B_")); // ESC _
+
+    if !buffer.ends_with(b"\x1B\\") {
+        return Ok(None);
+    }
+    let data = &buffer[2..buffer.len() - 2];
+
+    // The kitty graphics reply, ESC _ G <keys> ; <message> ESC \
+    match data.strip_prefix(b"G") {
+        Some(reply) => {
+            let message = reply
+                .iter()
+                .position(|byte| *byte == b';')
+                .map_or(&[][..], |separator| &reply[separator + 1..]);
+            Ok(Some(InternalEvent::KittyGraphics(Box::new(
+                String::from_utf8_lossy(message).into_owned(),
+            ))))
+        }
+        None => Err(could_not_parse_event_error()),
+    }
+}
+
+pub(crate) fn parse_osc(buffer: &[u8]) -> Result<Option<InternalEvent>> {
+    // ESC ] ... BEL or ESC ] ... ESC \
+    assert!(buffer.starts_with(b"\x1B]")); // ESC ]
+
+    let data = if let Some(data) = buffer.strip_suffix(b"\x07") {
+        &data[2..]
+    } else if let Some(data) = buffer.strip_suffix(b"\x1B\\") {
+        &data[2..]
+    } else {
+        return Ok(None);
+    };
+
+    // The clipboard content, ESC ] 52 ; Pc ; Pd BEL
+    //   Pc - the clipboard
+    //   Pd - the content encoded in base64
+    let mut split = data.splitn(3, |byte| *byte == b';');
+    match (split.next(), split.next(), split.next()) {
+        (Some(b"52"), Some(_), Some(content)) => {
+            let content = crate::base64::decode(content).ok_or_else(could_not_parse_event_error)?;
+            Ok(Some(InternalEvent::ClipboardContent(Box::new(content))))
+        }
+        _ => Err(could_not_parse_event_error()),
+    }
+}
+
+fn parse_modifiers(mask: u16) -> KeyModifiers {
     let modifier_mask = mask.saturating_sub(1);
     let mut modifiers = KeyModifiers::empty();
     if modifier_mask & 1 != 0 {
@@ -211,22 +502,43 @@ fn parse_modifiers(mask: u8) -> KeyModifiers {
     if modifier_mask & 4 != 0 {
         modifiers |= KeyModifiers::CONTROL;
     }
+    // Meta
+    if modifier_mask & 8 != 0 {
+        modifiers |= KeyModifiers::ALT;
+    }
     modifiers
 }
 
+fn parse_key_event_kind(kind: u8) -> KeyEventKind {
+    match kind {
+        2 => KeyEventKind::Repeat,
+        3 => KeyEventKind::Release,
+        _ => KeyEventKind::Press,
+    }
+}
+
+/// Parses `<modifiers>[:<event type>]`, the event type is only sent with
+/// `KeyboardEnhancementFlags::REPORT_EVENT_TYPES`.
+fn modifier_and_kind_parsed(field: &str) -> Result<(u16, KeyEventKind)> {
+    let mut split = field.split(':');
+    let modifier_mask = next_parsed::<u16>(&mut split)?;
+    let kind = next_parsed::<u8>(&mut split).map_or(KeyEventKind::Press, parse_key_event_kind);
+    Ok((modifier_mask, kind))
+}
+
 pub(crate) fn parse_csi_modifier_key_code(buffer: &[u8]) -> Result<Option<InternalEvent>> {
-    assert!(buffer.starts_with(&[b'\x1B', b'['])); // ESC [
+    assert!(buffer.starts_with(b"\x1B[")); // ESC [
 
-    let modifier_mask = buffer[buffer.len() - 2];
+    // ESC [ 1 ; <modifiers> [: <event type>] <key> or ESC [ <modifiers> <key>
+    let s = std::str::from_utf8(&buffer[2..buffer.len() - 1])
+        .map_err(|_| could_not_parse_event_error())?;
+    let (modifier_mask, kind) = modifier_and_kind_parsed(s.rsplit(';').next().unwrap_or_default())?;
     let key = buffer[buffer.len() - 1];
 
     let modifiers = parse_modifiers(modifier_mask);
 
     let keycode = match key {
-        b'A' => KeyCode::Up,
-        b'B' => KeyCode::Down,
-        b'C' => KeyCode::Right,
-        b'D' => KeyCode::Left,
+        b'A'..=b'D' => parse_arrow_key_code(key).unwrap(),
         b'F' => KeyCode::End,
         b'H' => KeyCode::Home,
         b'P' => KeyCode::F(1),
@@ -236,14 +548,176 @@ pub(crate) fn parse_csi_modifier_key_code(buffer: &[u8]) -> Result<Option<Intern
         _ => return Err(could_not_parse_event_error()),
     };
 
-    let input_event = Event::Key(KeyEvent::new(keycode, modifiers));
+    let input_event = Event::Key(KeyEvent::new_with_kind(keycode, modifiers, kind));
+
+    Ok(Some(InternalEvent::Event(input_event)))
+}
+
+/// Returns the modifiers of the kitty keyboard protocol, Super, Hyper and Meta have their own
+/// bits there. The Caps Lock and Num Lock bits are in the state of the key, see
+/// `parse_kitty_lock_state`.
+fn parse_kitty_modifiers(mask: u16) -> KeyModifiers {
+    let modifier_mask = mask.saturating_sub(1);
+    let mut modifiers = parse_modifiers((modifier_mask & 0b111) + 1);
+    if modifier_mask & 8 != 0 {
+        modifiers |= KeyModifiers::SUPER;
+    }
+    if modifier_mask & 16 != 0 {
+        modifiers |= KeyModifiers::HYPER;
+    }
+    if modifier_mask & 32 != 0 {
+        modifiers |= KeyModifiers::META;
+    }
+    modifiers
+}
+
+/// Returns the Caps Lock and Num Lock state of the modifiers of the kitty keyboard protocol.
+fn parse_kitty_lock_state(mask: u16) -> KeyEventState {
+    let modifier_mask = mask.saturating_sub(1);
+    let mut state = KeyEventState::NONE;
+    if modifier_mask & 64 != 0 {
+        state |= KeyEventState::CAPS_LOCK;
+    }
+    if modifier_mask & 128 != 0 {
+        state |= KeyEventState::NUM_LOCK;
+    }
+    state
+}
+
+/// Returns the key of a code point of the kitty keyboard protocol which isn't a character.
+fn kitty_functional_key_code(codepoint: u32) -> Option<KeyCode> {
+    match codepoint {
+        9 => Some(KeyCode::Tab),
+        13 => Some(KeyCode::Enter),
+        27 => Some(KeyCode::Esc),
+        127 => Some(KeyCode::Backspace),
+        // F13-F35, in the Private Use Area
+        57376..=57398 => Some(KeyCode::F((codepoint - 57376 + 13) as u8)),
+        57428 => Some(KeyCode::Media(MediaKeyCode::Play)),
+        57429 => Some(KeyCode::Media(MediaKeyCode::Pause)),
+        57430 => Some(KeyCode::Media(MediaKeyCode::PlayPause)),
+        57431 => Some(KeyCode::Media(MediaKeyCode::Reverse)),
+        57432 => Some(KeyCode::Media(MediaKeyCode::Stop)),
+        57433 => Some(KeyCode::Media(MediaKeyCode::FastForward)),
+        57434 => Some(KeyCode::Media(MediaKeyCode::Rewind)),
+        57435 => Some(KeyCode::Media(MediaKeyCode::TrackNext)),
+        57436 => Some(KeyCode::Media(MediaKeyCode::TrackPrevious)),
+        57437 => Some(KeyCode::Media(MediaKeyCode::Record)),
+        57438 => Some(KeyCode::Media(MediaKeyCode::LowerVolume)),
+        57439 => Some(KeyCode::Media(MediaKeyCode::RaiseVolume)),
+        57440 => Some(KeyCode::Media(MediaKeyCode::MuteVolume)),
+        57441 => Some(KeyCode::Modifier(ModifierKeyCode::LeftShift)),
+        57442 => Some(KeyCode::Modifier(ModifierKeyCode::LeftControl)),
+        57443 => Some(KeyCode::Modifier(ModifierKeyCode::LeftAlt)),
+        57444 => Some(KeyCode::Modifier(ModifierKeyCode::LeftSuper)),
+        57445 => Some(KeyCode::Modifier(ModifierKeyCode::LeftHyper)),
+        57446 => Some(KeyCode::Modifier(ModifierKeyCode::LeftMeta)),
+        57447 => Some(KeyCode::Modifier(ModifierKeyCode::RightShift)),
+        57448 => Some(KeyCode::Modifier(ModifierKeyCode::RightControl)),
+        57449 => Some(KeyCode::Modifier(ModifierKeyCode::RightAlt)),
+        57450 => Some(KeyCode::Modifier(ModifierKeyCode::RightSuper)),
+        57451 => Some(KeyCode::Modifier(ModifierKeyCode::RightHyper)),
+        57452 => Some(KeyCode::Modifier(ModifierKeyCode::RightMeta)),
+        57453 => Some(KeyCode::Modifier(ModifierKeyCode::IsoLevel3Shift)),
+        57454 => Some(KeyCode::Modifier(ModifierKeyCode::IsoLevel5Shift)),
+        _ => None,
+    }
+}
+
+/// Returns the key of a code point of the kitty keyboard protocol on the keypad.
+fn kitty_keypad_key_code(codepoint: u32) -> Option<KeyCode> {
+    match codepoint {
+        // KP_0-KP_9
+        57399..=57408 => std::char::from_digit(codepoint - 57399, 10).map(KeyCode::Char),
+        57409 => Some(KeyCode::Char('.')),
+        57410 => Some(KeyCode::Char('/')),
+        57411 => Some(KeyCode::Char('*')),
+        57412 => Some(KeyCode::Char('-')),
+        57413 => Some(KeyCode::Char('+')),
+        57414 => Some(KeyCode::Enter),
+        57415 => Some(KeyCode::Char('=')),
+        57416 => Some(KeyCode::Char(',')),
+        57417 => Some(KeyCode::Left),
+        57418 => Some(KeyCode::Right),
+        57419 => Some(KeyCode::Up),
+        57420 => Some(KeyCode::Down),
+        57421 => Some(KeyCode::PageUp),
+        57422 => Some(KeyCode::PageDown),
+        57423 => Some(KeyCode::Home),
+        57424 => Some(KeyCode::End),
+        57425 => Some(KeyCode::Insert),
+        57426 => Some(KeyCode::Delete),
+        _ => None,
+    }
+}
+
+pub(crate) fn parse_csi_u_encoded_key_code(buffer: &[u8]) -> Result<Option<InternalEvent>> {
+    // ESC [ <code point> [: <shifted key> [: <base layout key>]] [; <modifiers> [: <event>] [; <text>]] u
+    //   The kitty keyboard protocol, the code point is the key without Shift
+    assert!(buffer.starts_with(b"\x1B[")); // ESC [
+    assert!(buffer.ends_with(b"u"));
+
+    let s = std::str::from_utf8(&buffer[2..buffer.len() - 1])
+        .map_err(|_| could_not_parse_event_error())?;
+    let mut split = s.split(';');
+
+    let mut codes = split.next().unwrap_or_default().split(':');
+    let codepoint = next_parsed::<u32>(&mut codes)?;
+    // Sent with `KeyboardEnhancementFlags::REPORT_ALTERNATE_KEYS`
+    let shifted = next_parsed::<u32>(&mut codes)
+        .ok()
+        .and_then(std::char::from_u32);
+
+    // The field is empty if there's text but no modifiers
+    let mut state = KeyEventState::NONE;
+    let (modifiers, kind) = match split.next() {
+        Some(field) if !field.is_empty() => {
+            let (modifier_mask, kind) = modifier_and_kind_parsed(field)?;
+            state |= parse_kitty_lock_state(modifier_mask);
+            (parse_kitty_modifiers(modifier_mask), kind)
+        }
+        _ => (KeyModifiers::NONE, KeyEventKind::Press),
+    };
+
+    let keycode = match kitty_functional_key_code(codepoint) {
+        Some(KeyCode::Tab) if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+        Some(keycode) => keycode,
+        None => {
+            if let Some(keycode) = kitty_keypad_key_code(codepoint) {
+                state |= KeyEventState::KEYPAD;
+                keycode
+            } else {
+                let c = std::char::from_u32(codepoint).ok_or_else(could_not_parse_event_error)?;
+                match shifted {
+                    Some(shifted) if modifiers.contains(KeyModifiers::SHIFT) => {
+                        KeyCode::Char(shifted)
+                    }
+                    _ => KeyCode::Char(c),
+                }
+            }
+        }
+    };
+
+    // The letters are uppercase with Shift, like in the other encodings
+    let input_event = Event::Key(
+        KeyEvent::new_with_kind_and_state(keycode, modifiers, kind, state).normalize_case(),
+    );
 
     Ok(Some(InternalEvent::Event(input_event)))
 }
 
 pub(crate) fn parse_csi_special_key_code(buffer: &[u8]) -> Result<Option<InternalEvent>> {
-    assert!(buffer.starts_with(&[b'\x1B', b'['])); // ESC [
-    assert!(buffer.ends_with(&[b'~']));
+    // ESC [ <key> ; <modifiers> [: <event type>] ~, rxvt uses the last byte for the modifiers
+    // instead
+    assert!(buffer.starts_with(b"\x1B[")); // ESC [
+
+    let mut modifiers = match buffer.last() {
+        Some(b'~') => KeyModifiers::NONE,
+        Some(b'^') => KeyModifiers::CONTROL,
+        Some(b'$') => KeyModifiers::SHIFT,
+        Some(b'@') => KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+        _ => return Err(could_not_parse_event_error()),
+    };
 
     let s = std::str::from_utf8(&buffer[2..buffer.len() - 1])
         .map_err(|_| could_not_parse_event_error())?;
@@ -252,11 +726,11 @@ pub(crate) fn parse_csi_special_key_code(buffer: &[u8]) -> Result<Option<Interna
     // This CSI sequence can be a list of semicolon-separated numbers.
     let first = next_parsed::<u8>(&mut split)?;
 
-    let modifiers = if let Ok(modifier_mask) = next_parsed::<u8>(&mut split) {
-        parse_modifiers(modifier_mask)
-    } else {
-        KeyModifiers::NONE
-    };
+    let mut kind = KeyEventKind::Press;
+    if let Some(Ok((modifier_mask, event_kind))) = split.next().map(modifier_and_kind_parsed) {
+        modifiers |= parse_modifiers(modifier_mask);
+        kind = event_kind;
+    }
 
     let keycode = match first {
         1 | 7 => KeyCode::Home,
@@ -273,7 +747,7 @@ pub(crate) fn parse_csi_special_key_code(buffer: &[u8]) -> Result<Option<Interna
         _ => return Err(could_not_parse_event_error()),
     };
 
-    let input_event = Event::Key(KeyEvent::new(keycode, modifiers));
+    let input_event = Event::Key(KeyEvent::new_with_kind(keycode, modifiers, kind));
 
     Ok(Some(InternalEvent::Event(input_event)))
 }
@@ -282,8 +756,8 @@ pub(crate) fn parse_csi_rxvt_mouse(buffer: &[u8]) -> Result<Option<InternalEvent
     // rxvt mouse encoding:
     // ESC [ Cb ; Cx ; Cy ; M
 
-    assert!(buffer.starts_with(&[b'\x1B', b'['])); // ESC [
-    assert!(buffer.ends_with(&[b'M']));
+    assert!(buffer.starts_with(b"\x1B[")); // ESC [
+    assert!(buffer.ends_with(b"M"));
 
     let s = std::str::from_utf8(&buffer[2..buffer.len() - 1])
         .map_err(|_| could_not_parse_event_error())?;
@@ -294,21 +768,22 @@ pub(crate) fn parse_csi_rxvt_mouse(buffer: &[u8]) -> Result<Option<InternalEvent
         .ok_or_else(could_not_parse_event_error)?;
     let (kind, modifiers) = parse_cb(cb)?;
 
-    let cx = next_parsed::<u16>(&mut split)? - 1;
-    let cy = next_parsed::<u16>(&mut split)? - 1;
+    let cx = next_parsed::<u16>(&mut split)?.saturating_sub(1);
+    let cy = next_parsed::<u16>(&mut split)?.saturating_sub(1);
 
     Ok(Some(InternalEvent::Event(Event::Mouse(MouseEvent {
         kind,
         column: cx,
         row: cy,
         modifiers,
+        scroll_delta: ScrollDelta::notch(kind),
     }))))
 }
 
 pub(crate) fn parse_csi_normal_mouse(buffer: &[u8]) -> Result<Option<InternalEvent>> {
     // Normal mouse encoding: ESC [ M CB Cx Cy (6 characters only).
 
-    assert!(buffer.starts_with(&[b'\x1B', b'[', b'M'])); // ESC [ M
+    assert!(buffer.starts_with(b"\x1B[M")); // ESC [ M
 
     if buffer.len() < 6 {
         return Ok(None);
@@ -322,23 +797,24 @@ pub(crate) fn parse_csi_normal_mouse(buffer: &[u8]) -> Result<Option<InternalEve
     // See http://www.xfree86.org/current/ctlseqs.html#Mouse%20Tracking
     // The upper left character position on the terminal is denoted as 1,1.
     // Subtract 1 to keep it synced with cursor
-    let cx = u16::from(buffer[4].saturating_sub(32)) - 1;
-    let cy = u16::from(buffer[5].saturating_sub(32)) - 1;
+    let cx = u16::from(buffer[4].saturating_sub(32)).saturating_sub(1);
+    let cy = u16::from(buffer[5].saturating_sub(32)).saturating_sub(1);
 
     Ok(Some(InternalEvent::Event(Event::Mouse(MouseEvent {
         kind,
         column: cx,
         row: cy,
         modifiers,
+        scroll_delta: ScrollDelta::notch(kind),
     }))))
 }
 
 pub(crate) fn parse_csi_sgr_mouse(buffer: &[u8]) -> Result<Option<InternalEvent>> {
     // ESC [ < Cb ; Cx ; Cy (;) (M or m)
 
-    assert!(buffer.starts_with(&[b'\x1B', b'[', b'<'])); // ESC [ <
+    assert!(buffer.starts_with(b"\x1B[<")); // ESC [ <
 
-    if !buffer.ends_with(&[b'm']) && !buffer.ends_with(&[b'M']) {
+    if !buffer.ends_with(b"m") && !buffer.ends_with(b"M") {
         return Ok(None);
     }
 
@@ -352,8 +828,8 @@ pub(crate) fn parse_csi_sgr_mouse(buffer: &[u8]) -> Result<Option<InternalEvent>
     // See http://www.xfree86.org/current/ctlseqs.html#Mouse%20Tracking
     // The upper left character position on the terminal is denoted as 1,1.
     // Subtract 1 to keep it synced with cursor
-    let cx = next_parsed::<u16>(&mut split)? - 1;
-    let cy = next_parsed::<u16>(&mut split)? - 1;
+    let cx = next_parsed::<u16>(&mut split)?.saturating_sub(1);
+    let cy = next_parsed::<u16>(&mut split)?.saturating_sub(1);
 
     // When button 3 in Cb is used to represent mouse release, you can't tell which button was
     // released. SGR mode solves this by having the sequence end with a lowercase m if it's a
@@ -375,6 +851,7 @@ pub(crate) fn parse_csi_sgr_mouse(buffer: &[u8]) -> Result<Option<InternalEvent>
         column: cx,
         row: cy,
         modifiers,
+        scroll_delta: ScrollDelta::notch(kind),
     }))))
 }
 
@@ -403,9 +880,11 @@ fn parse_cb(cb: u8) -> Result<(MouseEventKind, KeyModifiers)> {
         (1, true) => MouseEventKind::Drag(MouseButton::Middle),
         (2, true) => MouseEventKind::Drag(MouseButton::Right),
         (3, false) => MouseEventKind::Up(MouseButton::Left),
-        (3, true) | (4, true) | (5, true) => MouseEventKind::Moved,
+        (3, true) | (4, true) | (5, true) | (6, true) | (7, true) => MouseEventKind::Moved,
         (4, false) => MouseEventKind::ScrollUp,
         (5, false) => MouseEventKind::ScrollDown,
+        (6, false) => MouseEventKind::ScrollLeft,
+        (7, false) => MouseEventKind::ScrollRight,
         // We do not support other buttons.
         _ => return Err(could_not_parse_event_error()),
     };
@@ -483,6 +962,26 @@ mod tests {
         assert_eq!(parse_event(b"\x1B", true).unwrap(), None,);
     }
 
+    #[test]
+    fn test_key_sequences() {
+        // F1 of the Sun console, no other test uses it
+        set_key_sequences(vec![(b"\x1B[224z".to_vec(), KeyCode::F(1).into())]);
+
+        assert_eq!(
+            parse_event(b"\x1B[224z", false).unwrap(),
+            Some(InternalEvent::Event(Event::Key(KeyCode::F(1).into()))),
+        );
+        assert_eq!(
+            parse_event(b"\x1B\x1B[224z", false).unwrap(),
+            Some(InternalEvent::Event(Event::Key(KeyEvent::new(
+                KeyCode::F(1),
+                KeyModifiers::ALT
+            )))),
+        );
+
+        set_key_sequences(Vec::new());
+    }
+
     #[test]
     fn test_alt_key() {
         assert_eq!(
@@ -556,6 +1055,7 @@ mod tests {
                 column: 29,
                 row: 39,
                 modifiers: KeyModifiers::empty(),
+                scroll_delta: ScrollDelta::default(),
             })))
         );
 
@@ -567,6 +1067,7 @@ mod tests {
                 column: 63,
                 row: 79,
                 modifiers: KeyModifiers::CONTROL,
+                scroll_delta: ScrollDelta::default(),
             })))
         );
 
@@ -578,6 +1079,7 @@ mod tests {
                 column: 19,
                 row: 9,
                 modifiers: KeyModifiers::empty(),
+                scroll_delta: ScrollDelta::default(),
             })))
         );
 
@@ -607,6 +1109,124 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_zero_coordinates() {
+        // Invalid, the coordinates start at 1, but they must not overflow
+        assert_eq!(
+            parse_csi_cursor_position(b"\x1B[0;0R").unwrap(),
+            Some(InternalEvent::CursorPosition(0, 0))
+        );
+        assert!(parse_event(b"\x1B[<0;0;0M", false).is_ok());
+        assert!(parse_event(b"\x1B[32;0;0M", false).is_ok());
+        assert!(parse_event(b"\x1B[M   ", false).is_ok());
+    }
+
+    #[test]
+    fn test_parse_csi_primary_device_attributes() {
+        assert_eq!(
+            parse_event(b"\x1B[?62;4;22c", false).unwrap(),
+            Some(InternalEvent::PrimaryDeviceAttributes(Box::new(vec![
+                62, 4, 22
+            ])))
+        );
+        assert_eq!(
+            parse_event(b"\x1B[?6c", false).unwrap(),
+            Some(InternalEvent::PrimaryDeviceAttributes(Box::new(vec![6])))
+        );
+        assert!(parse_event(b"\x1B[?c", false).is_err());
+    }
+
+    #[test]
+    fn test_parse_csi_mode_report() {
+        assert_eq!(
+            parse_event(b"\x1B[?1049;2$y", false).unwrap(),
+            Some(InternalEvent::ModeReport(1049, 2))
+        );
+        assert!(parse_event(b"\x1B[?1049$y", false).is_err());
+    }
+
+    #[test]
+    fn test_parse_csi_keyboard_enhancement_flags() {
+        assert_eq!(
+            parse_event(b"\x1B[?0u", false).unwrap(),
+            Some(InternalEvent::KeyboardEnhancementFlags(
+                KeyboardEnhancementFlags::empty()
+            ))
+        );
+        assert_eq!(
+            parse_event(b"\x1B[?5u", false).unwrap(),
+            Some(InternalEvent::KeyboardEnhancementFlags(
+                KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+                    | KeyboardEnhancementFlags::REPORT_ALTERNATE_KEYS
+            ))
+        );
+        assert!(parse_event(b"\x1B[?u", false).is_err());
+    }
+
+    #[test]
+    fn test_parse_dcs() {
+        assert_eq!(
+            parse_event(b"\x1BP>|xterm(367)\x1B\\", false).unwrap(),
+            Some(InternalEvent::TerminalVersion(Box::new(
+                "xterm(367)".to_string()
+            )))
+        );
+        assert_eq!(parse_event(b"\x1BP>|kitty", false).unwrap(), None);
+        assert!(parse_event(b"\x1BP1$r0m\x1B\\", false).is_err());
+        // XTGETTCAP, `Co=256` and `RGB`
+        assert_eq!(
+            parse_event(b"\x1BP1+r436F=323536\x1B\\", false).unwrap(),
+            Some(InternalEvent::Capability(Box::new(Some((
+                "Co".to_string(),
+                "256".to_string()
+            )))))
+        );
+        assert_eq!(
+            parse_event(b"\x1BP1+r524742\x1B\\", false).unwrap(),
+            Some(InternalEvent::Capability(Box::new(Some((
+                "RGB".to_string(),
+                String::new()
+            )))))
+        );
+        assert_eq!(
+            parse_event(b"\x1BP0+r54630A\x1B\\", false).unwrap(),
+            Some(InternalEvent::Capability(Box::new(None)))
+        );
+        assert!(parse_event(b"\x1BP1+r436\x1B\\", false).is_err());
+        // A lone `ESC P` is Alt+Shift+P
+        assert_eq!(
+            parse_event(b"\x1BP", false).unwrap(),
+            Some(InternalEvent::Event(Event::Key(KeyEvent::new(
+                KeyCode::Char('P'),
+                KeyModifiers::ALT | KeyModifiers::SHIFT
+            ))))
+        );
+    }
+
+    #[test]
+    fn test_parse_apc() {
+        assert_eq!(
+            parse_event(b"\x1B_Gi=31;ENOENT:file not found\x1B\\", false).unwrap(),
+            Some(InternalEvent::KittyGraphics(Box::new(
+                "ENOENT:file not found".to_string()
+            )))
+        );
+        assert!(parse_event(b"\x1B_x\x1B\\", false).is_err());
+    }
+
+    #[test]
+    fn test_parse_osc_clipboard_content() {
+        assert_eq!(
+            parse_event(b"\x1B]52;c;Zm9v\x07", false).unwrap(),
+            Some(InternalEvent::ClipboardContent(Box::new(b"foo".to_vec())))
+        );
+        assert_eq!(
+            parse_event(b"\x1B]52;p;\x1B\\", false).unwrap(),
+            Some(InternalEvent::ClipboardContent(Box::default()))
+        );
+        assert!(parse_event(b"\x1B]52;c;?\x07", false).is_err());
+    }
+
     #[test]
     fn test_parse_csi() {
         assert_eq!(
@@ -615,6 +1235,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_csi_focus() {
+        assert_eq!(
+            parse_event(b"\x1B[I", false).unwrap(),
+            Some(InternalEvent::Event(Event::FocusGained)),
+        );
+        assert_eq!(
+            parse_event(b"\x1B[O", false).unwrap(),
+            Some(InternalEvent::Event(Event::FocusLost)),
+        );
+    }
+
     #[test]
     fn test_parse_csi_modifier_key_code() {
         assert_eq!(
@@ -634,6 +1266,184 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_csi_u_encoded_key_code() {
+        let key = |code, modifiers| {
+            Some(InternalEvent::Event(Event::Key(KeyEvent::new(
+                code, modifiers,
+            ))))
+        };
+
+        assert_eq!(
+            parse_event(b"\x1B[97u", false).unwrap(),
+            key(KeyCode::Char('a'), KeyModifiers::NONE)
+        );
+        assert_eq!(
+            parse_event(b"\x1B[97;2u", false).unwrap(),
+            key(KeyCode::Char('A'), KeyModifiers::SHIFT)
+        );
+        assert_eq!(
+            parse_event(b"\x1B[49:33;2u", false).unwrap(),
+            key(KeyCode::Char('!'), KeyModifiers::SHIFT)
+        );
+        assert_eq!(
+            parse_event(b"\x1B[105;6u", false).unwrap(),
+            key(
+                KeyCode::Char('I'),
+                KeyModifiers::CONTROL | KeyModifiers::SHIFT
+            )
+        );
+        assert_eq!(
+            parse_event(b"\x1B[27u", false).unwrap(),
+            key(KeyCode::Esc, KeyModifiers::NONE)
+        );
+        assert_eq!(
+            parse_event(b"\x1B[9;2u", false).unwrap(),
+            key(KeyCode::BackTab, KeyModifiers::SHIFT)
+        );
+        assert_eq!(
+            parse_event(b"\x1B[13;9u", false).unwrap(),
+            key(KeyCode::Enter, KeyModifiers::SUPER)
+        );
+        assert_eq!(
+            parse_event(b"\x1B[57376;129u", false).unwrap(),
+            key(KeyCode::F(13), KeyModifiers::NONE)
+        );
+        assert_eq!(
+            parse_event(b"\x1B[97;;97u", false).unwrap(),
+            key(KeyCode::Char('a'), KeyModifiers::NONE)
+        );
+        assert_eq!(
+            parse_event(b"\x1B[57430u", false).unwrap(),
+            key(KeyCode::Media(MediaKeyCode::PlayPause), KeyModifiers::NONE)
+        );
+        assert_eq!(
+            parse_event(b"\x1B[57439;2u", false).unwrap(),
+            key(
+                KeyCode::Media(MediaKeyCode::RaiseVolume),
+                KeyModifiers::SHIFT
+            )
+        );
+        // The pressed modifier is in the modifiers as well
+        assert_eq!(
+            parse_event(b"\x1B[57443;3u", false).unwrap(),
+            key(
+                KeyCode::Modifier(ModifierKeyCode::LeftAlt),
+                KeyModifiers::ALT
+            )
+        );
+        assert_eq!(
+            parse_event(b"\x1B[57448u", false).unwrap(),
+            key(
+                KeyCode::Modifier(ModifierKeyCode::RightControl),
+                KeyModifiers::NONE
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_keypad_keys() {
+        let keypad = |code, modifiers| {
+            Some(InternalEvent::Event(Event::Key(
+                KeyEvent::new_with_kind_and_state(
+                    code,
+                    modifiers,
+                    KeyEventKind::Press,
+                    KeyEventState::KEYPAD,
+                ),
+            )))
+        };
+
+        // The kitty keyboard protocol
+        assert_eq!(
+            parse_event(b"\x1B[57399u", false).unwrap(),
+            keypad(KeyCode::Char('0'), KeyModifiers::NONE)
+        );
+        assert_eq!(
+            parse_event(b"\x1B[57414;5u", false).unwrap(),
+            keypad(KeyCode::Enter, KeyModifiers::CONTROL)
+        );
+        assert_eq!(
+            parse_event(b"\x1B[57419u", false).unwrap(),
+            keypad(KeyCode::Up, KeyModifiers::NONE)
+        );
+        // The application keypad mode
+        assert_eq!(
+            parse_event(b"\x1BOy", false).unwrap(),
+            keypad(KeyCode::Char('9'), KeyModifiers::NONE)
+        );
+        assert_eq!(
+            parse_event(b"\x1BOk", false).unwrap(),
+            keypad(KeyCode::Char('+'), KeyModifiers::NONE)
+        );
+        assert_eq!(
+            parse_event(b"\x1BOM", false).unwrap(),
+            keypad(KeyCode::Enter, KeyModifiers::NONE)
+        );
+        // Not the Enter of the main keyboard
+        assert_ne!(
+            parse_event(b"\x1BOM", false).unwrap(),
+            parse_event(b"\r", false).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_kitty_lock_state() {
+        let key = |code, modifiers, state| {
+            Some(InternalEvent::Event(Event::Key(
+                KeyEvent::new_with_kind_and_state(code, modifiers, KeyEventKind::Press, state),
+            )))
+        };
+
+        // Caps Lock is 64, Num Lock 128, added to the modifiers
+        assert_eq!(
+            parse_event(b"\x1B[97;65u", false).unwrap(),
+            key(
+                KeyCode::Char('a'),
+                KeyModifiers::NONE,
+                KeyEventState::CAPS_LOCK
+            )
+        );
+        assert_eq!(
+            parse_event(b"\x1B[57400;133u", false).unwrap(),
+            key(
+                KeyCode::Char('1'),
+                KeyModifiers::CONTROL,
+                KeyEventState::KEYPAD | KeyEventState::NUM_LOCK
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_key_event_kind() {
+        let key = |code, modifiers, kind| {
+            Some(InternalEvent::Event(Event::Key(KeyEvent::new_with_kind(
+                code, modifiers, kind,
+            ))))
+        };
+
+        assert_eq!(
+            parse_event(b"\x1B[97;1:1u", false).unwrap(),
+            key(KeyCode::Char('a'), KeyModifiers::NONE, KeyEventKind::Press)
+        );
+        assert_eq!(
+            parse_event(b"\x1B[97;5:2u", false).unwrap(),
+            key(
+                KeyCode::Char('a'),
+                KeyModifiers::CONTROL,
+                KeyEventKind::Repeat
+            )
+        );
+        assert_eq!(
+            parse_event(b"\x1B[1;1:3A", false).unwrap(),
+            key(KeyCode::Up, KeyModifiers::NONE, KeyEventKind::Release)
+        );
+        assert_eq!(
+            parse_event(b"\x1B[3;2:3~", false).unwrap(),
+            key(KeyCode::Delete, KeyModifiers::SHIFT, KeyEventKind::Release)
+        );
+    }
+
     #[test]
     fn test_parse_csi_special_key_code_multiple_values_not_supported() {
         assert_eq!(
@@ -654,6 +1464,7 @@ mod tests {
                 column: 29,
                 row: 39,
                 modifiers: KeyModifiers::empty(),
+                scroll_delta: ScrollDelta::default(),
             })))
         );
     }
@@ -667,6 +1478,7 @@ mod tests {
                 column: 63,
                 row: 79,
                 modifiers: KeyModifiers::CONTROL,
+                scroll_delta: ScrollDelta::default(),
             })))
         );
     }
@@ -680,6 +1492,7 @@ mod tests {
                 column: 19,
                 row: 9,
                 modifiers: KeyModifiers::empty(),
+                scroll_delta: ScrollDelta::default(),
             })))
         );
         assert_eq!(
@@ -689,6 +1502,7 @@ mod tests {
                 column: 19,
                 row: 9,
                 modifiers: KeyModifiers::empty(),
+                scroll_delta: ScrollDelta::default(),
             })))
         );
         assert_eq!(
@@ -698,6 +1512,7 @@ mod tests {
                 column: 19,
                 row: 9,
                 modifiers: KeyModifiers::empty(),
+                scroll_delta: ScrollDelta::default(),
             })))
         );
         assert_eq!(
@@ -707,10 +1522,36 @@ mod tests {
                 column: 19,
                 row: 9,
                 modifiers: KeyModifiers::empty(),
+                scroll_delta: ScrollDelta::default(),
             })))
         );
     }
 
+    #[test]
+    fn test_parse_csi_sgr_mouse_wheel() {
+        let wheel = |buffer: &[u8]| match parse_csi_sgr_mouse(buffer).unwrap() {
+            Some(InternalEvent::Event(Event::Mouse(event))) => (event.kind, event.scroll_delta),
+            event => panic!("not a mouse event: {:?}", event),
+        };
+
+        assert_eq!(
+            wheel(b"\x1B[<64;20;10M"),
+            (MouseEventKind::ScrollUp, ScrollDelta { x: 0, y: 120 })
+        );
+        assert_eq!(
+            wheel(b"\x1B[<65;20;10M"),
+            (MouseEventKind::ScrollDown, ScrollDelta { x: 0, y: -120 })
+        );
+        assert_eq!(
+            wheel(b"\x1B[<66;20;10M"),
+            (MouseEventKind::ScrollLeft, ScrollDelta { x: -120, y: 0 })
+        );
+        assert_eq!(
+            wheel(b"\x1B[<67;20;10M"),
+            (MouseEventKind::ScrollRight, ScrollDelta { x: 120, y: 0 })
+        );
+    }
+
     #[test]
     fn test_utf8() {
         // https://www.php.net/manual/en/reference.pcre.pattern.modifiers.php#54805
@@ -776,4 +1617,128 @@ mod tests {
             )))),
         );
     }
+
+    fn parse_key(buffer: &[u8]) -> KeyEvent {
+        match parse_event(buffer, false).unwrap() {
+            Some(InternalEvent::Event(Event::Key(key_event))) => key_event,
+            event => panic!("{:?} is not a key event", event),
+        }
+    }
+
+    #[test]
+    fn test_parse_xterm_modified_keys() {
+        let ctrl_up = KeyEvent::new(KeyCode::Up, KeyModifiers::CONTROL);
+        assert_eq!(parse_key(b"\x1B[1;5A"), ctrl_up);
+        // Older xterm versions leave out the 1
+        assert_eq!(parse_key(b"\x1B[5A"), ctrl_up);
+        assert_eq!(
+            parse_key(b"\x1B[1;6P"),
+            KeyEvent::new(KeyCode::F(1), KeyModifiers::CONTROL | KeyModifiers::SHIFT)
+        );
+        assert_eq!(
+            parse_key(b"\x1B[15;3~"),
+            KeyEvent::new(KeyCode::F(5), KeyModifiers::ALT)
+        );
+        // Meta is reported as Alt, the modifier has two digits then
+        assert_eq!(
+            parse_key(b"\x1B[1;10D"),
+            KeyEvent::new(KeyCode::Left, KeyModifiers::ALT | KeyModifiers::SHIFT)
+        );
+    }
+
+    #[test]
+    fn test_parse_application_mode_keys() {
+        assert_eq!(parse_key(b"\x1BOA"), KeyCode::Up.into());
+        assert_eq!(parse_key(b"\x1BOD"), KeyCode::Left.into());
+        assert_eq!(parse_key(b"\x1BOH"), KeyCode::Home.into());
+        assert_eq!(parse_key(b"\x1BOF"), KeyCode::End.into());
+        assert_eq!(parse_key(b"\x1BOQ"), KeyCode::F(2).into());
+        assert!(parse_event(b"\x1BOz", false).is_err());
+    }
+
+    #[test]
+    fn test_parse_modified_navigation_keys() {
+        assert_eq!(
+            parse_key(b"\x1B[1;5H"),
+            KeyEvent::new(KeyCode::Home, KeyModifiers::CONTROL)
+        );
+        assert_eq!(
+            parse_key(b"\x1B[1;2F"),
+            KeyEvent::new(KeyCode::End, KeyModifiers::SHIFT)
+        );
+        assert_eq!(
+            parse_key(b"\x1B[6;3~"),
+            KeyEvent::new(KeyCode::PageDown, KeyModifiers::ALT)
+        );
+        assert_eq!(
+            parse_key(b"\x1B[2;5~"),
+            KeyEvent::new(KeyCode::Insert, KeyModifiers::CONTROL)
+        );
+        assert_eq!(
+            parse_key(b"\x1B[3;6~"),
+            KeyEvent::new(KeyCode::Delete, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
+        );
+        assert_eq!(
+            parse_key(b"\x1B[24;2~"),
+            KeyEvent::new(KeyCode::F(12), KeyModifiers::SHIFT)
+        );
+        // The modifiers in SS3 sequences
+        assert_eq!(
+            parse_key(b"\x1BO5P"),
+            KeyEvent::new(KeyCode::F(1), KeyModifiers::CONTROL)
+        );
+        assert_eq!(
+            parse_key(b"\x1BO1;3H"),
+            KeyEvent::new(KeyCode::Home, KeyModifiers::ALT)
+        );
+    }
+
+    #[test]
+    fn test_parse_rxvt_modified_keys() {
+        assert_eq!(
+            parse_key(b"\x1BOa"),
+            KeyEvent::new(KeyCode::Up, KeyModifiers::CONTROL)
+        );
+        assert_eq!(
+            parse_key(b"\x1B[c"),
+            KeyEvent::new(KeyCode::Right, KeyModifiers::SHIFT)
+        );
+        assert_eq!(
+            parse_key(b"\x1B\x1B[B"),
+            KeyEvent::new(KeyCode::Down, KeyModifiers::ALT)
+        );
+        assert_eq!(
+            parse_key(b"\x1B[2^"),
+            KeyEvent::new(KeyCode::Insert, KeyModifiers::CONTROL)
+        );
+        assert_eq!(
+            parse_key(b"\x1B[3$"),
+            KeyEvent::new(KeyCode::Delete, KeyModifiers::SHIFT)
+        );
+        assert_eq!(
+            parse_key(b"\x1B[5@"),
+            KeyEvent::new(KeyCode::PageUp, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
+        );
+        assert_eq!(
+            parse_key(b"\x1B[11^"),
+            KeyEvent::new(KeyCode::F(1), KeyModifiers::CONTROL)
+        );
+    }
+
+    #[test]
+    fn test_parse_kitty_and_tmux_modified_keys() {
+        // Both send the xterm encoding, F3 is a `~` key in kitty to not look like a cursor position
+        assert_eq!(
+            parse_key(b"\x1B[13;5~"),
+            KeyEvent::new(KeyCode::F(3), KeyModifiers::CONTROL)
+        );
+        assert_eq!(
+            parse_key(b"\x1B[1;5H"),
+            KeyEvent::new(KeyCode::Home, KeyModifiers::CONTROL)
+        );
+        assert_eq!(
+            parse_key(b"\x1B[1;3B"),
+            KeyEvent::new(KeyCode::Down, KeyModifiers::ALT)
+        );
+    }
 }
@@ -0,0 +1,150 @@
+//! The signals reported as `Event::Signal`, see `event::set_signal_events`.
+//!
+//! The handlers write the number of the signal to a socket pair, the event sources poll the
+//! receiving end with the TTY. The pair is created once and shared by all the sources.
+
+use std::{
+    collections::VecDeque,
+    io::{self, Read},
+    os::unix::{
+        io::{AsRawFd, RawFd},
+        net::UnixStream,
+    },
+};
+
+use parking_lot::Mutex;
+use signal_hook::{
+    consts::{SIGHUP, SIGINT, SIGTERM},
+    low_level, SigId,
+};
+
+use crate::{
+    event::{Event, InternalEvent, SignalKind},
+    Result,
+};
+
+/// The signals which can be reported as events.
+const SIGNALS: [libc::c_int; 3] = [SIGINT, SIGTERM, SIGHUP];
+
+static PIPE: Mutex<Option<SignalPipe>> = parking_lot::const_mutex(None);
+
+struct SignalPipe {
+    receiver: UnixStream,
+    sender: UnixStream,
+    /// The handlers while the signals are reported.
+    handlers: Vec<SigId>,
+}
+
+fn with_pipe<T>(f: impl FnOnce(&mut SignalPipe) -> Result<T>) -> Result<T> {
+    let mut pipe = PIPE.lock();
+    if pipe.is_none() {
+        let (receiver, sender) = UnixStream::pair()?;
+        receiver.set_nonblocking(true)?;
+        sender.set_nonblocking(true)?;
+        *pipe = Some(SignalPipe {
+            receiver,
+            sender,
+            handlers: Vec::new(),
+        });
+    }
+    f(pipe.as_mut().expect("the signal pipe was just created"))
+}
+
+/// Returns the file descriptor which becomes readable when a signal is reported.
+pub(crate) fn signal_fd() -> Result<RawFd> {
+    with_pipe(|pipe| Ok(pipe.receiver.as_raw_fd()))
+}
+
+/// Registers the handlers reporting the signals as events, or unregisters them.
+///
+/// The handlers of the application run again once they're unregistered. signal-hook doesn't
+/// restore the default action though, a signal without any other handler is ignored then.
+pub(crate) fn set_signal_events(enabled: bool) -> Result<()> {
+    with_pipe(|pipe| {
+        if !enabled {
+            for id in pipe.handlers.drain(..) {
+                low_level::unregister(id);
+            }
+            return Ok(());
+        }
+
+        if !pipe.handlers.is_empty() {
+            return Ok(());
+        }
+
+        let sender = pipe.sender.as_raw_fd();
+        for signal in SIGNALS {
+            // Writing to a socket is safe in a signal handler
+            let id = unsafe {
+                low_level::register(signal, move || {
+                    let byte = signal as u8;
+                    libc::write(sender, &byte as *const u8 as *const libc::c_void, 1);
+                })
+            };
+            match id {
+                Ok(id) => pipe.handlers.push(id),
+                Err(e) => {
+                    for id in pipe.handlers.drain(..) {
+                        low_level::unregister(id);
+                    }
+                    return Err(e);
+                }
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Reads all the signals reported and queues their events.
+///
+/// Everything is read at once, the receiver doesn't become readable again for the signals left
+/// behind with an edge-triggered poll (mio).
+pub(crate) fn read_signal_events(events: &mut VecDeque<InternalEvent>) -> Result<()> {
+    with_pipe(|pipe| {
+        let mut buffer = [0u8; 32];
+        loop {
+            let count = match (&pipe.receiver).read(&mut buffer) {
+                Ok(0) => return Ok(()),
+                Ok(count) => count,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+
+            events.extend(buffer[..count].iter().filter_map(|signal| {
+                let kind = match *signal as libc::c_int {
+                    SIGINT => SignalKind::Interrupt,
+                    SIGTERM => SignalKind::Terminate,
+                    SIGHUP => SignalKind::Hangup,
+                    _ => return None,
+                };
+                Some(InternalEvent::Event(Event::Signal(kind)))
+            }));
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    use signal_hook::consts::SIGHUP;
+
+    use super::set_signal_events;
+
+    #[test]
+    fn test_disabled_signals_run_the_handlers_of_the_application() {
+        let received = Arc::new(AtomicBool::new(false));
+        let id = signal_hook::flag::register(SIGHUP, received.clone()).unwrap();
+
+        set_signal_events(true).unwrap();
+        set_signal_events(false).unwrap();
+
+        assert_eq!(unsafe { libc::raise(SIGHUP) }, 0);
+        assert!(received.load(Ordering::SeqCst));
+        signal_hook::low_level::unregister(id);
+    }
+}
@@ -0,0 +1,280 @@
+//! Reads the key sequences of a terminal from its terminfo entry.
+//!
+//! Only the compiled entries are read (see `term(5)`), `infocmp` isn't needed.
+
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// The magic number of the legacy format, the numbers are 16-bit.
+const MAGIC_LEGACY: i16 = 0o432;
+/// The magic number of the extended format of ncurses 6.1, the numbers are 32-bit.
+const MAGIC_32BIT: i16 = 0o1036;
+
+/// The directories searched after `TERMINFO`, `~/.terminfo` and `TERMINFO_DIRS`.
+const SYSTEM_DIRS: &[&str] = &[
+    "/etc/terminfo",
+    "/lib/terminfo",
+    "/usr/share/terminfo",
+    "/usr/lib/terminfo",
+    // illumos and Solaris
+    "/usr/share/lib/terminfo",
+];
+
+/// The key capabilities, with their index in the strings of an entry.
+///
+/// `kf13` and above aren't read, most terminals send the modified F1-F12 keys with them.
+const KEY_CAPABILITIES: &[(usize, KeyCode)] = &[
+    (59, KeyCode::Delete),   // kdch1
+    (61, KeyCode::Down),     // kcud1
+    (66, KeyCode::F(1)),     // kf1
+    (67, KeyCode::F(10)),    // kf10
+    (68, KeyCode::F(2)),     // kf2
+    (69, KeyCode::F(3)),     // kf3
+    (70, KeyCode::F(4)),     // kf4
+    (71, KeyCode::F(5)),     // kf5
+    (72, KeyCode::F(6)),     // kf6
+    (73, KeyCode::F(7)),     // kf7
+    (74, KeyCode::F(8)),     // kf8
+    (75, KeyCode::F(9)),     // kf9
+    (76, KeyCode::Home),     // khome
+    (77, KeyCode::Insert),   // kich1
+    (79, KeyCode::Left),     // kcub1
+    (81, KeyCode::PageDown), // knp
+    (82, KeyCode::PageUp),   // kpp
+    (83, KeyCode::Right),    // kcuf1
+    (87, KeyCode::Up),       // kcuu1
+    (148, KeyCode::BackTab), // kcbt
+    (164, KeyCode::End),     // kend
+    (216, KeyCode::F(11)),   // kf11
+    (217, KeyCode::F(12)),   // kf12
+];
+
+fn invalid_entry_error() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "Invalid terminfo entry")
+}
+
+/// Returns the key sequences of the terminal, the ones which don't start with `ESC` are left
+/// out (`kbs` is `^H` on terminals sending `^?`, Ctrl+H would be Backspace).
+pub(crate) fn key_sequences(term: &str) -> io::Result<Vec<(Vec<u8>, KeyEvent)>> {
+    let entry = fs::read(find_entry(term)?)?;
+    parse_key_sequences(&entry)
+}
+
+/// Returns the directories to search for the entries, in the order of ncurses.
+fn search_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    if let Some(dir) = env::var_os("TERMINFO") {
+        dirs.push(PathBuf::from(dir));
+    }
+    if let Some(home) = env::var_os("HOME") {
+        dirs.push(Path::new(&home).join(".terminfo"));
+    }
+    if let Some(list) = env::var_os("TERMINFO_DIRS") {
+        // An empty item stands for the system directories, they're searched anyway
+        dirs.extend(env::split_paths(&list).filter(|dir| !dir.as_os_str().is_empty()));
+    }
+    dirs.extend(SYSTEM_DIRS.iter().map(PathBuf::from));
+
+    dirs
+}
+
+/// Returns the path of the compiled entry of the terminal.
+fn find_entry(term: &str) -> io::Result<PathBuf> {
+    let first = match term.as_bytes().first() {
+        Some(first) if !term.contains('/') => *first,
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid terminal name {:?}", term),
+            ))
+        }
+    };
+
+    // The entries are grouped by their first letter, or its hexadecimal value on macOS
+    let subdirs = [(first as char).to_string(), format!("{:02x}", first)];
+
+    for dir in search_dirs() {
+        for subdir in subdirs.iter() {
+            let path = dir.join(subdir).join(term);
+            if path.is_file() {
+                return Ok(path);
+            }
+        }
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("No terminfo entry for {}", term),
+    ))
+}
+
+/// Reads the 16-bit little endian number at the given offset.
+fn read_i16(entry: &[u8], offset: usize) -> io::Result<i16> {
+    match entry.get(offset..offset + 2) {
+        Some(bytes) => Ok(i16::from_le_bytes([bytes[0], bytes[1]])),
+        None => Err(invalid_entry_error()),
+    }
+}
+
+/// Reads a size of the header, they can't be negative.
+fn read_size(entry: &[u8], offset: usize) -> io::Result<usize> {
+    let size = read_i16(entry, offset)?;
+    if size < 0 {
+        return Err(invalid_entry_error());
+    }
+    Ok(size as usize)
+}
+
+fn parse_key_sequences(entry: &[u8]) -> io::Result<Vec<(Vec<u8>, KeyEvent)>> {
+    let number_size = match read_i16(entry, 0)? {
+        MAGIC_LEGACY => 2,
+        MAGIC_32BIT => 4,
+        _ => return Err(invalid_entry_error()),
+    };
+    let names_size = read_size(entry, 2)?;
+    let booleans = read_size(entry, 4)?;
+    let numbers = read_size(entry, 6)?;
+    let strings = read_size(entry, 8)?;
+    let table_size = read_size(entry, 10)?;
+
+    let mut offsets_start = 12 + names_size + booleans;
+    // The numbers start on an even byte
+    offsets_start += offsets_start % 2;
+    offsets_start += numbers * number_size;
+
+    let table_start = offsets_start + strings * 2;
+    let table = entry
+        .get(table_start..table_start + table_size)
+        .ok_or_else(invalid_entry_error)?;
+
+    let mut keys = Vec::new();
+    for (index, code) in KEY_CAPABILITIES.iter() {
+        if *index >= strings {
+            continue;
+        }
+
+        // -1 is an absent capability, -2 a cancelled one
+        let offset = read_i16(entry, offsets_start + index * 2)?;
+        if offset < 0 {
+            continue;
+        }
+
+        let sequence: Vec<u8> = table
+            .get(offset as usize..)
+            .ok_or_else(invalid_entry_error)?
+            .iter()
+            .take_while(|byte| **byte != 0)
+            .copied()
+            .collect();
+
+        if sequence.len() > 1 && sequence[0] == b'\x1B' {
+            let modifiers = match code {
+                KeyCode::BackTab => KeyModifiers::SHIFT,
+                _ => KeyModifiers::NONE,
+            };
+            keys.push((sequence, KeyEvent::new(*code, modifiers)));
+        }
+    }
+
+    Ok(keys)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_key_sequences, MAGIC_32BIT, MAGIC_LEGACY};
+    use crate::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    /// Compiles an entry with the given strings, as `tic` does.
+    fn compile(magic: i16, strings: &[(usize, &[u8])]) -> Vec<u8> {
+        let names = b"test|a test terminal\0";
+        let booleans = [1u8, 0, 1];
+        let numbers = [80i16, 24];
+        let count = strings
+            .iter()
+            .map(|(index, _)| index + 1)
+            .max()
+            .unwrap_or(0);
+
+        let mut offsets = vec![-1i16; count];
+        let mut table = Vec::new();
+        for (index, string) in strings {
+            offsets[*index] = table.len() as i16;
+            table.extend_from_slice(string);
+            table.push(0);
+        }
+
+        let mut entry = Vec::new();
+        for value in [
+            magic,
+            names.len() as i16,
+            booleans.len() as i16,
+            numbers.len() as i16,
+            count as i16,
+            table.len() as i16,
+        ]
+        .iter()
+        {
+            entry.extend_from_slice(&value.to_le_bytes());
+        }
+        entry.extend_from_slice(names);
+        entry.extend_from_slice(&booleans);
+        if entry.len() % 2 == 1 {
+            entry.push(0);
+        }
+        for number in numbers.iter() {
+            if magic == MAGIC_32BIT {
+                entry.extend_from_slice(&i32::from(*number).to_le_bytes());
+            } else {
+                entry.extend_from_slice(&number.to_le_bytes());
+            }
+        }
+        for offset in offsets {
+            entry.extend_from_slice(&offset.to_le_bytes());
+        }
+        entry.extend_from_slice(&table);
+        entry
+    }
+
+    #[test]
+    fn test_parse_key_sequences() {
+        for magic in [MAGIC_LEGACY, MAGIC_32BIT].iter() {
+            // kbs, kf1, kcuu1 and kcbt
+            let entry = compile(
+                *magic,
+                &[
+                    (55, b"\x08"),
+                    (66, b"\x1B[224z"),
+                    (87, b"\x1B[A"),
+                    (148, b"\x1B\t"),
+                ],
+            );
+
+            assert_eq!(
+                parse_key_sequences(&entry).unwrap(),
+                vec![
+                    (b"\x1B[224z".to_vec(), KeyCode::F(1).into()),
+                    (b"\x1B[A".to_vec(), KeyCode::Up.into()),
+                    (
+                        b"\x1B\t".to_vec(),
+                        KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT)
+                    ),
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_invalid_entry() {
+        assert!(parse_key_sequences(b"").is_err());
+        assert!(parse_key_sequences(b"\x1A\x02\x00\x00").is_err());
+
+        let mut entry = compile(MAGIC_LEGACY, &[(87, b"\x1B[A")]);
+        entry.truncate(entry.len() - 2);
+        assert!(parse_key_sequences(&entry).is_err());
+    }
+}
@@ -1,36 +1,9 @@
-use std::sync::{Arc, Mutex};
-
-use mio::{Registry, Token};
-
-use crate::Result;
-
-/// Allows to wake up the `mio::Poll::poll()` method.
-/// This type wraps `mio::Waker`, for more information see its documentation.
-#[derive(Clone, Debug)]
-pub(crate) struct Waker {
-    inner: Arc<Mutex<mio::Waker>>,
-}
-
-impl Waker {
-    /// Create a new `Waker`.
-    pub(crate) fn new(registry: &Registry, waker_token: Token) -> Result<Self> {
-        Ok(Self {
-            inner: Arc::new(Mutex::new(mio::Waker::new(registry, waker_token)?)),
-        })
-    }
-
-    /// Wake up the [`Poll`] associated with this `Waker`.
-    ///
-    /// Readiness is set to `Ready::readable()`.
-    pub(crate) fn wake(&self) -> Result<()> {
-        self.inner.lock().unwrap().wake()
-    }
-
-    /// Resets the state so the same waker can be reused.
-    ///
-    /// This function is not impl
-    #[allow(dead_code, clippy::clippy::unnecessary_wraps)]
-    pub(crate) fn reset(&self) -> Result<()> {
-        Ok(())
-    }
-}
+#[cfg(all(feature = "mio-poll", not(target_os = "redox")))]
+mod mio;
+#[cfg(any(not(feature = "mio-poll"), target_os = "redox"))]
+mod tty;
+
+#[cfg(all(feature = "mio-poll", not(target_os = "redox")))]
+pub(crate) use self::mio::Waker;
+#[cfg(any(not(feature = "mio-poll"), target_os = "redox"))]
+pub(crate) use self::tty::Waker;
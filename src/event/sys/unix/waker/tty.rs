@@ -0,0 +1,43 @@
+use std::{
+    io::{self, Write},
+    os::unix::net::UnixStream,
+    sync::{Arc, Mutex},
+};
+
+use crate::Result;
+
+/// Allows to wake up the `InternalEventSource::try_read()` method.
+///
+/// A byte is written to the sending half of a socket pair, the event source polls the receiving
+/// half next to the TTY.
+#[derive(Clone, Debug)]
+pub(crate) struct Waker {
+    inner: Arc<Mutex<UnixStream>>,
+}
+
+impl Waker {
+    /// Create a new `Waker`.
+    pub(crate) fn new(writer: UnixStream) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(writer)),
+        }
+    }
+
+    /// Wake up the poll associated with this `Waker`.
+    pub(crate) fn wake(&self) -> Result<()> {
+        match self.inner.lock().unwrap().write(&[0]) {
+            // The socket buffer is full, the event source is going to be woken up anyway.
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => Ok(()),
+            Err(e) => Err(e),
+            Ok(_) => Ok(()),
+        }
+    }
+
+    /// Resets the state so the same waker can be reused.
+    ///
+    /// This function is not impl
+    #[allow(dead_code, clippy::unnecessary_wraps)]
+    pub(crate) fn reset(&self) -> Result<()> {
+        Ok(())
+    }
+}
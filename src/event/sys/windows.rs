@@ -1,51 +1,109 @@
 //! This is a WINDOWS specific implementation for input related action.
 
-use std::convert::TryFrom;
-use std::sync::atomic::{AtomicU64, Ordering};
+use crossterm_winapi::Handle;
+use parking_lot::Mutex;
+use winapi::um::wincon::ENABLE_VIRTUAL_TERMINAL_INPUT;
 
-use crossterm_winapi::{ConsoleMode, Handle};
-
-use crate::Result;
+use crate::{
+    terminal::sys::{current_in_handle, set_console_mode, update_console_mode},
+    Result,
+};
 
 #[cfg(feature = "event-stream")]
 pub(crate) mod waker;
 
+pub(crate) mod coordinates;
 pub(crate) mod parse;
 pub(crate) mod poll;
+pub(crate) mod signal;
 
 const ENABLE_MOUSE_MODE: u32 = 0x0010 | 0x0080 | 0x0008;
 
-/// This is a either `u64::MAX` if it's uninitialized or a valid `u32` that stores the original
-/// console mode if it's initialized.
-static ORIGINAL_CONSOLE_MODE: AtomicU64 = AtomicU64::new(u64::MAX);
-
-/// Initializes the default console color. It will will be skipped if it has already been initialized.
-fn init_original_console_mode(original_mode: u32) {
-    let _ = ORIGINAL_CONSOLE_MODE.compare_exchange(
-        u64::MAX,
-        u64::from(original_mode),
-        Ordering::Relaxed,
-        Ordering::Relaxed,
-    );
-}
-
-/// Returns the original console color, make sure to call `init_console_color` before calling this function. Otherwise this function will panic.
-fn original_console_mode() -> u32 {
-    u32::try_from(ORIGINAL_CONSOLE_MODE.load(Ordering::Relaxed))
-        // safe unwrap, initial console color was set with `init_console_color` in `WinApiColor::new()`
-        .expect("Original console mode not set")
-}
+/// The input handles with the mouse capture enabled along with the mode they had before.
+static MODES_BEFORE_MOUSE_CAPTURE: Mutex<Vec<(Handle, u32)>> = parking_lot::const_mutex(Vec::new());
 
 pub(crate) fn enable_mouse_capture() -> Result<()> {
-    let mode = ConsoleMode::from(Handle::current_in_handle()?);
-    init_original_console_mode(mode.mode()?);
-    mode.set_mode(ENABLE_MOUSE_MODE)?;
+    let handle = current_in_handle()?;
+
+    let mut modes = MODES_BEFORE_MOUSE_CAPTURE.lock();
+    // The keys keep coming as escape sequences if the raw mode asked for them, see
+    // `event::set_virtual_terminal_input`
+    let mode = update_console_mode(&handle, |mode| {
+        ENABLE_MOUSE_MODE | (mode & ENABLE_VIRTUAL_TERMINAL_INPUT)
+    })?;
+
+    // Enabling it twice must not forget the mode the handle had before the first time
+    if !modes.iter().any(|(captured, _)| **captured == *handle) {
+        modes.push((handle, mode));
+    }
 
     Ok(())
 }
 
 pub(crate) fn disable_mouse_capture() -> Result<()> {
-    let mode = ConsoleMode::from(Handle::current_in_handle()?);
-    mode.set_mode(original_console_mode())?;
+    let handle = current_in_handle()?;
+
+    let mut modes = MODES_BEFORE_MOUSE_CAPTURE.lock();
+    if let Some(index) = modes.iter().position(|(captured, _)| **captured == *handle) {
+        set_console_mode(&handle, modes[index].1)?;
+        modes.remove(index);
+    }
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use crossterm_winapi::ConsoleMode;
+
+    use super::*;
+    use crate::terminal::sys::{
+        disable_raw_mode, enable_raw_mode, is_virtual_terminal_input_enabled,
+    };
+
+    /// The tests change the mode of the same console.
+    static CONSOLE_MODE: Mutex<()> = parking_lot::const_mutex(());
+
+    #[test]
+    fn test_mouse_capture_restores_the_mode_of_its_handle() {
+        let _console_mode = CONSOLE_MODE.lock();
+        let handle = current_in_handle().unwrap();
+        let mode = ConsoleMode::from(handle.clone()).mode().unwrap();
+
+        enable_mouse_capture().unwrap();
+        enable_mouse_capture().unwrap();
+        assert_eq!(
+            ConsoleMode::from(handle.clone()).mode().unwrap(),
+            ENABLE_MOUSE_MODE
+        );
+
+        disable_mouse_capture().unwrap();
+        assert_eq!(ConsoleMode::from(handle.clone()).mode().unwrap(), mode);
+
+        // Nothing to restore anymore
+        disable_mouse_capture().unwrap();
+        assert_eq!(ConsoleMode::from(handle).mode().unwrap(), mode);
+    }
+
+    #[test]
+    fn test_mouse_capture_keeps_the_virtual_terminal_input() {
+        let _console_mode = CONSOLE_MODE.lock();
+        crate::event::set_virtual_terminal_input(true);
+        enable_raw_mode().unwrap();
+        let virtual_terminal_input = is_virtual_terminal_input_enabled();
+
+        enable_mouse_capture().unwrap();
+        let mode = ConsoleMode::from(current_in_handle().unwrap())
+            .mode()
+            .unwrap();
+
+        disable_mouse_capture().unwrap();
+        disable_raw_mode().unwrap();
+        crate::event::set_virtual_terminal_input(false);
+
+        // The consoles before Windows 10 refuse the virtual terminal input
+        if virtual_terminal_input {
+            assert_ne!(mode & ENABLE_VIRTUAL_TERMINAL_INPUT, 0);
+        }
+    }
+}
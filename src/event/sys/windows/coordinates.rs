@@ -0,0 +1,116 @@
+//! Translates the coordinates reported by the console to the ones of the console window.
+//!
+//! The console reports mouse positions relative to the screen buffer and the size of the screen
+//! buffer when it's resized. UNIX terminals report positions relative to the top left cell of the
+//! window, which is `(0, 0)`, and the size of the window.
+
+use crossterm_winapi::{Coord, ScreenBuffer, WindowPositions};
+
+use crate::{terminal::sys::current_out_handle, Result};
+
+/// Returns the position of the console window in the screen buffer.
+pub(crate) fn window() -> Result<WindowPositions> {
+    Ok(ScreenBuffer::from(current_out_handle()?)
+        .info()?
+        .terminal_window())
+}
+
+/// Keeps track of the console window to translate the coordinates of the input events.
+///
+/// The window is retrieved from the screen buffer of the output, which can fail (redirected
+/// output, closed handle, ...). If it does, the last known window is used.
+#[derive(Debug, Default)]
+pub(crate) struct ConsoleWindow {
+    last_known: Option<WindowPositions>,
+}
+
+impl ConsoleWindow {
+    /// Returns the current window, `None` if it was never retrieved successfully.
+    pub(crate) fn current(&mut self) -> Option<WindowPositions> {
+        self.update(window())
+    }
+
+    fn update(&mut self, window: Result<WindowPositions>) -> Option<WindowPositions> {
+        if let Ok(window) = window {
+            self.last_known = Some(window);
+        }
+        self.last_known
+    }
+}
+
+/// Translates a position in the screen buffer to the `(column, row)` in the given window.
+pub(crate) fn window_position(position: Coord, window: WindowPositions) -> (u16, u16) {
+    (
+        position.x.saturating_sub(window.left).max(0) as u16,
+        position.y.saturating_sub(window.top).max(0) as u16,
+    )
+}
+
+/// Returns the `(columns, rows)` of the given window.
+pub(crate) fn window_size(window: WindowPositions) -> (u16, u16) {
+    // The window positions are inclusive, add one to count the cells like UNIX does
+    (
+        (window.right - window.left + 1) as u16,
+        (window.bottom - window.top + 1) as u16,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm_winapi::{Coord, WindowPositions};
+
+    use crate::ErrorKind;
+
+    use super::{window_position, window_size, ConsoleWindow};
+
+    /// An 80x25 window scrolled back to the middle of a 9001 rows screen buffer.
+    const SCROLLED_WINDOW: WindowPositions = WindowPositions {
+        left: 0,
+        right: 79,
+        top: 2295,
+        bottom: 2319,
+    };
+
+    #[test]
+    fn test_window_position_is_relative_to_the_window() {
+        assert_eq!(
+            window_position(Coord::new(0, 2295), SCROLLED_WINDOW),
+            (0, 0)
+        );
+        assert_eq!(
+            window_position(Coord::new(79, 2319), SCROLLED_WINDOW),
+            (79, 24)
+        );
+    }
+
+    #[test]
+    fn test_window_position_is_relative_to_a_horizontally_scrolled_window() {
+        let window = WindowPositions {
+            left: 300,
+            right: 379,
+            ..SCROLLED_WINDOW
+        };
+
+        assert_eq!(window_position(Coord::new(310, 2300), window), (10, 5));
+    }
+
+    #[test]
+    fn test_window_position_outside_of_the_window() {
+        assert_eq!(window_position(Coord::new(0, 100), SCROLLED_WINDOW), (0, 0));
+    }
+
+    #[test]
+    fn test_window_size() {
+        assert_eq!(window_size(SCROLLED_WINDOW), (80, 25));
+    }
+
+    #[test]
+    fn test_console_window_falls_back_to_the_last_known_window() {
+        let failed_query = || Err(ErrorKind::other("no screen buffer"));
+
+        let mut window = ConsoleWindow::default();
+        assert_eq!(window.update(failed_query()), None);
+        assert_eq!(window.update(Ok(SCROLLED_WINDOW)), Some(SCROLLED_WINDOW));
+        assert_eq!(window.update(failed_query()), Some(SCROLLED_WINDOW));
+    }
+}
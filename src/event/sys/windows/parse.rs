@@ -1,35 +1,48 @@
-use crossterm_winapi::{ControlKeyState, EventFlags, KeyEventRecord, MouseEvent, ScreenBuffer};
+use std::ptr;
+
+use crossterm_winapi::{ControlKeyState, EventFlags, KeyEventRecord, MouseEvent};
 use winapi::um::{
     wincon::{
-        LEFT_ALT_PRESSED, LEFT_CTRL_PRESSED, RIGHT_ALT_PRESSED, RIGHT_CTRL_PRESSED, SHIFT_PRESSED,
+        CAPSLOCK_ON, LEFT_ALT_PRESSED, LEFT_CTRL_PRESSED, NUMLOCK_ON, RIGHT_ALT_PRESSED,
+        RIGHT_CTRL_PRESSED, SCROLLLOCK_ON, SHIFT_PRESSED,
     },
+    wincontypes::ENHANCED_KEY,
     winuser::{
-        VK_BACK, VK_CONTROL, VK_DELETE, VK_DOWN, VK_END, VK_ESCAPE, VK_F1, VK_F24, VK_HOME,
-        VK_INSERT, VK_LEFT, VK_MENU, VK_NEXT, VK_PRIOR, VK_RETURN, VK_RIGHT, VK_SHIFT, VK_UP,
+        GetForegroundWindow, GetKeyboardLayout, GetWindowThreadProcessId, MapVirtualKeyExW,
+        MAPVK_VK_TO_CHAR, VK_BACK, VK_CONTROL, VK_DELETE, VK_DIVIDE, VK_DOWN, VK_END, VK_ESCAPE,
+        VK_F1, VK_F24, VK_HOME, VK_INSERT, VK_LEFT, VK_MEDIA_NEXT_TRACK, VK_MEDIA_PLAY_PAUSE,
+        VK_MEDIA_PREV_TRACK, VK_MEDIA_STOP, VK_MENU, VK_NEXT, VK_NUMPAD0, VK_PACKET, VK_PLAY,
+        VK_PRIOR, VK_RETURN, VK_RIGHT, VK_SHIFT, VK_UP, VK_VOLUME_DOWN, VK_VOLUME_MUTE,
+        VK_VOLUME_UP,
     },
 };
 
 use crate::{
-    event::{Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEventKind},
+    event::{
+        Event, KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers, MediaKeyCode,
+        MouseButton, MouseEventKind, ScrollDelta,
+    },
     Result,
 };
 
-pub(crate) fn handle_mouse_event(mouse_event: MouseEvent) -> Option<Event> {
-    if let Ok(Some(event)) = parse_mouse_event_record(&mouse_event) {
+use super::coordinates::{window_position, ConsoleWindow};
+
+pub(crate) fn handle_mouse_event(
+    mouse_event: MouseEvent,
+    window: &mut ConsoleWindow,
+) -> Option<Event> {
+    if let Ok(Some(event)) = parse_mouse_event_record(&mouse_event, window) {
         return Some(Event::Mouse(event));
     }
 
     None
 }
 
-pub(crate) fn handle_key_event(key_event: KeyEventRecord) -> Option<Event> {
-    if key_event.key_down {
-        if let Some(event) = parse_key_event_record(&key_event) {
-            return Some(Event::Key(event));
-        }
-    }
-
-    None
+pub(crate) fn handle_key_event(
+    key_event: KeyEventRecord,
+    surrogate_buffers: &mut SurrogateBuffers,
+) -> Option<Event> {
+    parse_key_event_record(&key_event, surrogate_buffers).map(Event::Key)
 }
 
 impl From<ControlKeyState> for KeyModifiers {
@@ -54,13 +67,95 @@ impl From<ControlKeyState> for KeyModifiers {
     }
 }
 
-fn parse_key_event_record(key_event: &KeyEventRecord) -> Option<KeyEvent> {
+impl From<ControlKeyState> for KeyEventState {
+    fn from(state: ControlKeyState) -> Self {
+        let mut key_state = KeyEventState::empty();
+
+        if state.has_state(CAPSLOCK_ON) {
+            key_state |= KeyEventState::CAPS_LOCK;
+        }
+        if state.has_state(NUMLOCK_ON) {
+            key_state |= KeyEventState::NUM_LOCK;
+        }
+        if state.has_state(SCROLLLOCK_ON) {
+            key_state |= KeyEventState::SCROLL_LOCK;
+        }
+
+        key_state
+    }
+}
+
+/// Turns an UTF-16 code unit of a key event into a character.
+///
+/// Characters outside of the basic multilingual plane (emoji, ...) are reported as a high and a
+/// low surrogate in two key events. The high surrogate is kept until the low one arrives.
+fn char_from_utf16(unit: u16, surrogate_buffer: &mut Option<u16>) -> Option<char> {
+    if (0xD800..=0xDBFF).contains(&unit) {
+        *surrogate_buffer = Some(unit);
+        return None;
+    }
+
+    match (surrogate_buffer.take(), unit) {
+        (Some(high), 0xDC00..=0xDFFF) => std::char::decode_utf16([high, unit].iter().copied())
+            .next()?
+            .ok(),
+        _ => std::char::from_u32(unit as u32),
+    }
+}
+
+/// The high surrogates waiting for their low surrogate, of the presses and of the releases.
+///
+/// An IME sends the press and the release of both halves, the release of the high surrogate can
+/// come before the press of the low one.
+#[derive(Debug, Default)]
+pub(crate) struct SurrogateBuffers {
+    press: Option<u16>,
+    release: Option<u16>,
+}
+
+impl SurrogateBuffers {
+    /// Turns an UTF-16 code unit of a press or a release into a character, see
+    /// `char_from_utf16`.
+    pub(crate) fn char_from_utf16(&mut self, unit: u16, key_down: bool) -> Option<char> {
+        let buffer = if key_down {
+            &mut self.press
+        } else {
+            &mut self.release
+        };
+        char_from_utf16(unit, buffer)
+    }
+}
+
+fn parse_key_event_record(
+    key_event: &KeyEventRecord,
+    surrogate_buffers: &mut SurrogateBuffers,
+) -> Option<KeyEvent> {
     let modifiers = KeyModifiers::from(key_event.control_key_state);
+    // The repeats of a held key are key down records as well
+    let kind = if key_event.key_down {
+        KeyEventKind::Press
+    } else {
+        KeyEventKind::Release
+    };
 
     let key_code = key_event.virtual_key_code as i32;
 
+    // The press of a dead key has no character, the accent is combined with the next key. Its
+    // release carries the accent though, it would be reported without a press.
+    if !key_event.key_down && is_dead_key(key_event.virtual_key_code) {
+        return None;
+    }
+
     let parse_result = match key_code {
         VK_SHIFT | VK_CONTROL | VK_MENU => None,
+        // A character sent without a key, by an IME or `SendInput`. It's the character typed
+        // even with Ctrl held.
+        VK_PACKET => match key_event.u_char {
+            0 => None,
+            unit => surrogate_buffers
+                .char_from_utf16(unit, key_event.key_down)
+                .map(KeyCode::Char),
+        },
         VK_BACK => Some(KeyCode::Backspace),
         VK_ESCAPE => Some(KeyCode::Esc),
         VK_RETURN => Some(KeyCode::Enter),
@@ -75,6 +170,14 @@ fn parse_key_event_record(key_event: &KeyEventRecord) -> Option<KeyEvent> {
         VK_END => Some(KeyCode::End),
         VK_DELETE => Some(KeyCode::Delete),
         VK_INSERT => Some(KeyCode::Insert),
+        VK_PLAY => Some(KeyCode::Media(MediaKeyCode::Play)),
+        VK_MEDIA_PLAY_PAUSE => Some(KeyCode::Media(MediaKeyCode::PlayPause)),
+        VK_MEDIA_STOP => Some(KeyCode::Media(MediaKeyCode::Stop)),
+        VK_MEDIA_NEXT_TRACK => Some(KeyCode::Media(MediaKeyCode::TrackNext)),
+        VK_MEDIA_PREV_TRACK => Some(KeyCode::Media(MediaKeyCode::TrackPrevious)),
+        VK_VOLUME_DOWN => Some(KeyCode::Media(MediaKeyCode::LowerVolume)),
+        VK_VOLUME_UP => Some(KeyCode::Media(MediaKeyCode::RaiseVolume)),
+        VK_VOLUME_MUTE => Some(KeyCode::Media(MediaKeyCode::MuteVolume)),
         _ => {
             // Modifier Keys (Ctrl, Alt, Shift) Support
             let character_raw = key_event.u_char;
@@ -92,8 +195,8 @@ fn parse_key_event_record(key_event: &KeyEventRecord) -> Option<KeyEvent> {
                 {
                     // we need to do some parsing
                     character = match character_raw as u8 {
-                        c @ b'\x01'..=b'\x1A' => (c as u8 - 0x1 + b'a') as char,
-                        c @ b'\x1C'..=b'\x1F' => (c as u8 - 0x1C + b'4') as char,
+                        c @ b'\x01'..=b'\x1A' => (c - 0x1 + b'a') as char,
+                        c @ b'\x1C'..=b'\x1F' => (c - 0x1C + b'4') as char,
                         _ => return None,
                     }
                 }
@@ -106,30 +209,66 @@ fn parse_key_event_record(key_event: &KeyEventRecord) -> Option<KeyEvent> {
                     Some(KeyCode::Char(character))
                 }
             } else {
-                std::char::from_u32(character_raw as u32).map(KeyCode::Char)
+                surrogate_buffers
+                    .char_from_utf16(character_raw, key_event.key_down)
+                    .map(KeyCode::Char)
             }
         }
     };
 
+    let mut state = KeyEventState::from(key_event.control_key_state);
+    if is_keypad_key(key_event) {
+        state |= KeyEventState::KEYPAD;
+    }
+
     if let Some(key_code) = parse_result {
-        return Some(KeyEvent::new(key_code, modifiers));
+        return Some(KeyEvent::new_with_kind_and_state(
+            key_code, modifiers, kind, state,
+        ));
     }
 
     None
 }
 
-// The 'y' position of a mouse event or resize event is not relative to the window but absolute to screen buffer.
-// This means that when the mouse cursor is at the top left it will be x: 0, y: 2295 (e.g. y = number of cells conting from the absolute buffer height) instead of relative x: 0, y: 0 to the window.
-pub fn parse_relative_y(y: i16) -> Result<i16> {
-    let window_size = ScreenBuffer::current()?.info()?.terminal_window();
-    Ok(y - window_size.top)
+/// Returns whether the key is on the numeric keypad.
+fn is_keypad_key(key_event: &KeyEventRecord) -> bool {
+    let enhanced = key_event.control_key_state.has_state(ENHANCED_KEY);
+    match key_event.virtual_key_code as i32 {
+        // The digits and the operators
+        VK_NUMPAD0..=VK_DIVIDE => true,
+        // Enter is enhanced on the keypad, the navigation keys everywhere but on the keypad
+        VK_RETURN => enhanced,
+        VK_PRIOR | VK_NEXT | VK_END | VK_HOME | VK_LEFT | VK_UP | VK_RIGHT | VK_DOWN
+        | VK_INSERT | VK_DELETE => !enhanced,
+        _ => false,
+    }
+}
+
+/// Returns whether the key is a dead key in the keyboard layout of the console.
+fn is_dead_key(virtual_key_code: u16) -> bool {
+    // The console translates the keys with the layout of the window in the foreground, the one
+    // of the current thread is used if there's no such window
+    let mapped = unsafe {
+        let thread_id = GetWindowThreadProcessId(GetForegroundWindow(), ptr::null_mut());
+        MapVirtualKeyExW(
+            virtual_key_code as u32,
+            MAPVK_VK_TO_CHAR,
+            GetKeyboardLayout(thread_id),
+        )
+    };
+    // The top bit is set for the dead keys
+    mapped & 0x8000_0000 != 0
 }
 
-fn parse_mouse_event_record(event: &MouseEvent) -> Result<Option<crate::event::MouseEvent>> {
+fn parse_mouse_event_record(
+    event: &MouseEvent,
+    window: &mut ConsoleWindow,
+) -> Result<Option<crate::event::MouseEvent>> {
     let modifiers = KeyModifiers::from(event.control_key_state);
 
-    let xpos = event.mouse_position.x as u16;
-    let ypos = parse_relative_y(event.mouse_position.y)? as u16;
+    // Without any known window, the position in the screen buffer is better than no event at all
+    let window = window.current().unwrap_or_default();
+    let (column, row) = window_position(event.mouse_position, window);
 
     let button_state = event.button_state;
     let button = if button_state.right_button() {
@@ -140,6 +279,7 @@ fn parse_mouse_event_record(event: &MouseEvent) -> Result<Option<crate::event::M
         MouseButton::Left
     };
 
+    let mut scroll_delta = ScrollDelta::default();
     let kind = match event.event_flags {
         EventFlags::PressOrRelease => {
             if button_state.release_button() {
@@ -160,22 +300,82 @@ fn parse_mouse_event_record(event: &MouseEvent) -> Result<Option<crate::event::M
             // Vertical scroll
             // from https://docs.microsoft.com/en-us/windows/console/mouse-event-record-str
             // if `button_state` is negative then the wheel was rotated backward, toward the user.
-            if button_state.scroll_down() {
-                Some(MouseEventKind::ScrollDown)
-            } else if button_state.scroll_up() {
-                Some(MouseEventKind::ScrollUp)
-            } else {
-                None
+            scroll_delta.y = wheel_delta(button_state.state());
+            match scroll_delta.y.signum() {
+                -1 => Some(MouseEventKind::ScrollDown),
+                1 => Some(MouseEventKind::ScrollUp),
+                _ => None,
+            }
+        }
+        // The second press of a double click, the clicks are counted like on UNIX
+        EventFlags::DoubleClick => Some(MouseEventKind::Down(button)),
+        EventFlags::MouseHwheeled => {
+            // Horizontal scroll, a positive delta is a rotation to the right
+            scroll_delta.x = wheel_delta(button_state.state());
+            match scroll_delta.x.signum() {
+                -1 => Some(MouseEventKind::ScrollLeft),
+                1 => Some(MouseEventKind::ScrollRight),
+                _ => None,
             }
         }
-        EventFlags::DoubleClick => None, // double click not supported by unix terminals
-        EventFlags::MouseHwheeled => None, // horizontal scroll not supported by unix terminals
     };
 
     Ok(kind.map(|kind| crate::event::MouseEvent {
         kind,
-        column: xpos,
-        row: ypos,
+        column,
+        row,
         modifiers,
+        scroll_delta,
     }))
 }
+
+/// Returns the signed distance of a wheel event, the high word of `dwButtonState`.
+fn wheel_delta(button_state: i32) -> i16 {
+    (button_state >> 16) as i16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{char_from_utf16, wheel_delta, SurrogateBuffers};
+
+    #[test]
+    fn test_wheel_delta() {
+        // `WHEEL_DELTA` forward, and a quarter of it backward from a precision touchpad
+        assert_eq!(wheel_delta(120 << 16), 120);
+        assert_eq!(wheel_delta((-30i32) << 16), -30);
+    }
+
+    #[test]
+    fn test_char_from_utf16_combines_surrogate_pairs() {
+        let mut surrogate_buffer = None;
+
+        // 🦀 is U+1F980
+        assert_eq!(char_from_utf16(0xD83E, &mut surrogate_buffer), None);
+        assert_eq!(char_from_utf16(0xDD80, &mut surrogate_buffer), Some('🦀'));
+        assert_eq!(surrogate_buffer, None);
+
+        assert_eq!(char_from_utf16(0x20AC, &mut surrogate_buffer), Some('€'));
+    }
+
+    #[test]
+    fn test_char_from_utf16_drops_unpaired_surrogates() {
+        let mut surrogate_buffer = None;
+
+        assert_eq!(char_from_utf16(0xDD80, &mut surrogate_buffer), None);
+
+        assert_eq!(char_from_utf16(0xD83E, &mut surrogate_buffer), None);
+        assert_eq!(char_from_utf16(0x20AC, &mut surrogate_buffer), Some('€'));
+        assert_eq!(surrogate_buffer, None);
+    }
+
+    #[test]
+    fn test_surrogate_pairs_of_presses_and_releases_are_kept_apart() {
+        let mut surrogate_buffers = SurrogateBuffers::default();
+
+        // The IME of the console releases the high surrogate before pressing the low one
+        assert_eq!(surrogate_buffers.char_from_utf16(0xD83E, true), None);
+        assert_eq!(surrogate_buffers.char_from_utf16(0xD83E, false), None);
+        assert_eq!(surrogate_buffers.char_from_utf16(0xDD80, true), Some('🦀'));
+        assert_eq!(surrogate_buffers.char_from_utf16(0xDD80, false), Some('🦀'));
+    }
+}
@@ -1,7 +1,6 @@
 use std::io;
 use std::time::Duration;
 
-use crossterm_winapi::Handle;
 use winapi::{
     shared::winerror::WAIT_TIMEOUT,
     um::{
@@ -10,7 +9,7 @@ use winapi::{
     },
 };
 
-use crate::Result;
+use crate::{terminal::sys::current_in_handle, Result};
 
 #[cfg(feature = "event-stream")]
 pub(crate) use super::waker::Waker;
@@ -43,7 +42,7 @@ impl WinApiPoll {
             INFINITE
         };
 
-        let console_handle = Handle::current_in_handle()?;
+        let console_handle = current_in_handle()?;
 
         #[cfg(feature = "event-stream")]
         let semaphore = self.waker.semaphore();
@@ -67,16 +66,14 @@ impl WinApiPoll {
                 Err(io::Error::new(
                     io::ErrorKind::Interrupted,
                     "Poll operation was woken up by `Waker::wake`",
-                )
-                .into())
+                ))
             }
             WAIT_TIMEOUT | WAIT_ABANDONED_0 => {
                 // timeout elapsed
                 Ok(None)
             }
             WAIT_FAILED => Err(io::Error::last_os_error()),
-            _ => Err(io::Error::new(
-                io::ErrorKind::Other,
+            _ => Err(io::Error::other(
                 "WaitForMultipleObjects returned unexpected result.",
             )),
         }
@@ -0,0 +1,75 @@
+//! The console control events reported as `Event::Signal`, see `event::set_signal_events`.
+//!
+//! The control handler runs on a thread of its own. It queues the signal and writes a menu event
+//! record, which the applications ignore, to the console input: the event source waiting for the
+//! input wakes up and finds the signal when it reads the record.
+
+use std::{
+    collections::VecDeque,
+    io, mem,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use parking_lot::Mutex;
+use winapi::{
+    shared::minwindef::DWORD,
+    um::{
+        wincon::{WriteConsoleInputW, CTRL_BREAK_EVENT, CTRL_C_EVENT},
+        wincontypes::{INPUT_RECORD, MENU_EVENT},
+    },
+};
+
+use crate::{
+    event::{Event, SignalKind},
+    terminal::sys::{current_in_handle, register_ctrl_handler},
+    Result,
+};
+
+/// Whether the control events are reported, their default action runs otherwise.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+static PENDING_SIGNALS: Mutex<VecDeque<SignalKind>> = parking_lot::const_mutex(VecDeque::new());
+
+/// Reports the control events as events, or runs their default action again.
+pub(crate) fn set_signal_events(enabled: bool) -> Result<()> {
+    ENABLED.store(enabled, Ordering::SeqCst);
+    if enabled {
+        register_ctrl_handler();
+    }
+    Ok(())
+}
+
+/// Queues the control event if it's reported, returns whether it was handled.
+///
+/// Called by the console control handler of crossterm.
+pub(crate) fn handle_ctrl_event(ctrl_type: DWORD) -> bool {
+    if !ENABLED.load(Ordering::SeqCst) {
+        return false;
+    }
+
+    let kind = match ctrl_type {
+        CTRL_C_EVENT => SignalKind::Interrupt,
+        CTRL_BREAK_EVENT => SignalKind::Break,
+        _ => return false,
+    };
+    PENDING_SIGNALS.lock().push_back(kind);
+    let _ = write_menu_event();
+    true
+}
+
+/// Returns the next signal reported, the event source calls it for every menu event record.
+pub(crate) fn take_signal_event() -> Option<Event> {
+    PENDING_SIGNALS.lock().pop_front().map(Event::Signal)
+}
+
+fn write_menu_event() -> Result<()> {
+    let handle = current_in_handle()?;
+    let mut record: INPUT_RECORD = unsafe { mem::zeroed() };
+    record.EventType = MENU_EVENT;
+
+    let mut written = 0;
+    if unsafe { WriteConsoleInputW(*handle, &record, 1, &mut written) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
@@ -1,18 +1,95 @@
-use std::time::{Duration, Instant};
+use std::{
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "test-util")]
+use parking_lot::RwLock;
+
+/// The source of the current time of the timeouts.
+///
+/// It's `Instant::now` unless a test replaces it, to check the timeouts without sleeping.
+pub(crate) trait Clock: fmt::Debug + Send + Sync {
+    /// Returns the current time.
+    fn now(&self) -> Instant;
+}
+
+/// The clock of the new timeouts, `None` is the system clock. See
+/// `VirtualTerminal::enable_virtual_time`.
+#[cfg(feature = "test-util")]
+static CLOCK: RwLock<Option<Arc<dyn Clock>>> = parking_lot::const_rwlock(None);
+
+/// Makes the new timeouts use the clock, or the system clock again with `None`.
+#[cfg(feature = "test-util")]
+pub(crate) fn set_clock(clock: Option<Arc<dyn Clock>>) {
+    *CLOCK.write() = clock;
+}
+
+/// Returns the clock of the new timeouts, `None` is the system clock.
+#[cfg(feature = "test-util")]
+fn current_clock() -> Option<Arc<dyn Clock>> {
+    CLOCK.read().clone()
+}
+
+#[cfg(not(feature = "test-util"))]
+fn current_clock() -> Option<Arc<dyn Clock>> {
+    None
+}
+
+/// Returns the current time of the system clock.
+///
+/// It's `None` in the browser, where `Instant::now` panics. Nothing waits for the input there,
+/// the time stands still for the timeouts.
+fn system_now() -> Option<Instant> {
+    #[cfg(target_arch = "wasm32")]
+    {
+        None
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        Some(Instant::now())
+    }
+}
+
+/// Returns the current time of the clock of the new timeouts, `None` if there's no clock.
+pub(crate) fn now() -> Option<Instant> {
+    match current_clock() {
+        Some(clock) => Some(clock.now()),
+        None => system_now(),
+    }
+}
 
 /// Keeps track of the elapsed time since the moment the polling started.
 #[derive(Debug, Clone)]
 pub struct PollTimeout {
     timeout: Option<Duration>,
-    start: Instant,
+    /// `None` if the system clock isn't available.
+    start: Option<Instant>,
+    /// `None` is the system clock.
+    clock: Option<Arc<dyn Clock>>,
 }
 
 impl PollTimeout {
     /// Constructs a new `PollTimeout` with the given optional `Duration`.
     pub fn new(timeout: Option<Duration>) -> PollTimeout {
+        match current_clock() {
+            Some(clock) => PollTimeout::with_clock(timeout, clock),
+            None => PollTimeout {
+                timeout,
+                start: system_now(),
+                clock: None,
+            },
+        }
+    }
+
+    /// Constructs a new `PollTimeout` measuring the time with the given clock.
+    pub(crate) fn with_clock(timeout: Option<Duration>, clock: Arc<dyn Clock>) -> PollTimeout {
         PollTimeout {
             timeout,
-            start: Instant::now(),
+            start: Some(clock.now()),
+            clock: Some(clock),
         }
     }
 
@@ -21,14 +98,14 @@ impl PollTimeout {
     /// It always returns `false` if the initial timeout was set to `None`.
     pub fn elapsed(&self) -> bool {
         self.timeout
-            .map(|timeout| self.start.elapsed() >= timeout)
+            .map(|timeout| self.start_elapsed() >= timeout)
             .unwrap_or(false)
     }
 
     /// Returns the timeout leftover (initial timeout duration - elapsed duration).
     pub fn leftover(&self) -> Option<Duration> {
         self.timeout.map(|timeout| {
-            let elapsed = self.start.elapsed();
+            let elapsed = self.start_elapsed();
 
             if elapsed >= timeout {
                 Duration::from_secs(0)
@@ -37,13 +114,59 @@ impl PollTimeout {
             }
         })
     }
+
+    /// Returns the time elapsed since the start, zero if the clock went back in time.
+    fn start_elapsed(&self) -> Duration {
+        let now = match &self.clock {
+            Some(clock) => Some(clock.now()),
+            None => system_now(),
+        };
+        match (now, self.start) {
+            (Some(now), Some(start)) => now.saturating_duration_since(start),
+            _ => Duration::from_secs(0),
+        }
+    }
+}
+
+/// A clock for the tests, it only moves when it's set.
+#[cfg(test)]
+#[derive(Debug)]
+pub(crate) struct ManualClock {
+    origin: Instant,
+    elapsed: parking_lot::Mutex<Duration>,
+}
+
+#[cfg(test)]
+impl ManualClock {
+    /// Creates a clock at one hour after its origin, it can go back in time.
+    pub(crate) fn new() -> Arc<ManualClock> {
+        Arc::new(ManualClock {
+            origin: Instant::now(),
+            elapsed: parking_lot::Mutex::new(Duration::from_secs(3600)),
+        })
+    }
+
+    pub(crate) fn advance(&self, duration: Duration) {
+        *self.elapsed.lock() += duration;
+    }
+
+    pub(crate) fn rewind(&self, duration: Duration) {
+        *self.elapsed.lock() -= duration;
+    }
+}
+
+#[cfg(test)]
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.origin + *self.elapsed.lock()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::time::{Duration, Instant};
 
-    use super::PollTimeout;
+    use super::{ManualClock, PollTimeout};
 
     #[test]
     pub fn test_timeout_without_duration_does_not_have_leftover() {
@@ -63,7 +186,8 @@ mod tests {
 
         let timeout = PollTimeout {
             timeout: Some(Duration::from_millis(TIMEOUT_MILLIS)),
-            start: Instant::now() - Duration::from_millis(2 * TIMEOUT_MILLIS),
+            start: Some(Instant::now() - Duration::from_millis(2 * TIMEOUT_MILLIS)),
+            clock: None,
         };
 
         assert!(timeout.elapsed());
@@ -75,7 +199,8 @@ mod tests {
 
         let timeout = PollTimeout {
             timeout: Some(Duration::from_millis(TIMEOUT_MILLIS)),
-            start: Instant::now() - Duration::from_millis(2 * TIMEOUT_MILLIS),
+            start: Some(Instant::now() - Duration::from_millis(2 * TIMEOUT_MILLIS)),
+            clock: None,
         };
 
         assert!(timeout.elapsed());
@@ -89,4 +214,39 @@ mod tests {
         assert!(!timeout.elapsed());
         assert!(timeout.leftover().unwrap() > Duration::from_secs(0));
     }
+
+    #[test]
+    fn test_timeout_elapses_exactly_at_the_deadline() {
+        let clock = ManualClock::new();
+        let timeout = PollTimeout::with_clock(Some(Duration::from_millis(100)), clock.clone());
+
+        clock.advance(Duration::from_millis(100) - Duration::from_nanos(1));
+        assert!(!timeout.elapsed());
+        assert_eq!(timeout.leftover(), Some(Duration::from_nanos(1)));
+
+        clock.advance(Duration::from_nanos(1));
+        assert!(timeout.elapsed());
+        assert_eq!(timeout.leftover(), Some(Duration::from_millis(0)));
+    }
+
+    #[test]
+    fn test_timeout_survives_clock_jumps() {
+        let clock = ManualClock::new();
+        let timeout = PollTimeout::with_clock(Some(Duration::from_millis(100)), clock.clone());
+
+        // Back in time, as if nothing elapsed
+        clock.rewind(Duration::from_secs(60));
+        assert!(!timeout.elapsed());
+        assert_eq!(timeout.leftover(), Some(Duration::from_millis(100)));
+
+        // Far ahead
+        clock.advance(Duration::from_secs(24 * 3600));
+        assert!(timeout.elapsed());
+        assert_eq!(timeout.leftover(), Some(Duration::from_millis(0)));
+
+        let infinite = PollTimeout::with_clock(None, clock.clone());
+        clock.advance(Duration::from_secs(24 * 3600));
+        assert!(!infinite.elapsed());
+        assert_eq!(infinite.leftover(), None);
+    }
 }
@@ -0,0 +1,85 @@
+use std::io;
+#[cfg(unix)]
+use std::{os::unix::io::RawFd, time::Duration};
+
+#[cfg(unix)]
+use tokio::io::{unix::AsyncFd, Interest};
+
+use crate::Result;
+
+#[cfg(unix)]
+use super::async_wait::{readiness, SourceFd};
+use super::{
+    async_wait::{try_read, wait_blocking},
+    Event,
+};
+
+/// Reads a single [`Event`](../enum.Event.html) without blocking the thread of the executor.
+///
+/// **This function is not available by default. You have to use the `tokio` feature flag to make
+/// it available.**
+///
+/// It's the async counterpart of [`event::read`](../fn.read.html) for the
+/// [Tokio](https://crates.io/crates/tokio) runtime and must be called within it. On UNIX the
+/// terminal is waited for in the reactor of Tokio, on Windows and with the `poll(2)` event source
+/// (without the `mio-poll` feature) a thread of the blocking pool waits for the events.
+///
+/// The future can be dropped at any time, in `tokio::select!` or `tokio::time::timeout` for
+/// example, without losing an event. The same rules as for `read` apply: don't combine it with
+/// `read`, `poll` or the [`EventStream`](../struct.EventStream.html) on another thread.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use crossterm::{event, Result};
+///
+/// async fn print_events() -> Result<()> {
+///     loop {
+///         match tokio::time::timeout(Duration::from_secs(1), event::tokio::read()).await {
+///             Ok(event) => println!("{:?}", event?),
+///             Err(_) => println!("No event within a second"),
+///         }
+///     }
+/// }
+/// ```
+pub async fn read() -> Result<Event> {
+    loop {
+        if let Some(event) = try_read()? {
+            return Ok(event);
+        }
+
+        #[cfg(unix)]
+        {
+            if let Some((fd, escape_timeout)) = readiness() {
+                wait_readable(fd, escape_timeout).await?;
+                continue;
+            }
+        }
+
+        wait_blocking(|poll| async move {
+            tokio::task::spawn_blocking(move || poll.run())
+                .await
+                .map_err(io::Error::other)?
+        })
+        .await?;
+    }
+}
+
+/// Waits until the file descriptor of the event source is readable, or the escape timeout of the
+/// ambiguous bytes elapsed.
+#[cfg(unix)]
+async fn wait_readable(fd: RawFd, escape_timeout: Option<Duration>) -> Result<()> {
+    let fd = AsyncFd::with_interest(SourceFd(fd), Interest::READABLE)?;
+    let readable = match escape_timeout {
+        Some(timeout) => match tokio::time::timeout(timeout, fd.readable()).await {
+            Ok(readable) => readable,
+            Err(_) => return Ok(()),
+        },
+        None => fd.readable().await,
+    };
+    // The event source reads the input, the next poll finds out if it's an event
+    readable?.clear_ready();
+    Ok(())
+}
@@ -0,0 +1,494 @@
+//! # Graphics
+//!
+//! The `graphics` module provides functionality to display images in the terminal, with one of
+//! the three protocols implemented by the terminals:
+//!
+//! * The [kitty graphics protocol](https://sw.kovidgoyal.net/kitty/graphics-protocol/), implemented
+//!   by kitty, WezTerm, Konsole...
+//! * The iTerm2 inline images, implemented by iTerm2, WezTerm, mintty...
+//! * Sixel, implemented by xterm, foot, mlterm, Windows Terminal...
+//!
+//! [`query_protocol`](fn.query_protocol.html) finds out which one the terminal supports on UNIX.
+//! The images are decoded by the application, an [`Image`](struct.Image.html) holds the RGBA
+//! pixels.
+//!
+//! This module is only available with the `graphics` feature.
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use std::io::stdout;
+//!
+//! use crossterm::{
+//!     execute,
+//!     graphics::{query_protocol, DisplayImage, Image},
+//!     Result,
+//! };
+//!
+//! fn main() -> Result<()> {
+//!     // A red square
+//!     let image = Image::from_rgba(16, 16, [255, 0, 0, 255].repeat(16 * 16))?;
+//!
+//!     if let Some(protocol) = query_protocol()? {
+//!         // Scaled to 4 columns and 2 rows
+//!         execute!(stdout(), DisplayImage::new(&image, protocol).size(4, 2))?;
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::{fmt, io};
+
+use crate::{base64, impl_display, Command, ErrorKind, Result};
+
+mod png;
+mod sixel;
+
+/// The number of base64 bytes sent in one kitty graphics command, the maximum of the protocol.
+const KITTY_CHUNK_LENGTH: usize = 4096;
+
+/// A kitty graphics query with a 1x1 image, the terminals implementing the protocol reply `OK`.
+#[cfg(unix)]
+const KITTY_QUERY: &str = "\x1B_Gi=31,s=1,v=1,a=q,t=d,f=24;AAAA\x1B\\";
+
+/// The protocols to display images.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GraphicsProtocol {
+    /// The kitty graphics protocol.
+    Kitty,
+    /// The iTerm2 inline images.
+    ITerm2,
+    /// Sixel graphics.
+    Sixel,
+}
+
+/// An image, the RGBA pixels row by row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    /// Creates an image from its RGBA pixels, 4 bytes per pixel row by row.
+    ///
+    /// Fails with `io::ErrorKind::InvalidInput` if the image is empty or the number of bytes
+    /// doesn't match the size.
+    pub fn from_rgba(width: u32, height: u32, pixels: Vec<u8>) -> Result<Image> {
+        let expected = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|count| count.checked_mul(4));
+
+        if width == 0 || height == 0 || expected != Some(pixels.len()) {
+            return Err(ErrorKind::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "{} bytes aren't the RGBA pixels of a {}x{} image",
+                    pixels.len(),
+                    width,
+                    height
+                ),
+            ));
+        }
+
+        Ok(Image {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    /// Returns the width in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the height in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the RGBA pixels, row by row.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Returns the RGBA value of the pixel.
+    fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let offset = (y as usize * self.width as usize + x as usize) * 4;
+        let mut pixel = [0; 4];
+        pixel.copy_from_slice(&self.pixels[offset..offset + 4]);
+        pixel
+    }
+
+    /// Returns the image scaled to the given size, with the nearest neighbor of every pixel.
+    ///
+    /// The width and the height are at least 1.
+    pub fn resize(&self, width: u32, height: u32) -> Image {
+        let (width, height) = (width.max(1), height.max(1));
+        let mut pixels = Vec::with_capacity(width as usize * height as usize * 4);
+
+        for y in 0..height {
+            let source_y = (u64::from(y) * u64::from(self.height) / u64::from(height)) as u32;
+            for x in 0..width {
+                let source_x = (u64::from(x) * u64::from(self.width) / u64::from(width)) as u32;
+                pixels.extend_from_slice(&self.pixel(source_x, source_y));
+            }
+        }
+
+        Image {
+            width,
+            height,
+            pixels,
+        }
+    }
+}
+
+/// A command that displays an image at the cursor position.
+///
+/// The image covers the cells from the cursor position, at its size in pixels unless a size in
+/// cells is given. The cursor is moved after the image, like after text.
+///
+/// # Notes
+///
+/// * Commands must be executed/queued for execution otherwise they do nothing.
+/// * The terminals scale the images themselves with the kitty and iTerm2 protocols. With sixel,
+///   the image is scaled before it's sent, which requires the size of a cell in pixels.
+/// * The Windows versions without ANSI support don't support it, nothing happens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisplayImage<'a> {
+    image: &'a Image,
+    protocol: GraphicsProtocol,
+    size: Option<(u16, u16)>,
+    cell_size: Option<(u16, u16)>,
+    id: Option<u32>,
+}
+
+impl<'a> DisplayImage<'a> {
+    /// Creates the command displaying the image with the protocol.
+    pub fn new(image: &'a Image, protocol: GraphicsProtocol) -> DisplayImage<'a> {
+        DisplayImage {
+            image,
+            protocol,
+            size: None,
+            cell_size: None,
+            id: None,
+        }
+    }
+
+    /// Scales the image to cover the given number of cells.
+    ///
+    /// The aspect ratio isn't kept. With sixel, the image is only scaled if the size of a cell is
+    /// known, see [`cell_size`](#method.cell_size).
+    pub fn size(mut self, columns: u16, rows: u16) -> DisplayImage<'a> {
+        self.size = Some((columns.max(1), rows.max(1)));
+        self
+    }
+
+    /// Sets the size of a cell in pixels `(width, height)`, used to scale the image with sixel.
+    ///
+    /// [`cell_size`](fn.cell_size.html) returns it on UNIX.
+    pub fn cell_size(mut self, width: u16, height: u16) -> DisplayImage<'a> {
+        self.cell_size = Some((width, height));
+        self
+    }
+
+    /// Sets the id of the image, for [`DeleteImage`](struct.DeleteImage.html).
+    ///
+    /// Only used with the kitty protocol.
+    pub fn id(mut self, id: u32) -> DisplayImage<'a> {
+        self.id = Some(id);
+        self
+    }
+
+    fn write_kitty(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        let encoded = base64::encode(&self.image.pixels);
+
+        // `a=T` transmits and displays, `f=32` is RGBA, `q=2` suppresses the replies
+        write!(
+            f,
+            "\x1B_Ga=T,f=32,s={},v={},q=2",
+            self.image.width, self.image.height
+        )?;
+        if let Some((columns, rows)) = self.size {
+            write!(f, ",c={},r={}", columns, rows)?;
+        }
+        if let Some(id) = self.id {
+            write!(f, ",i={}", id)?;
+        }
+
+        // The data is sent in chunks, `m=1` says that more chunks follow
+        let mut chunks = encoded.as_bytes().chunks(KITTY_CHUNK_LENGTH).peekable();
+        let mut first = true;
+        while let Some(chunk) = chunks.next() {
+            if !first {
+                f.write_str("\x1B_G")?;
+            }
+            let more = if chunks.peek().is_some() { 1 } else { 0 };
+            write!(
+                f,
+                "{}m={};{}\x1B\\",
+                if first { "," } else { "" },
+                more,
+                // Safe, base64 is ASCII
+                std::str::from_utf8(chunk).map_err(|_| fmt::Error)?
+            )?;
+            first = false;
+        }
+        Ok(())
+    }
+
+    fn write_iterm2(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        let png = png::encode(self.image);
+
+        write!(f, "\x1B]1337;File=inline=1;size={}", png.len())?;
+        if let Some((columns, rows)) = self.size {
+            write!(
+                f,
+                ";width={};height={};preserveAspectRatio=0",
+                columns, rows
+            )?;
+        }
+        write!(f, ":{}\x07", base64::encode(&png))
+    }
+
+    fn write_sixel(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        match (self.size, self.cell_size) {
+            (Some((columns, rows)), Some((width, height))) => {
+                let scaled = self.image.resize(
+                    u32::from(columns) * u32::from(width),
+                    u32::from(rows) * u32::from(height),
+                );
+                sixel::encode(&scaled, f)
+            }
+            _ => sixel::encode(self.image, f),
+        }
+    }
+}
+
+impl Command for DisplayImage<'_> {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        match self.protocol {
+            GraphicsProtocol::Kitty => self.write_kitty(f),
+            GraphicsProtocol::ITerm2 => self.write_iterm2(f),
+            GraphicsProtocol::Sixel => self.write_sixel(f),
+        }
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A command that deletes the image displayed with the given id, see
+/// [`DisplayImage::id`](struct.DisplayImage.html#method.id).
+///
+/// # Notes
+///
+/// * Commands must be executed/queued for execution otherwise they do nothing.
+/// * Only the kitty protocol can delete images. The images displayed with the other protocols
+///   are made of cells, they are replaced by the text written over them or cleared with
+///   [`Clear`](../terminal/struct.Clear.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeleteImage(pub u32);
+
+impl Command for DeleteImage {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        write!(f, "\x1B_Ga=d,d=I,i={},q=2\x1B\\", self.0)
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A command that deletes all the images displayed with the kitty protocol.
+///
+/// # Notes
+///
+/// * Commands must be executed/queued for execution otherwise they do nothing.
+/// * Only the kitty protocol can delete images, see [`DeleteImage`](struct.DeleteImage.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeleteAllImages;
+
+impl Command for DeleteAllImages {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str("\x1B_Ga=d,d=A,q=2\x1B\\")
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl_display!(for DisplayImage<'_>);
+impl_display!(for DeleteImage);
+impl_display!(for DeleteAllImages);
+
+/// Queries the best protocol to display images supported by the terminal.
+///
+/// The kitty protocol is preferred, then the iTerm2 inline images and sixel. Returns `None` if
+/// the terminal doesn't support any of them. Fails like
+/// [`query_device_attributes`](../terminal/fn.query_device_attributes.html).
+///
+/// Inside of a [multiplexer](../terminal/fn.multiplexer.html), only sixel is queried: the
+/// multiplexers don't forward the images of the other protocols, and tmux displays sixel itself.
+///
+/// Only available on UNIX.
+#[cfg(unix)]
+pub fn query_protocol() -> Result<Option<GraphicsProtocol>> {
+    use crate::event::InternalEvent;
+
+    if crate::terminal::multiplexer().is_some() {
+        let attributes = crate::terminal::query_device_attributes()?;
+        return Ok(sixel(&attributes).then_some(GraphicsProtocol::Sixel));
+    }
+
+    let (kitty, attributes) = crate::terminal::sys::query(KITTY_QUERY, |event| match event {
+        InternalEvent::KittyGraphics(message) => Some(message.as_str() == "OK"),
+        _ => None,
+    })?;
+    if kitty == Some(true) {
+        return Ok(Some(GraphicsProtocol::Kitty));
+    }
+
+    let iterm2 = |name: &str| name.starts_with("iTerm") || name.starts_with("WezTerm");
+    if std::env::var("TERM_PROGRAM").is_ok_and(|name| iterm2(&name))
+        || crate::terminal::query_version()?.is_some_and(|name| iterm2(&name))
+    {
+        return Ok(Some(GraphicsProtocol::ITerm2));
+    }
+
+    Ok(sixel(&attributes).then_some(GraphicsProtocol::Sixel))
+}
+
+/// Returns whether the device attributes of the terminal contain sixel, `4`.
+#[cfg(unix)]
+fn sixel(attributes: &[u16]) -> bool {
+    attributes.iter().skip(1).any(|attribute| *attribute == 4)
+}
+
+/// Returns the size of a cell in pixels `(width, height)`, for
+/// [`DisplayImage::cell_size`](struct.DisplayImage.html#method.cell_size).
+///
+/// Returns `None` if the terminal doesn't report its size in pixels.
+///
+/// Only available on UNIX.
+#[cfg(unix)]
+pub fn cell_size() -> Result<Option<(u16, u16)>> {
+    crate::terminal::sys::cell_size()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use crate::Command;
+
+    use super::{DeleteAllImages, DeleteImage, DisplayImage, GraphicsProtocol, Image};
+
+    fn ansi(command: impl Command) -> String {
+        let mut ansi = String::new();
+        command.write_ansi(&mut ansi).unwrap();
+        ansi
+    }
+
+    /// A 2x1 image, a red and a transparent pixel.
+    fn image() -> Image {
+        Image::from_rgba(2, 1, vec![255, 0, 0, 255, 0, 0, 0, 0]).unwrap()
+    }
+
+    #[test]
+    fn test_image_checks_its_size() {
+        for (width, height, length) in &[(2, 1, 7), (0, 0, 0), (1, 0, 0), (u32::MAX, u32::MAX, 4)] {
+            assert_eq!(
+                Image::from_rgba(*width, *height, vec![0; *length])
+                    .unwrap_err()
+                    .kind(),
+                io::ErrorKind::InvalidInput
+            );
+        }
+    }
+
+    #[test]
+    fn test_image_resize() {
+        let image = Image::from_rgba(2, 2, (0..16).collect()).unwrap();
+
+        let larger = image.resize(4, 1);
+        assert_eq!((larger.width(), larger.height()), (4, 1));
+        assert_eq!(
+            larger.pixels(),
+            &[0, 1, 2, 3, 0, 1, 2, 3, 4, 5, 6, 7, 4, 5, 6, 7][..]
+        );
+
+        let smaller = image.resize(0, 1);
+        assert_eq!(smaller.pixels(), &[0, 1, 2, 3][..]);
+    }
+
+    #[test]
+    fn test_display_image_kitty() {
+        let image = image();
+
+        assert_eq!(
+            ansi(DisplayImage::new(&image, GraphicsProtocol::Kitty)),
+            "\x1B_Ga=T,f=32,s=2,v=1,q=2,m=0;/wAA/wAAAAA=\x1B\\"
+        );
+        assert_eq!(
+            ansi(
+                DisplayImage::new(&image, GraphicsProtocol::Kitty)
+                    .size(4, 2)
+                    .id(7)
+            ),
+            "\x1B_Ga=T,f=32,s=2,v=1,q=2,c=4,r=2,i=7,m=0;/wAA/wAAAAA=\x1B\\"
+        );
+    }
+
+    #[test]
+    fn test_display_image_kitty_in_chunks() {
+        // 3072 bytes are 4096 bytes of base64
+        let image = Image::from_rgba(32, 32, vec![0; 4096]).unwrap();
+
+        let ansi = ansi(DisplayImage::new(&image, GraphicsProtocol::Kitty));
+        let chunks: Vec<&str> = ansi.split("\x1B\\").filter(|s| !s.is_empty()).collect();
+
+        assert_eq!(chunks.len(), 2);
+        assert!(chunks[0].starts_with("\x1B_Ga=T,f=32,s=32,v=32,q=2,m=1;AAAA"));
+        assert_eq!(chunks[0].len() - chunks[0].find(';').unwrap() - 1, 4096);
+        assert!(chunks[1].starts_with("\x1B_Gm=0;AAAA"));
+    }
+
+    #[test]
+    fn test_display_image_iterm2() {
+        let image = image();
+        let ansi = ansi(DisplayImage::new(&image, GraphicsProtocol::ITerm2).size(4, 2));
+
+        assert!(ansi.starts_with("\x1B]1337;File=inline=1;size="));
+        assert!(ansi.contains(";width=4;height=2;preserveAspectRatio=0:iVBORw0KGgo"));
+        assert!(ansi.ends_with('\x07'));
+    }
+
+    #[test]
+    fn test_display_image_sixel_is_scaled_with_the_cell_size() {
+        let image = image();
+
+        let unscaled = ansi(DisplayImage::new(&image, GraphicsProtocol::Sixel).size(2, 1));
+        assert!(unscaled.contains("\"1;1;2;1"));
+
+        let scaled = ansi(
+            DisplayImage::new(&image, GraphicsProtocol::Sixel)
+                .size(2, 1)
+                .cell_size(3, 6),
+        );
+        assert!(scaled.contains("\"1;1;6;6"));
+    }
+
+    #[test]
+    fn test_delete_images() {
+        assert_eq!(ansi(DeleteImage(7)), "\x1B_Ga=d,d=I,i=7,q=2\x1B\\");
+        assert_eq!(ansi(DeleteAllImages), "\x1B_Ga=d,d=A,q=2\x1B\\");
+    }
+}
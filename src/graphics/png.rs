@@ -0,0 +1,135 @@
+//! A minimal PNG encoder for the iTerm2 inline images, which can't be sent as raw pixels.
+//!
+//! The pixels aren't compressed, they are stored in deflate blocks as they are.
+
+use super::Image;
+
+const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1A\n";
+
+/// The maximum length of a stored deflate block.
+const MAX_STORED_BLOCK_LENGTH: usize = 0xFFFF;
+
+/// Encodes the image to an RGBA PNG.
+pub(super) fn encode(image: &Image) -> Vec<u8> {
+    let mut png = SIGNATURE.to_vec();
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&image.width.to_be_bytes());
+    header.extend_from_slice(&image.height.to_be_bytes());
+    // 8 bits per channel, RGBA, deflate, adaptive filtering, no interlacing
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+    write_chunk(&mut png, b"IHDR", &header);
+
+    // Every row starts with its filter type, 0 is none
+    let row_length = image.width as usize * 4;
+    let mut scanlines = Vec::with_capacity(image.pixels.len() + image.height as usize);
+    for row in image.pixels.chunks(row_length) {
+        scanlines.push(0);
+        scanlines.extend_from_slice(row);
+    }
+    write_chunk(&mut png, b"IDAT", &zlib_stored(&scanlines));
+
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Wraps the data in a zlib stream of stored deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let blocks = data.len().div_ceil(MAX_STORED_BLOCK_LENGTH).max(1);
+    let mut zlib = Vec::with_capacity(data.len() + blocks * 5 + 6);
+
+    // Deflate with a 32 KiB window, no dictionary, the fastest compression level
+    zlib.extend_from_slice(&[0x78, 0x01]);
+
+    let mut chunks = data.chunks(MAX_STORED_BLOCK_LENGTH).peekable();
+    if chunks.peek().is_none() {
+        zlib.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+    }
+    while let Some(chunk) = chunks.next() {
+        let last = chunks.peek().is_none();
+        let length = chunk.len() as u16;
+        zlib.push(last as u8);
+        zlib.extend_from_slice(&length.to_le_bytes());
+        zlib.extend_from_slice(&(!length).to_le_bytes());
+        zlib.extend_from_slice(chunk);
+    }
+
+    zlib.extend_from_slice(&adler32(data).to_be_bytes());
+    zlib
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MODULO: u32 = 65521;
+
+    let (mut a, mut b) = (1u32, 0u32);
+    // 5552 bytes is the most that can be summed before `b` overflows
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += u32::from(byte);
+            b += a;
+        }
+        a %= MODULO;
+        b %= MODULO;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{adler32, crc32, encode, zlib_stored, Image};
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+        assert_eq!(adler32(&[0xFF; 100_000]), 0x149A_302C);
+    }
+
+    #[test]
+    fn test_zlib_stored_blocks() {
+        assert_eq!(
+            zlib_stored(b"ab"),
+            [0x78, 0x01, 1, 2, 0, 0xFD, 0xFF, b'a', b'b', 0x01, 0x26, 0x00, 0xC4]
+        );
+
+        let zlib = zlib_stored(&[0; 0x10000]);
+        assert_eq!(&zlib[2..5], &[0, 0xFF, 0xFF]);
+        assert_eq!(&zlib[0x10006..0x10009], &[1, 1, 0]);
+    }
+
+    #[test]
+    fn test_encode() {
+        let image = Image::from_rgba(1, 1, vec![255, 0, 0, 255]).unwrap();
+        let png = encode(&image);
+
+        assert!(
+            png.starts_with(b"\x89PNG\r\n\x1A\n\0\0\0\x0DIHDR\0\0\0\x01\0\0\0\x01\x08\x06\0\0\0")
+        );
+        assert!(png.ends_with(b"\0\0\0\0IEND\xAE\x42\x60\x82"));
+    }
+}
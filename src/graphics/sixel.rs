@@ -0,0 +1,162 @@
+//! A sixel encoder.
+//!
+//! The colors are reduced to a palette of 6 levels of red, green and blue, the 216 colors of the
+//! 256 color palette. The pixels which are more transparent than opaque aren't drawn.
+
+use std::fmt;
+
+use super::Image;
+
+/// The number of levels of every channel in the palette.
+const LEVELS: u16 = 6;
+
+/// Writes the image as a sixel sequence.
+pub(super) fn encode(image: &Image, f: &mut impl fmt::Write) -> fmt::Result {
+    let (width, height) = (image.width as usize, image.height as usize);
+
+    // The palette index of every pixel, `None` if it's transparent
+    let indices: Vec<Option<u16>> = image
+        .pixels
+        .chunks(4)
+        .map(|pixel| match pixel {
+            [r, g, b, a] if *a >= 128 => {
+                Some(level(*r) * LEVELS * LEVELS + level(*g) * LEVELS + level(*b))
+            }
+            _ => None,
+        })
+        .collect();
+
+    // `P2=1` leaves the pixels which aren't drawn as they are, the raster attributes give the
+    // aspect ratio 1:1 and the size
+    write!(f, "\x1BP0;1;0q\"1;1;{};{}", width, height)?;
+
+    let mut used = [false; (LEVELS * LEVELS * LEVELS) as usize];
+    for index in indices.iter().flatten() {
+        used[*index as usize] = true;
+    }
+    for (index, _) in used.iter().enumerate().filter(|(_, used)| **used) {
+        let index = index as u16;
+        write!(
+            f,
+            "#{};2;{};{};{}",
+            index,
+            percent(index / (LEVELS * LEVELS)),
+            percent(index / LEVELS % LEVELS),
+            percent(index % LEVELS)
+        )?;
+    }
+
+    // Every sixel is a column of 6 pixels, the image is drawn in bands of 6 rows, one pass per
+    // color
+    let mut bits = vec![0u8; width];
+    for (band, band_top) in (0..height).step_by(6).enumerate() {
+        if band > 0 {
+            f.write_char('-')?;
+        }
+        let band_indices = &indices[band_top * width..(band_top + 6).min(height) * width];
+
+        let mut colors: Vec<u16> = band_indices.iter().flatten().copied().collect();
+        colors.sort_unstable();
+        colors.dedup();
+
+        for (pass, color) in colors.into_iter().enumerate() {
+            if pass > 0 {
+                // Back to the start of the band
+                f.write_char('$')?;
+            }
+
+            for (x, sixel) in bits.iter_mut().enumerate() {
+                *sixel = (0..6)
+                    .filter(|row| band_indices.get(row * width + x) == Some(&Some(color)))
+                    .fold(0, |sixel, row| sixel | 1 << row);
+            }
+            // The empty sixels at the end of the pass don't need to be drawn
+            let length = bits
+                .iter()
+                .rposition(|sixel| *sixel != 0)
+                .map_or(0, |x| x + 1);
+
+            write!(f, "#{}", color)?;
+            write_run_length_encoded(&bits[..length], f)?;
+        }
+    }
+
+    f.write_str("\x1B\\")
+}
+
+/// Writes the sixels, the repeated sixels as `!<count><sixel>`.
+fn write_run_length_encoded(bits: &[u8], f: &mut impl fmt::Write) -> fmt::Result {
+    let mut rest = bits;
+
+    while let Some(&sixel) = rest.first() {
+        let count = rest.iter().take_while(|other| **other == sixel).count();
+        let character = (b'?' + sixel) as char;
+
+        if count > 3 {
+            write!(f, "!{}{}", count, character)?;
+        } else {
+            for _ in 0..count {
+                f.write_char(character)?;
+            }
+        }
+        rest = &rest[count..];
+    }
+    Ok(())
+}
+
+/// Returns the nearest level of the channel in the palette.
+fn level(channel: u8) -> u16 {
+    (u16::from(channel) * (LEVELS - 1) + 127) / 255
+}
+
+/// Returns the value of the level in percent, used by the sixel color registers.
+fn percent(level: u16) -> u16 {
+    level * 100 / (LEVELS - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encode, write_run_length_encoded, Image};
+
+    fn sixel(image: &Image) -> String {
+        let mut sixel = String::new();
+        encode(image, &mut sixel).unwrap();
+        sixel
+    }
+
+    #[test]
+    fn test_run_length_encoding() {
+        let mut encoded = String::new();
+        write_run_length_encoded(&[0, 0, 0, 63, 63, 63, 63, 1], &mut encoded).unwrap();
+        assert_eq!(encoded, "???!4~@");
+    }
+
+    #[test]
+    fn test_encode() {
+        // A red pixel, a transparent pixel and a blue pixel on the first row, then a red pixel
+        let mut pixels = vec![
+            255, 0, 0, 255, 0, 0, 0, 0, 0, 0, 250, 255, //
+            255, 0, 0, 255, 0, 0, 0, 0, 0, 0, 0, 0,
+        ];
+        // A gray pixel in the second band
+        pixels.extend_from_slice(&[0; 12 * 5]);
+        pixels.extend_from_slice(&[128, 128, 128, 255, 0, 0, 0, 0, 0, 0, 0, 0]);
+        let image = Image::from_rgba(3, 8, pixels).unwrap();
+
+        assert_eq!(
+            sixel(&image),
+            "\x1BP0;1;0q\"1;1;3;8\
+             #5;2;0;0;100#129;2;60;60;60#180;2;100;0;0\
+             #5??@$#180B\
+             -#129A\
+             \x1B\\"
+        );
+    }
+
+    #[test]
+    fn test_encode_transparent_image() {
+        let image = Image::from_rgba(1, 1, vec![255, 255, 255, 0]).unwrap();
+
+        assert_eq!(sixel(&image), "\x1BP0;1;0q\"1;1;1;1\x1B\\");
+    }
+}
@@ -53,6 +53,12 @@
 //! - Module [`event`](event/index.html)
 //!   - Mouse events - [`EnableMouseCapture`](event/struct.EnableMouseCapture.html),
 //!     [`DisableMouseCapture`](event/struct.DisableMouseCapture.html)
+//!   - Focus events - [`EnableFocusChange`](event/struct.EnableFocusChange.html),
+//!     [`DisableFocusChange`](event/struct.DisableFocusChange.html)
+//!   - Pasted text - [`EnableBracketedPaste`](event/struct.EnableBracketedPaste.html),
+//!     [`DisableBracketedPaste`](event/struct.DisableBracketedPaste.html)
+//!   - Keyboard protocol - [`PushKeyboardEnhancementFlags`](event/struct.PushKeyboardEnhancementFlags.html),
+//!     [`PopKeyboardEnhancementFlags`](event/struct.PopKeyboardEnhancementFlags.html)
 //! - Module [`style`](style/index.html)
 //!   - Colors - [`SetForegroundColor`](style/struct.SetForegroundColor.html),
 //!     [`SetBackgroundColor`](style/struct.SetBackgroundColor.html),
@@ -67,8 +73,17 @@
 //!     [`SetTitle`](terminal/struct.SetTitle.html)
 //!     [`DisableLineWrap`](terminal/struct.DisableLineWrap.html)
 //!     [`EnableLineWrap`](terminal/struct.EnableLineWrap.html)
+//!     [`SetProgress`](terminal/struct.SetProgress.html)
+//!     [`ReportCwd`](terminal/struct.ReportCwd.html)
 //!   - Alternate screen - [`EnterAlternateScreen`](terminal/struct.EnterAlternateScreen.html),
 //!     [`LeaveAlternateScreen`](terminal/struct.LeaveAlternateScreen.html)
+//!   - Prompt marks - [`PromptStart`](terminal/struct.PromptStart.html),
+//!     [`CommandStart`](terminal/struct.CommandStart.html),
+//!     [`CommandExecuted`](terminal/struct.CommandExecuted.html),
+//!     [`CommandFinished`](terminal/struct.CommandFinished.html)
+//!
+//! On Windows, the [`conpty`](conpty/index.html) module hosts other console programs in a pseudo
+//! console.
 //!
 //! ### Command Execution
 //!
@@ -232,26 +247,62 @@
 //! [stderr]: https://doc.rust-lang.org/std/io/fn.stderr.html
 //! [flush]: https://doc.rust-lang.org/std/io/trait.Write.html#tymethod.flush
 
+#[cfg(feature = "command-sink")]
+pub use crate::command::CommandSink;
 pub use crate::{
-    command::{Command, ExecutableCommand, QueueableCommand},
+    command::{Command, DynCommand, ExecutableCommand, QueueableCommand},
     error::{ErrorKind, Result},
 };
 
+/// A module to reduce the decorations for accessibility.
+pub mod accessibility;
+/// A module to access the clipboard of the terminal.
+pub mod clipboard;
+/// A module easing the migration from other terminal libraries.
+#[cfg(feature = "termion-compat")]
+pub mod compat;
+/// A module to host console programs in a pseudo console.
+#[cfg(windows)]
+pub mod conpty;
 /// A module to work with the terminal cursor
 pub mod cursor;
+/// A module to inspect the state of the terminal when debugging.
+pub mod debug;
 /// A module to read events.
 pub mod event;
+/// A module to display images.
+#[cfg(feature = "graphics")]
+pub mod graphics;
+/// A module to draw lines and borders.
+pub mod line_drawing;
+/// A module to read the lines typed by the user.
+#[cfg(feature = "prompt")]
+pub mod prompt;
 /// A module to apply attributes and colors on your text.
 pub mod style;
 /// A module to work with the terminal.
 pub mod terminal;
+/// A module with an in-memory terminal for tests.
+#[cfg(feature = "test-util")]
+pub mod test_util;
+/// A module to measure, wrap and truncate text to the columns of the terminal.
+pub mod text;
 
-/// A module to query if the current instance is a tty.
+/// A module to query if the current instance is a tty and to drive other terminal devices.
 pub mod tty;
+/// A module to connect to a terminal running in the browser.
+#[cfg(target_arch = "wasm32")]
+pub mod wasm;
+
+#[cfg(all(target_arch = "wasm32", feature = "event-stream"))]
+compile_error!(
+    "the `event-stream`, `tokio` and `async-std` features aren't supported on `wasm32`, see the `wasm` module"
+);
 
 #[cfg(windows)]
 /// A module that exposes one function to check if the current terminal supports ansi sequences.
 pub mod ansi_support;
+mod base64;
 mod command;
 mod error;
 pub(crate) mod macros;
@@ -0,0 +1,266 @@
+//! # Line drawing
+//!
+//! The box-drawing characters, for borders and tables, and a portable way to print them.
+//!
+//! Most terminals render the Unicode box-drawing characters, but not a terminal whose locale
+//! isn't UTF-8 nor the hardware terminals and their emulations (`TERM=vt100`, ...). Those
+//! draw the lines of the DEC Special Graphics character set, selected with `ESC ( 0`.
+//! [`PrintLines`](struct.PrintLines.html) prints the text with the box-drawing characters in the
+//! way the terminal renders them, chosen by [`line_drawing`](fn.line_drawing.html).
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use std::io::stdout;
+//!
+//! use crossterm::{
+//!     execute,
+//!     line_drawing::{PrintLines, HORIZONTAL, TOP_LEFT, TOP_RIGHT},
+//!     Result,
+//! };
+//!
+//! fn main() -> Result<()> {
+//!     let border = format!("{}{}{}", TOP_LEFT, HORIZONTAL.to_string().repeat(10), TOP_RIGHT);
+//!     execute!(stdout(), PrintLines(border))
+//! }
+//! ```
+
+use std::{
+    env, fmt,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(windows)]
+use crate::Result;
+use crate::{impl_display, Command};
+
+/// `─`
+pub const HORIZONTAL: char = '─';
+/// `│`
+pub const VERTICAL: char = '│';
+/// `┌`
+pub const TOP_LEFT: char = '┌';
+/// `┐`
+pub const TOP_RIGHT: char = '┐';
+/// `└`
+pub const BOTTOM_LEFT: char = '└';
+/// `┘`
+pub const BOTTOM_RIGHT: char = '┘';
+/// `├`
+pub const LEFT_TEE: char = '├';
+/// `┤`
+pub const RIGHT_TEE: char = '┤';
+/// `┬`
+pub const TOP_TEE: char = '┬';
+/// `┴`
+pub const BOTTOM_TEE: char = '┴';
+/// `┼`
+pub const CROSS: char = '┼';
+
+/// Selects the DEC Special Graphics character set.
+const DEC_SPECIAL_GRAPHICS: &str = "\x1B(0";
+/// Selects the ASCII character set again.
+const ASCII: &str = "\x1B(B";
+
+/// How the box-drawing characters are printed, see [`line_drawing`](fn.line_drawing.html).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LineDrawing {
+    /// The Unicode characters are printed as is.
+    Unicode,
+    /// The characters are printed in the DEC Special Graphics character set.
+    DecSpecialGraphics,
+}
+
+/// The mode wasn't set yet, it's detected on first use.
+const UNSET: u8 = 0;
+const UNICODE: u8 = 1;
+const DEC_SPECIAL_GRAPHICS_MODE: u8 = 2;
+
+static LINE_DRAWING: AtomicU8 = AtomicU8::new(UNSET);
+
+/// Sets how the box-drawing characters are printed, it overrides the detection.
+pub fn set_line_drawing(line_drawing: LineDrawing) {
+    LINE_DRAWING.store(encode(line_drawing), Ordering::Relaxed);
+}
+
+/// Returns how the box-drawing characters are printed.
+///
+/// Unless it was set with [`set_line_drawing`](fn.set_line_drawing.html), it's detected from the
+/// environment variables:
+///
+/// * With a `TERM` of a DEC terminal (`vt100`, `vt220`, ...), the DEC Special Graphics.
+/// * With a UTF-8 locale (the first of `LC_ALL`, `LC_CTYPE` and `LANG` which is set), Unicode.
+/// * Otherwise the DEC Special Graphics.
+///
+/// The Windows console always prints Unicode.
+pub fn line_drawing() -> LineDrawing {
+    match LINE_DRAWING.load(Ordering::Relaxed) {
+        UNSET => {
+            let detected = if cfg!(any(windows, target_arch = "wasm32")) {
+                LineDrawing::Unicode
+            } else {
+                line_drawing_from_environment(|name| env::var(name).ok())
+            };
+            // Unless it was set in the meantime
+            let _ = LINE_DRAWING.compare_exchange(
+                UNSET,
+                encode(detected),
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            );
+            line_drawing()
+        }
+        DEC_SPECIAL_GRAPHICS_MODE => LineDrawing::DecSpecialGraphics,
+        _ => LineDrawing::Unicode,
+    }
+}
+
+fn encode(line_drawing: LineDrawing) -> u8 {
+    match line_drawing {
+        LineDrawing::Unicode => UNICODE,
+        LineDrawing::DecSpecialGraphics => DEC_SPECIAL_GRAPHICS_MODE,
+    }
+}
+
+/// Returns the mode fitting the terminal and the locale of the environment variables.
+fn line_drawing_from_environment(var: impl Fn(&str) -> Option<String>) -> LineDrawing {
+    // The hardware terminals only know their own character sets, whatever the locale says
+    if var("TERM").is_some_and(|term| term.starts_with("vt")) {
+        return LineDrawing::DecSpecialGraphics;
+    }
+
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .find_map(|name| var(name).filter(|value| !value.is_empty()))
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    if locale.contains("utf-8") || locale.contains("utf8") {
+        LineDrawing::Unicode
+    } else {
+        LineDrawing::DecSpecialGraphics
+    }
+}
+
+/// Returns the character standing for the box-drawing character in the DEC Special Graphics
+/// character set, if there's one.
+pub fn dec_special_graphics(c: char) -> Option<char> {
+    Some(match c {
+        HORIZONTAL => 'q',
+        VERTICAL => 'x',
+        TOP_LEFT => 'l',
+        TOP_RIGHT => 'k',
+        BOTTOM_LEFT => 'm',
+        BOTTOM_RIGHT => 'j',
+        LEFT_TEE => 't',
+        RIGHT_TEE => 'u',
+        TOP_TEE => 'w',
+        BOTTOM_TEE => 'v',
+        CROSS => 'n',
+        _ => return None,
+    })
+}
+
+/// Writes the text, the box-drawing characters in the DEC Special Graphics character set.
+fn write_dec_special_graphics(f: &mut impl fmt::Write, text: &str) -> fmt::Result {
+    let mut selected = false;
+    for c in text.chars() {
+        match dec_special_graphics(c) {
+            Some(graphic) => {
+                if !selected {
+                    f.write_str(DEC_SPECIAL_GRAPHICS)?;
+                    selected = true;
+                }
+                f.write_char(graphic)?;
+            }
+            None => {
+                if selected {
+                    f.write_str(ASCII)?;
+                    selected = false;
+                }
+                f.write_char(c)?;
+            }
+        }
+    }
+    if selected {
+        f.write_str(ASCII)?;
+    }
+    Ok(())
+}
+
+/// A command that prints the text with box-drawing characters the way the terminal renders them.
+///
+/// The box-drawing characters (the constants of this module) are printed as is or in the DEC
+/// Special Graphics character set, see [`line_drawing`](fn.line_drawing.html). The rest of the
+/// text is printed as is.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrintLines<T: fmt::Display>(pub T);
+
+impl<T: fmt::Display> Command for PrintLines<T> {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        match line_drawing() {
+            LineDrawing::Unicode => write!(f, "{}", self.0),
+            LineDrawing::DecSpecialGraphics => write_dec_special_graphics(f, &self.0.to_string()),
+        }
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        panic!("tried to execute PrintLines command using WinAPI, use ANSI instead");
+    }
+
+    #[cfg(windows)]
+    fn is_ansi_code_supported(&self) -> bool {
+        true
+    }
+}
+
+impl_display!(for PrintLines<String>);
+impl_display!(for PrintLines<&'static str>);
+
+#[cfg(test)]
+mod tests {
+    use super::{line_drawing_from_environment, write_dec_special_graphics, LineDrawing};
+
+    #[test]
+    fn test_line_drawing_from_environment() {
+        let detect = |variables: &[(&str, &str)]| {
+            line_drawing_from_environment(|name| {
+                variables
+                    .iter()
+                    .find(|(variable, _)| *variable == name)
+                    .map(|(_, value)| value.to_string())
+            })
+        };
+
+        assert_eq!(detect(&[("LANG", "en_US.UTF-8")]), LineDrawing::Unicode);
+        assert_eq!(
+            detect(&[("LC_ALL", "C"), ("LANG", "en_US.UTF-8")]),
+            LineDrawing::DecSpecialGraphics
+        );
+        assert_eq!(
+            detect(&[("LC_ALL", ""), ("LC_CTYPE", "de_DE.utf8")]),
+            LineDrawing::Unicode
+        );
+        assert_eq!(
+            detect(&[("TERM", "vt220"), ("LANG", "en_US.UTF-8")]),
+            LineDrawing::DecSpecialGraphics
+        );
+        assert_eq!(detect(&[]), LineDrawing::DecSpecialGraphics);
+    }
+
+    #[test]
+    fn test_write_dec_special_graphics() {
+        let mut output = String::new();
+        write_dec_special_graphics(&mut output, "┌─┐\n│x│").unwrap();
+
+        assert_eq!(output, "\x1B(0lqk\x1B(B\n\x1B(0x\x1B(Bx\x1B(0x\x1B(B");
+    }
+}
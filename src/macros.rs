@@ -105,10 +105,10 @@ macro_rules! queue {
 ///
 /// * In the case of UNIX and Windows 10, ANSI codes are written to the given 'writer'.
 /// * In case of Windows versions lower than 10, a direct WinAPI call will be made.
-///     The reason for this is that Windows versions lower than 10 do not support ANSI codes,
-///     and can therefore not be written to the given `writer`.
-///     Therefore, there is no difference between [execute](macro.execute.html)
-///     and [queue](macro.queue.html) for those old Windows versions.
+///   The reason for this is that Windows versions lower than 10 do not support ANSI codes,
+///   and can therefore not be written to the given `writer`.
+///   Therefore, there is no difference between [execute](macro.execute.html)
+///   and [queue](macro.queue.html) for those old Windows versions.
 #[macro_export]
 macro_rules! execute {
     ($writer:expr $(, $command:expr)* $(,)? ) => {{
@@ -122,6 +122,129 @@ macro_rules! execute {
     }}
 }
 
+/// Implements the [Command](./trait.Command.html) trait for the given type.
+///
+/// The command is written as an ANSI escape code, formatted with the given format string and
+/// arguments. The command itself is bound to the identifier between the pipes, so its fields can
+/// be used as format arguments.
+///
+/// On Windows versions that do not support ANSI escape codes, the optional `winapi` function is
+/// called instead. When omitted, the command does nothing on those versions.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::io::{stdout, Write};
+///
+/// use crossterm::{execute, impl_command, Result};
+///
+/// /// Draws a horizontal border of the given width at the given row.
+/// struct DrawBorder {
+///     row: u16,
+///     width: usize,
+/// }
+///
+/// impl_command!(DrawBorder |border| "\x1B[{};1H{:─<2$}", border.row + 1, "", border.width);
+///
+/// /// Rings the terminal bell.
+/// struct Bell;
+///
+/// impl_command!(Bell |_bell| "\x07"; winapi |_bell| {
+///     // Call into the WinAPI here...
+///     Ok(())
+/// });
+///
+/// fn main() -> Result<()> {
+///     execute!(stdout(), DrawBorder { row: 0, width: 10 }, Bell)
+/// }
+/// ```
+#[macro_export]
+macro_rules! impl_command {
+    ($t:ty |$this:tt| $fmt:expr $(, $arg:expr)* $(,)? $(; winapi |$winapi_this:tt| $winapi:block)?) => {
+        impl $crate::Command for $t {
+            fn write_ansi(&self, f: &mut impl ::std::fmt::Write) -> ::std::fmt::Result {
+                let $this = self;
+                write!(f, $fmt $(, $arg)*)
+            }
+
+            #[cfg(windows)]
+            fn execute_winapi(&self) -> $crate::Result<()> {
+                $crate::impl_command!(@winapi self $(, $winapi_this, $winapi)?)
+            }
+        }
+    };
+    (@winapi $self:ident) => {
+        Ok(())
+    };
+    (@winapi $self:ident, $this:tt, $winapi:block) => {{
+        let $this = $self;
+        $winapi
+    }};
+}
+
+/// Asserts that the screen of a [`VirtualTerminal`](test_util/struct.VirtualTerminal.html)
+/// contains the expected rows.
+///
+/// The trailing spaces of the rows are ignored and every row of the screen has to be given. When
+/// the screen doesn't match, the panic message shows both screens and marks the rows which differ.
+/// Like `assert_eq!`, a custom message can follow the expected rows.
+///
+/// Only available with the `test-util` feature.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm::{assert_screen_eq, style::Print, test_util::VirtualTerminal};
+///
+/// let mut terminal = VirtualTerminal::new(5, 3);
+/// terminal.execute(Print("┌───┐\n│ hi│\n└───┘")).unwrap();
+///
+/// assert_screen_eq!(terminal, ["┌───┐", "│ hi│", "└───┘"]);
+/// ```
+#[cfg(feature = "test-util")]
+#[macro_export]
+macro_rules! assert_screen_eq {
+    ($terminal:expr, $expected:expr $(,)?) => {
+        $crate::test_util::assert_screen_eq(&$terminal, &$expected, None)
+    };
+    ($terminal:expr, $expected:expr, $($arg:tt)+) => {
+        $crate::test_util::assert_screen_eq(&$terminal, &$expected, Some(format_args!($($arg)+)))
+    };
+}
+
+/// Asserts that the text matches the snapshot with the given name.
+///
+/// The snapshot is the file `tests/snapshots/<name>.snap` of the crate calling the macro. Use it
+/// with the output recorded by an [`OutputRecorder`](test_util/struct.OutputRecorder.html) to
+/// verify the bytes written by commands. A missing snapshot is written and the assertion fails,
+/// set the `CROSSTERM_UPDATE_SNAPSHOTS` environment variable to update the existing ones.
+///
+/// Only available with the `test-util` feature.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm::{assert_snapshot, cursor::MoveTo, queue, test_util::OutputRecorder};
+///
+/// let mut output = OutputRecorder::default();
+/// queue!(output, MoveTo(1, 2)).unwrap();
+///
+/// assert_snapshot!("move_to", output.escaped());
+/// ```
+#[cfg(feature = "test-util")]
+#[macro_export]
+macro_rules! assert_snapshot {
+    ($name:expr, $actual:expr $(,)?) => {
+        $crate::test_util::assert_snapshot(
+            ::std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("tests")
+                .join("snapshots")
+                .join(format!("{}.snap", $name)),
+            &$actual,
+        )
+    };
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! impl_display {
@@ -153,7 +276,7 @@ mod tests {
 
     // Helper for execute tests to confirm flush
     #[derive(Default, Debug, Clone)]
-    pub(self) struct FakeWrite {
+    struct FakeWrite {
         buffer: String,
         flushed: bool,
     }
@@ -178,7 +301,7 @@ mod tests {
         use std::fmt;
 
         use super::FakeWrite;
-        use crate::command::Command;
+        use crate::command::{Command, DynCommand};
 
         pub struct FakeCommand;
 
@@ -235,6 +358,42 @@ mod tests {
             assert_eq!(&result.buffer, "cmdcmd");
             assert!(result.flushed);
         }
+
+        struct FakeMacroCommand(u16, &'static str);
+
+        impl_command!(FakeMacroCommand | cmd | csi!("{};{}"), cmd.0, cmd.1);
+
+        struct FakeMacroCommandWinApi;
+
+        impl_command!(FakeMacroCommandWinApi |_cmd| "winapi"; winapi |_cmd| { Ok(()) });
+
+        #[test]
+        fn test_impl_command() {
+            let mut result = FakeWrite::default();
+            queue!(
+                &mut result,
+                FakeMacroCommand(10, "cmd"),
+                FakeMacroCommandWinApi
+            )
+            .unwrap();
+            assert_eq!(&result.buffer, "\x1B[10;cmdwinapi");
+        }
+
+        #[test]
+        fn test_queue_boxed_commands() {
+            let commands: Vec<Box<dyn DynCommand>> = vec![
+                Box::new(FakeCommand),
+                Box::new(crate::style::Print("print")),
+                Box::new(FakeCommand),
+            ];
+
+            let mut result = FakeWrite::default();
+            for command in &commands {
+                queue!(&mut result, command).unwrap();
+            }
+            assert_eq!(&result.buffer, "cmdprintcmd");
+            assert!(!result.flushed);
+        }
     }
 
     #[cfg(windows)]
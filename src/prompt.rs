@@ -0,0 +1,57 @@
+//! # Prompt
+//!
+//! The `prompt` module reads lines typed by the user, with the events and the commands of this
+//! crate only. It works the same on UNIX and Windows, and in a program using the raw mode.
+//!
+//! [`LineEditor`](struct.LineEditor.html) edits the line with the usual keys, remembers the
+//! history and completes the line with a callback.
+//! [`read_password`](fn.read_password.html) reads a password without showing it.
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use crossterm::{prompt::LineEditor, Result};
+//!
+//! fn main() -> Result<()> {
+//!     let mut editor = LineEditor::new("> ");
+//!     while let Some(line) = editor.read_line()? {
+//!         println!("read {:?}", line);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+pub use self::{
+    line_editor::LineEditor,
+    password::{read_password, read_password_with_mask},
+};
+
+use crate::{terminal, Result};
+
+mod line_editor;
+mod password;
+
+/// Enables the raw mode until it's dropped, unless it was enabled already.
+///
+/// The mode is restored when the prompt returns early with an error or panics.
+struct RawModeGuard {
+    enabled: bool,
+}
+
+impl RawModeGuard {
+    fn enable() -> Result<RawModeGuard> {
+        let enabled = !terminal::sys::is_raw_mode_enabled();
+        if enabled {
+            terminal::enable_raw_mode()?;
+        }
+        Ok(RawModeGuard { enabled })
+    }
+}
+
+impl Drop for RawModeGuard {
+    fn drop(&mut self) {
+        if self.enabled {
+            let _ = terminal::disable_raw_mode();
+        }
+    }
+}
@@ -0,0 +1,550 @@
+use std::{
+    fmt,
+    io::{self, Write},
+    mem,
+};
+
+use unicode_width::UnicodeWidthChar;
+
+use crate::{
+    cursor::MoveRight,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    style::Print,
+    terminal::{self, Clear, ClearType},
+    text, QueueableCommand, Result,
+};
+
+use super::RawModeGuard;
+
+/// The number of lines remembered by default.
+const DEFAULT_HISTORY_SIZE: usize = 100;
+
+/// A completion callback, see [`LineEditor::completion`](struct.LineEditor.html#method.completion).
+type Completion = Box<dyn FnMut(&str) -> Vec<String>>;
+
+/// A line editor, reading a line typed by the user after a prompt.
+///
+/// The line is edited with the keys of readline:
+///
+/// * `Left`/`Ctrl+B`, `Right`/`Ctrl+F`, `Home`/`Ctrl+A` and `End`/`Ctrl+E` move the cursor,
+///   `Ctrl+Left`/`Alt+B` and `Ctrl+Right`/`Alt+F` by words.
+/// * `Backspace`/`Ctrl+H` and `Delete` delete a character, `Ctrl+W` the word before the cursor,
+///   `Ctrl+U` everything before the cursor and `Ctrl+K` everything after it.
+/// * `Up`/`Ctrl+P` and `Down`/`Ctrl+N` go through the history.
+/// * `Tab` completes the line, see [`completion`](#method.completion).
+/// * `Enter` accepts the line, `Ctrl+D` on an empty line ends the input and `Ctrl+C` interrupts
+///   it.
+///
+/// The prompt and the line are written on the row of the cursor, from its first column. A line
+/// wider than the terminal scrolls horizontally.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm::{prompt::LineEditor, Result};
+///
+/// fn main() -> Result<()> {
+///     let commands = ["help", "history", "quit"];
+///     let mut editor = LineEditor::new("> ").completion(move |line| {
+///         commands
+///             .iter()
+///             .filter(|command| command.starts_with(line))
+///             .map(|command| command.to_string())
+///             .collect()
+///     });
+///
+///     while let Some(line) = editor.read_line()? {
+///         if line == "quit" {
+///             break;
+///         }
+///     }
+///     Ok(())
+/// }
+/// ```
+pub struct LineEditor {
+    prompt: String,
+    history: Vec<String>,
+    history_size: usize,
+    completion: Option<Completion>,
+}
+
+impl fmt::Debug for LineEditor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LineEditor")
+            .field("prompt", &self.prompt)
+            .field("history", &self.history)
+            .field("history_size", &self.history_size)
+            .finish()
+    }
+}
+
+/// What the editor does after a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Continue,
+    Accept,
+    EndOfInput,
+    Interrupt,
+}
+
+/// The state of the line being read.
+#[derive(Debug, Default)]
+struct Session {
+    line: Vec<char>,
+    cursor: usize,
+    /// The entry of the history shown, the history length for the line being typed.
+    history_index: usize,
+    /// The line being typed, while an entry of the history is shown.
+    draft: Vec<char>,
+    /// The candidates of the last completion and the one shown, while `Tab` is pressed again.
+    completions: Option<(Vec<String>, Option<usize>)>,
+    /// The first character shown, when the line is wider than the terminal.
+    offset: usize,
+}
+
+impl Session {
+    /// Deletes the characters between the cursor and the position, before or after it.
+    fn delete(&mut self, position: usize) {
+        let position = position.min(self.line.len());
+        if position < self.cursor {
+            self.line.drain(position..self.cursor);
+            self.cursor = position;
+        } else {
+            self.line.drain(self.cursor..position);
+        }
+    }
+}
+
+impl LineEditor {
+    /// Creates a line editor writing the prompt before the line.
+    pub fn new(prompt: impl Into<String>) -> LineEditor {
+        LineEditor {
+            prompt: prompt.into(),
+            history: Vec::new(),
+            history_size: DEFAULT_HISTORY_SIZE,
+            completion: None,
+        }
+    }
+
+    /// Sets the number of lines the history remembers, 100 by default.
+    pub fn history_size(mut self, history_size: usize) -> LineEditor {
+        self.history_size = history_size;
+        self.trim_history();
+        self
+    }
+
+    /// Sets the callback completing the line when `Tab` is pressed.
+    ///
+    /// The callback gets the line up to the cursor and returns the candidates replacing it. A
+    /// single candidate replaces it right away. With several candidates, the line is completed
+    /// up to their common prefix and the next presses of `Tab` go through them.
+    pub fn completion(mut self, completion: impl FnMut(&str) -> Vec<String> + 'static) -> Self {
+        self.completion = Some(Box::new(completion));
+        self
+    }
+
+    /// Sets the prompt written before the line.
+    pub fn set_prompt(&mut self, prompt: impl Into<String>) {
+        self.prompt = prompt.into();
+    }
+
+    /// Returns the lines of the history, the most recent last.
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    /// Adds the line to the history.
+    ///
+    /// The lines read are added already, except the empty ones and the repetitions of the last
+    /// one.
+    pub fn add_history(&mut self, line: impl Into<String>) {
+        self.history.push(line.into());
+        self.trim_history();
+    }
+
+    /// Removes the lines of the history.
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    fn trim_history(&mut self) {
+        let excess = self.history.len().saturating_sub(self.history_size);
+        self.history.drain(..excess);
+    }
+
+    /// Reads a line from the user.
+    ///
+    /// Returns `None` when the input ends, on `Ctrl+D` with an empty line or when the terminal
+    /// was closed. Fails with `io::ErrorKind::Interrupted` on `Ctrl+C`.
+    ///
+    /// The raw mode is enabled while the line is read, unless it was already, and restored
+    /// afterwards even if reading the events fails.
+    pub fn read_line(&mut self) -> Result<Option<String>> {
+        let _raw_mode = RawModeGuard::enable()?;
+        let mut stdout = io::stdout();
+        let mut session = Session {
+            history_index: self.history.len(),
+            ..Session::default()
+        };
+        let mut columns = terminal::size().map_or(80, |(columns, _)| columns);
+
+        self.render(&mut session, &mut stdout, columns)?;
+        stdout.flush()?;
+
+        loop {
+            let outcome = match event::read()? {
+                Event::Key(key) if key.kind != KeyEventKind::Release => {
+                    self.handle_key(&mut session, key)
+                }
+                Event::Resize(new_columns, _) => {
+                    columns = new_columns;
+                    Outcome::Continue
+                }
+                Event::Closed => Outcome::EndOfInput,
+                _ => continue,
+            };
+
+            if outcome != Outcome::Continue {
+                stdout.write_all(b"\r\n")?;
+                stdout.flush()?;
+            }
+
+            match outcome {
+                Outcome::Continue => {
+                    self.render(&mut session, &mut stdout, columns)?;
+                    stdout.flush()?;
+                }
+                Outcome::Accept => {
+                    let line: String = session.line.into_iter().collect();
+                    if !line.is_empty() && self.history.last() != Some(&line) {
+                        self.add_history(line.clone());
+                    }
+                    return Ok(Some(line));
+                }
+                Outcome::EndOfInput => return Ok(None),
+                Outcome::Interrupt => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::Interrupted,
+                        "The line editor was interrupted with Ctrl+C",
+                    ))
+                }
+            }
+        }
+    }
+
+    fn handle_key(&mut self, session: &mut Session, key: KeyEvent) -> Outcome {
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+
+        if key.code == KeyCode::Tab {
+            self.complete(session);
+            return Outcome::Continue;
+        }
+        session.completions = None;
+
+        let line = &session.line;
+        match key.code {
+            KeyCode::Enter => return Outcome::Accept,
+            KeyCode::Char('c') if control => return Outcome::Interrupt,
+            KeyCode::Char('d') if control && line.is_empty() => return Outcome::EndOfInput,
+
+            KeyCode::Left if control => session.cursor = word_start(line, session.cursor),
+            KeyCode::Char('b') if alt => session.cursor = word_start(line, session.cursor),
+            KeyCode::Right if control => session.cursor = word_end(line, session.cursor),
+            KeyCode::Char('f') if alt => session.cursor = word_end(line, session.cursor),
+            KeyCode::Left => session.cursor = session.cursor.saturating_sub(1),
+            KeyCode::Char('b') if control => session.cursor = session.cursor.saturating_sub(1),
+            KeyCode::Right => session.cursor = (session.cursor + 1).min(line.len()),
+            KeyCode::Char('f') if control => session.cursor = (session.cursor + 1).min(line.len()),
+            KeyCode::Home => session.cursor = 0,
+            KeyCode::Char('a') if control => session.cursor = 0,
+            KeyCode::End => session.cursor = line.len(),
+            KeyCode::Char('e') if control => session.cursor = line.len(),
+
+            KeyCode::Backspace => session.delete(session.cursor.saturating_sub(1)),
+            KeyCode::Char('h') if control => session.delete(session.cursor.saturating_sub(1)),
+            KeyCode::Delete => session.delete(session.cursor + 1),
+            KeyCode::Char('d') if control => session.delete(session.cursor + 1),
+            KeyCode::Char('w') if control => session.delete(word_start(line, session.cursor)),
+            KeyCode::Char('u') if control => session.delete(0),
+            KeyCode::Char('k') if control => session.delete(line.len()),
+
+            KeyCode::Up => self.show_history(session, -1),
+            KeyCode::Char('p') if control => self.show_history(session, -1),
+            KeyCode::Down => self.show_history(session, 1),
+            KeyCode::Char('n') if control => self.show_history(session, 1),
+
+            KeyCode::Char(c) if !control && !alt => {
+                session.line.insert(session.cursor, c);
+                session.cursor += 1;
+            }
+            _ => {}
+        }
+        Outcome::Continue
+    }
+
+    /// Shows the previous (`-1`) or the next (`1`) entry of the history.
+    fn show_history(&self, session: &mut Session, direction: isize) {
+        let index = match session.history_index.checked_add_signed(direction) {
+            Some(index) if index <= self.history.len() => index,
+            _ => return,
+        };
+
+        if session.history_index == self.history.len() {
+            session.draft = mem::take(&mut session.line);
+        }
+        session.line = match self.history.get(index) {
+            Some(entry) => entry.chars().collect(),
+            None => mem::take(&mut session.draft),
+        };
+        session.history_index = index;
+        session.cursor = session.line.len();
+    }
+
+    fn complete(&mut self, session: &mut Session) {
+        let candidate = match &mut session.completions {
+            // Pressed again, the next candidate
+            Some((candidates, shown)) => {
+                let next = shown.map_or(0, |shown| (shown + 1) % candidates.len());
+                *shown = Some(next);
+                candidates[next].clone()
+            }
+            None => {
+                let completion = match &mut self.completion {
+                    Some(completion) => completion,
+                    None => return,
+                };
+                let before_cursor: String = session.line[..session.cursor].iter().collect();
+                let candidates = completion(&before_cursor);
+                if candidates.is_empty() {
+                    return;
+                }
+
+                let prefix = common_prefix(&candidates);
+                if candidates.len() == 1 {
+                    prefix
+                } else if prefix.chars().count() > session.cursor {
+                    session.completions = Some((candidates, None));
+                    prefix
+                } else {
+                    let first = candidates[0].clone();
+                    session.completions = Some((candidates, Some(0)));
+                    first
+                }
+            }
+        };
+
+        let after_cursor = session.line.split_off(session.cursor);
+        session.line = candidate.chars().chain(after_cursor).collect();
+        session.cursor = candidate.chars().count();
+    }
+
+    /// Writes the prompt and the part of the line around the cursor fitting in the columns.
+    fn render(&self, session: &mut Session, writer: &mut impl Write, columns: u16) -> Result<()> {
+        let prompt_width = text::width(&self.prompt);
+        // The last column is kept for the cursor
+        let available = (columns as usize).saturating_sub(prompt_width + 1).max(1);
+
+        let width =
+            |chars: &[char]| -> usize { chars.iter().map(|c| c.width().unwrap_or(0)).sum() };
+        session.offset = session.offset.min(session.cursor);
+        while width(&session.line[session.offset..session.cursor]) > available {
+            session.offset += 1;
+        }
+
+        let mut shown_width = 0;
+        let shown: String = session.line[session.offset..]
+            .iter()
+            .take_while(|c| {
+                shown_width += width(&[**c]);
+                shown_width <= available
+            })
+            .collect();
+
+        writer
+            .queue(Print('\r'))?
+            .queue(Print(&self.prompt))?
+            .queue(Print(shown))?
+            .queue(Clear(ClearType::UntilNewLine))?
+            .queue(Print('\r'))?
+            .queue(MoveRight(
+                (prompt_width + width(&session.line[session.offset..session.cursor])) as u16,
+            ))?;
+        Ok(())
+    }
+}
+
+/// Returns where the word before the cursor starts, the whitespace before the cursor is skipped.
+fn word_start(line: &[char], cursor: usize) -> usize {
+    let before = &line[..cursor];
+    let end = before
+        .iter()
+        .rposition(|c| !c.is_whitespace())
+        .map_or(0, |last| last + 1);
+    before[..end]
+        .iter()
+        .rposition(|c| c.is_whitespace())
+        .map_or(0, |space| space + 1)
+}
+
+/// Returns where the word after the cursor ends, the whitespace after the cursor is skipped.
+fn word_end(line: &[char], cursor: usize) -> usize {
+    let after = &line[cursor..];
+    let start = after
+        .iter()
+        .position(|c| !c.is_whitespace())
+        .unwrap_or(after.len());
+    cursor
+        + after[start..]
+            .iter()
+            .position(|c| c.is_whitespace())
+            .map_or(after.len(), |space| start + space)
+}
+
+/// Returns the longest prefix of all the candidates.
+fn common_prefix(candidates: &[String]) -> String {
+    let mut prefix: Vec<char> = candidates[0].chars().collect();
+    for candidate in &candidates[1..] {
+        let common = prefix
+            .iter()
+            .zip(candidate.chars())
+            .take_while(|(a, b)| **a == *b)
+            .count();
+        prefix.truncate(common);
+    }
+    prefix.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use super::{LineEditor, Outcome, Session};
+
+    fn type_keys(editor: &mut LineEditor, session: &mut Session, keys: &[KeyEvent]) -> Outcome {
+        let mut outcome = Outcome::Continue;
+        for key in keys {
+            outcome = editor.handle_key(session, *key);
+        }
+        outcome
+    }
+
+    fn text(text: &str) -> Vec<KeyEvent> {
+        text.chars().map(|c| KeyCode::Char(c).into()).collect()
+    }
+
+    fn control(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    fn line(session: &Session) -> String {
+        session.line.iter().collect()
+    }
+
+    #[test]
+    fn test_editing() {
+        let mut editor = LineEditor::new("> ");
+        let mut session = Session::default();
+
+        type_keys(&mut editor, &mut session, &text("hello world"));
+        type_keys(
+            &mut editor,
+            &mut session,
+            &[control('w'), KeyCode::Home.into(), KeyCode::Delete.into()],
+        );
+        assert_eq!(line(&session), "ello ");
+
+        type_keys(&mut editor, &mut session, &[KeyCode::End.into()]);
+        type_keys(&mut editor, &mut session, &text("there"));
+        type_keys(
+            &mut editor,
+            &mut session,
+            &[
+                KeyEvent::new(KeyCode::Left, KeyModifiers::CONTROL),
+                KeyCode::Backspace.into(),
+                control('k'),
+            ],
+        );
+        assert_eq!(line(&session), "ello");
+        assert_eq!(session.cursor, 4);
+
+        assert_eq!(
+            type_keys(&mut editor, &mut session, &[control('u'), control('d')]),
+            Outcome::EndOfInput
+        );
+        assert_eq!(
+            type_keys(&mut editor, &mut session, &[control('c')]),
+            Outcome::Interrupt
+        );
+    }
+
+    #[test]
+    fn test_history() {
+        let mut editor = LineEditor::new("> ").history_size(2);
+        editor.add_history("first");
+        editor.add_history("second");
+        editor.add_history("third");
+        assert_eq!(editor.history(), ["second", "third"]);
+
+        let mut session = Session {
+            history_index: 2,
+            ..Session::default()
+        };
+        type_keys(&mut editor, &mut session, &text("draft"));
+        type_keys(
+            &mut editor,
+            &mut session,
+            &[KeyCode::Up.into(), KeyCode::Up.into(), KeyCode::Up.into()],
+        );
+        assert_eq!(line(&session), "second");
+
+        type_keys(
+            &mut editor,
+            &mut session,
+            &[KeyCode::Down.into(), KeyCode::Down.into()],
+        );
+        assert_eq!(line(&session), "draft");
+    }
+
+    #[test]
+    fn test_completion() {
+        let mut editor = LineEditor::new("> ").completion(|line| {
+            ["history", "help", "quit"]
+                .iter()
+                .filter(|command| command.starts_with(line))
+                .map(|command| command.to_string())
+                .collect()
+        });
+        let mut session = Session::default();
+
+        type_keys(&mut editor, &mut session, &text("q"));
+        type_keys(&mut editor, &mut session, &[KeyCode::Tab.into()]);
+        assert_eq!(line(&session), "quit");
+
+        type_keys(&mut editor, &mut session, &[control('u')]);
+        type_keys(&mut editor, &mut session, &text("h"));
+        type_keys(&mut editor, &mut session, &[KeyCode::Tab.into()]);
+        assert_eq!(line(&session), "history");
+        type_keys(&mut editor, &mut session, &[KeyCode::Tab.into()]);
+        assert_eq!(line(&session), "help");
+    }
+
+    #[test]
+    fn test_render() {
+        let editor = LineEditor::new("> ");
+        let mut session = Session {
+            line: "abcdefghij".chars().collect(),
+            cursor: 10,
+            ..Session::default()
+        };
+
+        let mut output = Vec::new();
+        editor.render(&mut session, &mut output, 8).unwrap();
+
+        // The end of the line fits in the 5 columns after the prompt
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "\r> fghij\x1B[K\r\x1B[7C"
+        );
+    }
+}
@@ -0,0 +1,263 @@
+use std::io::{self, Write};
+
+use crate::{
+    cursor::MoveLeft,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    style::Print,
+    terminal::{Clear, ClearType},
+    QueueableCommand, Result,
+};
+
+use super::RawModeGuard;
+
+/// The characters typed so far, overwritten when they're dropped or moved to a larger buffer.
+struct Secret {
+    chars: Vec<char>,
+}
+
+impl Secret {
+    fn new() -> Secret {
+        Secret {
+            chars: Vec::with_capacity(64),
+        }
+    }
+
+    fn push(&mut self, c: char) {
+        // Grown by hand, a reallocation would leave a copy behind
+        if self.chars.len() == self.chars.capacity() {
+            let mut chars = Vec::with_capacity(self.chars.capacity() * 2);
+            chars.extend_from_slice(&self.chars);
+            self.clear();
+            self.chars = chars;
+        }
+        self.chars.push(c);
+    }
+
+    fn pop(&mut self) -> bool {
+        match self.chars.last_mut() {
+            Some(last) => {
+                *last = '\0';
+                self.chars.pop();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.chars.iter_mut().for_each(|c| *c = '\0');
+        self.chars.clear();
+    }
+}
+
+impl Drop for Secret {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+/// What the prompt does after a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Continue,
+    Accept,
+    Interrupt,
+    EndOfInput,
+}
+
+/// Reads a password typed by the user after the prompt, without showing it.
+///
+/// `Backspace` deletes the last character, `Ctrl+U` all of them. Fails with
+/// `io::ErrorKind::Interrupted` on `Ctrl+C`, and with `io::ErrorKind::UnexpectedEof` on `Ctrl+D`
+/// with nothing typed or when the terminal was closed.
+///
+/// The raw mode, which doesn't echo the input, is enabled while the password is read, unless it
+/// was already, and restored afterwards even if reading the events fails or panics. The
+/// characters typed are overwritten in memory when they are thrown away.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm::{prompt, Result};
+///
+/// fn main() -> Result<()> {
+///     let password = prompt::read_password("Password: ")?;
+///     Ok(())
+/// }
+/// ```
+pub fn read_password(prompt: &str) -> Result<String> {
+    read(prompt, None)
+}
+
+/// Reads a password typed by the user after the prompt, showing the mask for every character.
+///
+/// It works like [`read_password`](fn.read_password.html), the user sees how many characters
+/// were typed.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm::{prompt, Result};
+///
+/// fn main() -> Result<()> {
+///     let password = prompt::read_password_with_mask("Password: ", '*')?;
+///     Ok(())
+/// }
+/// ```
+pub fn read_password_with_mask(prompt: &str, mask: char) -> Result<String> {
+    read(prompt, Some(mask))
+}
+
+fn read(prompt: &str, mask: Option<char>) -> Result<String> {
+    let _raw_mode = RawModeGuard::enable()?;
+    let mut stdout = io::stdout();
+    stdout.write_all(prompt.as_bytes())?;
+    stdout.flush()?;
+
+    let mut secret = Secret::new();
+    loop {
+        let outcome = match event::read()? {
+            Event::Key(key) if key.kind != KeyEventKind::Release => {
+                handle_key(&mut secret, key, mask, &mut stdout)?
+            }
+            Event::Closed => Outcome::EndOfInput,
+            _ => continue,
+        };
+
+        if outcome != Outcome::Continue {
+            stdout.write_all(b"\r\n")?;
+        }
+        stdout.flush()?;
+
+        match outcome {
+            Outcome::Continue => {}
+            Outcome::Accept => return Ok(secret.chars.iter().collect()),
+            Outcome::Interrupt => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Interrupted,
+                    "The password prompt was interrupted with Ctrl+C",
+                ))
+            }
+            Outcome::EndOfInput => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "The input ended before a password was entered",
+                ))
+            }
+        }
+    }
+}
+
+/// Edits the password, and updates the masks shown.
+fn handle_key(
+    secret: &mut Secret,
+    key: KeyEvent,
+    mask: Option<char>,
+    writer: &mut impl Write,
+) -> Result<Outcome> {
+    let control = key.modifiers.contains(KeyModifiers::CONTROL);
+
+    if key.code == KeyCode::Backspace || (control && key.code == KeyCode::Char('h')) {
+        if secret.pop() && mask.is_some() {
+            writer
+                .queue(MoveLeft(1))?
+                .queue(Clear(ClearType::UntilNewLine))?;
+        }
+        return Ok(Outcome::Continue);
+    }
+
+    match key.code {
+        KeyCode::Enter => return Ok(Outcome::Accept),
+        KeyCode::Char('c') if control => return Ok(Outcome::Interrupt),
+        KeyCode::Char('d') if control && secret.chars.is_empty() => return Ok(Outcome::EndOfInput),
+        KeyCode::Char('u') if control => {
+            let typed = secret.chars.len();
+            secret.clear();
+            if typed > 0 && mask.is_some() {
+                writer
+                    .queue(MoveLeft(typed as u16))?
+                    .queue(Clear(ClearType::UntilNewLine))?;
+            }
+        }
+        KeyCode::Char(c) if !control && !key.modifiers.contains(KeyModifiers::ALT) => {
+            secret.push(c);
+            if let Some(mask) = mask {
+                writer.queue(Print(mask))?;
+            }
+        }
+        _ => {}
+    }
+    Ok(Outcome::Continue)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use super::{handle_key, Outcome, Secret};
+
+    fn type_keys(secret: &mut Secret, keys: &[KeyEvent], mask: Option<char>) -> String {
+        let mut output = Vec::new();
+        for key in keys {
+            handle_key(secret, *key, mask, &mut output).unwrap();
+        }
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_editing() {
+        let mut secret = Secret::new();
+        let keys = [
+            KeyCode::Char('a').into(),
+            KeyCode::Char('b').into(),
+            KeyCode::Backspace.into(),
+            KeyCode::Char('c').into(),
+        ];
+
+        assert_eq!(type_keys(&mut secret, &keys, Some('*')), "**\x1B[1D\x1B[K*");
+        assert_eq!(secret.chars, ['a', 'c']);
+
+        let clear = KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL);
+        assert_eq!(type_keys(&mut secret, &[clear], Some('*')), "\x1B[2D\x1B[K");
+        assert!(secret.chars.is_empty());
+
+        // Nothing is shown without a mask
+        assert_eq!(type_keys(&mut secret, &keys, None), "");
+    }
+
+    #[test]
+    fn test_outcomes() {
+        let mut secret = Secret::new();
+        let mut output = Vec::new();
+        let mut outcome = |secret: &mut Secret, key: KeyEvent| {
+            handle_key(secret, key, None, &mut output).unwrap()
+        };
+
+        assert_eq!(
+            outcome(
+                &mut secret,
+                KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL)
+            ),
+            Outcome::EndOfInput
+        );
+        assert_eq!(
+            outcome(
+                &mut secret,
+                KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)
+            ),
+            Outcome::Interrupt
+        );
+        assert_eq!(outcome(&mut secret, KeyCode::Enter.into()), Outcome::Accept);
+    }
+
+    #[test]
+    fn test_secret_growth() {
+        let mut secret = Secret::new();
+        for _ in 0..100 {
+            secret.push('x');
+        }
+
+        assert_eq!(secret.chars.len(), 100);
+        assert!(secret.chars.capacity() >= 100);
+    }
+}
@@ -15,6 +15,9 @@
 //! * [Color](enum.Color.html#platform-specific-notes)
 //! * [Attribute](enum.Attribute.html#platform-specific-notes)
 //!
+//! The colors and the attributes aren't written in the
+//! [reduced decorations](../accessibility/index.html) mode.
+//!
 //! ## Examples
 //!
 //! A few examples of how to use the style module.
@@ -117,7 +120,8 @@ use std::{
     fmt::{self, Display},
 };
 
-use crate::command::execute_fmt;
+use crate::accessibility::reduced_decorations;
+use crate::command::{execute_fmt, write_u16};
 #[cfg(windows)]
 use crate::Result;
 use crate::{csi, impl_display, Command};
@@ -125,6 +129,7 @@ use crate::{csi, impl_display, Command};
 pub use self::{
     attributes::Attributes,
     content_style::ContentStyle,
+    link::{print_link, supports_hyperlinks},
     styled_content::StyledContent,
     stylize::Stylize,
     types::{Attribute, Color, Colored, Colors},
@@ -132,9 +137,10 @@ pub use self::{
 
 mod attributes;
 mod content_style;
+mod link;
 mod styled_content;
 mod stylize;
-mod sys;
+pub(crate) mod sys;
 mod types;
 
 /// Creates a `StyledContent`.
@@ -184,11 +190,21 @@ pub struct SetForegroundColor(pub Color);
 
 impl Command for SetForegroundColor {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
-        write!(f, csi!("{}m"), Colored::ForegroundColor(self.0))
+        if reduced_decorations() {
+            return Ok(());
+        }
+
+        f.write_str(csi!())?;
+        Colored::ForegroundColor(self.0).write_parameters(f)?;
+        f.write_str("m")
     }
 
     #[cfg(windows)]
     fn execute_winapi(&self) -> Result<()> {
+        if reduced_decorations() {
+            return Ok(());
+        }
+
         sys::windows::set_foreground_color(self.0)
     }
 }
@@ -208,11 +224,21 @@ pub struct SetBackgroundColor(pub Color);
 
 impl Command for SetBackgroundColor {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
-        write!(f, csi!("{}m"), Colored::BackgroundColor(self.0))
+        if reduced_decorations() {
+            return Ok(());
+        }
+
+        f.write_str(csi!())?;
+        Colored::BackgroundColor(self.0).write_parameters(f)?;
+        f.write_str("m")
     }
 
     #[cfg(windows)]
     fn execute_winapi(&self) -> Result<()> {
+        if reduced_decorations() {
+            return Ok(());
+        }
+
         sys::windows::set_background_color(self.0)
     }
 }
@@ -254,6 +280,10 @@ impl Command for SetColors {
 
     #[cfg(windows)]
     fn execute_winapi(&self) -> Result<()> {
+        if reduced_decorations() {
+            return Ok(());
+        }
+
         if let Some(color) = self.0.foreground {
             sys::windows::set_foreground_color(color)?;
         }
@@ -276,7 +306,13 @@ pub struct SetAttribute(pub Attribute);
 
 impl Command for SetAttribute {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
-        write!(f, csi!("{}m"), self.0.sgr())
+        if self.0 != Attribute::Reset && reduced_decorations() {
+            return Ok(());
+        }
+
+        f.write_str(csi!())?;
+        write_u16(f, self.0.sgr() as u16)?;
+        f.write_str("m")
     }
 
     #[cfg(windows)]
@@ -298,11 +334,25 @@ pub struct SetAttributes(pub Attributes);
 
 impl Command for SetAttributes {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
-        for attr in Attribute::iterator() {
-            if self.0.has(attr) {
-                SetAttribute(attr).write_ansi(f)?;
+        // All attributes are written as parameters of a single SGR sequence, only the reset is
+        // left with the reduced decorations
+        let reduced = reduced_decorations();
+        let mut attributes = Attribute::iterator().filter(|attribute| {
+            self.0.has(*attribute) && (!reduced || *attribute == Attribute::Reset)
+        });
+
+        if let Some(attribute) = attributes.next() {
+            f.write_str(csi!())?;
+            write_u16(f, attribute.sgr() as u16)?;
+
+            for attribute in attributes {
+                f.write_str(";")?;
+                write_u16(f, attribute.sgr() as u16)?;
             }
+
+            f.write_str("m")?;
         }
+
         Ok(())
     }
 
@@ -325,6 +375,10 @@ pub struct PrintStyledContent<D: Display>(pub StyledContent<D>);
 
 impl<D: Display> Command for PrintStyledContent<D> {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        if reduced_decorations() {
+            return write!(f, "{}", self.0.content());
+        }
+
         let style = self.0.style();
 
         let mut reset_background = false;
@@ -431,3 +485,40 @@ impl_display!(for ResetColor);
 fn parse_next_u8<'a>(iter: &mut impl Iterator<Item = &'a str>) -> Option<u8> {
     iter.next().and_then(|s| s.parse().ok())
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::Command;
+
+    use super::{style, Attribute, Attributes, Color, PrintStyledContent, SetAttributes, Stylize};
+
+    fn ansi(command: impl Command) -> String {
+        let mut result = String::new();
+        command.write_ansi(&mut result).unwrap();
+        result
+    }
+
+    #[test]
+    fn test_set_attributes_writes_single_sequence() {
+        assert_eq!(ansi(SetAttributes(Attributes::default())), "");
+        assert_eq!(ansi(SetAttributes(Attribute::Bold.into())), "\x1B[1m");
+        assert_eq!(
+            ansi(SetAttributes(
+                Attributes::from(Attribute::Bold) | Attribute::Italic
+            )),
+            "\x1B[1;3m"
+        );
+    }
+
+    #[test]
+    fn test_print_styled_content() {
+        assert_eq!(
+            ansi(PrintStyledContent(style("crossterm").with(Color::Red))),
+            "\x1B[38;5;9mcrossterm\x1B[39m"
+        );
+        assert_eq!(
+            ansi(PrintStyledContent(style("crossterm").bold().italic())),
+            "\x1B[1;3mcrossterm\x1B[0m"
+        );
+    }
+}
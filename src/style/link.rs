@@ -0,0 +1,194 @@
+use std::{
+    env,
+    fmt::Display,
+    io::{self, Write},
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+use crate::{
+    terminal::{multiplexer_from_environment, Multiplexer},
+    tty::IsTty,
+    Result,
+};
+
+/// The support of the hyperlinks wasn't detected yet.
+const UNKNOWN: u8 = 0;
+const UNSUPPORTED: u8 = 1;
+const SUPPORTED: u8 = 2;
+
+static HYPERLINKS: AtomicU8 = AtomicU8::new(UNKNOWN);
+
+/// Prints a hyperlink to the standard output.
+///
+/// The terminals supporting hyperlinks show the text, clicking it opens the URL (the `OSC 8`
+/// sequence). The other ones show `text (url)`, or the URL alone if it's the text as well. It's
+/// the same when the standard output isn't a terminal, the URL isn't lost in a file.
+///
+/// The support is detected once, see [`supports_hyperlinks`](fn.supports_hyperlinks.html).
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm::style::print_link;
+///
+/// print!("See the ");
+/// print_link("documentation", "https://docs.rs/crossterm")?;
+/// println!(".");
+/// # crossterm::Result::Ok(())
+/// ```
+pub fn print_link(text: impl Display, url: &str) -> Result<()> {
+    let mut stdout = io::stdout();
+    write_link(&mut stdout, text, url, supports_hyperlinks())?;
+    stdout.flush()
+}
+
+/// Returns whether the standard output is a terminal supporting hyperlinks.
+///
+/// The terminal is identified with the environment variables (`TERM_PROGRAM`, `VTE_VERSION`,
+/// `WT_SESSION`...), and on UNIX with [`query_version`](../terminal/fn.query_version.html) if
+/// they don't tell. The result is kept, the terminal is only queried once.
+pub fn supports_hyperlinks() -> bool {
+    match HYPERLINKS.load(Ordering::Relaxed) {
+        UNKNOWN => {
+            let supported = detect_hyperlinks();
+            HYPERLINKS.store(
+                if supported { SUPPORTED } else { UNSUPPORTED },
+                Ordering::Relaxed,
+            );
+            supported
+        }
+        support => support == SUPPORTED,
+    }
+}
+
+fn detect_hyperlinks() -> bool {
+    if !io::stdout().is_tty() {
+        return false;
+    }
+
+    if let Some(supported) = hyperlinks_from_environment(|name| env::var(name).ok()) {
+        return supported;
+    }
+
+    #[cfg(unix)]
+    if let Ok(Some(version)) = crate::terminal::query_version() {
+        return hyperlinks_from_version(&version);
+    }
+
+    false
+}
+
+/// Returns whether the terminal identified by the environment variables supports hyperlinks,
+/// `None` if they don't identify it.
+fn hyperlinks_from_environment(var: impl Fn(&str) -> Option<String>) -> Option<bool> {
+    // tmux and screen don't forward the links of the applications by default
+    if matches!(
+        multiplexer_from_environment(&var),
+        Some(Multiplexer::Tmux) | Some(Multiplexer::Screen)
+    ) {
+        return Some(false);
+    }
+
+    if var("DOMTERM").is_some() || var("WT_SESSION").is_some() || var("KONSOLE_VERSION").is_some() {
+        return Some(true);
+    }
+
+    // The VTE terminals (GNOME Terminal, Tilix...) support them since 0.50
+    if let Some(version) = var("VTE_VERSION") {
+        return Some(version.parse::<u32>().is_ok_and(|version| version >= 5000));
+    }
+
+    if let Some(program) = var("TERM_PROGRAM") {
+        return match program.as_str() {
+            "iTerm.app" | "WezTerm" | "vscode" | "Hyper" | "ghostty" => Some(true),
+            "Apple_Terminal" => Some(false),
+            _ => None,
+        };
+    }
+
+    match var("TERM")?.as_str() {
+        "xterm-kitty" | "foot" | "foot-extra" | "alacritty" | "xterm-ghostty" => Some(true),
+        "linux" | "dumb" => Some(false),
+        _ => None,
+    }
+}
+
+/// Returns whether the terminal named by `XTVERSION` supports hyperlinks.
+#[cfg(unix)]
+fn hyperlinks_from_version(version: &str) -> bool {
+    [
+        "kitty",
+        "WezTerm",
+        "foot",
+        "iTerm2",
+        "contour",
+        "ghostty",
+        "alacritty",
+    ]
+    .iter()
+    .any(|name| version.starts_with(name))
+}
+
+fn write_link(
+    writer: &mut impl Write,
+    text: impl Display,
+    url: &str,
+    hyperlinks: bool,
+) -> io::Result<()> {
+    let text = text.to_string();
+    if hyperlinks {
+        write!(writer, "\x1B]8;;{}\x1B\\{}\x1B]8;;\x1B\\", url, text)
+    } else if text == url {
+        writer.write_all(url.as_bytes())
+    } else {
+        write!(writer, "{} ({})", text, url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{hyperlinks_from_environment, write_link};
+
+    fn link(text: &str, url: &str, hyperlinks: bool) -> String {
+        let mut output = Vec::new();
+        write_link(&mut output, text, url, hyperlinks).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    fn from_environment(variables: &[(&str, &str)]) -> Option<bool> {
+        let variables: HashMap<&str, &str> = variables.iter().copied().collect();
+        hyperlinks_from_environment(|name| variables.get(name).map(|value| value.to_string()))
+    }
+
+    #[test]
+    fn test_write_link() {
+        assert_eq!(
+            link("docs", "https://docs.rs", true),
+            "\x1B]8;;https://docs.rs\x1B\\docs\x1B]8;;\x1B\\"
+        );
+        assert_eq!(
+            link("docs", "https://docs.rs", false),
+            "docs (https://docs.rs)"
+        );
+        assert_eq!(
+            link("https://docs.rs", "https://docs.rs", false),
+            "https://docs.rs"
+        );
+    }
+
+    #[test]
+    fn test_hyperlinks_from_environment() {
+        assert_eq!(from_environment(&[("TERM_PROGRAM", "WezTerm")]), Some(true));
+        assert_eq!(from_environment(&[("VTE_VERSION", "6003")]), Some(true));
+        assert_eq!(from_environment(&[("VTE_VERSION", "4601")]), Some(false));
+        assert_eq!(from_environment(&[("TERM", "xterm-kitty")]), Some(true));
+        assert_eq!(
+            from_environment(&[("TERM", "xterm-kitty"), ("TMUX", "/tmp/tmux")]),
+            Some(false)
+        );
+        assert_eq!(from_environment(&[("TERM", "xterm-256color")]), None);
+        assert_eq!(from_environment(&[]), None);
+    }
+}
@@ -1,10 +1,13 @@
 use std::convert::TryFrom;
 use std::sync::atomic::{AtomicU32, Ordering};
 
-use crossterm_winapi::{Console, Handle, HandleType, ScreenBuffer};
+use crossterm_winapi::{Console, ScreenBuffer};
 use winapi::um::wincon;
 
-use crate::Result;
+use crate::{
+    terminal::sys::{current_out_handle, register_ctrl_handler},
+    Result,
+};
 
 use super::super::{Color, Colored};
 
@@ -18,60 +21,45 @@ const BG_RED: u16 = wincon::BACKGROUND_RED;
 const BG_BLUE: u16 = wincon::BACKGROUND_BLUE;
 const BG_INTENSITY: u16 = wincon::BACKGROUND_INTENSITY;
 
-pub(crate) fn set_foreground_color(fg_color: Color) -> Result<()> {
-    init_console_color()?;
-
-    let color_value: u16 = Colored::ForegroundColor(fg_color).into();
-
-    let screen_buffer = ScreenBuffer::current()?;
-    let csbi = screen_buffer.info()?;
-
-    // Notice that the color values are stored in wAttribute.
-    // So we need to use bitwise operators to check if the values exists or to get current console colors.
-    let mut color: u16;
-    let attrs = csbi.attributes();
-    let bg_color = attrs & 0x0070;
-    color = color_value | bg_color;
-
-    // background intensity is a separate value in attrs,
-    // wee need to check if this was applied to the current bg color.
-    if (attrs & wincon::BACKGROUND_INTENSITY as u16) != 0 {
-        color |= wincon::BACKGROUND_INTENSITY as u16;
-    }
+const FG_MASK: u16 = FG_INTENSITY | FG_RED | FG_GREEN | FG_BLUE;
+const BG_MASK: u16 = BG_INTENSITY | BG_RED | BG_GREEN | BG_BLUE;
 
-    Console::from(screen_buffer.handle().clone()).set_text_attribute(color)?;
-    Ok(())
+pub(crate) fn set_foreground_color(fg_color: Color) -> Result<()> {
+    set_color(Colored::ForegroundColor(fg_color))
 }
 
 pub(crate) fn set_background_color(bg_color: Color) -> Result<()> {
-    init_console_color()?;
+    set_color(Colored::BackgroundColor(bg_color))
+}
 
-    let color_value: u16 = Colored::BackgroundColor(bg_color).into();
+fn set_color(colored: Colored) -> Result<()> {
+    init_console_color()?;
 
-    let screen_buffer = ScreenBuffer::current()?;
-    let csbi = screen_buffer.info()?;
+    let screen_buffer = ScreenBuffer::from(current_out_handle()?);
+    let attributes = screen_buffer.info()?.attributes();
 
-    // Notice that the color values are stored in wAttribute.
-    // So wee need to use bitwise operators to check if the values exists or to get current console colors.
-    let mut color: u16;
-    let attrs = csbi.attributes();
-    let fg_color = attrs & 0x0007;
-    color = fg_color | color_value;
+    Console::from(screen_buffer.handle().clone())
+        .set_text_attribute(replace_color(attributes, colored))?;
+    Ok(())
+}
 
-    // Foreground intensity is a separate value in attrs,
-    // So we need to check if this was applied to the current fg color.
-    if (attrs & wincon::FOREGROUND_INTENSITY as u16) != 0 {
-        color |= wincon::FOREGROUND_INTENSITY as u16;
-    }
+/// Replaces the foreground or the background color of the attributes.
+///
+/// The other color and the other attributes (underscore, reverse video, ...) are kept, a custom
+/// color scheme of the console stays intact.
+fn replace_color(attributes: u16, colored: Colored) -> u16 {
+    let mask = match colored {
+        Colored::ForegroundColor(_) => FG_MASK,
+        Colored::BackgroundColor(_) => BG_MASK,
+    };
 
-    Console::from(screen_buffer.handle().clone()).set_text_attribute(color)?;
-    Ok(())
+    u16::from(colored) | (attributes & !mask)
 }
 
+/// Restores the attributes the console had before crossterm changed its colors for the first time.
 pub(crate) fn reset() -> Result<()> {
     if let Ok(original_color) = u16::try_from(ORIGINAL_CONSOLE_COLOR.load(Ordering::Relaxed)) {
-        Console::from(Handle::new(HandleType::CurrentOutputHandle)?)
-            .set_text_attribute(original_color)?;
+        Console::from(current_out_handle()?).set_text_attribute(original_color)?;
     }
 
     Ok(())
@@ -80,9 +68,19 @@ pub(crate) fn reset() -> Result<()> {
 /// Initializes the default console color. It will will be skipped if it has already been initialized.
 pub(crate) fn init_console_color() -> Result<()> {
     if ORIGINAL_CONSOLE_COLOR.load(Ordering::Relaxed) == u32::MAX {
-        let screen_buffer = ScreenBuffer::current()?;
+        let screen_buffer = ScreenBuffer::from(current_out_handle()?);
         let attr = screen_buffer.info()?.attributes();
-        ORIGINAL_CONSOLE_COLOR.store(u32::from(attr), Ordering::Relaxed);
+
+        // Another thread may have been faster, the first color is the original one
+        let _ = ORIGINAL_CONSOLE_COLOR.compare_exchange(
+            u32::MAX,
+            u32::from(attr),
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+        );
+
+        // Restore the colors when the process is interrupted as well
+        register_ctrl_handler();
     }
 
     Ok(())
@@ -122,15 +120,8 @@ impl From<Colored> for u16 {
                     Color::White => FG_INTENSITY | FG_RED | FG_GREEN | FG_BLUE,
                     Color::Grey => FG_RED | FG_GREEN | FG_BLUE,
 
-                    Color::Reset => {
-                        // safe unwrap, initial console color was set with `init_console_color`.
-                        let original_color = original_console_color();
-
-                        const REMOVE_BG_MASK: u16 = BG_INTENSITY | BG_RED | BG_GREEN | BG_BLUE;
-                        // remove all background values from the original color, we don't want to reset those.
-
-                        original_color & !REMOVE_BG_MASK
-                    }
+                    // safe unwrap, initial console color was set with `init_console_color`.
+                    Color::Reset => original_console_color() & FG_MASK,
 
                     /* WinAPI will be used for systems that do not support ANSI, those are windows version less then 10. RGB and 255 (AnsiBValue) colors are not supported in that case.*/
                     Color::Rgb { .. } => 0,
@@ -156,14 +147,7 @@ impl From<Colored> for u16 {
                     Color::White => BG_INTENSITY | BG_RED | BG_GREEN | BG_BLUE,
                     Color::Grey => BG_RED | BG_GREEN | BG_BLUE,
 
-                    Color::Reset => {
-                        let original_color = original_console_color();
-
-                        const REMOVE_FG_MASK: u16 = FG_INTENSITY | FG_RED | FG_GREEN | FG_BLUE;
-                        // remove all foreground values from the original color, we don't want to reset those.
-
-                        original_color & !REMOVE_FG_MASK
-                    }
+                    Color::Reset => original_console_color() & BG_MASK,
                     /* WinAPI will be used for systems that do not support ANSI, those are windows version less then 10. RGB and 255 (AnsiBValue) colors are not supported in that case.*/
                     Color::Rgb { .. } => 0,
                     Color::AnsiValue(_val) => 0,
@@ -180,7 +164,8 @@ mod tests {
     use crate::style::sys::windows::set_foreground_color;
 
     use super::{
-        Color, Colored, BG_INTENSITY, BG_RED, FG_INTENSITY, FG_RED, ORIGINAL_CONSOLE_COLOR,
+        replace_color, Color, Colored, BG_BLUE, BG_INTENSITY, BG_RED, FG_GREEN, FG_INTENSITY,
+        FG_RED, ORIGINAL_CONSOLE_COLOR,
     };
 
     #[test]
@@ -195,6 +180,21 @@ mod tests {
         assert_eq!(Into::<u16>::into(colored), BG_INTENSITY | BG_RED);
     }
 
+    #[test]
+    fn test_replace_color_keeps_other_attributes() {
+        const REVERSE_VIDEO: u16 = 0x4000;
+        let attributes = REVERSE_VIDEO | BG_BLUE | FG_GREEN;
+
+        assert_eq!(
+            replace_color(attributes, Colored::ForegroundColor(Color::Red)),
+            REVERSE_VIDEO | BG_BLUE | FG_INTENSITY | FG_RED
+        );
+        assert_eq!(
+            replace_color(attributes, Colored::BackgroundColor(Color::Red)),
+            REVERSE_VIDEO | BG_INTENSITY | BG_RED | FG_GREEN
+        );
+    }
+
     #[test]
     fn test_original_console_color_is_set() {
         assert_eq!(ORIGINAL_CONSOLE_COLOR.load(Ordering::Relaxed), u32::MAX);
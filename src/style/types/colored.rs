@@ -3,6 +3,7 @@ use std::fmt::{self, Formatter};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+use crate::command::write_u16;
 use crate::style::{parse_next_u8, Color};
 
 /// Represents a foreground or background color.
@@ -61,11 +62,12 @@ impl Colored {
     }
 }
 
-impl fmt::Display for Colored {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+impl Colored {
+    /// Writes the SGR parameters of this color, as found within an `ESC [ <str> m` escape sequence.
+    pub(crate) fn write_parameters(self, f: &mut impl fmt::Write) -> fmt::Result {
         let color;
 
-        match *self {
+        match self {
             Colored::ForegroundColor(new_color) => {
                 if new_color == Color::Reset {
                     return f.write_str("39");
@@ -101,13 +103,29 @@ impl fmt::Display for Colored {
             Color::DarkCyan => f.write_str("5;6"),
             Color::White => f.write_str("5;15"),
             Color::Grey => f.write_str("5;7"),
-            Color::Rgb { r, g, b } => write!(f, "2;{};{};{}", r, g, b),
-            Color::AnsiValue(val) => write!(f, "5;{}", val),
+            Color::Rgb { r, g, b } => {
+                f.write_str("2;")?;
+                write_u16(f, r.into())?;
+                f.write_str(";")?;
+                write_u16(f, g.into())?;
+                f.write_str(";")?;
+                write_u16(f, b.into())
+            }
+            Color::AnsiValue(val) => {
+                f.write_str("5;")?;
+                write_u16(f, val.into())
+            }
             _ => Ok(()),
         }
     }
 }
 
+impl fmt::Display for Colored {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.write_parameters(f)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::style::{Color, Colored};
@@ -81,20 +81,61 @@
 //!
 //! For manual execution control check out [crossterm::queue](../macro.queue.html).
 
-use std::fmt;
+use std::{
+    fmt, io,
+    path::Path,
+    sync::atomic::{AtomicBool, Ordering},
+};
 
 #[cfg(windows)]
-use crossterm_winapi::{ConsoleMode, Handle, ScreenBuffer};
+use crossterm_winapi::{Handle, ScreenBuffer};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 #[cfg(windows)]
 use winapi::um::wincon::ENABLE_WRAP_AT_EOL_OUTPUT;
 
 #[doc(no_inline)]
-use crate::Command;
-use crate::{csi, impl_display, Result};
+use crate::{command::write_u16, Command, ExecutableCommand};
+use crate::{csi, impl_display, ErrorKind, Result};
 
+pub use self::{
+    log_writer::LogWriter,
+    multiplexer::{multiplexer, Multiplexer},
+    status_line::StatusLine,
+    viewport::Viewport,
+};
+
+pub(crate) use self::multiplexer::multiplexer_from_environment;
+
+mod log_writer;
+mod multiplexer;
+mod status_line;
 pub(crate) mod sys;
+mod viewport;
+
+/// Whether the last alternate screen command written was `EnterAlternateScreen`.
+static ALTERNATE_SCREEN: AtomicBool = AtomicBool::new(false);
+
+/// Returns whether the alternate screen was entered, as tracked by the alternate screen commands.
+pub(crate) fn is_alternate_screen_active() -> bool {
+    ALTERNATE_SCREEN.load(Ordering::Relaxed)
+}
+
+/// Whether the last `SetProgress` command written shows a progress.
+#[cfg(windows)]
+static PROGRESS_SHOWN: AtomicBool = AtomicBool::new(false);
+
+/// Hides the progress shown by `SetProgress`, when the process is interrupted.
+#[cfg(windows)]
+pub(crate) fn hide_progress() {
+    use std::io::Write;
+
+    if PROGRESS_SHOWN.swap(false, Ordering::Relaxed) {
+        let mut stdout = io::stdout();
+        let _ = write!(stdout, "{}", SetProgress(ProgressState::Hidden));
+        let _ = stdout.flush();
+    }
+}
 
 /// Enables raw mode.
 ///
@@ -110,6 +151,155 @@ pub fn disable_raw_mode() -> Result<()> {
     sys::disable_raw_mode()
 }
 
+/// Queries the primary device attributes of the terminal.
+///
+/// The first attribute is the conformance level (`62` for VT220, `65` for VT500...), the next
+/// ones are the supported features (`4` for sixel graphics, `22` for ANSI colors...). Every
+/// terminal emulator replies to this query.
+///
+/// The query is written to the standard output and the reply read from the input, like
+/// [`cursor::position`](../cursor/fn.position.html). Fails if the terminal doesn't reply within 2
+/// seconds.
+///
+/// Only available on UNIX.
+#[cfg(unix)]
+pub fn query_device_attributes() -> Result<Vec<u16>> {
+    sys::query_device_attributes()
+}
+
+/// Queries whether the terminal supports the DEC private mode and its status.
+///
+/// The private modes are the ones set with `ESC [ ? <mode> h`: `1049` for the alternate screen,
+/// `1006` for the SGR mouse encoding, `2004` for the bracketed paste... The terminals which don't
+/// implement the query (`DECRQM`) report every mode as `ModeStatus::NotRecognized`.
+///
+/// Fails like [`query_device_attributes`](fn.query_device_attributes.html).
+///
+/// Only available on UNIX.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm::terminal::{query_mode, ModeStatus};
+///
+/// let sgr_mouse = query_mode(1006)? != ModeStatus::NotRecognized;
+/// # crossterm::Result::Ok(())
+/// ```
+#[cfg(unix)]
+pub fn query_mode(mode: u16) -> Result<ModeStatus> {
+    sys::query_mode(mode)
+}
+
+/// Returns whether the terminal supports the
+/// [kitty keyboard protocol](https://sw.kovidgoyal.net/kitty/keyboard-protocol/), the
+/// enhancements pushed with
+/// [`PushKeyboardEnhancementFlags`](../event/struct.PushKeyboardEnhancementFlags.html).
+///
+/// The terminal is asked for the enhancements currently enabled (`ESC [ ? u`), the terminals
+/// which don't know the protocol only reply to the primary device attributes query sent after
+/// it. Fails like [`query_device_attributes`](fn.query_device_attributes.html) on UNIX, always
+/// returns `false` on Windows.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::stdout;
+///
+/// use crossterm::{
+///     event::{KeyboardEnhancementFlags, PushKeyboardEnhancementFlags},
+///     execute, terminal,
+/// };
+///
+/// if terminal::supports_keyboard_enhancement()? {
+///     execute!(
+///         stdout(),
+///         PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
+///     )?;
+/// }
+/// # crossterm::Result::Ok(())
+/// ```
+pub fn supports_keyboard_enhancement() -> Result<bool> {
+    #[cfg(unix)]
+    {
+        sys::supports_keyboard_enhancement()
+    }
+    #[cfg(not(unix))]
+    {
+        Ok(false)
+    }
+}
+
+/// Queries the name and version of the terminal emulator, `xterm(367)` or `WezTerm 20220101`
+/// for example.
+///
+/// Inside of tmux and screen, the query is wrapped in their passthrough sequence to identify the
+/// outer terminal. Returns `None` if the terminal doesn't implement the query (`XTVERSION`), or
+/// if the multiplexer doesn't forward it. Fails like
+/// [`query_device_attributes`](fn.query_device_attributes.html).
+///
+/// Only available on UNIX.
+#[cfg(unix)]
+pub fn query_version() -> Result<Option<String>> {
+    sys::query_version()
+}
+
+/// Queries the value of a terminfo capability from the terminal itself, rather than from the
+/// terminfo database of the machine the program runs on.
+///
+/// The names are the short terminfo names, with the extensions some terminals added: `Co` or
+/// `colors` for the number of colors, `RGB` or `Tc` for the true colors, `Sync` for the
+/// synchronized output, `Smulx` for the styled underlines, `TN` for the name of the terminal...
+/// The value of a boolean capability is an empty string. Returns `None` if the terminal doesn't
+/// know the capability or doesn't implement the query (`XTGETTCAP`, supported by xterm, kitty,
+/// foot, WezTerm and tmux among others). Fails like
+/// [`query_device_attributes`](fn.query_device_attributes.html).
+///
+/// Inside of a multiplexer, the multiplexer replies with its own capabilities, the ones the
+/// program can rely on.
+///
+/// Only available on UNIX.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm::terminal::query_capability;
+///
+/// let true_colors = query_capability("RGB")?.is_some() || query_capability("Tc")?.is_some();
+/// # crossterm::Result::Ok(())
+/// ```
+#[cfg(unix)]
+pub fn query_capability(name: &str) -> Result<Option<String>> {
+    sys::query_capability(name)
+}
+
+/// The status of a DEC private mode, returned by [`query_mode`](fn.query_mode.html).
+#[cfg(unix)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ModeStatus {
+    /// The terminal doesn't know the mode, or doesn't implement the query.
+    NotRecognized,
+    /// The mode is set.
+    Set,
+    /// The mode is reset.
+    Reset,
+    /// The mode is set and can't be changed.
+    PermanentlySet,
+    /// The mode is reset and can't be changed.
+    PermanentlyReset,
+}
+
+/// Restores the modes of the console to the state before crossterm changed them (raw mode, mouse
+/// capture, line wrapping, virtual terminal processing).
+///
+/// This happens automatically when the console window is closed or the process is interrupted
+/// with `Ctrl+C`/`Ctrl+Break`. Call it from a panic hook to leave a usable console behind after a
+/// panic as well.
+#[cfg(windows)]
+pub fn restore_console_modes() -> Result<()> {
+    sys::restore_console_modes()
+}
+
 /// Returns the terminal size `(columns, rows)`.
 ///
 /// The top left cell is represented `(1, 1)`.
@@ -117,6 +307,43 @@ pub fn size() -> Result<(u16, u16)> {
     sys::size()
 }
 
+/// The size of the terminal in cells and in pixels, see [`window_size`](fn.window_size.html).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WindowSize {
+    /// The number of columns.
+    pub columns: u16,
+    /// The number of rows.
+    pub rows: u16,
+    /// The width of the window in pixels, `0` if it isn't known.
+    pub width: u16,
+    /// The height of the window in pixels, `0` if it isn't known.
+    pub height: u16,
+}
+
+/// Returns the size of the terminal in cells and in pixels.
+///
+/// The size in pixels allows to compute the size of a cell, to display an image over a number of
+/// cells for example. It's the one the terminal reports with `TIOCGWINSZ`, some terminals (and
+/// the Linux console) leave it `0`. It's always `0` on Windows and in the browser.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm::{terminal, Result};
+///
+/// fn main() -> Result<()> {
+///     let size = terminal::window_size()?;
+///     if size.width > 0 && size.columns > 0 {
+///         println!("A cell is {} pixels wide", size.width / size.columns);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn window_size() -> Result<WindowSize> {
+    sys::window_size()
+}
+
 /// Disables line wrapping.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct DisableLineWrap;
@@ -128,10 +355,9 @@ impl Command for DisableLineWrap {
 
     #[cfg(windows)]
     fn execute_winapi(&self) -> Result<()> {
-        let screen_buffer = ScreenBuffer::current()?;
-        let console_mode = ConsoleMode::from(screen_buffer.handle().clone());
-        let new_mode = console_mode.mode()? & !ENABLE_WRAP_AT_EOL_OUTPUT;
-        console_mode.set_mode(new_mode)?;
+        sys::update_console_mode(&sys::current_out_handle()?, |mode| {
+            mode & !ENABLE_WRAP_AT_EOL_OUTPUT
+        })?;
         Ok(())
     }
 }
@@ -147,10 +373,9 @@ impl Command for EnableLineWrap {
 
     #[cfg(windows)]
     fn execute_winapi(&self) -> Result<()> {
-        let screen_buffer = ScreenBuffer::current()?;
-        let console_mode = ConsoleMode::from(screen_buffer.handle().clone());
-        let new_mode = console_mode.mode()? | ENABLE_WRAP_AT_EOL_OUTPUT;
-        console_mode.set_mode(new_mode)?;
+        sys::update_console_mode(&sys::current_out_handle()?, |mode| {
+            mode | ENABLE_WRAP_AT_EOL_OUTPUT
+        })?;
         Ok(())
     }
 }
@@ -182,6 +407,7 @@ pub struct EnterAlternateScreen;
 
 impl Command for EnterAlternateScreen {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        ALTERNATE_SCREEN.store(true, Ordering::Relaxed);
         f.write_str(csi!("?1049h"))
     }
 
@@ -189,6 +415,8 @@ impl Command for EnterAlternateScreen {
     fn execute_winapi(&self) -> Result<()> {
         let alternate_screen = ScreenBuffer::create()?;
         alternate_screen.show()?;
+        sys::reset_current_out_handle();
+        ALTERNATE_SCREEN.store(true, Ordering::Relaxed);
         Ok(())
     }
 }
@@ -220,6 +448,7 @@ pub struct LeaveAlternateScreen;
 
 impl Command for LeaveAlternateScreen {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        ALTERNATE_SCREEN.store(false, Ordering::Relaxed);
         f.write_str(csi!("?1049l"))
     }
 
@@ -227,6 +456,8 @@ impl Command for LeaveAlternateScreen {
     fn execute_winapi(&self) -> Result<()> {
         let screen_buffer = ScreenBuffer::from(Handle::current_out_handle()?);
         screen_buffer.show()?;
+        sys::reset_current_out_handle();
+        ALTERNATE_SCREEN.store(false, Ordering::Relaxed);
         Ok(())
     }
 }
@@ -258,7 +489,9 @@ pub struct ScrollUp(pub u16);
 impl Command for ScrollUp {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
         if self.0 != 0 {
-            write!(f, csi!("{}S"), self.0)?;
+            f.write_str(csi!())?;
+            write_u16(f, self.0)?;
+            f.write_str("S")?;
         }
         Ok(())
     }
@@ -267,6 +500,24 @@ impl Command for ScrollUp {
     fn execute_winapi(&self) -> Result<()> {
         sys::scroll_up(self.0)
     }
+
+    fn validate(&self, (_, rows): (u16, u16)) -> Result<()> {
+        validate_scroll_count(self.0, rows)
+    }
+}
+
+/// Scrolling more rows than the screen has is handled differently by the terminals.
+fn validate_scroll_count(count: u16, rows: u16) -> Result<()> {
+    if count > rows {
+        return Err(ErrorKind::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "can't scroll {} rows of a terminal with {} rows",
+                count, rows
+            ),
+        ));
+    }
+    Ok(())
 }
 
 /// A command that scrolls the terminal screen a given number of rows down.
@@ -280,7 +531,9 @@ pub struct ScrollDown(pub u16);
 impl Command for ScrollDown {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
         if self.0 != 0 {
-            write!(f, csi!("{}T"), self.0)?;
+            f.write_str(csi!())?;
+            write_u16(f, self.0)?;
+            f.write_str("T")?;
         }
         Ok(())
     }
@@ -289,6 +542,10 @@ impl Command for ScrollDown {
     fn execute_winapi(&self) -> Result<()> {
         sys::scroll_down(self.0)
     }
+
+    fn validate(&self, (_, rows): (u16, u16)) -> Result<()> {
+        validate_scroll_count(self.0, rows)
+    }
 }
 
 /// A command that clears the terminal screen buffer.
@@ -328,13 +585,27 @@ pub struct SetSize(pub u16, pub u16);
 
 impl Command for SetSize {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
-        write!(f, csi!("8;{};{}t"), self.1, self.0)
+        f.write_str(csi!("8;"))?;
+        write_u16(f, self.1)?;
+        f.write_str(";")?;
+        write_u16(f, self.0)?;
+        f.write_str("t")
     }
 
     #[cfg(windows)]
     fn execute_winapi(&self) -> Result<()> {
         sys::set_size(self.0, self.1)
     }
+
+    fn validate(&self, _size: (u16, u16)) -> Result<()> {
+        if self.0 == 0 || self.1 == 0 {
+            return Err(ErrorKind::new(
+                io::ErrorKind::InvalidInput,
+                format!("terminal size {}x{} is empty", self.0, self.1),
+            ));
+        }
+        Ok(())
+    }
 }
 
 /// A command that sets the terminal title
@@ -356,18 +627,326 @@ impl<T: fmt::Display> Command for SetTitle<T> {
     }
 }
 
+/// A command that reports the current working directory to the terminal.
+///
+/// The terminals open the new tabs and windows in that directory, like they do when a shell runs.
+/// The path should be absolute, it's sent as a `file://` URL with the host name.
+///
+/// # Notes
+///
+/// * Commands must be executed/queued for execution otherwise they do nothing.
+/// * It's the `OSC 7` sequence, the terminals which don't support it ignore it.
+/// * The Windows versions without ANSI support don't support it, nothing happens.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::{env, io::stdout};
+///
+/// use crossterm::{execute, terminal::ReportCwd, Result};
+///
+/// fn main() -> Result<()> {
+///     execute!(stdout(), ReportCwd(env::current_dir()?))
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReportCwd<P>(pub P);
+
+impl<P: AsRef<Path>> Command for ReportCwd<P> {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str("\x1B]7;")?;
+        write_file_url(f, &sys::hostname(), self.0.as_ref())?;
+        f.write_str("\x07")
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Writes the path as a `file://` URL, the bytes which aren't allowed in a URL are
+/// percent-encoded.
+fn write_file_url(f: &mut impl fmt::Write, host: &str, path: &Path) -> fmt::Result {
+    f.write_str("file://")?;
+    write_percent_encoded(f, host.as_bytes())?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+
+        write_percent_encoded(f, path.as_os_str().as_bytes())
+    }
+
+    #[cfg(not(unix))]
+    {
+        // `C:\Users` is `/C:/Users` on Windows
+        let path = path.to_string_lossy().replace('\\', "/");
+        if !path.starts_with('/') {
+            f.write_char('/')?;
+        }
+        write_percent_encoded(f, path.as_bytes())
+    }
+}
+
+fn write_percent_encoded(f: &mut impl fmt::Write, bytes: &[u8]) -> fmt::Result {
+    for &byte in bytes {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                f.write_char(byte as char)?
+            }
+            _ => write!(f, "%{:02X}", byte)?,
+        }
+    }
+    Ok(())
+}
+
+/// A command that marks the start of a prompt, for the shell integration of the terminals.
+///
+/// The prompt marks let the terminals jump between the prompts, select the output of a command...
+/// A prompt is written after `PromptStart`, the user types the command after
+/// [`CommandStart`](struct.CommandStart.html), the command writes its output after
+/// [`CommandExecuted`](struct.CommandExecuted.html) and ends with
+/// [`CommandFinished`](struct.CommandFinished.html).
+///
+/// # Notes
+///
+/// * Commands must be executed/queued for execution otherwise they do nothing.
+/// * It's the `OSC 133` sequence of FinalTerm, the terminals which don't support it ignore it.
+/// * The Windows versions without ANSI support don't support it, nothing happens.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::stdout;
+///
+/// use crossterm::{
+///     execute,
+///     style::Print,
+///     terminal::{CommandExecuted, CommandFinished, CommandStart, PromptStart},
+///     Result,
+/// };
+///
+/// fn main() -> Result<()> {
+///     execute!(stdout(), PromptStart, Print("> "), CommandStart)?;
+///     let mut command = String::new();
+///     std::io::stdin().read_line(&mut command)?;
+///
+///     execute!(stdout(), CommandExecuted, Print(command), CommandFinished(Some(0)))
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PromptStart;
+
+impl Command for PromptStart {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str("\x1B]133;A\x07")
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A command that marks the end of a prompt, where the user types the command.
+///
+/// See [`PromptStart`](struct.PromptStart.html).
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommandStart;
+
+impl Command for CommandStart {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str("\x1B]133;B\x07")
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A command that marks the start of the output of a command.
+///
+/// See [`PromptStart`](struct.PromptStart.html).
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommandExecuted;
+
+impl Command for CommandExecuted {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str("\x1B]133;C\x07")
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A command that marks the end of a command, with its exit code if any.
+///
+/// The terminals show the commands which failed, with an exit code other than 0. See
+/// [`PromptStart`](struct.PromptStart.html).
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommandFinished(pub Option<i32>);
+
+impl Command for CommandFinished {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        match self.0 {
+            Some(code) => write!(f, "\x1B]133;D;{}\x07", code),
+            None => f.write_str("\x1B]133;D\x07"),
+        }
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// The progress shown by [`SetProgress`](struct.SetProgress.html), in percent.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProgressState {
+    /// No progress is shown.
+    Hidden,
+    /// The progress of an operation, from 0 to 100.
+    Normal(u8),
+    /// The progress of an operation which failed, from 0 to 100.
+    Error(u8),
+    /// An operation whose progress isn't known.
+    Indeterminate,
+    /// The progress of an operation which is paused, from 0 to 100.
+    Paused(u8),
+}
+
+/// A command that shows the progress of an operation in the taskbar or the tab of the terminal.
+///
+/// The progress stays shown until it's hidden with `ProgressState::Hidden`, even after the
+/// process exited. [`ProgressGuard`](struct.ProgressGuard.html) hides it when it's dropped.
+///
+/// # Notes
+///
+/// * Commands must be executed/queued for execution otherwise they do nothing.
+/// * It's the `OSC 9;4` sequence of ConEmu, supported by Windows Terminal and a few other
+///   terminals. The other terminals ignore it.
+/// * A progress larger than 100 is shown as 100.
+/// * On Windows, the progress is hidden when the process is interrupted with `Ctrl+C`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::stdout;
+///
+/// use crossterm::{execute, terminal::{ProgressState, SetProgress}, Result};
+///
+/// fn main() -> Result<()> {
+///     execute!(stdout(), SetProgress(ProgressState::Normal(42)))
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetProgress(pub ProgressState);
+
+impl Command for SetProgress {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        #[cfg(windows)]
+        {
+            PROGRESS_SHOWN.store(self.0 != ProgressState::Hidden, Ordering::Relaxed);
+            sys::register_ctrl_handler();
+        }
+
+        let (state, progress) = match self.0 {
+            ProgressState::Hidden => (0, 0),
+            ProgressState::Normal(progress) => (1, progress),
+            ProgressState::Error(progress) => (2, progress),
+            ProgressState::Indeterminate => (3, 0),
+            ProgressState::Paused(progress) => (4, progress),
+        };
+        write!(f, "\x1B]9;4;{};{}\x07", state, progress.min(100))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Shows a progress with [`SetProgress`](struct.SetProgress.html) and hides it when it's dropped.
+///
+/// The progress is hidden when the operation ends, even if it ends with an error or a panic.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::stdout;
+///
+/// use crossterm::{terminal::{ProgressGuard, ProgressState}, Result};
+///
+/// fn main() -> Result<()> {
+///     let mut progress = ProgressGuard::new(stdout());
+///     for percent in 0..=100 {
+///         progress.set(ProgressState::Normal(percent))?;
+///         // ...
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ProgressGuard<W: io::Write> {
+    writer: W,
+}
+
+impl<W: io::Write> ProgressGuard<W> {
+    /// Creates the guard writing the progress to the writer, no progress is shown yet.
+    pub fn new(writer: W) -> ProgressGuard<W> {
+        ProgressGuard { writer }
+    }
+
+    /// Shows the progress.
+    pub fn set(&mut self, state: ProgressState) -> Result<()> {
+        self.writer.execute(SetProgress(state))?;
+        Ok(())
+    }
+}
+
+impl<W: io::Write> Drop for ProgressGuard<W> {
+    fn drop(&mut self) {
+        let _ = self.set(ProgressState::Hidden);
+    }
+}
+
 impl_display!(for ScrollUp);
 impl_display!(for ScrollDown);
 impl_display!(for SetSize);
 impl_display!(for Clear);
+impl_display!(for SetProgress);
+impl_display!(for PromptStart);
+impl_display!(for CommandStart);
+impl_display!(for CommandExecuted);
+impl_display!(for CommandFinished);
 
 #[cfg(test)]
 mod tests {
-    use std::{io::stdout, thread, time};
+    use std::{io::stdout, path::Path, thread, time};
 
-    use crate::execute;
+    use crate::{execute, Command};
 
-    use super::{size, SetSize};
+    use super::{
+        size, write_file_url, CommandExecuted, CommandFinished, CommandStart, ProgressGuard,
+        ProgressState, PromptStart, ScrollDown, ScrollUp, SetProgress, SetSize,
+    };
 
     // Test is disabled, because it's failing on Travis CI
     #[test]
@@ -390,4 +969,65 @@ mod tests {
 
         assert_eq!((width, height), size().unwrap());
     }
+
+    #[test]
+    fn test_validate() {
+        assert!(ScrollUp(24).validate((80, 24)).is_ok());
+        assert!(ScrollUp(25).validate((80, 24)).is_err());
+        assert!(ScrollDown(25).validate((80, 24)).is_err());
+
+        assert!(SetSize(1, 1).validate((80, 24)).is_ok());
+        assert!(SetSize(0, 24).validate((80, 24)).is_err());
+        assert!(SetSize(80, 0).validate((80, 24)).is_err());
+    }
+
+    #[test]
+    fn test_set_progress() {
+        for (state, ansi) in &[
+            (ProgressState::Hidden, "\x1B]9;4;0;0\x07"),
+            (ProgressState::Normal(42), "\x1B]9;4;1;42\x07"),
+            (ProgressState::Error(200), "\x1B]9;4;2;100\x07"),
+            (ProgressState::Indeterminate, "\x1B]9;4;3;0\x07"),
+            (ProgressState::Paused(7), "\x1B]9;4;4;7\x07"),
+        ] {
+            assert_eq!(SetProgress(*state).to_string(), *ansi);
+        }
+    }
+
+    #[test]
+    fn test_progress_guard_hides_the_progress() {
+        let mut output = Vec::new();
+        {
+            let mut progress = ProgressGuard::new(&mut output);
+            progress.set(ProgressState::Indeterminate).unwrap();
+            progress.set(ProgressState::Normal(50)).unwrap();
+        }
+
+        assert_eq!(output, b"\x1B]9;4;3;0\x07\x1B]9;4;1;50\x07\x1B]9;4;0;0\x07");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_file_url() {
+        let mut url = String::new();
+        write_file_url(&mut url, "host", Path::new("/home/me/my files/100%/é")).unwrap();
+        assert_eq!(url, "file://host/home/me/my%20files/100%25/%C3%A9");
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_file_url() {
+        let mut url = String::new();
+        write_file_url(&mut url, "HOST", Path::new("C:\\Users\\me\\my files")).unwrap();
+        assert_eq!(url, "file://HOST/C:/Users/me/my%20files");
+    }
+
+    #[test]
+    fn test_prompt_marks() {
+        assert_eq!(PromptStart.to_string(), "\x1B]133;A\x07");
+        assert_eq!(CommandStart.to_string(), "\x1B]133;B\x07");
+        assert_eq!(CommandExecuted.to_string(), "\x1B]133;C\x07");
+        assert_eq!(CommandFinished(Some(-1)).to_string(), "\x1B]133;D;-1\x07");
+        assert_eq!(CommandFinished(None).to_string(), "\x1B]133;D\x07");
+    }
 }
@@ -0,0 +1,257 @@
+use std::{
+    io::{self, Write},
+    mem,
+    sync::Arc,
+};
+
+use parking_lot::Mutex;
+
+use crate::{
+    command::write_command_ansi,
+    cursor::{MoveTo, RestorePosition, SavePosition},
+};
+
+/// A writer for the log output (`env_logger`, `tracing`, ...) of an application using the raw
+/// mode or the alternate screen.
+///
+/// The terminal doesn't return to the first column on `\n` in raw mode, the log lines end up
+/// stair-stepped. The writer keeps them readable:
+///
+/// * By default, `\n` is written as `\r\n` while the raw mode is enabled or the alternate screen
+///   is active.
+/// * [`set_region`](#method.set_region) writes the lines in rows reserved for them, with a scroll
+///   region. The cursor of the UI is restored after every line.
+/// * [`pause`](#method.pause) keeps the lines in memory until [`resume`](#method.resume), while
+///   the UI draws or when the log shouldn't appear on its screen at all.
+///
+/// The writer is cloned to keep a handle, the clones share the output and the settings.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::{stderr, Write};
+///
+/// use crossterm::{terminal::{self, LogWriter}, Result};
+///
+/// fn main() -> Result<()> {
+///     let log = LogWriter::new(stderr());
+///     // Give a clone to the logger, `env_logger::Target::Pipe(Box::new(log.clone()))`
+///
+///     let (_, rows) = terminal::size()?;
+///     // The last 3 rows of the screen show the log
+///     log.set_region(Some((rows - 3, rows - 1)));
+///
+///     writeln!(log.clone(), "Started")?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct LogWriter<W: Write> {
+    state: Arc<Mutex<State<W>>>,
+}
+
+#[derive(Debug)]
+struct State<W: Write> {
+    writer: W,
+    /// The rows `(top, bottom)` the lines are written in.
+    region: Option<(u16, u16)>,
+    paused: bool,
+    /// The output written while paused.
+    held: Vec<u8>,
+    /// The beginning of a line written in the region, without its `\n` yet.
+    partial_line: Vec<u8>,
+    last_byte: Option<u8>,
+}
+
+impl<W: Write> LogWriter<W> {
+    /// Creates a writer writing the lines to the writer, where the cursor is.
+    pub fn new(writer: W) -> LogWriter<W> {
+        LogWriter {
+            state: Arc::new(Mutex::new(State {
+                writer,
+                region: None,
+                paused: false,
+                held: Vec::new(),
+                partial_line: Vec::new(),
+                last_byte: None,
+            })),
+        }
+    }
+
+    /// Writes the lines in the rows `top..=bottom` (0-based) of the screen, `None` writes them
+    /// where the cursor is again.
+    ///
+    /// Every line is written on the bottom row, the rows above scroll up. The rest of the screen
+    /// isn't touched, but the scroll region of the UI is reset.
+    pub fn set_region(&self, region: Option<(u16, u16)>) {
+        self.state.lock().region = region;
+    }
+
+    /// Keeps the lines in memory until [`resume`](#method.resume).
+    pub fn pause(&self) {
+        self.state.lock().paused = true;
+    }
+
+    /// Writes the lines kept since [`pause`](#method.pause) and the next ones right away.
+    pub fn resume(&self) -> io::Result<()> {
+        let mut state = self.state.lock();
+        state.paused = false;
+
+        let held = mem::take(&mut state.held);
+        state.output(&held, needs_carriage_return())?;
+        state.writer.flush()
+    }
+}
+
+impl<W: Write> Clone for LogWriter<W> {
+    fn clone(&self) -> LogWriter<W> {
+        LogWriter {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<W: Write> Write for LogWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut state = self.state.lock();
+        if state.paused {
+            state.held.extend_from_slice(buf);
+        } else {
+            state.output(buf, needs_carriage_return())?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.state.lock().writer.flush()
+    }
+}
+
+impl<W: Write> State<W> {
+    /// Writes the bytes, `\n` is written as `\r\n` if `carriage_return` is set.
+    fn output(&mut self, buf: &[u8], carriage_return: bool) -> io::Result<()> {
+        if let Some((top, bottom)) = self.region {
+            return self.output_in_region(buf, top, bottom);
+        }
+
+        if !carriage_return {
+            self.last_byte = buf.last().copied().or(self.last_byte);
+            return self.writer.write_all(buf);
+        }
+
+        // Written at once, the standard error isn't buffered
+        let mut translated = Vec::with_capacity(buf.len() + 8);
+        for byte in buf {
+            if *byte == b'\n' && self.last_byte != Some(b'\r') {
+                translated.push(b'\r');
+            }
+            translated.push(*byte);
+            self.last_byte = Some(*byte);
+        }
+        self.writer.write_all(&translated)
+    }
+
+    fn output_in_region(&mut self, buf: &[u8], top: u16, bottom: u16) -> io::Result<()> {
+        self.partial_line.extend_from_slice(buf);
+
+        while let Some(end) = self.partial_line.iter().position(|byte| *byte == b'\n') {
+            let rest = self.partial_line.split_off(end + 1);
+            let mut line = mem::replace(&mut self.partial_line, rest);
+            line.pop();
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+
+            write_command_ansi(&mut self.writer, SavePosition)?;
+            // The scroll region moves the cursor, it's moved to the bottom row afterwards
+            write!(self.writer, "\x1B[{};{}r", top + 1, bottom + 1)?;
+            write_command_ansi(&mut self.writer, MoveTo(0, bottom))?;
+            // Scrolls the region up
+            self.writer.write_all(b"\n\r")?;
+            self.writer.write_all(&line)?;
+            self.writer.write_all(b"\x1B[r")?;
+            write_command_ansi(&mut self.writer, RestorePosition)?;
+        }
+        self.last_byte = Some(b'\n');
+        Ok(())
+    }
+}
+
+impl<W: Write> Drop for State<W> {
+    fn drop(&mut self) {
+        // Don't lose the log of a program exiting while paused
+        if !self.held.is_empty() {
+            let held = mem::take(&mut self.held);
+            let _ = self.output(&held, needs_carriage_return());
+            let _ = self.writer.flush();
+        }
+    }
+}
+
+/// Returns whether `\n` doesn't return to the first column.
+fn needs_carriage_return() -> bool {
+    super::sys::is_raw_mode_enabled() || super::is_alternate_screen_active()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::{LogWriter, State};
+
+    fn output(writer: &LogWriter<Vec<u8>>) -> String {
+        String::from_utf8(writer.state.lock().writer.clone()).unwrap()
+    }
+
+    #[test]
+    fn test_carriage_return() {
+        let mut state = State {
+            writer: Vec::new(),
+            region: None,
+            paused: false,
+            held: Vec::new(),
+            partial_line: Vec::new(),
+            last_byte: None,
+        };
+
+        state.output(b"first\nsecond\r\nthird", true).unwrap();
+        state.output(b"\n", true).unwrap();
+        state.output(b"fourth\n", false).unwrap();
+
+        assert_eq!(
+            String::from_utf8(state.writer.clone()).unwrap(),
+            "first\r\nsecond\r\nthird\r\nfourth\n"
+        );
+    }
+
+    #[test]
+    fn test_region() {
+        let mut writer = LogWriter::new(Vec::new());
+        writer.set_region(Some((20, 23)));
+
+        write!(writer, "first ").unwrap();
+        assert_eq!(output(&writer), "");
+
+        writeln!(writer, "line").unwrap();
+        assert_eq!(
+            output(&writer),
+            "\x1B7\x1B[21;24r\x1B[24;1H\n\rfirst line\x1B[r\x1B8"
+        );
+    }
+
+    #[test]
+    fn test_pause() {
+        let mut writer = LogWriter::new(Vec::new());
+        writer.set_region(Some((0, 1)));
+
+        writer.pause();
+        writeln!(writer, "held").unwrap();
+        assert_eq!(output(&writer), "");
+
+        writer.resume().unwrap();
+        assert_eq!(
+            output(&writer),
+            "\x1B7\x1B[1;2r\x1B[2;1H\n\rheld\x1B[r\x1B8"
+        );
+    }
+}
@@ -0,0 +1,116 @@
+use std::{env, fmt};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A terminal multiplexer, returned by [`multiplexer`](fn.multiplexer.html).
+///
+/// The multiplexers are terminals themselves, drawing the programs running in them on the outer
+/// terminal. crossterm adjusts to them:
+///
+/// * The default [escape timeout](../event/fn.set_escape_timeout.html) is longer, the
+///   multiplexers can split the key sequences they forward.
+/// * The sequences the multiplexer doesn't forward are wrapped in its passthrough sequence, see
+///   [`CopyToClipboard`](../clipboard/struct.CopyToClipboard.html).
+/// * [`query_version`](fn.query_version.html) reports the outer terminal, when the multiplexer
+///   forwards the query.
+/// * [`graphics::query_protocol`](../graphics/fn.query_protocol.html) only queries the protocols
+///   the multiplexer displays itself.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Multiplexer {
+    /// tmux, detected with the `TMUX` environment variable.
+    Tmux,
+    /// GNU screen, detected with the `STY` environment variable.
+    Screen,
+    /// Zellij, detected with the `ZELLIJ` environment variable.
+    Zellij,
+}
+
+impl Multiplexer {
+    /// Writes the sequence so that it reaches the outer terminal.
+    ///
+    /// Zellij doesn't have a passthrough sequence, the sequence is written as is.
+    pub(crate) fn write_passthrough(self, f: &mut impl fmt::Write, sequence: &str) -> fmt::Result {
+        match self {
+            // tmux forwards the content of `ESC P tmux ; ... ESC \`, with the `ESC`s doubled
+            Multiplexer::Tmux => write!(
+                f,
+                "\x1BPtmux;{}\x1B\\",
+                sequence.replace('\x1B', "\x1B\x1B")
+            ),
+            // screen forwards the content of `ESC P ... ESC \`
+            Multiplexer::Screen => write!(f, "\x1BP{}\x1B\\", sequence),
+            Multiplexer::Zellij => f.write_str(sequence),
+        }
+    }
+}
+
+/// Returns the terminal multiplexer this process runs in.
+///
+/// The multiplexer is identified with its environment variables, a program started with `sudo`
+/// or over SSH from a multiplexer doesn't see them.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm::terminal::{multiplexer, Multiplexer};
+///
+/// if multiplexer() == Some(Multiplexer::Tmux) {
+///     println!("Enable `allow-passthrough` to copy to the clipboard");
+/// }
+/// ```
+pub fn multiplexer() -> Option<Multiplexer> {
+    multiplexer_from_environment(|name| env::var(name).ok())
+}
+
+/// Returns the multiplexer identified by the environment variables.
+pub(crate) fn multiplexer_from_environment(
+    var: impl Fn(&str) -> Option<String>,
+) -> Option<Multiplexer> {
+    // The innermost multiplexer sets its variable last, but they rarely nest
+    if var("TMUX").is_some() {
+        Some(Multiplexer::Tmux)
+    } else if var("ZELLIJ").is_some() {
+        Some(Multiplexer::Zellij)
+    } else if var("STY").is_some() {
+        Some(Multiplexer::Screen)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{multiplexer_from_environment, Multiplexer};
+
+    #[test]
+    fn test_multiplexer_from_environment() {
+        let detect = |name: &'static str| {
+            multiplexer_from_environment(|var| Some(String::new()).filter(|_| var == name))
+        };
+
+        assert_eq!(detect("TMUX"), Some(Multiplexer::Tmux));
+        assert_eq!(detect("STY"), Some(Multiplexer::Screen));
+        assert_eq!(detect("ZELLIJ"), Some(Multiplexer::Zellij));
+        assert_eq!(detect("TERM"), None);
+    }
+
+    #[test]
+    fn test_passthrough() {
+        let passthrough = |multiplexer: Multiplexer| {
+            let mut sequence = String::new();
+            multiplexer
+                .write_passthrough(&mut sequence, "\x1B[>0q")
+                .unwrap();
+            sequence
+        };
+
+        assert_eq!(
+            passthrough(Multiplexer::Tmux),
+            "\x1BPtmux;\x1B\x1B[>0q\x1B\\"
+        );
+        assert_eq!(passthrough(Multiplexer::Screen), "\x1BP\x1B[>0q\x1B\\");
+        assert_eq!(passthrough(Multiplexer::Zellij), "\x1B[>0q");
+    }
+}
@@ -0,0 +1,215 @@
+use std::{
+    fmt::Display,
+    io::{self, Write},
+    sync::Arc,
+};
+
+use parking_lot::Mutex;
+
+use crate::{
+    command::write_command_ansi,
+    cursor::{MoveTo, MoveUp, RestorePosition, SavePosition},
+    style::{Attribute, SetAttribute},
+    terminal::{Clear, ClearType},
+    text, Result,
+};
+
+/// A status line on the bottom row of the screen, kept in place while the rest of the output
+/// scrolls.
+///
+/// The bottom row is reserved with a scroll region, the output written above it scrolls without
+/// moving the status line. [`set`](#method.set) updates it from any thread: the cursor and its
+/// style are saved before and restored after, the output continues where it was.
+///
+/// The status line is also the writer of the rest of the output. The output and the updates are
+/// written one after the other, an update never splits a command queued to the status line. The
+/// status line is cloned to keep a handle, the clones share the output and the content.
+///
+/// When the last clone is dropped, also when unwinding after a panic, the scroll region is reset
+/// and the status line cleared.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::{stdout, Write};
+///
+/// use crossterm::{style::Stylize, terminal::StatusLine, Result};
+///
+/// fn main() -> Result<()> {
+///     let mut status = StatusLine::new(stdout())?;
+///
+///     for file in 1..=3 {
+///         status.set(format!("Downloading {}/3", file).reverse())?;
+///         writeln!(status, "Downloaded file {}", file)?;
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct StatusLine<W: Write> {
+    state: Arc<Mutex<State<W>>>,
+}
+
+#[derive(Debug)]
+struct State<W: Write> {
+    writer: W,
+    /// The size of the screen, the status line is on the last row.
+    size: (u16, u16),
+    content: String,
+}
+
+impl<W: Write> StatusLine<W> {
+    /// Reserves the bottom row of the screen for the status line, and writes the output to the
+    /// writer.
+    ///
+    /// The line the cursor is on scrolls up if it was on the bottom row.
+    pub fn new(writer: W) -> Result<StatusLine<W>> {
+        StatusLine::with_size(writer, super::size()?)
+    }
+
+    fn with_size(writer: W, size: (u16, u16)) -> Result<StatusLine<W>> {
+        let mut state = State {
+            writer,
+            size,
+            content: String::new(),
+        };
+        // Makes room for the status line, the cursor stays on the same line of the output
+        state.writer.write_all(b"\x1BD")?;
+        write_command_ansi(&mut state.writer, MoveUp(1))?;
+        state.reserve()?;
+        state.writer.flush()?;
+
+        Ok(StatusLine {
+            state: Arc::new(Mutex::new(state)),
+        })
+    }
+
+    /// Shows the content on the status line, styled content works as well.
+    ///
+    /// The content is truncated to the width of the screen, it should be a single line.
+    pub fn set(&self, content: impl Display) -> Result<()> {
+        let mut state = self.state.lock();
+        state.content = content.to_string();
+        state.draw()?;
+        state.writer.flush()
+    }
+
+    /// Clears the status line.
+    pub fn clear(&self) -> Result<()> {
+        self.set("")
+    }
+
+    /// Moves the status line to the bottom row of the resized screen, call it on
+    /// `Event::Resize`.
+    pub fn resize(&self, columns: u16, rows: u16) -> Result<()> {
+        let mut state = self.state.lock();
+        state.size = (columns, rows);
+        state.reserve()?;
+        state.writer.flush()
+    }
+}
+
+impl<W: Write> Clone for StatusLine<W> {
+    fn clone(&self) -> StatusLine<W> {
+        StatusLine {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<W: Write> Write for StatusLine<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.state.lock().writer.write(buf)
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.state.lock().writer.write_all(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.state.lock().writer.flush()
+    }
+}
+
+impl<W: Write> State<W> {
+    /// Sets the scroll region above the status line and draws it.
+    fn reserve(&mut self) -> Result<()> {
+        let rows = self.size.1;
+        // The scroll region moves the cursor to the top left cell
+        write_command_ansi(&mut self.writer, SavePosition)?;
+        write!(self.writer, "\x1B[1;{}r", rows.saturating_sub(1).max(1))?;
+        write_command_ansi(&mut self.writer, RestorePosition)?;
+        self.draw()
+    }
+
+    fn draw(&mut self) -> Result<()> {
+        let (columns, rows) = self.size;
+        let content = text::truncate_with_ellipsis(&self.content, columns as usize);
+
+        // Saves the style of the output with the cursor
+        write_command_ansi(&mut self.writer, SavePosition)?;
+        write_command_ansi(&mut self.writer, MoveTo(0, rows.saturating_sub(1)))?;
+        write_command_ansi(&mut self.writer, SetAttribute(Attribute::Reset))?;
+        // Cleared first, clearing after a character written in the last column erases it
+        write_command_ansi(&mut self.writer, Clear(ClearType::UntilNewLine))?;
+        self.writer.write_all(content.as_bytes())?;
+        write_command_ansi(&mut self.writer, SetAttribute(Attribute::Reset))?;
+        write_command_ansi(&mut self.writer, RestorePosition)
+    }
+}
+
+impl<W: Write> Drop for State<W> {
+    fn drop(&mut self) {
+        let _ = write_command_ansi(&mut self.writer, SavePosition);
+        let _ = self.writer.write_all(b"\x1B[r");
+        let _ = write_command_ansi(&mut self.writer, MoveTo(0, self.size.1.saturating_sub(1)));
+        let _ = write_command_ansi(&mut self.writer, Clear(ClearType::CurrentLine));
+        let _ = write_command_ansi(&mut self.writer, RestorePosition);
+        let _ = self.writer.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Write, mem};
+
+    use crate::style::Stylize;
+
+    use super::StatusLine;
+
+    fn take_output(status: &StatusLine<Vec<u8>>) -> String {
+        String::from_utf8(mem::take(&mut status.state.lock().writer)).unwrap()
+    }
+
+    #[test]
+    fn test_reserve_and_set() {
+        let status = StatusLine::with_size(Vec::new(), (10, 24)).unwrap();
+        assert_eq!(
+            take_output(&status),
+            "\x1BD\x1B[1A\x1B7\x1B[1;23r\x1B8\x1B7\x1B[24;1H\x1B[0m\x1B[K\x1B[0m\x1B8"
+        );
+
+        status.set("Downloading".bold()).unwrap();
+        assert_eq!(
+            take_output(&status),
+            "\x1B7\x1B[24;1H\x1B[0m\x1B[K\x1B[1mDownloadi…\x1B[0m\x1B[0m\x1B8"
+        );
+
+        let mut output = status.clone();
+        write!(output, "line").unwrap();
+        assert_eq!(take_output(&status), "line");
+    }
+
+    #[test]
+    fn test_resize() {
+        let status = StatusLine::with_size(Vec::new(), (10, 24)).unwrap();
+        status.set("ok").unwrap();
+        take_output(&status);
+
+        status.resize(10, 10).unwrap();
+        assert_eq!(
+            take_output(&status),
+            "\x1B7\x1B[1;9r\x1B8\x1B7\x1B[10;1H\x1B[0m\x1B[Kok\x1B[0m\x1B8"
+        );
+    }
+}
@@ -1,15 +1,34 @@
 //! This module provides platform related functions.
 
+#[cfg(all(unix, test))]
+pub(crate) use self::unix::raw_terminal_attr;
+#[cfg(all(unix, feature = "graphics"))]
+pub(crate) use self::unix::{cell_size, query};
 #[cfg(unix)]
-pub(crate) use self::unix::{disable_raw_mode, enable_raw_mode, is_raw_mode_enabled, size};
+pub(crate) use self::unix::{
+    disable_raw_mode, enable_raw_mode, hostname, is_raw_mode_enabled, query_capability,
+    query_clipboard, query_device_attributes, query_mode, query_version, reapply_raw_mode, size,
+    size_from_fd, supports_keyboard_enhancement, terminal_modes, window_size,
+};
 #[cfg(windows)]
 pub(crate) use self::windows::{
-    clear, disable_raw_mode, enable_raw_mode, scroll_down, scroll_up, set_size, set_window_title,
-    size,
+    clear, current_in_handle, current_out_handle, disable_raw_mode, enable_raw_mode, hostname,
+    is_raw_mode_enabled, is_virtual_terminal_input_enabled, register_ctrl_handler,
+    reset_current_out_handle, restore_console_modes, scroll_down, scroll_up, set_console_mode,
+    set_size, set_window_title, size, terminal_modes, update_console_mode, window_size,
+};
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) use self::wasm::{
+    disable_raw_mode, enable_raw_mode, hostname, is_raw_mode_enabled, size, terminal_modes,
+    window_size,
 };
 
 #[cfg(windows)]
 mod windows;
 
+#[cfg(target_arch = "wasm32")]
+mod wasm;
+
 #[cfg(unix)]
 mod unix;
@@ -1,17 +1,27 @@
 //! UNIX related logic for terminal manipulation.
 
 use std::fs::File;
+use std::io::Write;
 use std::os::unix::io::{IntoRawFd, RawFd};
+use std::time::Duration;
 use std::{io, mem, process};
 
+#[cfg(not(any(target_os = "illumos", target_os = "solaris")))]
+use libc::cfmakeraw;
 use libc::{
-    cfmakeraw, ioctl, tcgetattr, tcsetattr, termios as Termios, winsize, STDOUT_FILENO, TCSANOW,
-    TIOCGWINSZ,
+    ioctl, tcgetattr, tcsetattr, termios as Termios, winsize, STDOUT_FILENO, TCSANOW, TIOCGWINSZ,
 };
 use parking_lot::Mutex;
 
 use crate::error::Result;
-use crate::event::sys::unix::file_descriptor::{tty_fd, FileDesc};
+use crate::event::{
+    filter::{AwaitedReply, QueryReplyFilter},
+    poll_internal, read_internal,
+    sys::unix::file_descriptor::{tty_fd, FileDesc},
+    timeout::PollTimeout,
+    InternalEvent,
+};
+use crate::terminal::{ModeStatus, WindowSize};
 
 // Some(Termios) -> we're in the raw mode and this is the previous mode
 // None -> we're not in the raw mode
@@ -21,8 +31,64 @@ pub(crate) fn is_raw_mode_enabled() -> bool {
     TERMINAL_MODE_PRIOR_RAW_MODE.lock().is_some()
 }
 
-#[allow(clippy::useless_conversion)]
 pub(crate) fn size() -> Result<(u16, u16)> {
+    let file = File::open("/dev/tty").map(|file| FileDesc::new(file.into_raw_fd(), true));
+    let fd = if let Ok(file) = &file {
+        file.raw_fd()
+    } else {
+        // Fallback to libc::STDOUT_FILENO if /dev/tty is missing
+        STDOUT_FILENO
+    };
+
+    match size_from_fd(fd) {
+        Ok(size) => Ok(size),
+        Err(e) => tput_size().ok_or(e),
+    }
+}
+
+/// Returns the size of the terminal the given file descriptor refers to.
+///
+/// Unlike `size`, this never falls back to `tput` and is safe to call from the event loop.
+pub(crate) fn size_from_fd(fd: RawFd) -> Result<(u16, u16)> {
+    let size = winsize_from_fd(fd)?;
+    Ok((size.ws_col, size.ws_row))
+}
+
+/// Returns the size of the terminal in cells and in pixels, the pixels are `0` if the terminal
+/// doesn't report them.
+pub(crate) fn window_size() -> Result<WindowSize> {
+    let file = File::open("/dev/tty").map(|file| FileDesc::new(file.into_raw_fd(), true));
+    let fd = if let Ok(file) = &file {
+        file.raw_fd()
+    } else {
+        STDOUT_FILENO
+    };
+
+    let size = winsize_from_fd(fd)?;
+    Ok(WindowSize {
+        columns: size.ws_col,
+        rows: size.ws_row,
+        width: size.ws_xpixel,
+        height: size.ws_ypixel,
+    })
+}
+
+/// Returns the size of a cell in pixels `(width, height)`, `None` if the terminal doesn't report
+/// its size in pixels.
+#[cfg(feature = "graphics")]
+pub(crate) fn cell_size() -> Result<Option<(u16, u16)>> {
+    let size = window_size()?;
+    if size.columns == 0 || size.rows == 0 || size.width == 0 || size.height == 0 {
+        return Ok(None);
+    }
+    Ok(Some((
+        (size.width / size.columns).max(1),
+        (size.height / size.rows).max(1),
+    )))
+}
+
+#[allow(clippy::useless_conversion)]
+fn winsize_from_fd(fd: RawFd) -> Result<winsize> {
     // http://rosettacode.org/wiki/Terminal_control/Dimensions#Library:_BSD_libc
     let mut size = winsize {
         ws_row: 0,
@@ -31,19 +97,18 @@ pub(crate) fn size() -> Result<(u16, u16)> {
         ws_ypixel: 0,
     };
 
-    let file = File::open("/dev/tty").map(|file| (FileDesc::new(file.into_raw_fd(), true)));
-    let fd = if let Ok(file) = &file {
-        file.raw_fd()
-    } else {
-        // Fallback to libc::STDOUT_FILENO if /dev/tty is missing
-        STDOUT_FILENO
-    };
+    wrap_with_result(unsafe { ioctl(fd, TIOCGWINSZ.into(), &mut size) })?;
+    Ok(size)
+}
 
-    if wrap_with_result(unsafe { ioctl(fd, TIOCGWINSZ.into(), &mut size) }).is_ok() {
-        Ok((size.ws_col, size.ws_row))
-    } else {
-        tput_size().ok_or_else(|| std::io::Error::last_os_error().into())
+/// Returns the host name, empty if it can't be read.
+pub(crate) fn hostname() -> String {
+    let mut name = [0u8; 256];
+    if unsafe { libc::gethostname(name.as_mut_ptr() as *mut libc::c_char, name.len()) } != 0 {
+        return String::new();
     }
+    let length = name.iter().position(|b| *b == 0).unwrap_or(name.len());
+    String::from_utf8_lossy(&name[..length]).into_owned()
 }
 
 pub(crate) fn enable_raw_mode() -> Result<()> {
@@ -80,6 +145,181 @@ pub(crate) fn disable_raw_mode() -> Result<()> {
     Ok(())
 }
 
+/// Switches the terminal to the raw mode again if the raw mode is enabled.
+///
+/// The mode can be changed behind our back, the shell restores its own mode when the process is
+/// stopped for example. Nothing happens while the process is in the background, changing the
+/// mode would stop it again (`SIGTTOU`).
+pub(crate) fn reapply_raw_mode() -> Result<()> {
+    let original_mode = TERMINAL_MODE_PRIOR_RAW_MODE.lock();
+
+    if let Some(original_mode_ios) = original_mode.as_ref() {
+        let tty = tty_fd()?;
+        let fd = tty.raw_fd();
+
+        if unsafe { libc::tcgetpgrp(fd) != libc::getpgrp() } {
+            return Ok(());
+        }
+
+        let mut ios = *original_mode_ios;
+        raw_terminal_attr(&mut ios);
+        set_terminal_attr(fd, &ios)?;
+    }
+
+    Ok(())
+}
+
+/// Returns the flags of the terminal which matter to crossterm and whether they are set.
+pub(crate) fn terminal_modes() -> Result<Vec<(&'static str, bool)>> {
+    let tty = tty_fd()?;
+    let ios = get_terminal_attr(tty.raw_fd())?;
+
+    Ok(vec![
+        ("BRKINT", ios.c_iflag & libc::BRKINT != 0),
+        ("ICRNL", ios.c_iflag & libc::ICRNL != 0),
+        ("INPCK", ios.c_iflag & libc::INPCK != 0),
+        ("ISTRIP", ios.c_iflag & libc::ISTRIP != 0),
+        ("IXON", ios.c_iflag & libc::IXON != 0),
+        ("OPOST", ios.c_oflag & libc::OPOST != 0),
+        ("CS8", ios.c_cflag & libc::CSIZE == libc::CS8),
+        ("ECHO", ios.c_lflag & libc::ECHO != 0),
+        ("ICANON", ios.c_lflag & libc::ICANON != 0),
+        ("IEXTEN", ios.c_lflag & libc::IEXTEN != 0),
+        ("ISIG", ios.c_lflag & libc::ISIG != 0),
+    ])
+}
+
+pub(crate) fn query_device_attributes() -> Result<Vec<u16>> {
+    query("", |_| None::<()>).map(|(_, attributes)| attributes)
+}
+
+pub(crate) fn query_mode(mode: u16) -> Result<ModeStatus> {
+    let (status, _) = query(&format!("\x1B[?{}$p", mode), |event| match event {
+        InternalEvent::ModeReport(reported, status) if *reported == mode => Some(match status {
+            1 => ModeStatus::Set,
+            2 => ModeStatus::Reset,
+            3 => ModeStatus::PermanentlySet,
+            4 => ModeStatus::PermanentlyReset,
+            _ => ModeStatus::NotRecognized,
+        }),
+        _ => None,
+    })?;
+    Ok(status.unwrap_or(ModeStatus::NotRecognized))
+}
+
+pub(crate) fn supports_keyboard_enhancement() -> Result<bool> {
+    let (flags, _) = query("\x1B[?u", |event| match event {
+        InternalEvent::KeyboardEnhancementFlags(flags) => Some(*flags),
+        _ => None,
+    })?;
+    Ok(flags.is_some())
+}
+
+pub(crate) fn query_version() -> Result<Option<String>> {
+    // The multiplexers reply with their own version
+    let query_sequence = match crate::terminal::multiplexer() {
+        Some(multiplexer) => {
+            let mut sequence = String::new();
+            let _ = multiplexer.write_passthrough(&mut sequence, "\x1B[>0q");
+            sequence
+        }
+        None => "\x1B[>0q".to_string(),
+    };
+    let (version, _) = query(&query_sequence, |event| match event {
+        InternalEvent::TerminalVersion(version) => Some(version.to_string()),
+        _ => None,
+    })?;
+    Ok(version)
+}
+
+pub(crate) fn query_capability(name: &str) -> Result<Option<String>> {
+    // The name is encoded in hexadecimal, XTGETTCAP ESC P + q Pn ESC \
+    let hex_name: String = name.bytes().map(|byte| format!("{:02X}", byte)).collect();
+    let (value, _) = query(&format!("\x1BP+q{}\x1B\\", hex_name), |event| match event {
+        InternalEvent::Capability(capability) => match capability.as_ref() {
+            Some((reported, value)) if reported == name => Some(Some(value.clone())),
+            Some(_) => None,
+            None => Some(None),
+        },
+        _ => None,
+    })?;
+    Ok(value.flatten())
+}
+
+pub(crate) fn query_clipboard(selection: char) -> Result<Option<Vec<u8>>> {
+    let (content, _) = query(
+        &format!("\x1B]52;{};?\x07", selection),
+        |event| match event {
+            InternalEvent::ClipboardContent(content) => Some(content.to_vec()),
+            _ => None,
+        },
+    )?;
+    Ok(content)
+}
+
+/// Writes the query followed by the primary device attributes query, which every terminal
+/// replies to, and reads the replies.
+///
+/// Returns the reply to the query if the terminal sent one before the device attributes, and the
+/// device attributes.
+pub(crate) fn query<T>(
+    query: &str,
+    reply: impl FnMut(&InternalEvent) -> Option<T>,
+) -> Result<(Option<T>, Vec<u16>)> {
+    if is_raw_mode_enabled() {
+        query_raw(query, reply)
+    } else {
+        enable_raw_mode()?;
+        let replies = query_raw(query, reply);
+        disable_raw_mode()?;
+        replies
+    }
+}
+
+fn query_raw<T>(
+    query: &str,
+    mut reply: impl FnMut(&InternalEvent) -> Option<T>,
+) -> Result<(Option<T>, Vec<u16>)> {
+    // Another thread blocked on the events hands the reader over when the reply is read
+    let _awaited = AwaitedReply::query_reply();
+
+    let mut stdout = io::stdout();
+    stdout.write_all(query.as_bytes())?;
+    stdout.write_all(b"\x1B[c")?;
+    stdout.flush()?;
+
+    // Events the user triggers in the meantime are skipped by the filter. They stay in the
+    // queue and are returned by the next `read` of the application.
+    let timeout = PollTimeout::new(Some(Duration::from_millis(2000)));
+    let mut replied = None;
+
+    loop {
+        match poll_internal(timeout.leftover(), &QueryReplyFilter) {
+            Ok(true) => match read_internal(&QueryReplyFilter) {
+                Ok(InternalEvent::PrimaryDeviceAttributes(attributes)) => {
+                    return Ok((replied, *attributes));
+                }
+                Ok(event) => {
+                    if replied.is_none() {
+                        replied = reply(&event);
+                    }
+                }
+                Err(_) => {}
+            },
+            // Handed over by another thread, the reply can't be far
+            Ok(false) if !timeout.elapsed() => {}
+            Ok(false) => {
+                return Err(io::Error::other(
+                    "The terminal didn't reply to the query within a normal duration",
+                ));
+            }
+            // The poll was woken up (`EventStream`, ...), keep waiting
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
 /// execute tput with the given argument and parse
 /// the output as a u16.
 ///
@@ -111,10 +351,32 @@ fn tput_size() -> Option<(u16, u16)> {
 }
 
 // Transform the given mode into an raw mode (non-canonical) mode.
-fn raw_terminal_attr(termios: &mut Termios) {
+#[cfg(not(any(target_os = "illumos", target_os = "solaris")))]
+pub(crate) fn raw_terminal_attr(termios: &mut Termios) {
     unsafe { cfmakeraw(termios) }
 }
 
+// There's no `cfmakeraw` on illumos and Solaris, the flags are the ones it changes elsewhere.
+#[cfg(any(target_os = "illumos", target_os = "solaris"))]
+pub(crate) fn raw_terminal_attr(termios: &mut Termios) {
+    termios.c_iflag &= !(libc::IGNBRK
+        | libc::BRKINT
+        | libc::PARMRK
+        | libc::ISTRIP
+        | libc::INLCR
+        | libc::IGNCR
+        | libc::ICRNL
+        | libc::IXON);
+    termios.c_oflag &= !libc::OPOST;
+    termios.c_lflag &= !(libc::ECHO | libc::ECHONL | libc::ICANON | libc::ISIG | libc::IEXTEN);
+    termios.c_cflag &= !(libc::CSIZE | libc::PARENB);
+    termios.c_cflag |= libc::CS8;
+    // `VMIN` and `VTIME` share their slots with `VEOF` and `VEOL`, which are set in the
+    // canonical mode
+    termios.c_cc[libc::VMIN] = 1;
+    termios.c_cc[libc::VTIME] = 0;
+}
+
 fn get_terminal_attr(fd: RawFd) -> Result<Termios> {
     unsafe {
         let mut termios = mem::zeroed();
@@ -0,0 +1,57 @@
+//! WebAssembly related logic for terminal manipulation, the terminal is connected with the
+//! `wasm` module.
+
+use std::{
+    io,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::{terminal::WindowSize, wasm, Result};
+
+/// The terminals of the browser have no line discipline, the input always reaches the
+/// application as it's typed. The raw mode is only tracked.
+static RAW_MODE: AtomicBool = AtomicBool::new(false);
+
+pub(crate) fn is_raw_mode_enabled() -> bool {
+    RAW_MODE.load(Ordering::Relaxed)
+}
+
+pub(crate) fn enable_raw_mode() -> Result<()> {
+    RAW_MODE.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+pub(crate) fn disable_raw_mode() -> Result<()> {
+    RAW_MODE.store(false, Ordering::Relaxed);
+    Ok(())
+}
+
+pub(crate) fn size() -> Result<(u16, u16)> {
+    wasm::size().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotConnected,
+            "The terminal size wasn't set with `crossterm::wasm::set_size`",
+        )
+    })
+}
+
+/// The size in pixels isn't given with `crossterm::wasm::set_size`.
+pub(crate) fn window_size() -> Result<WindowSize> {
+    let (columns, rows) = size()?;
+    Ok(WindowSize {
+        columns,
+        rows,
+        width: 0,
+        height: 0,
+    })
+}
+
+/// There's no terminal driver, it has no flags.
+pub(crate) fn terminal_modes() -> Result<Vec<(&'static str, bool)>> {
+    Ok(Vec::new())
+}
+
+/// The browser doesn't give the host name.
+pub(crate) fn hostname() -> String {
+    String::new()
+}
@@ -1,44 +1,172 @@
 //! WinAPI related logic for terminal manipulation.
 
 use std::fmt::{self, Write};
-use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::{io, mem};
 
-use crossterm_winapi::{Console, ConsoleMode, Coord, Handle, ScreenBuffer, Size};
+use crossterm_winapi::{Console, ConsoleMode, Handle, ScreenBuffer, Size};
+use parking_lot::Mutex;
 use winapi::{
     shared::minwindef::DWORD,
-    um::wincon::{SetConsoleTitleW, ENABLE_ECHO_INPUT, ENABLE_LINE_INPUT, ENABLE_PROCESSED_INPUT},
+    um::{
+        consoleapi::GetConsoleMode,
+        wincon::{
+            ScrollConsoleScreenBufferW, SetConsoleTitleW, CHAR_INFO, COORD,
+            DISABLE_NEWLINE_AUTO_RETURN, ENABLE_ECHO_INPUT, ENABLE_EXTENDED_FLAGS,
+            ENABLE_LINE_INPUT, ENABLE_MOUSE_INPUT, ENABLE_PROCESSED_INPUT, ENABLE_PROCESSED_OUTPUT,
+            ENABLE_QUICK_EDIT_MODE, ENABLE_VIRTUAL_TERMINAL_INPUT,
+            ENABLE_VIRTUAL_TERMINAL_PROCESSING, ENABLE_WINDOW_INPUT, ENABLE_WRAP_AT_EOL_OUTPUT,
+            SMALL_RECT,
+        },
+    },
 };
 
-use crate::{cursor, terminal::ClearType, ErrorKind, Result};
+use crate::{
+    cursor,
+    terminal::{ClearType, WindowSize},
+    ErrorKind, Result,
+};
+
+pub(crate) use self::console_mode::{
+    register_ctrl_handler, restore_console_modes, set_console_mode, update_console_mode,
+};
+
+mod console_mode;
 
 const RAW_MODE_MASK: DWORD = ENABLE_LINE_INPUT | ENABLE_ECHO_INPUT | ENABLE_PROCESSED_INPUT;
 
-pub(crate) fn enable_raw_mode() -> Result<()> {
-    let console_mode = ConsoleMode::from(Handle::current_in_handle()?);
+/// Whether `ENABLE_VIRTUAL_TERMINAL_INPUT` was set with the raw mode, see
+/// `event::set_virtual_terminal_input`.
+static VIRTUAL_TERMINAL_INPUT: AtomicBool = AtomicBool::new(false);
 
-    let dw_mode = console_mode.mode()?;
+// The `CONIN$` and `CONOUT$` handles, opened once instead of for every command.
+static CURRENT_IN_HANDLE: Mutex<Option<Handle>> = parking_lot::const_mutex(None);
+static CURRENT_OUT_HANDLE: Mutex<Option<Handle>> = parking_lot::const_mutex(None);
 
-    let new_mode = dw_mode & !RAW_MODE_MASK;
+fn cached_handle(cache: &Mutex<Option<Handle>>, open: fn() -> Result<Handle>) -> Result<Handle> {
+    let mut cache = cache.lock();
 
-    console_mode.set_mode(new_mode)?;
+    if let Some(handle) = cache.as_ref() {
+        // The handle is no longer a console handle once the process detaches from its console
+        // (`FreeConsole`/`AllocConsole`), open a new one in that case.
+        let mut mode = 0;
+        if unsafe { GetConsoleMode(**handle, &mut mode) } != 0 {
+            return Ok(handle.clone());
+        }
+    }
+
+    let handle = open()?;
+    *cache = Some(handle.clone());
+    Ok(handle)
+}
+
+/// Returns the handle of the console input buffer (`CONIN$`).
+pub(crate) fn current_in_handle() -> Result<Handle> {
+    cached_handle(&CURRENT_IN_HANDLE, Handle::current_in_handle)
+}
+
+/// Returns the handle of the active console screen buffer (`CONOUT$`).
+pub(crate) fn current_out_handle() -> Result<Handle> {
+    cached_handle(&CURRENT_OUT_HANDLE, Handle::current_out_handle)
+}
+
+/// Forgets the cached handle of the active console screen buffer.
+///
+/// Must be called whenever another screen buffer becomes the active one.
+pub(crate) fn reset_current_out_handle() {
+    *CURRENT_OUT_HANDLE.lock() = None;
+}
+
+pub(crate) fn enable_raw_mode() -> Result<()> {
+    let handle = current_in_handle()?;
+    update_console_mode(&handle, |mode| mode & !RAW_MODE_MASK)?;
+
+    if crate::event::virtual_terminal_input_requested() {
+        // Not supported before Windows 10, the keys are read from the input records then
+        let enabled =
+            update_console_mode(&handle, |mode| mode | ENABLE_VIRTUAL_TERMINAL_INPUT).is_ok();
+        VIRTUAL_TERMINAL_INPUT.store(enabled, Ordering::SeqCst);
+    }
 
     Ok(())
 }
 
 pub(crate) fn disable_raw_mode() -> Result<()> {
-    let console_mode = ConsoleMode::from(Handle::current_in_handle()?);
+    let virtual_terminal_input = VIRTUAL_TERMINAL_INPUT.swap(false, Ordering::SeqCst);
+    update_console_mode(&current_in_handle()?, |mode| {
+        if virtual_terminal_input {
+            (mode | RAW_MODE_MASK) & !ENABLE_VIRTUAL_TERMINAL_INPUT
+        } else {
+            mode | RAW_MODE_MASK
+        }
+    })?;
 
-    let dw_mode = console_mode.mode()?;
+    Ok(())
+}
 
-    let new_mode = dw_mode | RAW_MODE_MASK;
+/// Returns whether the keys are sent as escape sequences, set with the raw mode.
+pub(crate) fn is_virtual_terminal_input_enabled() -> bool {
+    VIRTUAL_TERMINAL_INPUT.load(Ordering::SeqCst)
+}
 
-    console_mode.set_mode(new_mode)?;
+pub(crate) fn is_raw_mode_enabled() -> bool {
+    current_in_handle()
+        .and_then(|handle| ConsoleMode::from(handle).mode())
+        .map(|mode| mode & RAW_MODE_MASK == 0)
+        .unwrap_or(false)
+}
 
-    Ok(())
+/// Returns the flags of the console input and output modes and whether they are set.
+pub(crate) fn terminal_modes() -> Result<Vec<(&'static str, bool)>> {
+    let input = ConsoleMode::from(current_in_handle()?).mode()?;
+    let output = ConsoleMode::from(current_out_handle()?).mode()?;
+
+    Ok(vec![
+        (
+            "ENABLE_PROCESSED_INPUT",
+            input & ENABLE_PROCESSED_INPUT != 0,
+        ),
+        ("ENABLE_LINE_INPUT", input & ENABLE_LINE_INPUT != 0),
+        ("ENABLE_ECHO_INPUT", input & ENABLE_ECHO_INPUT != 0),
+        ("ENABLE_WINDOW_INPUT", input & ENABLE_WINDOW_INPUT != 0),
+        ("ENABLE_MOUSE_INPUT", input & ENABLE_MOUSE_INPUT != 0),
+        (
+            "ENABLE_QUICK_EDIT_MODE",
+            input & ENABLE_QUICK_EDIT_MODE != 0,
+        ),
+        ("ENABLE_EXTENDED_FLAGS", input & ENABLE_EXTENDED_FLAGS != 0),
+        (
+            "ENABLE_VIRTUAL_TERMINAL_INPUT",
+            input & ENABLE_VIRTUAL_TERMINAL_INPUT != 0,
+        ),
+        (
+            "ENABLE_PROCESSED_OUTPUT",
+            output & ENABLE_PROCESSED_OUTPUT != 0,
+        ),
+        (
+            "ENABLE_WRAP_AT_EOL_OUTPUT",
+            output & ENABLE_WRAP_AT_EOL_OUTPUT != 0,
+        ),
+        (
+            "ENABLE_VIRTUAL_TERMINAL_PROCESSING",
+            output & ENABLE_VIRTUAL_TERMINAL_PROCESSING != 0,
+        ),
+        (
+            "DISABLE_NEWLINE_AUTO_RETURN",
+            output & DISABLE_NEWLINE_AUTO_RETURN != 0,
+        ),
+    ])
+}
+
+/// Returns the host name, empty if it can't be read.
+pub(crate) fn hostname() -> String {
+    std::env::var("COMPUTERNAME").unwrap_or_default()
 }
 
 pub(crate) fn size() -> Result<(u16, u16)> {
-    let terminal_size = ScreenBuffer::current()?.info()?.terminal_size();
+    let terminal_size = ScreenBuffer::from(current_out_handle()?)
+        .info()?
+        .terminal_size();
     // windows starts counting at 0, unix at 1, add one to replicated unix behaviour.
     Ok((
         (terminal_size.width + 1) as u16,
@@ -46,29 +174,69 @@ pub(crate) fn size() -> Result<(u16, u16)> {
     ))
 }
 
+/// The console doesn't report its size in pixels.
+pub(crate) fn window_size() -> Result<WindowSize> {
+    let (columns, rows) = size()?;
+    Ok(WindowSize {
+        columns,
+        rows,
+        width: 0,
+        height: 0,
+    })
+}
+
 pub(crate) fn clear(clear_type: ClearType) -> Result<()> {
-    let screen_buffer = ScreenBuffer::current()?;
+    let screen_buffer = ScreenBuffer::from(current_out_handle()?);
     let csbi = screen_buffer.info()?;
 
     let pos = csbi.cursor_pos();
     let buffer_size = csbi.buffer_size();
     let current_attribute = csbi.attributes();
+    let handle = screen_buffer.handle();
+    let fill = |rect| clear_rect(handle, rect, current_attribute);
+
+    let last_column = buffer_size.width - 1;
+    let last_row = buffer_size.height - 1;
 
     match clear_type {
         ClearType::All => {
-            clear_entire_screen(buffer_size, current_attribute)?;
+            fill(rect(0, 0, last_column, last_row))?;
+
+            // put the cursor back at cell 0,0
+            cursor::sys::move_to(0, 0)?;
+        }
+        ClearType::FromCursorDown => {
+            let mut first_row = pos.y;
+            if pos.x > 0 {
+                fill(rect(pos.x, pos.y, last_column, pos.y))?;
+                first_row += 1;
+            }
+            if first_row <= last_row {
+                fill(rect(0, first_row, last_column, last_row))?;
+            }
+        }
+        ClearType::FromCursorUp => {
+            if pos.y > 0 {
+                fill(rect(0, 0, last_column, pos.y - 1))?;
+            }
+            fill(rect(0, pos.y, pos.x, pos.y))?;
+        }
+        ClearType::CurrentLine => {
+            fill(rect(0, pos.y, last_column, pos.y))?;
+
+            // put the cursor back at cell 1 on current row
+            cursor::sys::move_to(0, pos.y as u16)?;
+        }
+        ClearType::UntilNewLine => {
+            fill(rect(pos.x, pos.y, last_column, pos.y))?;
         }
-        ClearType::FromCursorDown => clear_after_cursor(pos, buffer_size, current_attribute)?,
-        ClearType::FromCursorUp => clear_before_cursor(pos, buffer_size, current_attribute)?,
-        ClearType::CurrentLine => clear_current_line(pos, buffer_size, current_attribute)?,
-        ClearType::UntilNewLine => clear_until_line(pos, buffer_size, current_attribute)?,
     };
     Ok(())
 }
 
 pub(crate) fn scroll_up(row_count: u16) -> Result<()> {
-    let csbi = ScreenBuffer::current()?;
-    let mut window = csbi.info()?.terminal_window();
+    let screen_buffer = ScreenBuffer::from(current_out_handle()?);
+    let mut window = screen_buffer.info()?.terminal_window();
 
     // check whether the window is too close to the screen buffer top
     let count = row_count as i16;
@@ -76,13 +244,13 @@ pub(crate) fn scroll_up(row_count: u16) -> Result<()> {
         window.top -= count; // move top down
         window.bottom -= count; // move bottom down
 
-        Console::output()?.set_console_info(true, window)?;
+        Console::from(screen_buffer.handle().clone()).set_console_info(true, window)?;
     }
     Ok(())
 }
 
 pub(crate) fn scroll_down(row_count: u16) -> Result<()> {
-    let screen_buffer = ScreenBuffer::current()?;
+    let screen_buffer = ScreenBuffer::from(current_out_handle()?);
     let csbi = screen_buffer.info()?;
     let mut window = csbi.terminal_window();
     let buffer_size = csbi.buffer_size();
@@ -93,7 +261,7 @@ pub(crate) fn scroll_down(row_count: u16) -> Result<()> {
         window.top += count; // move top down
         window.bottom += count; // move bottom down
 
-        Console::output()?.set_console_info(true, window)?;
+        Console::from(screen_buffer.handle().clone()).set_console_info(true, window)?;
     }
     Ok(())
 }
@@ -114,7 +282,7 @@ pub(crate) fn set_size(width: u16, height: u16) -> Result<()> {
     }
 
     // get the position of the current console window
-    let screen_buffer = ScreenBuffer::current()?;
+    let screen_buffer = ScreenBuffer::from(current_out_handle()?);
     let console = Console::from(screen_buffer.handle().clone());
     let csbi = screen_buffer.info()?;
 
@@ -129,7 +297,7 @@ pub(crate) fn set_size(width: u16, height: u16) -> Result<()> {
 
     let width = width as i16;
     if current_size.width < window.left + width {
-        if window.left >= i16::max_value() - width {
+        if window.left >= i16::MAX - width {
             return Err(ErrorKind::new(
                 io::ErrorKind::InvalidInput,
                 "terminal width too large",
@@ -141,7 +309,7 @@ pub(crate) fn set_size(width: u16, height: u16) -> Result<()> {
     }
     let height = height as i16;
     if current_size.height < window.top + height {
-        if window.top >= i16::max_value() - height {
+        if window.top >= i16::MAX - height {
             return Err(ErrorKind::new(
                 io::ErrorKind::InvalidInput,
                 "terminal height too large",
@@ -208,93 +376,35 @@ pub(crate) fn set_window_title(title: impl fmt::Display) -> Result<()> {
     }
 }
 
-fn clear_after_cursor(location: Coord, buffer_size: Size, current_attribute: u16) -> Result<()> {
-    let (mut x, mut y) = (location.x, location.y);
-
-    // if cursor position is at the outer right position
-    if x as i16 > buffer_size.width {
-        y += 1;
-        x = 0;
+fn rect(left: i16, top: i16, right: i16, bottom: i16) -> SMALL_RECT {
+    SMALL_RECT {
+        Left: left,
+        Top: top,
+        Right: right,
+        Bottom: bottom,
     }
-
-    // location where to start clearing
-    let start_location = Coord::new(x, y);
-
-    // get sum cells before cursor
-    let cells_to_write = buffer_size.width as u32 * buffer_size.height as u32;
-
-    clear_winapi(start_location, cells_to_write, current_attribute)
-}
-
-fn clear_before_cursor(location: Coord, buffer_size: Size, current_attribute: u16) -> Result<()> {
-    let (xpos, ypos) = (location.x, location.y);
-
-    // one cell after cursor position
-    let x = 0;
-    // one at row of cursor position
-    let y = 0;
-
-    // location where to start clearing
-    let start_location = Coord::new(x, y);
-
-    // get sum cells before cursor
-    let cells_to_write = (buffer_size.width as u32 * ypos as u32) + (xpos as u32 + 1);
-
-    // clear everything before cursor position
-    clear_winapi(start_location, cells_to_write, current_attribute)
-}
-
-fn clear_entire_screen(buffer_size: Size, current_attribute: u16) -> Result<()> {
-    // get sum cells before cursor
-    let cells_to_write = buffer_size.width as u32 * buffer_size.height as u32;
-
-    // location where to start clearing
-    let start_location = Coord::new(0, 0);
-
-    // clear the entire screen
-    clear_winapi(start_location, cells_to_write, current_attribute)?;
-
-    // put the cursor back at cell 0,0
-    cursor::sys::move_to(0, 0)?;
-    Ok(())
-}
-
-fn clear_current_line(location: Coord, buffer_size: Size, current_attribute: u16) -> Result<()> {
-    // location where to start clearing
-    let start_location = Coord::new(0, location.y);
-
-    // get sum cells before cursor
-    let cells_to_write = buffer_size.width as u32;
-
-    // clear the whole current line
-    clear_winapi(start_location, cells_to_write, current_attribute)?;
-
-    // put the cursor back at cell 1 on current row
-    cursor::sys::move_to(0, location.y as u16)?;
-    Ok(())
 }
 
-fn clear_until_line(location: Coord, buffer_size: Size, current_attribute: u16) -> Result<()> {
-    let (x, y) = (location.x, location.y);
-
-    // location where to start clearing
-    let start_location = Coord::new(x, y);
-
-    // get sum cells before cursor
-    let cells_to_write = (buffer_size.width - x as i16) as u32;
-
-    // clear until the current line
-    clear_winapi(start_location, cells_to_write, current_attribute)?;
+/// Clears the given rectangle of the screen buffer with the given attribute.
+///
+/// The rectangle is scrolled out of itself and the console fills the area left behind with blanks,
+/// which takes a single call instead of a `FillConsoleOutputCharacter` and a
+/// `FillConsoleOutputAttribute` call per range of cells.
+fn clear_rect(handle: &Handle, rect: SMALL_RECT, attribute: u16) -> Result<()> {
+    let mut fill: CHAR_INFO = unsafe { mem::zeroed() };
+    unsafe {
+        *fill.Char.UnicodeChar_mut() = ' ' as u16;
+    }
+    fill.Attributes = attribute;
 
-    // put the cursor back at original cursor position before we did the clearing
-    cursor::sys::move_to(x as u16, y as u16)?;
-    Ok(())
-}
+    let destination = COORD {
+        X: rect.Left,
+        Y: rect.Top - (rect.Bottom - rect.Top + 1),
+    };
 
-fn clear_winapi(start_location: Coord, cells_to_write: u32, current_attribute: u16) -> Result<()> {
-    let console = Console::from(Handle::current_out_handle()?);
-    console.fill_whit_character(start_location, cells_to_write, ' ')?;
-    console.fill_whit_attribute(start_location, cells_to_write, current_attribute)?;
+    if unsafe { ScrollConsoleScreenBufferW(**handle, &rect, &rect, destination, &fill) } == 0 {
+        return Err(ErrorKind::last_os_error());
+    }
     Ok(())
 }
 
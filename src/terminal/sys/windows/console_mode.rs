@@ -0,0 +1,97 @@
+//! Keeps track of the console modes changed by crossterm, so that they can be restored.
+//!
+//! A console mode outlives the process. A program which crashes or is killed leaves the console
+//! without echo, line input or QuickEdit. The original mode of every handle is remembered when
+//! it's changed for the first time and a console control handler restores it when the console
+//! window is closed, the user logs off or the system shuts down.
+
+use crossterm_winapi::{ConsoleMode, Handle};
+use parking_lot::{Mutex, Once};
+use winapi::{
+    shared::minwindef::{BOOL, DWORD, FALSE, TRUE},
+    um::{
+        consoleapi::SetConsoleCtrlHandler,
+        wincon::{CTRL_CLOSE_EVENT, CTRL_LOGOFF_EVENT, CTRL_SHUTDOWN_EVENT},
+    },
+};
+
+use crate::Result;
+
+/// The handles whose mode was changed along with their original mode.
+static ORIGINAL_CONSOLE_MODES: Mutex<Vec<(Handle, u32)>> = parking_lot::const_mutex(Vec::new());
+static CTRL_HANDLER: Once = Once::new();
+
+/// Updates the mode of the given console handle and returns the previous mode.
+///
+/// The original mode is remembered first. Updates are serialized, no thread can change the mode
+/// between reading and writing it in another one.
+pub(crate) fn update_console_mode(handle: &Handle, update: impl FnOnce(u32) -> u32) -> Result<u32> {
+    let console_mode = ConsoleMode::from(handle.clone());
+
+    let mut original_modes = ORIGINAL_CONSOLE_MODES.lock();
+    let mode = console_mode.mode()?;
+
+    if !original_modes
+        .iter()
+        .any(|(changed, _)| **changed == **handle)
+    {
+        original_modes.push((handle.clone(), mode));
+    }
+
+    register_ctrl_handler();
+
+    console_mode.set_mode(update(mode))?;
+    Ok(mode)
+}
+
+/// Sets the mode of the given console handle, the original mode is remembered first.
+pub(crate) fn set_console_mode(handle: &Handle, mode: u32) -> Result<()> {
+    update_console_mode(handle, |_| mode).map(|_| ())
+}
+
+/// Restores the original mode of every console handle whose mode was changed.
+///
+/// Every handle is restored even if some of them fail, the first error is returned.
+pub(crate) fn restore_console_modes() -> Result<()> {
+    let mut result = Ok(());
+
+    for (handle, mode) in ORIGINAL_CONSOLE_MODES.lock().drain(..) {
+        if let Err(e) = ConsoleMode::from(handle).set_mode(mode) {
+            result = result.and(Err(e));
+        }
+    }
+
+    result
+}
+
+/// Registers the console control handler restoring the console when the process is terminated.
+pub(crate) fn register_ctrl_handler() {
+    CTRL_HANDLER.call_once(|| unsafe {
+        SetConsoleCtrlHandler(Some(ctrl_handler), TRUE);
+    });
+}
+
+unsafe extern "system" fn ctrl_handler(ctrl_type: DWORD) -> BOOL {
+    // Reported as an event, the process keeps running
+    if crate::event::sys::windows::signal::handle_ctrl_event(ctrl_type) {
+        return TRUE;
+    }
+
+    // The process is terminated once the handlers return for these events only. A handler the
+    // application registered before crossterm runs after this one and can keep the process
+    // running on the other events (Ctrl+C, Ctrl+Break), the modes must stay as they are then.
+    if !matches!(
+        ctrl_type,
+        CTRL_CLOSE_EVENT | CTRL_LOGOFF_EVENT | CTRL_SHUTDOWN_EVENT
+    ) {
+        return FALSE;
+    }
+
+    let _ = restore_console_modes();
+    // Restores the colors as well
+    let _ = crate::style::sys::windows::reset();
+    crate::terminal::hide_progress();
+
+    // Let the next handler (`ExitProcess` by default) handle the event
+    FALSE
+}
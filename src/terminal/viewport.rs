@@ -0,0 +1,302 @@
+use std::{fmt::Display, io::Write};
+
+use crate::{
+    cursor::MoveTo,
+    style::{ContentStyle, PrintStyledContent, StyledContent},
+    QueueableCommand, Result,
+};
+
+/// A cell of a [`Viewport`](struct.Viewport.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Cell {
+    symbol: char,
+    style: ContentStyle,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Cell {
+            symbol: ' ',
+            style: ContentStyle::default(),
+        }
+    }
+}
+
+/// A rectangular region of the screen, drawn in memory and rendered with the commands needed for
+/// the rows which changed.
+///
+/// The viewport has its own coordinates, `(0, 0)` is its top left cell wherever it's on the
+/// screen. The content printed outside of it is clipped. [`render`](#method.render) compares the
+/// cells with the ones it rendered last and only writes the changed part of the changed rows, so
+/// a status bar or a log in a part of the screen can be redrawn entirely on every update.
+///
+/// Limitations:
+///
+/// * Every character takes a single cell, even the wide ones. The control characters are
+///   skipped.
+/// * The viewport doesn't know what else was written to the screen, call
+///   [`invalidate`](#method.invalidate) after the screen was cleared or resized.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::{stdout, Write};
+///
+/// use crossterm::{
+///     style::Stylize,
+///     terminal::{self, Viewport},
+///     Result,
+/// };
+///
+/// fn main() -> Result<()> {
+///     let (columns, rows) = terminal::size()?;
+///     // A status bar on the last row
+///     let mut status = Viewport::new(0, rows - 1, columns, 1);
+///
+///     let column = status.print_styled(0, 0, &"Ready".bold());
+///     status.print(column + 1, 0, "- 3 files");
+///
+///     let mut stdout = stdout();
+///     status.render(&mut stdout)?;
+///     stdout.flush()?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Viewport {
+    column: u16,
+    row: u16,
+    width: u16,
+    height: u16,
+    cells: Vec<Cell>,
+    /// The cells as they were rendered last.
+    rendered: Vec<Cell>,
+    /// The rows rendered entirely by the next render, whatever was rendered before.
+    damaged: Vec<bool>,
+}
+
+impl Viewport {
+    /// Creates an empty viewport with its top left cell at the column and row of the screen.
+    ///
+    /// The first render draws the whole viewport, clearing what the screen showed there.
+    pub fn new(column: u16, row: u16, width: u16, height: u16) -> Viewport {
+        let cells = width as usize * height as usize;
+        Viewport {
+            column,
+            row,
+            width,
+            height,
+            cells: vec![Cell::default(); cells],
+            rendered: vec![Cell::default(); cells],
+            damaged: vec![true; height as usize],
+        }
+    }
+
+    /// Returns the column and the row of the screen of the top left cell.
+    pub fn position(&self) -> (u16, u16) {
+        (self.column, self.row)
+    }
+
+    /// Returns the width and the height.
+    pub fn size(&self) -> (u16, u16) {
+        (self.width, self.height)
+    }
+
+    /// Moves the viewport to the column and the row of the screen, the next render draws it
+    /// entirely.
+    ///
+    /// The cells of the screen the viewport left aren't cleared.
+    pub fn set_position(&mut self, column: u16, row: u16) {
+        self.column = column;
+        self.row = row;
+        self.invalidate();
+    }
+
+    /// Resizes the viewport, the next render draws it entirely.
+    ///
+    /// The content keeps its coordinates, what doesn't fit anymore is thrown away.
+    pub fn resize(&mut self, width: u16, height: u16) {
+        let mut cells = vec![Cell::default(); width as usize * height as usize];
+        for row in 0..self.height.min(height) {
+            let columns = self.width.min(width) as usize;
+            let from = self.index(0, row);
+            let to = row as usize * width as usize;
+            cells[to..to + columns].copy_from_slice(&self.cells[from..from + columns]);
+        }
+
+        self.width = width;
+        self.height = height;
+        self.rendered = cells.clone();
+        self.cells = cells;
+        self.damaged = vec![true; height as usize];
+    }
+
+    /// Prints the content without a style, starting at the column and row of the viewport.
+    ///
+    /// Returns the column following the content, it can be outside of the viewport.
+    pub fn print(&mut self, column: u16, row: u16, content: impl Display) -> u16 {
+        self.print_with_style(column, row, &content.to_string(), ContentStyle::default())
+    }
+
+    /// Prints the styled content, starting at the column and row of the viewport.
+    ///
+    /// Returns the column following the content, it can be outside of the viewport.
+    pub fn print_styled<D: Display>(
+        &mut self,
+        column: u16,
+        row: u16,
+        content: &StyledContent<D>,
+    ) -> u16 {
+        self.print_with_style(
+            column,
+            row,
+            &content.content().to_string(),
+            *content.style(),
+        )
+    }
+
+    fn print_with_style(&mut self, column: u16, row: u16, text: &str, style: ContentStyle) -> u16 {
+        let mut column = column;
+        for symbol in text.chars().filter(|symbol| !symbol.is_control()) {
+            if row < self.height && column < self.width {
+                let index = self.index(column, row);
+                self.cells[index] = Cell { symbol, style };
+            }
+            column = column.saturating_add(1);
+        }
+        column
+    }
+
+    /// Clears the viewport.
+    pub fn clear(&mut self) {
+        self.cells.fill(Cell::default());
+    }
+
+    /// Clears the row of the viewport.
+    pub fn clear_row(&mut self, row: u16) {
+        if row < self.height {
+            let start = self.index(0, row);
+            self.cells[start..start + self.width as usize].fill(Cell::default());
+        }
+    }
+
+    /// Moves the content up by the number of rows, the rows at the bottom are cleared.
+    ///
+    /// Printing the new line on the last row afterwards makes a scrolling log.
+    pub fn scroll_up(&mut self, rows: u16) {
+        let cells = (rows.min(self.height) as usize) * self.width as usize;
+        self.cells.drain(..cells);
+        self.cells
+            .extend(std::iter::repeat_n(Cell::default(), cells));
+    }
+
+    /// Makes the next render draw the viewport entirely, after the screen was cleared or
+    /// resized.
+    pub fn invalidate(&mut self) {
+        self.damaged.fill(true);
+    }
+
+    /// Queues the commands drawing the changes since the last render, the writer must be flushed
+    /// afterwards.
+    ///
+    /// The cursor is left after the last cell written.
+    pub fn render(&mut self, writer: &mut impl Write) -> Result<()> {
+        for row in 0..self.height {
+            let start = self.index(0, row);
+            let end = start + self.width as usize;
+            let cells = &self.cells[start..end];
+            let rendered = &self.rendered[start..end];
+
+            // The changed part of the row
+            let (first, last) = if self.damaged[row as usize] {
+                (0, cells.len())
+            } else {
+                match cells.iter().zip(rendered).position(|(a, b)| a != b) {
+                    Some(first) => {
+                        let unchanged = cells
+                            .iter()
+                            .rev()
+                            .zip(rendered.iter().rev())
+                            .take_while(|(a, b)| a == b)
+                            .count();
+                        (first, cells.len() - unchanged)
+                    }
+                    None => continue,
+                }
+            };
+
+            writer.queue(MoveTo(
+                self.column.saturating_add(first as u16),
+                self.row.saturating_add(row),
+            ))?;
+            // Every run of cells with the same style at once
+            for run in cells[first..last].chunk_by(|a, b| a.style == b.style) {
+                let text: String = run.iter().map(|cell| cell.symbol).collect();
+                writer.queue(PrintStyledContent(run[0].style.apply(text)))?;
+            }
+
+            self.rendered[start..end].copy_from_slice(&self.cells[start..end]);
+            self.damaged[row as usize] = false;
+        }
+        Ok(())
+    }
+
+    fn index(&self, column: u16, row: u16) -> usize {
+        row as usize * self.width as usize + column as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::style::Stylize;
+
+    use super::Viewport;
+
+    fn render(viewport: &mut Viewport) -> String {
+        let mut output = Vec::new();
+        viewport.render(&mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_render_changes() {
+        let mut viewport = Viewport::new(2, 10, 4, 2);
+
+        assert_eq!(viewport.print(0, 0, "abcdef"), 6);
+        assert_eq!(render(&mut viewport), "\x1B[11;3Habcd\x1B[12;3H    ");
+        assert_eq!(render(&mut viewport), "");
+
+        viewport.print(1, 1, "xy");
+        viewport.print(0, 0, "a");
+        assert_eq!(render(&mut viewport), "\x1B[12;4Hxy");
+
+        viewport.invalidate();
+        assert_eq!(render(&mut viewport), "\x1B[11;3Habcd\x1B[12;3H xy ");
+    }
+
+    #[test]
+    fn test_render_styles() {
+        let mut viewport = Viewport::new(0, 0, 5, 1);
+
+        let column = viewport.print_styled(0, 0, &"ab".bold());
+        viewport.print(column, 0, "c");
+
+        assert_eq!(render(&mut viewport), "\x1B[1;1H\x1B[1mab\x1B[0mc  ");
+    }
+
+    #[test]
+    fn test_scroll_and_resize() {
+        let mut viewport = Viewport::new(0, 0, 2, 2);
+        viewport.print(0, 0, "ab");
+        viewport.print(0, 1, "cd");
+        render(&mut viewport);
+
+        viewport.scroll_up(1);
+        viewport.print(0, 1, "e");
+        assert_eq!(render(&mut viewport), "\x1B[1;1Hcd\x1B[2;1He ");
+
+        viewport.resize(1, 3);
+        assert_eq!(viewport.size(), (1, 3));
+        assert_eq!(render(&mut viewport), "\x1B[1;1Hc\x1B[2;1He\x1B[3;1H ");
+    }
+}
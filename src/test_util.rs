@@ -0,0 +1,52 @@
+//! # Test utilities
+//!
+//! The `test-util` feature provides a [`VirtualTerminal`](struct.VirtualTerminal.html), an
+//! in-memory terminal which understands the sequences written by the commands of this crate. It
+//! allows to test the rendering and the input handling of an application without a real
+//! terminal, in CI for example.
+//!
+//! ```no_run
+//! use crossterm::{
+//!     assert_screen_eq,
+//!     cursor::MoveTo,
+//!     event::{read, Event, KeyCode},
+//!     style::{Color, Print, SetForegroundColor},
+//!     test_util::VirtualTerminal,
+//! };
+//!
+//! fn main() -> crossterm::Result<()> {
+//!     let mut terminal = VirtualTerminal::new(20, 5);
+//!
+//!     terminal
+//!         .execute(MoveTo(2, 1))?
+//!         .execute(SetForegroundColor(Color::Red))?
+//!         .execute(Print("Hello"))?;
+//!
+//!     assert_screen_eq!(terminal, ["", "  Hello", "", "", ""]);
+//!     assert_eq!(terminal.cell(2, 1).unwrap().fg(), Color::Red);
+//!
+//!     // `read` and `poll` return the events sent to the virtual terminal
+//!     terminal.attach_input();
+//!     terminal.send_event(Event::Key(KeyCode::Enter.into()));
+//!     assert_eq!(read()?, Event::Key(KeyCode::Enter.into()));
+//!     crossterm::test_util::detach_input();
+//!
+//!     Ok(())
+//! }
+//! ```
+
+#[doc(hidden)]
+pub use self::assertions::assert_screen_eq;
+pub use self::clock::VirtualClock;
+#[cfg(unix)]
+pub use self::pty::Pty;
+pub use self::snapshot::{assert_snapshot, escape_output, OutputRecorder};
+pub use self::virtual_terminal::{detach_input, Cell, VirtualTerminal};
+
+mod assertions;
+mod clock;
+mod input;
+#[cfg(unix)]
+mod pty;
+mod snapshot;
+mod virtual_terminal;
@@ -0,0 +1,106 @@
+use std::fmt::{self, Write};
+
+use super::VirtualTerminal;
+
+/// The implementation of the [`assert_screen_eq!`](../macro.assert_screen_eq.html) macro.
+#[doc(hidden)]
+#[track_caller]
+pub fn assert_screen_eq<S: AsRef<str>>(
+    terminal: &VirtualTerminal,
+    expected: &[S],
+    message: Option<fmt::Arguments<'_>>,
+) {
+    let lines = terminal.lines();
+    let actual: Vec<&str> = lines.iter().map(String::as_str).collect();
+    let expected: Vec<&str> = expected
+        .iter()
+        .map(|row| row.as_ref().trim_end_matches(' '))
+        .collect();
+
+    if actual == expected {
+        return;
+    }
+
+    let mut report = String::from("assertion failed: the screen doesn't match");
+    if let Some(message) = message {
+        let _ = write!(report, ": {}", message);
+    }
+    report.push_str("\nrows which differ are marked with `>`");
+
+    let rows = actual.len().max(expected.len());
+    let width = terminal.size().0 as usize;
+    for (title, lines) in [("expected", &expected), ("actual", &actual)] {
+        let _ = write!(report, "\n{}:", title);
+        for row in 0..rows {
+            let marker = if actual.get(row) == expected.get(row) {
+                ' '
+            } else {
+                '>'
+            };
+            match lines.get(row) {
+                Some(line) => {
+                    let padding = width.saturating_sub(line.chars().count());
+                    let _ = write!(
+                        report,
+                        "\n{}{:3} |{}{}|",
+                        marker,
+                        row,
+                        line,
+                        " ".repeat(padding)
+                    );
+                }
+                None => {
+                    let _ = write!(report, "\n{}{:3}  (missing)", marker, row);
+                }
+            }
+        }
+    }
+
+    panic!("{}", report);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic;
+
+    use crate::style::Print;
+
+    use super::super::VirtualTerminal;
+
+    fn boxed_terminal() -> VirtualTerminal {
+        let mut terminal = VirtualTerminal::new(5, 3);
+        terminal.execute(Print("┌───┐\n│ hi│\n└───┘")).unwrap();
+        terminal
+    }
+
+    #[test]
+    fn test_assert_screen_eq() {
+        let terminal = boxed_terminal();
+
+        crate::assert_screen_eq!(terminal, ["┌───┐", "│ hi│", "└───┘"]);
+        let rows: Vec<String> = ["┌───┐  ", "│ hi│", "└───┘"]
+            .iter()
+            .map(|row| row.to_string())
+            .collect();
+        crate::assert_screen_eq!(terminal, rows, "with {}", "spaces");
+    }
+
+    #[test]
+    fn test_assert_screen_eq_reports_the_differences() {
+        let terminal = boxed_terminal();
+
+        let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            crate::assert_screen_eq!(terminal, ["┌───┐", "│ ho│"], "after {}", "printing");
+        }));
+        let error = result.unwrap_err();
+        let message = error.downcast_ref::<String>().unwrap();
+
+        assert_eq!(
+            message,
+            "assertion failed: the screen doesn't match: after printing\n\
+             rows which differ are marked with `>`\n\
+             expected:\n   0 |┌───┐|\n>  1 |│ ho│|\n>  2  (missing)\n\
+             actual:\n   0 |┌───┐|\n>  1 |│ hi│|\n>  2 |└───┘|"
+        );
+    }
+}
@@ -0,0 +1,53 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+
+use crate::event::timeout::Clock;
+
+/// A clock which only moves forward when it's told to.
+///
+/// Returned by [`VirtualTerminal::enable_virtual_time`](struct.VirtualTerminal.html#method.enable_virtual_time).
+/// While the input of the virtual terminal is attached, the timeouts of
+/// [`event::poll`](../event/fn.poll.html) and the other functions waiting for events use this
+/// clock instead of the real time.
+///
+/// The clones share the same time.
+#[derive(Debug, Clone)]
+pub struct VirtualClock {
+    origin: Instant,
+    elapsed: Arc<Mutex<Duration>>,
+}
+
+impl VirtualClock {
+    pub(crate) fn new() -> VirtualClock {
+        VirtualClock {
+            origin: Instant::now(),
+            elapsed: Arc::new(Mutex::new(Duration::from_secs(0))),
+        }
+    }
+
+    /// Returns the virtual time elapsed since the clock was created.
+    pub fn elapsed(&self) -> Duration {
+        *self.elapsed.lock()
+    }
+
+    /// Moves the clock forward.
+    pub fn advance(&self, duration: Duration) {
+        *self.elapsed.lock() += duration;
+    }
+
+    /// Moves the clock forward to the given time, nothing happens if it's in the past.
+    pub(crate) fn advance_to(&self, elapsed: Duration) {
+        let mut current = self.elapsed.lock();
+        *current = (*current).max(elapsed);
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> Instant {
+        self.origin + self.elapsed()
+    }
+}
@@ -0,0 +1,164 @@
+use std::{
+    collections::VecDeque,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use parking_lot::{Condvar, Mutex};
+
+#[cfg(feature = "event-stream")]
+use crate::event::{
+    source::wake_signal::{WakeSignal, WAKE_CHECK_INTERVAL},
+    sys::Waker,
+};
+use crate::{
+    event::{source::InternalEventSource, timeout::PollTimeout, InternalEvent},
+    Result,
+};
+
+use super::VirtualClock;
+
+/// The events sent to a virtual terminal, shared with the event source reading them.
+#[derive(Debug)]
+pub(crate) struct InputQueue {
+    /// The events and when they are due, in that order.
+    events: Mutex<VecDeque<(Duration, InternalEvent)>>,
+    available: Condvar,
+    /// When the terminal was created, the timeline starts there without a virtual clock.
+    origin: Instant,
+    clock: Mutex<Option<VirtualClock>>,
+}
+
+impl Default for InputQueue {
+    fn default() -> Self {
+        InputQueue {
+            events: Mutex::new(VecDeque::new()),
+            available: Condvar::new(),
+            origin: Instant::now(),
+            clock: Mutex::new(None),
+        }
+    }
+}
+
+impl InputQueue {
+    pub(crate) fn push(&self, event: InternalEvent) {
+        self.schedule(self.elapsed(), event);
+    }
+
+    /// Adds an event which is due at the given time of the timeline.
+    pub(crate) fn schedule(&self, due: Duration, event: InternalEvent) {
+        let mut events = self.events.lock();
+        let index = events
+            .iter()
+            .position(|(other, _)| *other > due)
+            .unwrap_or_else(|| events.len());
+        events.insert(index, (due, event));
+        self.available.notify_all();
+    }
+
+    pub(crate) fn clock(&self) -> Option<VirtualClock> {
+        self.clock.lock().clone()
+    }
+
+    /// Returns the virtual clock, it's created the first time.
+    pub(crate) fn enable_clock(&self) -> VirtualClock {
+        self.clock
+            .lock()
+            .get_or_insert_with(VirtualClock::new)
+            .clone()
+    }
+
+    /// Returns the time elapsed on the timeline, virtual or real.
+    fn elapsed(&self) -> Duration {
+        match self.clock.lock().as_ref() {
+            Some(clock) => clock.elapsed(),
+            None => self.origin.elapsed(),
+        }
+    }
+}
+
+/// Removes the first event if it's due.
+fn pop_due(
+    events: &mut VecDeque<(Duration, InternalEvent)>,
+    elapsed: Duration,
+) -> Option<InternalEvent> {
+    match events.front() {
+        Some((due, _)) if *due <= elapsed => events.pop_front().map(|(_, event)| event),
+        _ => None,
+    }
+}
+
+/// An `InternalEventSource` reading the events of an `InputQueue`.
+pub(crate) struct VirtualEventSource {
+    queue: Arc<InputQueue>,
+    #[cfg(feature = "event-stream")]
+    wake_signal: WakeSignal,
+}
+
+impl VirtualEventSource {
+    pub(crate) fn new(queue: Arc<InputQueue>) -> Result<VirtualEventSource> {
+        Ok(VirtualEventSource {
+            queue,
+            #[cfg(feature = "event-stream")]
+            wake_signal: WakeSignal::new()?,
+        })
+    }
+}
+
+impl InternalEventSource for VirtualEventSource {
+    fn try_read(&mut self, timeout: Option<Duration>) -> Result<Option<InternalEvent>> {
+        let timeout = PollTimeout::new(timeout);
+        let mut events = self.queue.events.lock();
+
+        loop {
+            let elapsed = self.queue.elapsed();
+            if let Some(event) = pop_due(&mut events, elapsed) {
+                return Ok(Some(event));
+            }
+
+            #[cfg(feature = "event-stream")]
+            if self.wake_signal.take() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Interrupted,
+                    "Poll operation was woken up by `Waker::wake`",
+                ));
+            }
+
+            if timeout.elapsed() {
+                return Ok(None);
+            }
+
+            // Nothing happens before the next scheduled event or the timeout
+            let next_event = events.front().map(|(due, _)| *due - elapsed);
+            let wait = match (next_event, timeout.leftover()) {
+                (Some(next_event), Some(leftover)) => Some(next_event.min(leftover)),
+                (next_event, leftover) => next_event.or(leftover),
+            };
+
+            if let (Some(clock), Some(wait)) = (self.queue.clock(), wait) {
+                clock.advance_to(elapsed + wait);
+                continue;
+            }
+
+            #[cfg(feature = "event-stream")]
+            let wait = Some(wait.map_or(WAKE_CHECK_INTERVAL, |wait| wait.min(WAKE_CHECK_INTERVAL)));
+
+            match wait {
+                Some(wait) => {
+                    self.queue.available.wait_for(&mut events, wait);
+                }
+                None => self.queue.available.wait(&mut events),
+            }
+        }
+    }
+
+    fn try_read_buffered(&mut self) -> Option<InternalEvent> {
+        let elapsed = self.queue.elapsed();
+        pop_due(&mut self.queue.events.lock(), elapsed)
+    }
+
+    #[cfg(feature = "event-stream")]
+    fn waker(&self) -> Waker {
+        self.wake_signal.waker.clone()
+    }
+}
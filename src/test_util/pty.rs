@@ -0,0 +1,511 @@
+use std::{
+    env,
+    fs::File,
+    io::{self, Read, Write},
+    os::unix::{
+        io::{AsRawFd, FromRawFd},
+        process::CommandExt,
+    },
+    process::{self, Child, ExitStatus},
+    ptr, thread,
+    time::Duration,
+};
+
+use crate::{event::timeout::PollTimeout, Result};
+
+/// The environment variable telling the test binary that it runs in a pseudo terminal.
+const TEST_CHILD_VAR: &str = "CROSSTERM_PTY_TEST";
+
+/// How long to wait at once for the process to exit, the output is read in between.
+const WAIT_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A process running in a pseudo terminal.
+///
+/// The process gets a real terminal: the raw mode, the terminal size, the events... behave like in
+/// a terminal emulator. Its input is written with [`write`](#method.write) and its output is read
+/// with [`expect`](#method.expect).
+///
+/// The process is killed when the `Pty` is dropped.
+///
+/// Only available on UNIX.
+///
+/// # Examples
+///
+/// A test running again in a pseudo terminal:
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use crossterm::{event, terminal, test_util::Pty};
+///
+/// // #[test]
+/// fn test_reads_a_key() {
+///     if Pty::is_test_child() {
+///         // The test binary runs again in the pseudo terminal
+///         terminal::enable_raw_mode().unwrap();
+///         println!("ready");
+///         let event = event::read().unwrap();
+///         terminal::disable_raw_mode().unwrap();
+///         println!("{:?}", event);
+///         return;
+///     }
+///
+///     let mut pty = Pty::spawn_test("test_reads_a_key").unwrap();
+///     pty.expect("ready", Duration::from_secs(5)).unwrap();
+///     pty.write(b"a").unwrap();
+///     pty.expect("Char('a')", Duration::from_secs(5)).unwrap();
+///     assert!(pty.wait(Duration::from_secs(5)).unwrap().success());
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Pty {
+    master: File,
+    child: Child,
+    output: Vec<u8>,
+    /// Where the output which wasn't matched by `expect` starts.
+    unmatched: usize,
+}
+
+impl Pty {
+    /// Spawns the command in a new pseudo terminal with the given size.
+    ///
+    /// The pseudo terminal is the standard input, output and error and the controlling terminal
+    /// of the process.
+    pub fn spawn(mut command: process::Command, columns: u16, rows: u16) -> Result<Pty> {
+        let size = libc::winsize {
+            ws_row: rows,
+            ws_col: columns,
+            ws_xpixel: 0,
+            ws_ypixel: 0,
+        };
+        let (mut master, mut slave) = (0, 0);
+        if unsafe {
+            libc::openpty(
+                &mut master,
+                &mut slave,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                &size as *const _ as *mut _,
+            )
+        } != 0
+        {
+            return Err(io::Error::last_os_error());
+        }
+        let master = unsafe { File::from_raw_fd(master) };
+        let slave = unsafe { File::from_raw_fd(slave) };
+
+        // The process only gets the pseudo terminal through its standard streams
+        for file in &[&master, &slave] {
+            if unsafe { libc::fcntl(file.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC) } == -1 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+
+        command
+            .stdin(slave.try_clone()?)
+            .stdout(slave.try_clone()?)
+            .stderr(slave);
+        unsafe {
+            command.pre_exec(|| {
+                // A new session makes the pseudo terminal the controlling terminal
+                if libc::setsid() == -1 || libc::ioctl(0, libc::TIOCSCTTY as _, 0) == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        let child = command.spawn()?;
+
+        Ok(Pty {
+            master,
+            child,
+            output: Vec::new(),
+            unmatched: 0,
+        })
+    }
+
+    /// Runs the test again in a new 80x24 pseudo terminal.
+    ///
+    /// The test is the path printed by the test harness, `tests::test_raw_mode` for example. The
+    /// test can call [`is_test_child`](#method.is_test_child) to know where it runs.
+    pub fn spawn_test(test: &str) -> Result<Pty> {
+        let mut command = process::Command::new(env::current_exe()?);
+        command
+            .args([test, "--exact", "--nocapture", "--test-threads=1"])
+            .env(TEST_CHILD_VAR, test)
+            // The pseudo terminal isn't a pane of the multiplexer the tests may run in
+            .env_remove("TMUX")
+            .env_remove("STY")
+            .env_remove("ZELLIJ");
+
+        Pty::spawn(command, 80, 24)
+    }
+
+    /// Returns whether the current process is a test spawned by
+    /// [`spawn_test`](#method.spawn_test).
+    pub fn is_test_child() -> bool {
+        env::var_os(TEST_CHILD_VAR).is_some()
+    }
+
+    /// Writes the bytes to the input of the process, as if they were typed.
+    pub fn write(&mut self, input: &[u8]) -> Result<()> {
+        self.master.write_all(input)?;
+        self.master.flush()
+    }
+
+    /// Waits until the output of the process contains the expected bytes.
+    ///
+    /// Every call searches the output after the previous match. Fails with
+    /// `io::ErrorKind::TimedOut` if the output doesn't contain them within the timeout, or
+    /// `io::ErrorKind::UnexpectedEof` if the process closed the terminal before.
+    pub fn expect(&mut self, expected: impl AsRef<[u8]>, timeout: Duration) -> Result<()> {
+        let expected = expected.as_ref();
+        let timeout = PollTimeout::new(Some(timeout));
+
+        loop {
+            let unmatched = &self.output[self.unmatched..];
+            if let Some(position) = unmatched
+                .windows(expected.len().max(1))
+                .position(|window| window == expected)
+            {
+                self.unmatched += position + expected.len();
+                return Ok(());
+            }
+
+            let kind = if timeout.elapsed() {
+                io::ErrorKind::TimedOut
+            } else if !self.read_output(timeout.leftover())? {
+                io::ErrorKind::UnexpectedEof
+            } else {
+                continue;
+            };
+
+            return Err(io::Error::new(
+                kind,
+                format!(
+                    "Expected {:?} in the output, got {:?}",
+                    String::from_utf8_lossy(expected),
+                    String::from_utf8_lossy(&self.output[self.unmatched..])
+                ),
+            ));
+        }
+    }
+
+    /// Returns all the output of the process so far.
+    pub fn output(&self) -> &[u8] {
+        &self.output
+    }
+
+    /// Resizes the pseudo terminal, the process receives a `SIGWINCH`.
+    pub fn resize(&mut self, columns: u16, rows: u16) -> Result<()> {
+        let size = libc::winsize {
+            ws_row: rows,
+            ws_col: columns,
+            ws_xpixel: 0,
+            ws_ypixel: 0,
+        };
+        if unsafe { libc::ioctl(self.master.as_raw_fd(), libc::TIOCSWINSZ, &size) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// Waits for the process to exit and reads the rest of its output.
+    ///
+    /// The process is killed if it doesn't exit within the timeout and `io::ErrorKind::TimedOut`
+    /// is returned.
+    pub fn wait(&mut self, timeout: Duration) -> Result<ExitStatus> {
+        let timeout = PollTimeout::new(Some(timeout));
+
+        loop {
+            // The process blocks when nobody reads its output
+            let open = self.read_output(Some(WAIT_INTERVAL))?;
+
+            if let Some(status) = self.child.try_wait()? {
+                while self.read_output(Some(Duration::from_millis(0)))? {}
+                return Ok(status);
+            }
+
+            if timeout.elapsed() {
+                let _ = self.child.kill();
+                let _ = self.child.wait();
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "The process didn't exit in time.",
+                ));
+            }
+
+            if !open {
+                thread::sleep(WAIT_INTERVAL);
+            }
+        }
+    }
+
+    /// Reads the output available within the timeout.
+    ///
+    /// Returns `Ok(false)` if the terminal is closed, the process and its children exited.
+    fn read_output(&mut self, timeout: Option<Duration>) -> Result<bool> {
+        let mut fd = libc::pollfd {
+            fd: self.master.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let millis = timeout.map_or(-1, |timeout| {
+            timeout.as_millis().min(i32::MAX as u128) as i32
+        });
+
+        match unsafe { libc::poll(&mut fd, 1, millis) } {
+            -1 => {
+                let e = io::Error::last_os_error();
+                return if e.kind() == io::ErrorKind::Interrupted {
+                    Ok(true)
+                } else {
+                    Err(e)
+                };
+            }
+            0 => return Ok(true),
+            _ => {}
+        }
+
+        let mut buffer = [0u8; 1024];
+        match self.master.read(&mut buffer) {
+            Ok(0) => Ok(false),
+            Ok(count) => {
+                self.output.extend_from_slice(&buffer[..count]);
+                Ok(true)
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => Ok(true),
+            // Linux fails with `EIO` once the other side of the terminal is closed
+            Err(e) if e.raw_os_error() == Some(libc::EIO) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl Drop for Pty {
+    fn drop(&mut self) {
+        if let Ok(None) = self.child.try_wait() {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::stdout, time::Duration};
+
+    use crate::{
+        clipboard::{self, ClipboardSelection},
+        cursor, debug, event, execute,
+        terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
+    };
+
+    use super::Pty;
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Spawns the test in a pseudo terminal, the test name is relative to this module.
+    fn spawn_test(name: &str) -> Pty {
+        Pty::spawn_test(&format!("test_util::pty::tests::{}", name)).unwrap()
+    }
+
+    fn read_one_event() {
+        terminal::enable_raw_mode().unwrap();
+        // Creates the event source, the signal handlers are registered before `ready`
+        event::poll(Duration::from_millis(0)).unwrap();
+        println!("ready");
+        let event = event::read();
+        terminal::disable_raw_mode().unwrap();
+        println!("event {:?}", event);
+    }
+
+    #[test]
+    fn test_raw_mode_reads_control_keys() {
+        if Pty::is_test_child() {
+            return read_one_event();
+        }
+
+        let mut pty = spawn_test("test_raw_mode_reads_control_keys");
+        pty.expect("ready", TIMEOUT).unwrap();
+        // Ctrl+C is a key in the raw mode, not a `SIGINT`
+        pty.write(b"\x03").unwrap();
+        pty.expect(
+            "event Ok(Key(KeyEvent { code: Char('c'), modifiers: CONTROL",
+            TIMEOUT,
+        )
+        .unwrap();
+
+        assert!(pty.wait(TIMEOUT).unwrap().success());
+    }
+
+    #[test]
+    fn test_size_and_resize_event() {
+        if Pty::is_test_child() {
+            println!("size {:?}", terminal::size());
+            return read_one_event();
+        }
+
+        let mut pty = spawn_test("test_size_and_resize_event");
+        pty.expect("size Ok((80, 24))", TIMEOUT).unwrap();
+        pty.expect("ready", TIMEOUT).unwrap();
+        pty.resize(100, 30).unwrap();
+        pty.expect("event Ok(Resize(100, 30))", TIMEOUT).unwrap();
+
+        assert!(pty.wait(TIMEOUT).unwrap().success());
+    }
+
+    #[test]
+    fn test_dump_state() {
+        if Pty::is_test_child() {
+            terminal::enable_raw_mode().unwrap();
+            execute!(stdout(), event::EnableMouseCapture).unwrap();
+            let state = debug::dump_state();
+            execute!(stdout(), event::DisableMouseCapture).unwrap();
+            terminal::disable_raw_mode().unwrap();
+            println!("{}", state);
+            return;
+        }
+
+        let mut pty = spawn_test("test_dump_state");
+        pty.expect("tty: stdin true, stdout true, stderr true", TIMEOUT)
+            .unwrap();
+        pty.expect("size: 80x24", TIMEOUT).unwrap();
+        pty.expect("raw mode: true", TIMEOUT).unwrap();
+        pty.expect("alternate screen: false", TIMEOUT).unwrap();
+        pty.expect("mouse capture: true", TIMEOUT).unwrap();
+        pty.expect(" -ECHO -ICANON", TIMEOUT).unwrap();
+        pty.expect(" -ISIG", TIMEOUT).unwrap();
+
+        assert!(pty.wait(TIMEOUT).unwrap().success());
+    }
+
+    #[test]
+    fn test_queries() {
+        if Pty::is_test_child() {
+            println!("attributes {:?}", terminal::query_device_attributes());
+            println!("mode {:?}", terminal::query_mode(1049));
+            println!("mode {:?}", terminal::query_mode(2026));
+            println!("version {:?}", terminal::query_version());
+            println!("version {:?}", terminal::query_version());
+            println!("capability {:?}", terminal::query_capability("Co"));
+            println!("capability {:?}", terminal::query_capability("Tc"));
+            return;
+        }
+
+        let mut pty = spawn_test("test_queries");
+        pty.expect("\x1B[c", TIMEOUT).unwrap();
+        pty.write(b"\x1B[?65;4;22c").unwrap();
+        pty.expect("attributes Ok([65, 4, 22])", TIMEOUT).unwrap();
+
+        pty.expect("\x1B[?1049$p\x1B[c", TIMEOUT).unwrap();
+        pty.write(b"\x1B[?1049;2$y\x1B[?65c").unwrap();
+        pty.expect("mode Ok(Reset)", TIMEOUT).unwrap();
+
+        // Terminals which don't know the query only reply to the device attributes
+        pty.expect("\x1B[?2026$p\x1B[c", TIMEOUT).unwrap();
+        pty.write(b"\x1B[?65c").unwrap();
+        pty.expect("mode Ok(NotRecognized)", TIMEOUT).unwrap();
+
+        pty.expect("\x1B[>0q\x1B[c", TIMEOUT).unwrap();
+        pty.write(b"\x1BP>|xterm(367)\x1B\\\x1B[?65c").unwrap();
+        pty.expect("version Ok(Some(\"xterm(367)\"))", TIMEOUT)
+            .unwrap();
+
+        pty.expect("\x1B[>0q\x1B[c", TIMEOUT).unwrap();
+        pty.write(b"\x1B[?65c").unwrap();
+        pty.expect("version Ok(None)", TIMEOUT).unwrap();
+
+        pty.expect("\x1BP+q436F\x1B\\\x1B[c", TIMEOUT).unwrap();
+        pty.write(b"\x1BP1+r436F=323536\x1B\\\x1B[?65c").unwrap();
+        pty.expect("capability Ok(Some(\"256\"))", TIMEOUT).unwrap();
+
+        pty.expect("\x1BP+q5463\x1B\\\x1B[c", TIMEOUT).unwrap();
+        pty.write(b"\x1BP0+r5463\x1B\\\x1B[?65c").unwrap();
+        pty.expect("capability Ok(None)", TIMEOUT).unwrap();
+
+        assert!(pty.wait(TIMEOUT).unwrap().success());
+    }
+
+    #[test]
+    fn test_clipboard_queries() {
+        if Pty::is_test_child() {
+            println!("support {:?}", clipboard::query_support());
+            let content = clipboard::query_clipboard(ClipboardSelection::Clipboard);
+            println!("content {:?}", content);
+            let content = clipboard::query_clipboard(ClipboardSelection::Primary);
+            println!("content {:?}", content);
+            return;
+        }
+
+        let mut pty = spawn_test("test_clipboard_queries");
+        pty.expect("\x1B[c", TIMEOUT).unwrap();
+        pty.write(b"\x1B[?65;4;52c").unwrap();
+        pty.expect("support Ok(true)", TIMEOUT).unwrap();
+
+        pty.expect("\x1B]52;c;?\x07\x1B[c", TIMEOUT).unwrap();
+        pty.write(b"\x1B]52;c;Zm9v\x07\x1B[?65c").unwrap();
+        pty.expect("content Ok(Some([102, 111, 111]))", TIMEOUT)
+            .unwrap();
+
+        // The terminals which don't allow to read the clipboard don't reply
+        pty.expect("\x1B]52;p;?\x07\x1B[c", TIMEOUT).unwrap();
+        pty.write(b"\x1B[?65c").unwrap();
+        pty.expect("content Ok(None)", TIMEOUT).unwrap();
+
+        assert!(pty.wait(TIMEOUT).unwrap().success());
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn test_graphics_protocol_query() {
+        use crate::graphics;
+
+        if Pty::is_test_child() {
+            // The protocol of the terminal running the tests mustn't be detected
+            std::env::remove_var("TERM_PROGRAM");
+            println!("protocol {:?}", graphics::query_protocol());
+            println!("protocol {:?}", graphics::query_protocol());
+            println!("cell size {:?}", graphics::cell_size());
+            return;
+        }
+
+        let mut pty = spawn_test("test_graphics_protocol_query");
+        pty.expect("\x1B_Gi=31,s=1,v=1,a=q,t=d,f=24;AAAA\x1B\\\x1B[c", TIMEOUT)
+            .unwrap();
+        pty.write(b"\x1B_Gi=31;OK\x1B\\\x1B[?62;4c").unwrap();
+        pty.expect("protocol Ok(Some(Kitty))", TIMEOUT).unwrap();
+
+        // Sixel is reported in the device attributes
+        pty.expect("\x1B\\\x1B[c", TIMEOUT).unwrap();
+        pty.write(b"\x1B[?62;4c").unwrap();
+        pty.expect("\x1B[>0q\x1B[c", TIMEOUT).unwrap();
+        pty.write(b"\x1B[?62;4c").unwrap();
+        pty.expect("protocol Ok(Some(Sixel))", TIMEOUT).unwrap();
+
+        // The size of the pseudo terminal isn't given in pixels
+        pty.expect("cell size Ok(None)", TIMEOUT).unwrap();
+
+        assert!(pty.wait(TIMEOUT).unwrap().success());
+    }
+
+    #[test]
+    fn test_alternate_screen_and_cursor_position() {
+        if Pty::is_test_child() {
+            execute!(stdout(), EnterAlternateScreen).unwrap();
+            let position = cursor::position();
+            execute!(stdout(), LeaveAlternateScreen).unwrap();
+            println!("position {:?}", position);
+            return;
+        }
+
+        let mut pty = spawn_test("test_alternate_screen_and_cursor_position");
+        pty.expect("\x1B[?1049h", TIMEOUT).unwrap();
+        pty.expect("\x1B[6n", TIMEOUT).unwrap();
+        pty.write(b"\x1B[5;10R").unwrap();
+        pty.expect("\x1B[?1049l", TIMEOUT).unwrap();
+        pty.expect("position Ok((9, 4))", TIMEOUT).unwrap();
+
+        assert!(pty.wait(TIMEOUT).unwrap().success());
+    }
+}
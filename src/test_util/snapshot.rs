@@ -0,0 +1,246 @@
+use std::{env, fmt::Write as _, fs, io, path::Path};
+
+/// The environment variable which makes the snapshot assertions write the actual output.
+const UPDATE_VAR: &str = "CROSSTERM_UPDATE_SNAPSHOTS";
+
+/// Records the bytes written to it, the output of the commands executed on it.
+///
+/// On the Windows versions without ANSI support, `queue!` and `execute!` call the WinAPI instead
+/// of writing the sequences.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm::{cursor::MoveTo, queue, style::Print, test_util::OutputRecorder};
+///
+/// let mut output = OutputRecorder::default();
+/// queue!(output, MoveTo(1, 2), Print("hello")).unwrap();
+///
+/// assert_eq!(output.escaped(), "\\x1B[3;2Hhello");
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct OutputRecorder {
+    bytes: Vec<u8>,
+}
+
+impl OutputRecorder {
+    /// Returns the bytes written so far.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the bytes written so far, escaped with [`escape_output`](fn.escape_output.html).
+    pub fn escaped(&self) -> String {
+        escape_output(&self.bytes)
+    }
+
+    /// Forgets the bytes written so far.
+    pub fn clear(&mut self) {
+        self.bytes.clear();
+    }
+}
+
+impl io::Write for OutputRecorder {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.bytes.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Makes the output of commands readable and comparable as text.
+///
+/// The control characters, `ESC` included, and the bytes which aren't valid UTF-8 are written
+/// like `\x1B`, the backslash is doubled. Every escape sequence starts a new line and so does
+/// the text after a `\n`, those line breaks are only there to make the differences readable.
+pub fn escape_output(bytes: &[u8]) -> String {
+    let mut escaped = String::with_capacity(bytes.len() * 2);
+    let mut rest = bytes;
+
+    while !rest.is_empty() {
+        let (valid, invalid) = match std::str::from_utf8(rest) {
+            Ok(valid) => (valid, &[][..]),
+            Err(e) => {
+                let (valid, invalid) = rest.split_at(e.valid_up_to());
+                let length = e.error_len().unwrap_or(invalid.len());
+                (
+                    // Safe, the bytes were validated by `from_utf8`
+                    unsafe { std::str::from_utf8_unchecked(valid) },
+                    &invalid[..length],
+                )
+            }
+        };
+
+        for c in valid.chars() {
+            match c {
+                '\x1B' => {
+                    if !escaped.is_empty() && !escaped.ends_with('\n') {
+                        escaped.push('\n');
+                    }
+                    escaped.push_str("\\x1B");
+                }
+                '\n' => escaped.push_str("\\n\n"),
+                '\\' => escaped.push_str("\\\\"),
+                c if c.is_control() => {
+                    let _ = write!(escaped, "\\x{:02X}", c as u32);
+                }
+                c => escaped.push(c),
+            }
+        }
+        for byte in invalid {
+            let _ = write!(escaped, "\\x{:02X}", byte);
+        }
+
+        rest = &rest[valid.len() + invalid.len()..];
+    }
+
+    escaped
+}
+
+/// The implementation of the [`assert_snapshot!`](../macro.assert_snapshot.html) macro.
+///
+/// Compares the text with the content of the file. The file is written instead when it doesn't
+/// exist yet, or when the `CROSSTERM_UPDATE_SNAPSHOTS` environment variable is set. The
+/// assertion fails when a new snapshot is written, check it and run the tests again.
+#[track_caller]
+pub fn assert_snapshot(path: impl AsRef<Path>, actual: &str) {
+    let path = path.as_ref();
+
+    let expected = match fs::read_to_string(path) {
+        Ok(expected) if env::var_os(UPDATE_VAR).is_none() => expected,
+        Ok(_) => {
+            write_snapshot(path, actual);
+            return;
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            write_snapshot(path, actual);
+            panic!(
+                "the snapshot {} didn't exist, it was written. Check it and run the test again.",
+                path.display()
+            );
+        }
+        Err(e) => panic!("failed to read the snapshot {}: {}", path.display(), e),
+    };
+
+    if expected == actual {
+        return;
+    }
+
+    let mut report = format!(
+        "assertion failed: the output doesn't match the snapshot {}\n\
+         set {}=1 to update it if the change is expected",
+        path.display(),
+        UPDATE_VAR
+    );
+    let (expected, actual): (Vec<&str>, Vec<&str>) =
+        (expected.lines().collect(), actual.lines().collect());
+    for line in 0..expected.len().max(actual.len()) {
+        match (expected.get(line), actual.get(line)) {
+            (Some(expected), Some(actual)) if expected == actual => {}
+            (expected, actual) => {
+                let _ = write!(
+                    report,
+                    "\nline {}:\n  expected: {}\n  actual:   {}",
+                    line + 1,
+                    expected.unwrap_or(&"(missing)"),
+                    actual.unwrap_or(&"(missing)")
+                );
+            }
+        }
+    }
+
+    panic!("{}", report);
+}
+
+fn write_snapshot(path: &Path, actual: &str) {
+    let written = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(path, actual));
+
+    if let Err(e) = written {
+        panic!("failed to write the snapshot {}: {}", path.display(), e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cursor::{self, CursorShape},
+        event::{DisableMouseCapture, EnableMouseCapture},
+        queue,
+        style::{
+            Attribute, Attributes, Color, Colors, Print, PrintStyledContent, ResetColor,
+            SetAttribute, SetAttributes, SetBackgroundColor, SetColors, SetForegroundColor,
+            Stylize,
+        },
+        terminal::{self, ClearType},
+    };
+
+    use super::{escape_output, OutputRecorder};
+
+    #[test]
+    fn test_escape_output() {
+        assert_eq!(
+            escape_output(b"a\x1B[1mb\\\x07\n\xFFc\xC3\xA9\x1B7"),
+            "a\n\\x1B[1mb\\\\\\x07\\n\n\\xFFcé\n\\x1B7"
+        );
+    }
+
+    /// The output of every command, a change of the formatting shows up as a difference.
+    #[test]
+    fn test_commands_output() {
+        let mut output = OutputRecorder::default();
+
+        queue!(
+            output,
+            cursor::MoveTo(2, 3),
+            cursor::MoveToNextLine(2),
+            cursor::MoveToPreviousLine(2),
+            cursor::MoveToColumn(4),
+            cursor::MoveToRow(5),
+            cursor::MoveUp(6),
+            cursor::MoveRight(7),
+            cursor::MoveDown(8),
+            cursor::MoveLeft(9),
+            cursor::SavePosition,
+            cursor::RestorePosition,
+            cursor::Hide,
+            cursor::Show,
+            cursor::EnableBlinking,
+            cursor::DisableBlinking,
+            cursor::SetCursorShape(CursorShape::UnderScore),
+            terminal::DisableLineWrap,
+            terminal::EnableLineWrap,
+            terminal::EnterAlternateScreen,
+            terminal::LeaveAlternateScreen,
+            terminal::ScrollUp(2),
+            terminal::ScrollDown(3),
+            terminal::Clear(ClearType::All),
+            terminal::Clear(ClearType::FromCursorDown),
+            terminal::Clear(ClearType::FromCursorUp),
+            terminal::Clear(ClearType::CurrentLine),
+            terminal::Clear(ClearType::UntilNewLine),
+            terminal::SetSize(80, 24),
+            terminal::SetTitle("title"),
+            EnableMouseCapture,
+            DisableMouseCapture,
+            SetForegroundColor(Color::Red),
+            SetBackgroundColor(Color::AnsiValue(42)),
+            SetColors(Colors::new(Color::Rgb { r: 1, g: 2, b: 3 }, Color::Reset)),
+            SetAttribute(Attribute::Bold),
+            SetAttributes(Attributes::from(
+                [Attribute::Italic, Attribute::CrossedOut].as_ref()
+            )),
+            ResetColor,
+            PrintStyledContent("styled".blue().on_white().underlined()),
+            Print("line\n"),
+        )
+        .unwrap();
+
+        crate::assert_snapshot!("commands", output.escaped());
+    }
+}
@@ -0,0 +1,987 @@
+use std::{fmt, io, str, sync::Arc, time::Duration};
+
+use crate::{
+    event::{
+        timeout::{self, Clock},
+        Event, InternalEvent,
+    },
+    line_drawing::{
+        self, BOTTOM_LEFT, BOTTOM_RIGHT, BOTTOM_TEE, CROSS, HORIZONTAL, LEFT_TEE, RIGHT_TEE,
+        TOP_LEFT, TOP_RIGHT, TOP_TEE, VERTICAL,
+    },
+    style::{Attribute, Attributes, Color},
+    Command, Result,
+};
+
+use super::{
+    input::{InputQueue, VirtualEventSource},
+    VirtualClock,
+};
+
+/// The box-drawing characters of the DEC Special Graphics character set.
+const LINES: [char; 11] = [
+    HORIZONTAL,
+    VERTICAL,
+    TOP_LEFT,
+    TOP_RIGHT,
+    BOTTOM_LEFT,
+    BOTTOM_RIGHT,
+    LEFT_TEE,
+    RIGHT_TEE,
+    TOP_TEE,
+    BOTTOM_TEE,
+    CROSS,
+];
+
+/// A cell of the screen of a [`VirtualTerminal`](struct.VirtualTerminal.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    symbol: char,
+    fg: Color,
+    bg: Color,
+    attributes: Attributes,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Cell {
+            symbol: ' ',
+            fg: Color::Reset,
+            bg: Color::Reset,
+            attributes: Attributes::default(),
+        }
+    }
+}
+
+impl Cell {
+    /// Returns the character in the cell, a space if nothing was printed there.
+    pub fn symbol(&self) -> char {
+        self.symbol
+    }
+
+    /// Returns the foreground color of the cell.
+    pub fn fg(&self) -> Color {
+        self.fg
+    }
+
+    /// Returns the background color of the cell.
+    pub fn bg(&self) -> Color {
+        self.bg
+    }
+
+    /// Returns the attributes of the cell (bold, italic, ...).
+    pub fn attributes(&self) -> Attributes {
+        self.attributes
+    }
+}
+
+/// Where the output parser is within an escape sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Ground,
+    Escape,
+    /// After `ESC (`, selecting the character set.
+    Charset,
+    Csi,
+    Osc,
+    OscEscape,
+}
+
+/// An in-memory terminal, a grid of styled cells.
+///
+/// The bytes written to it are interpreted like a terminal would do, the commands of this crate
+/// can be executed with [`execute`](#method.execute) or the `queue!`/`execute!` macros. The
+/// screen contents can be inspected afterwards.
+///
+/// The events sent with [`send_event`](#method.send_event) are returned by
+/// [`event::read`](../event/fn.read.html), [`event::poll`](../event/fn.poll.html) and the
+/// `EventStream` once [`attach_input`](#method.attach_input) was called.
+///
+/// Limitations:
+///
+/// * Every character takes a single cell, even the wide ones.
+/// * `\n` moves to the beginning of the next line, as the output processing of a terminal which
+///   isn't in the raw mode does.
+/// * Only the sequences written by the commands of this crate are understood, others are
+///   ignored.
+/// * The functions querying the terminal (`terminal::size`, `cursor::position`, ...) keep
+///   querying the real terminal.
+pub struct VirtualTerminal {
+    columns: u16,
+    rows: u16,
+    screen: Vec<Cell>,
+    /// The main screen and the cursor position while the alternate screen is active.
+    main_screen: Option<(Vec<Cell>, (u16, u16))>,
+    cursor: (u16, u16),
+    /// The last column was printed to, the next character goes to the next line.
+    pending_wrap: bool,
+    saved_cursor: Option<(u16, u16)>,
+    pen: Cell,
+    cursor_visible: bool,
+    line_wrap: bool,
+    mouse_capture: bool,
+    focus_change: bool,
+    title: String,
+    /// The DEC Special Graphics character set is selected.
+    dec_special_graphics: bool,
+    state: State,
+    sequence: Vec<u8>,
+    /// The bytes of a character split across writes.
+    utf8: Vec<u8>,
+    input: Arc<InputQueue>,
+}
+
+impl fmt::Debug for VirtualTerminal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VirtualTerminal")
+            .field("size", &(self.columns, self.rows))
+            .field("cursor", &self.cursor)
+            .field("lines", &self.lines())
+            .finish()
+    }
+}
+
+impl VirtualTerminal {
+    /// Creates an empty virtual terminal with the given size.
+    pub fn new(columns: u16, rows: u16) -> VirtualTerminal {
+        VirtualTerminal {
+            columns,
+            rows,
+            screen: vec![Cell::default(); columns as usize * rows as usize],
+            main_screen: None,
+            cursor: (0, 0),
+            pending_wrap: false,
+            saved_cursor: None,
+            pen: Cell::default(),
+            cursor_visible: true,
+            line_wrap: true,
+            mouse_capture: false,
+            focus_change: false,
+            title: String::new(),
+            dec_special_graphics: false,
+            state: State::Ground,
+            sequence: Vec::new(),
+            utf8: Vec::new(),
+            input: Arc::new(InputQueue::default()),
+        }
+    }
+
+    /// Executes the command on the virtual terminal.
+    ///
+    /// Unlike the `execute!` macro, the ANSI sequences are used on every platform.
+    pub fn execute(&mut self, command: impl Command) -> Result<&mut Self> {
+        #[cfg(feature = "validate")]
+        command.validate(self.size())?;
+
+        let mut ansi = String::new();
+        command
+            .write_ansi(&mut ansi)
+            .map_err(|_| io::Error::other("Failed to write the command."))?;
+        self.process(ansi.as_bytes());
+
+        Ok(self)
+    }
+
+    /// Returns the `(columns, rows)` of the terminal.
+    pub fn size(&self) -> (u16, u16) {
+        (self.columns, self.rows)
+    }
+
+    /// Resizes the terminal and sends the `Event::Resize`, like a real terminal does when its
+    /// window is resized.
+    ///
+    /// The contents are kept, cut off at the new size.
+    pub fn resize(&mut self, columns: u16, rows: u16) {
+        let (old_columns, old_rows) = (self.columns, self.rows);
+        let resized = |screen: &[Cell]| {
+            let mut cells = vec![Cell::default(); columns as usize * rows as usize];
+            for row in 0..rows.min(old_rows) as usize {
+                for column in 0..columns.min(old_columns) as usize {
+                    cells[row * columns as usize + column] =
+                        screen[row * old_columns as usize + column];
+                }
+            }
+            cells
+        };
+
+        self.screen = resized(&self.screen);
+        self.main_screen = self
+            .main_screen
+            .take()
+            .map(|(screen, cursor)| (resized(&screen), cursor));
+        self.columns = columns;
+        self.rows = rows;
+        self.cursor = self.clamped(self.cursor.0, self.cursor.1);
+        self.pending_wrap = false;
+
+        self.send_event(Event::Resize(columns, rows));
+    }
+
+    /// Returns the `(column, row)` of the cursor, starting at `(0, 0)`.
+    pub fn cursor_position(&self) -> (u16, u16) {
+        self.cursor
+    }
+
+    /// Returns the cell at the given position, `None` if it's outside of the screen.
+    pub fn cell(&self, column: u16, row: u16) -> Option<&Cell> {
+        if column < self.columns && row < self.rows {
+            self.screen.get(self.index(column, row))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the cells of a row, `None` if it's outside of the screen.
+    pub fn row(&self, row: u16) -> Option<&[Cell]> {
+        if row < self.rows {
+            let start = self.index(0, row);
+            Some(&self.screen[start..start + self.columns as usize])
+        } else {
+            None
+        }
+    }
+
+    /// Returns the `(column, row)` where the text starts the first time, `None` if it's nowhere
+    /// on the screen.
+    ///
+    /// The text has to be on a single row.
+    pub fn find(&self, text: &str) -> Option<(u16, u16)> {
+        let text: Vec<char> = text.chars().collect();
+        if text.is_empty() {
+            return Some((0, 0));
+        }
+
+        (0..self.rows).find_map(|row| {
+            let symbols: Vec<char> = self.row(row)?.iter().map(Cell::symbol).collect();
+            symbols
+                .windows(text.len())
+                .position(|window| window == &text[..])
+                .map(|column| (column as u16, row))
+        })
+    }
+
+    /// Returns the characters of every row, without the trailing spaces.
+    pub fn lines(&self) -> Vec<String> {
+        self.screen
+            .chunks(self.columns.max(1) as usize)
+            .take(self.rows as usize)
+            .map(|row| {
+                row.iter()
+                    .map(Cell::symbol)
+                    .collect::<String>()
+                    .trim_end_matches(' ')
+                    .to_string()
+            })
+            .collect()
+    }
+
+    /// Returns the title set with `SetTitle`.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Returns whether the cursor is visible.
+    pub fn is_cursor_visible(&self) -> bool {
+        self.cursor_visible
+    }
+
+    /// Returns whether the alternate screen is active.
+    pub fn is_alternate_screen(&self) -> bool {
+        self.main_screen.is_some()
+    }
+
+    /// Returns whether the text wraps at the end of the line.
+    pub fn is_line_wrap_enabled(&self) -> bool {
+        self.line_wrap
+    }
+
+    /// Returns whether the mouse events are captured.
+    pub fn is_mouse_capture_enabled(&self) -> bool {
+        self.mouse_capture
+    }
+
+    /// Returns whether the focus events are enabled.
+    pub fn is_focus_change_enabled(&self) -> bool {
+        self.focus_change
+    }
+
+    /// Sends an event, as if it was the input of the terminal.
+    pub fn send_event(&self, event: Event) {
+        self.input.push(InternalEvent::Event(event));
+    }
+
+    /// Sends an event which becomes available at the given time.
+    ///
+    /// The time counts from the creation of the terminal, or it's the time of the
+    /// [`VirtualClock`](struct.VirtualClock.html) once the virtual time is enabled. The events
+    /// are read in the order of their time.
+    pub fn schedule_event(&self, at: Duration, event: Event) {
+        self.input.schedule(at, InternalEvent::Event(event));
+    }
+
+    /// Makes the input of the terminal use a virtual clock, starting at zero.
+    ///
+    /// The time only moves when the clock is advanced, or when waiting for events: waiting jumps
+    /// to the next scheduled event or to the end of the timeout right away. `poll(timeout)`
+    /// becomes deterministic and the tests don't sleep.
+    ///
+    /// Call it before [`attach_input`](#method.attach_input), the timeouts of `poll` use the
+    /// clock while the input is attached. Waiting without a timeout, when no event is scheduled,
+    /// still waits for an event sent by another thread.
+    pub fn enable_virtual_time(&self) -> VirtualClock {
+        self.input.enable_clock()
+    }
+
+    /// Sends the bytes, as if the terminal received them as input.
+    ///
+    /// They are parsed like the bytes read from a real terminal, replies to queries like the
+    /// cursor position can be sent as well.
+    pub fn send_input(&self, bytes: &[u8]) {
+        let mut parser = crate::event::source::unix::Parser::default();
+        parser.advance(bytes, false);
+        for event in parser {
+            self.input.push(event);
+        }
+    }
+
+    /// Makes [`event::read`](../event/fn.read.html), [`event::poll`](../event/fn.poll.html) and
+    /// the `EventStream` return the events sent to this terminal instead of the input of the
+    /// real terminal.
+    ///
+    /// The events which weren't read yet are thrown away. Use [`detach_input`](fn.detach_input.html)
+    /// to read from the real terminal again.
+    pub fn attach_input(&self) {
+        if let Ok(source) = VirtualEventSource::new(self.input.clone()) {
+            crate::event::replace_event_source(Some(Box::new(source)));
+            timeout::set_clock(
+                self.input
+                    .clock()
+                    .map(|clock| Arc::new(clock) as Arc<dyn Clock>),
+            );
+        }
+    }
+
+    fn index(&self, column: u16, row: u16) -> usize {
+        row as usize * self.columns as usize + column as usize
+    }
+
+    fn clamped(&self, column: u16, row: u16) -> (u16, u16) {
+        (
+            column.min(self.columns.saturating_sub(1)),
+            row.min(self.rows.saturating_sub(1)),
+        )
+    }
+
+    fn move_cursor(&mut self, column: u16, row: u16) {
+        self.cursor = self.clamped(column, row);
+        self.pending_wrap = false;
+    }
+
+    fn process(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.advance(*byte);
+        }
+    }
+
+    fn advance(&mut self, byte: u8) {
+        match self.state {
+            State::Ground => match byte {
+                b'\x1B' => {
+                    self.utf8.clear();
+                    self.state = State::Escape;
+                }
+                0x00..=0x1F | 0x7F => self.control(byte),
+                _ => self.print_byte(byte),
+            },
+            State::Escape => {
+                self.state = State::Ground;
+                match byte {
+                    b'[' => {
+                        self.sequence.clear();
+                        self.state = State::Csi;
+                    }
+                    b']' => {
+                        self.sequence.clear();
+                        self.state = State::Osc;
+                    }
+                    b'(' => self.state = State::Charset,
+                    b'7' => self.saved_cursor = Some(self.cursor),
+                    b'8' => self.restore_cursor(),
+                    _ => {}
+                }
+            }
+            State::Charset => {
+                self.state = State::Ground;
+                self.dec_special_graphics = byte == b'0';
+            }
+            State::Csi => match byte {
+                0x20..=0x3F => self.sequence.push(byte),
+                0x40..=0x7E => {
+                    self.state = State::Ground;
+                    let parameters = std::mem::take(&mut self.sequence);
+                    self.csi(str::from_utf8(&parameters).unwrap_or_default(), byte);
+                }
+                // Not a sequence we know, throw it away
+                _ => self.state = State::Ground,
+            },
+            State::Osc => match byte {
+                b'\x07' => self.osc(),
+                b'\x1B' => self.state = State::OscEscape,
+                _ => self.sequence.push(byte),
+            },
+            State::OscEscape => {
+                self.osc();
+                if byte != b'\\' {
+                    self.advance(byte);
+                }
+            }
+        }
+    }
+
+    fn control(&mut self, byte: u8) {
+        match byte {
+            b'\r' => self.move_cursor(0, self.cursor.1),
+            b'\n' => {
+                self.line_feed();
+                self.move_cursor(0, self.cursor.1);
+            }
+            b'\x08' => self.move_cursor(self.cursor.0.saturating_sub(1), self.cursor.1),
+            b'\t' => self.move_cursor((self.cursor.0 / 8 + 1) * 8, self.cursor.1),
+            _ => {}
+        }
+    }
+
+    fn print_byte(&mut self, byte: u8) {
+        self.utf8.push(byte);
+        match str::from_utf8(&self.utf8) {
+            Ok(s) => {
+                let c = s.chars().next().unwrap_or(char::REPLACEMENT_CHARACTER);
+                self.utf8.clear();
+                self.print(c);
+            }
+            // The rest of the character follows
+            Err(e) if e.error_len().is_none() => {}
+            Err(_) => {
+                self.utf8.clear();
+                self.print(char::REPLACEMENT_CHARACTER);
+            }
+        }
+    }
+
+    fn print(&mut self, symbol: char) {
+        if self.columns == 0 || self.rows == 0 {
+            return;
+        }
+
+        if self.pending_wrap {
+            self.line_feed();
+            self.cursor.0 = 0;
+            self.pending_wrap = false;
+        }
+
+        let symbol = if self.dec_special_graphics {
+            LINES
+                .iter()
+                .copied()
+                .find(|line| line_drawing::dec_special_graphics(*line) == Some(symbol))
+                .unwrap_or(symbol)
+        } else {
+            symbol
+        };
+
+        let (column, row) = self.cursor;
+        let index = self.index(column, row);
+        self.screen[index] = Cell { symbol, ..self.pen };
+
+        if column + 1 < self.columns {
+            self.cursor.0 += 1;
+        } else if self.line_wrap {
+            self.pending_wrap = true;
+        }
+    }
+
+    fn line_feed(&mut self) {
+        if self.cursor.1 + 1 < self.rows {
+            self.cursor.1 += 1;
+        } else {
+            self.scroll_up(1);
+        }
+    }
+
+    fn scroll_up(&mut self, rows: u16) {
+        let cells = rows.min(self.rows) as usize * self.columns as usize;
+        self.screen.drain(..cells);
+        self.screen
+            .extend(std::iter::repeat_n(Cell::default(), cells));
+    }
+
+    fn scroll_down(&mut self, rows: u16) {
+        let cells = rows.min(self.rows) as usize * self.columns as usize;
+        self.screen.truncate(self.screen.len() - cells);
+        self.screen
+            .splice(0..0, std::iter::repeat_n(Cell::default(), cells));
+    }
+
+    fn clear(&mut self, from: usize, to: usize) {
+        let to = to.min(self.screen.len());
+        if from < to {
+            self.screen[from..to].fill(Cell::default());
+        }
+    }
+
+    fn restore_cursor(&mut self) {
+        if let Some((column, row)) = self.saved_cursor {
+            self.move_cursor(column, row);
+        }
+    }
+
+    fn csi(&mut self, parameters: &str, action: u8) {
+        if let Some(modes) = parameters.strip_prefix('?') {
+            match action {
+                b'h' => modes.split(';').for_each(|mode| self.set_mode(mode, true)),
+                b'l' => modes.split(';').for_each(|mode| self.set_mode(mode, false)),
+                _ => {}
+            }
+            return;
+        }
+
+        let values: Vec<&str> = parameters.split(';').collect();
+        // The parameter at the index, 0 and missing ones are the default
+        let value = |index: usize, default: u16| {
+            values
+                .get(index)
+                .and_then(|value| value.parse::<u16>().ok())
+                .filter(|value| *value != 0)
+                .unwrap_or(default)
+        };
+        let (column, row) = self.cursor;
+        let line_start = self.index(0, row);
+        let line_end = line_start + self.columns as usize;
+
+        match action {
+            b'A' => self.move_cursor(column, row.saturating_sub(value(0, 1))),
+            b'B' => self.move_cursor(column, row.saturating_add(value(0, 1))),
+            b'C' => self.move_cursor(column.saturating_add(value(0, 1)), row),
+            b'D' => self.move_cursor(column.saturating_sub(value(0, 1)), row),
+            b'E' => self.move_cursor(0, row.saturating_add(value(0, 1))),
+            b'F' => self.move_cursor(0, row.saturating_sub(value(0, 1))),
+            b'G' => self.move_cursor(value(0, 1) - 1, row),
+            b'd' => self.move_cursor(column, value(0, 1) - 1),
+            b'H' | b'f' => self.move_cursor(value(1, 1) - 1, value(0, 1) - 1),
+            b'J' => match value(0, 0) {
+                0 => self.clear(self.index(column, row), self.screen.len()),
+                1 => self.clear(0, self.index(column, row) + 1),
+                _ => self.clear(0, self.screen.len()),
+            },
+            b'K' => match value(0, 0) {
+                0 => self.clear(self.index(column, row), line_end),
+                1 => self.clear(line_start, self.index(column, row) + 1),
+                _ => self.clear(line_start, line_end),
+            },
+            b'S' => self.scroll_up(value(0, 1)),
+            b'T' => self.scroll_down(value(0, 1)),
+            b's' => self.saved_cursor = Some(self.cursor),
+            b'u' => self.restore_cursor(),
+            b'm' => self.sgr(&values),
+            b't' if value(0, 0) == 8 => {
+                let (columns, rows) = (value(2, self.columns), value(1, self.rows));
+                self.resize(columns, rows);
+            }
+            _ => {}
+        }
+    }
+
+    fn set_mode(&mut self, mode: &str, enabled: bool) {
+        match mode {
+            "7" => self.line_wrap = enabled,
+            "25" => self.cursor_visible = enabled,
+            "1000" => self.mouse_capture = enabled,
+            "1004" => self.focus_change = enabled,
+            "1049" if enabled && self.main_screen.is_none() => {
+                let screen = vec![Cell::default(); self.screen.len()];
+                self.main_screen = Some((std::mem::replace(&mut self.screen, screen), self.cursor));
+            }
+            "1049" if !enabled => {
+                if let Some((screen, (column, row))) = self.main_screen.take() {
+                    self.screen = screen;
+                    self.move_cursor(column, row);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn sgr(&mut self, values: &[&str]) {
+        const COLORS: [Color; 8] = [
+            Color::Black,
+            Color::DarkRed,
+            Color::DarkGreen,
+            Color::DarkYellow,
+            Color::DarkBlue,
+            Color::DarkMagenta,
+            Color::DarkCyan,
+            Color::Grey,
+        ];
+        const BRIGHT_COLORS: [Color; 8] = [
+            Color::DarkGrey,
+            Color::Red,
+            Color::Green,
+            Color::Yellow,
+            Color::Blue,
+            Color::Magenta,
+            Color::Cyan,
+            Color::White,
+        ];
+
+        let mut index = 0;
+        while index < values.len() {
+            let code = values[index].parse::<i16>().unwrap_or(0);
+            index += 1;
+
+            match code {
+                38 | 48 => {
+                    // `5;<n>` or `2;<r>;<g>;<b>`
+                    let count = if values.get(index) == Some(&"2") {
+                        4
+                    } else {
+                        2
+                    };
+                    let end = (index + count).min(values.len());
+                    let color = Color::parse_ansi_iter(&mut values[index..end].iter().copied());
+                    index = end;
+
+                    if let Some(color) = color {
+                        if code == 38 {
+                            self.pen.fg = color;
+                        } else {
+                            self.pen.bg = color;
+                        }
+                    }
+                }
+                39 => self.pen.fg = Color::Reset,
+                49 => self.pen.bg = Color::Reset,
+                30..=37 => self.pen.fg = COLORS[code as usize - 30],
+                40..=47 => self.pen.bg = COLORS[code as usize - 40],
+                90..=97 => self.pen.fg = BRIGHT_COLORS[code as usize - 90],
+                100..=107 => self.pen.bg = BRIGHT_COLORS[code as usize - 100],
+                _ => {
+                    if let Some(attribute) = Attribute::iterator().find(|a| a.sgr() == code) {
+                        self.set_attribute(attribute);
+                    }
+                }
+            }
+        }
+    }
+
+    fn set_attribute(&mut self, attribute: Attribute) {
+        let attributes = &mut self.pen.attributes;
+        match attribute {
+            Attribute::Reset => self.pen = Cell::default(),
+            Attribute::NoBold => attributes.unset(Attribute::Bold),
+            Attribute::NormalIntensity => {
+                attributes.unset(Attribute::Bold);
+                attributes.unset(Attribute::Dim);
+            }
+            Attribute::NoItalic => attributes.unset(Attribute::Italic),
+            Attribute::NoUnderline => attributes.unset(Attribute::Underlined),
+            Attribute::NoBlink => {
+                attributes.unset(Attribute::SlowBlink);
+                attributes.unset(Attribute::RapidBlink);
+            }
+            Attribute::NoReverse => attributes.unset(Attribute::Reverse),
+            Attribute::NoHidden => attributes.unset(Attribute::Hidden),
+            Attribute::NotCrossedOut => attributes.unset(Attribute::CrossedOut),
+            Attribute::NotFramedOrEncircled => {
+                attributes.unset(Attribute::Framed);
+                attributes.unset(Attribute::Encircled);
+            }
+            Attribute::NotOverLined => attributes.unset(Attribute::OverLined),
+            attribute => attributes.set(attribute),
+        }
+    }
+
+    fn osc(&mut self) {
+        self.state = State::Ground;
+        let sequence = std::mem::take(&mut self.sequence);
+        let sequence = String::from_utf8_lossy(&sequence);
+
+        if let Some(title) = sequence
+            .strip_prefix("0;")
+            .or_else(|| sequence.strip_prefix("2;"))
+        {
+            self.title = title.to_string();
+        }
+    }
+}
+
+impl io::Write for VirtualTerminal {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.process(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Makes [`event::read`](../event/fn.read.html), [`event::poll`](../event/fn.poll.html) and the
+/// `EventStream` read from the real terminal again after
+/// [`VirtualTerminal::attach_input`](struct.VirtualTerminal.html#method.attach_input).
+pub fn detach_input() {
+    crate::event::replace_event_source(None);
+    timeout::set_clock(None);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        cursor::{Hide, MoveTo, MoveToNextLine, RestorePosition, SavePosition},
+        event::{self, EnableFocusChange, EnableMouseCapture, Event, KeyCode},
+        style::{
+            Attribute, Color, Print, ResetColor, SetAttribute, SetBackgroundColor,
+            SetForegroundColor,
+        },
+        terminal::{
+            Clear, ClearType, DisableLineWrap, EnterAlternateScreen, LeaveAlternateScreen,
+            ScrollUp, SetSize, SetTitle,
+        },
+    };
+
+    use super::VirtualTerminal;
+
+    /// Serializes the tests replacing the global event source.
+    static INPUT_LOCK: parking_lot::Mutex<()> = parking_lot::const_mutex(());
+
+    #[test]
+    fn test_print_and_move() {
+        let mut terminal = VirtualTerminal::new(10, 3);
+
+        terminal
+            .execute(Print("ab"))
+            .unwrap()
+            .execute(MoveTo(4, 2))
+            .unwrap()
+            .execute(Print("cd"))
+            .unwrap()
+            .execute(MoveToNextLine(1))
+            .unwrap();
+
+        assert_eq!(terminal.lines(), vec!["ab", "", "    cd"]);
+        assert_eq!(terminal.cursor_position(), (0, 2));
+    }
+
+    #[test]
+    fn test_wrap_and_scroll() {
+        let mut terminal = VirtualTerminal::new(3, 2);
+
+        terminal.execute(Print("abcdefgh")).unwrap();
+        assert_eq!(terminal.lines(), vec!["def", "gh"]);
+
+        terminal
+            .execute(DisableLineWrap)
+            .unwrap()
+            .execute(MoveTo(0, 0))
+            .unwrap()
+            .execute(Print("xyzw"))
+            .unwrap();
+        assert_eq!(terminal.lines(), vec!["xyw", "gh"]);
+
+        terminal.execute(ScrollUp(1)).unwrap();
+        assert_eq!(terminal.lines(), vec!["gh", ""]);
+    }
+
+    #[test]
+    fn test_styles() {
+        let mut terminal = VirtualTerminal::new(10, 1);
+
+        terminal
+            .execute(SetForegroundColor(Color::Red))
+            .unwrap()
+            .execute(SetBackgroundColor(Color::Rgb { r: 1, g: 2, b: 3 }))
+            .unwrap()
+            .execute(SetAttribute(Attribute::Bold))
+            .unwrap()
+            .execute(Print("a"))
+            .unwrap()
+            .execute(ResetColor)
+            .unwrap()
+            .execute(Print("b"))
+            .unwrap();
+
+        let styled = terminal.cell(0, 0).unwrap();
+        assert_eq!(styled.symbol(), 'a');
+        assert_eq!(styled.fg(), Color::Red);
+        assert_eq!(styled.bg(), Color::Rgb { r: 1, g: 2, b: 3 });
+        assert!(styled.attributes().has(Attribute::Bold));
+
+        let reset = terminal.cell(1, 0).unwrap();
+        assert_eq!(reset.fg(), Color::Reset);
+        assert!(reset.attributes().is_empty());
+        assert!(terminal.cell(10, 0).is_none());
+    }
+
+    #[test]
+    fn test_clear_and_cursor() {
+        let mut terminal = VirtualTerminal::new(5, 2);
+
+        terminal
+            .execute(Print("abcde12345"))
+            .unwrap()
+            .execute(MoveTo(2, 0))
+            .unwrap()
+            .execute(SavePosition)
+            .unwrap()
+            .execute(Clear(ClearType::UntilNewLine))
+            .unwrap()
+            .execute(MoveTo(0, 1))
+            .unwrap()
+            .execute(RestorePosition)
+            .unwrap()
+            .execute(Hide)
+            .unwrap();
+
+        assert_eq!(terminal.lines(), vec!["ab", "12345"]);
+        assert_eq!(terminal.cursor_position(), (2, 0));
+        assert!(!terminal.is_cursor_visible());
+
+        terminal.execute(Clear(ClearType::All)).unwrap();
+        assert_eq!(terminal.lines(), vec!["", ""]);
+    }
+
+    #[test]
+    fn test_modes() {
+        let mut terminal = VirtualTerminal::new(5, 2);
+
+        terminal
+            .execute(Print("main"))
+            .unwrap()
+            .execute(EnterAlternateScreen)
+            .unwrap()
+            .execute(MoveTo(0, 0))
+            .unwrap()
+            .execute(Print("alt"))
+            .unwrap()
+            .execute(SetTitle("title"))
+            .unwrap()
+            .execute(EnableMouseCapture)
+            .unwrap()
+            .execute(EnableFocusChange)
+            .unwrap();
+
+        assert!(terminal.is_alternate_screen());
+        assert!(terminal.is_mouse_capture_enabled());
+        assert!(terminal.is_focus_change_enabled());
+        assert_eq!(terminal.title(), "title");
+        assert_eq!(terminal.lines(), vec!["alt", ""]);
+
+        terminal.execute(LeaveAlternateScreen).unwrap();
+        assert_eq!(terminal.lines(), vec!["main", ""]);
+        assert_eq!(terminal.cursor_position(), (4, 0));
+    }
+
+    #[test]
+    fn test_dec_special_graphics() {
+        let mut terminal = VirtualTerminal::new(5, 1);
+
+        terminal.execute(Print("\x1B(0lqk\x1B(Bq")).unwrap();
+
+        assert_eq!(terminal.lines(), vec!["┌─┐q"]);
+    }
+
+    #[test]
+    fn test_queue_macro_and_split_writes() {
+        let mut terminal = VirtualTerminal::new(5, 1);
+
+        crate::queue!(terminal, Print("é"), MoveTo(3, 0)).unwrap();
+        std::io::Write::write_all(&mut terminal, b"\x1B[").unwrap();
+        std::io::Write::write_all(&mut terminal, b"1Gx").unwrap();
+
+        assert_eq!(terminal.lines(), vec!["x"]);
+    }
+
+    #[test]
+    fn test_cell_queries() {
+        let mut terminal = VirtualTerminal::new(6, 2);
+
+        terminal
+            .execute(MoveTo(1, 1))
+            .unwrap()
+            .execute(SetForegroundColor(Color::Blue))
+            .unwrap()
+            .execute(Print("héhé"))
+            .unwrap();
+
+        assert_eq!(terminal.find("éh"), Some((2, 1)));
+        assert_eq!(terminal.find("hé"), Some((1, 1)));
+        assert_eq!(terminal.find("x"), None);
+
+        let row = terminal.row(1).unwrap();
+        assert_eq!(row.len(), 6);
+        assert_eq!(row[2].symbol(), 'é');
+        assert_eq!(row[2].fg(), Color::Blue);
+        assert_eq!(row[0].fg(), Color::Reset);
+        assert!(terminal.row(2).is_none());
+    }
+
+    #[test]
+    fn test_input_round_trip() {
+        let _guard = INPUT_LOCK.lock();
+        let mut terminal = VirtualTerminal::new(5, 1);
+        terminal.attach_input();
+
+        terminal.execute(SetSize(8, 2)).unwrap();
+        terminal.send_event(Event::Key(KeyCode::Enter.into()));
+
+        assert_eq!(terminal.size(), (8, 2));
+        assert_eq!(event::read().unwrap(), Event::Resize(8, 2));
+        assert_eq!(event::read().unwrap(), Event::Key(KeyCode::Enter.into()));
+        assert!(!event::poll(Duration::from_millis(10)).unwrap());
+
+        super::detach_input();
+    }
+
+    #[test]
+    fn test_virtual_time() {
+        let _guard = INPUT_LOCK.lock();
+        let terminal = VirtualTerminal::new(5, 1);
+        let clock = terminal.enable_virtual_time();
+        terminal.attach_input();
+
+        terminal.schedule_event(Duration::from_millis(500), Event::Resize(1, 1));
+        terminal.schedule_event(Duration::from_millis(200), Event::Resize(2, 2));
+
+        // The time jumps to the end of the timeout
+        assert!(!event::poll(Duration::from_millis(100)).unwrap());
+        assert_eq!(clock.elapsed(), Duration::from_millis(100));
+
+        // And to the next event
+        assert!(event::poll(Duration::from_secs(3600)).unwrap());
+        assert_eq!(clock.elapsed(), Duration::from_millis(200));
+        assert_eq!(event::read().unwrap(), Event::Resize(2, 2));
+
+        clock.advance(Duration::from_millis(300));
+        assert!(event::poll(Duration::from_millis(0)).unwrap());
+        assert_eq!(event::read().unwrap(), Event::Resize(1, 1));
+        assert_eq!(clock.elapsed(), Duration::from_millis(500));
+
+        super::detach_input();
+    }
+
+    #[test]
+    fn test_scheduled_events_in_real_time() {
+        let _guard = INPUT_LOCK.lock();
+        let terminal = VirtualTerminal::new(5, 1);
+        terminal.attach_input();
+
+        terminal.schedule_event(Duration::from_secs(3600), Event::Resize(1, 1));
+        terminal.schedule_event(Duration::from_millis(0), Event::Resize(2, 2));
+
+        assert_eq!(event::read().unwrap(), Event::Resize(2, 2));
+        assert!(!event::poll(Duration::from_millis(10)).unwrap());
+
+        super::detach_input();
+    }
+}
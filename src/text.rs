@@ -0,0 +1,308 @@
+//! # Text
+//!
+//! Measuring, wrapping and truncating text to the columns of the terminal.
+//!
+//! The width of a text isn't its length: the wide characters (CJK, most emoji) take 2 columns,
+//! the combining marks none, and the escape sequences of styled text none either. The functions
+//! of this module count the columns the terminal displays, and keep the escape sequences in
+//! place.
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use crossterm::{terminal, text, Result};
+//!
+//! fn main() -> Result<()> {
+//!     let (columns, _) = terminal::size()?;
+//!     for line in text::wrap("The quick brown fox jumps over the lazy dog", columns as usize) {
+//!         println!("{}", line);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::mem;
+
+use unicode_width::UnicodeWidthChar;
+
+/// The character ending the truncated text.
+const ELLIPSIS: char = '…';
+
+/// A part of a text, an escape sequence or a character.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Segment<'a> {
+    Escape(&'a str),
+    Char(char),
+}
+
+impl Segment<'_> {
+    fn width(self) -> usize {
+        match self {
+            Segment::Escape(_) => 0,
+            Segment::Char(c) => c.width().unwrap_or(0),
+        }
+    }
+
+    fn is_whitespace(self) -> bool {
+        matches!(self, Segment::Char(c) if c.is_whitespace())
+    }
+
+    fn push_to(self, s: &mut String) {
+        match self {
+            Segment::Escape(escape) => s.push_str(escape),
+            Segment::Char(c) => s.push(c),
+        }
+    }
+}
+
+/// Returns the length of the escape sequence the text starts with.
+///
+/// The CSI sequences (`ESC [ ... final`), the OSC strings (`ESC ] ... BEL` or `ESC ] ... ESC \`,
+/// the hyperlinks) and the other two characters sequences are recognized.
+fn escape_len(text: &str) -> usize {
+    let bytes = text.as_bytes();
+    match bytes.get(1) {
+        Some(b'[') => bytes[2..]
+            .iter()
+            .position(|byte| (0x40..=0x7E).contains(byte))
+            .map_or(bytes.len(), |end| end + 3),
+        Some(b']') => (2..bytes.len())
+            .find_map(|i| match bytes[i] {
+                b'\x07' => Some(i + 1),
+                b'\x1B' if bytes.get(i + 1) == Some(&b'\\') => Some(i + 2),
+                _ => None,
+            })
+            .unwrap_or(bytes.len()),
+        // A character can follow, not only an ASCII one
+        Some(_) => 1 + text[1..].chars().next().map_or(0, char::len_utf8),
+        None => 1,
+    }
+}
+
+/// Splits the text into escape sequences and characters.
+fn segments(text: &str) -> impl Iterator<Item = Segment<'_>> {
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        let c = rest.chars().next()?;
+        let len = if c == '\x1B' {
+            escape_len(rest)
+        } else {
+            c.len_utf8()
+        };
+        let (segment, next) = rest.split_at(len);
+        rest = next;
+        Some(if c == '\x1B' {
+            Segment::Escape(segment)
+        } else {
+            Segment::Char(c)
+        })
+    })
+}
+
+/// Returns the number of columns the text takes, the escape sequences take none.
+///
+/// The control characters take none either, the text should be a single line.
+///
+/// # Examples
+///
+/// ```
+/// use crossterm::text;
+///
+/// assert_eq!(text::width("\x1B[1mbold\x1B[0m"), 4);
+/// assert_eq!(text::width("日本"), 4);
+/// ```
+pub fn width(text: &str) -> usize {
+    segments(text).map(Segment::width).sum()
+}
+
+/// Wraps the text into lines of at most `width` columns.
+///
+/// The lines are broken at whitespace, the whitespace at the breaks is removed. A word wider than
+/// a line is broken where it reaches the end of the line, a character never is (a wide character
+/// wider than the line takes a line of its own). `\n` always breaks the line.
+///
+/// The escape sequences are kept in place and take no columns. The styles carry over to the next
+/// lines when they are printed one after the other, like the original text would.
+///
+/// # Examples
+///
+/// ```
+/// use crossterm::text;
+///
+/// assert_eq!(text::wrap("Hello brave new world", 11), ["Hello brave", "new world"]);
+/// ```
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    for paragraph in text.split('\n') {
+        let paragraph = paragraph.strip_suffix('\r').unwrap_or(paragraph);
+        let mut line = String::new();
+        let mut line_width = 0;
+
+        for (whitespace, word) in words(paragraph) {
+            let whitespace_width = self::width(whitespace);
+            let word_width = self::width(word);
+
+            if line_width > 0 && line_width + whitespace_width + word_width > width {
+                lines.push(mem::take(&mut line));
+                line_width = 0;
+            } else {
+                line.push_str(whitespace);
+                line_width += whitespace_width;
+            }
+
+            if line_width + word_width <= width {
+                line.push_str(word);
+                line_width += word_width;
+                continue;
+            }
+
+            // Broken where it reaches the end of the line
+            for segment in segments(word) {
+                let segment_width = segment.width();
+                if segment_width > 0 && line_width > 0 && line_width + segment_width > width {
+                    lines.push(mem::take(&mut line));
+                    line_width = 0;
+                }
+                segment.push_to(&mut line);
+                line_width += segment_width;
+            }
+        }
+
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// Splits the text into the words and the whitespace before each of them, the whitespace at the
+/// end is dropped.
+fn words(text: &str) -> Vec<(&str, &str)> {
+    let mut words = Vec::new();
+    let mut word_start = 0;
+    let mut whitespace_start = 0;
+    let mut in_word = false;
+    let mut offset = 0;
+
+    for segment in segments(text) {
+        let len = match segment {
+            Segment::Escape(escape) => escape.len(),
+            Segment::Char(c) => c.len_utf8(),
+        };
+
+        match (in_word, segment.is_whitespace()) {
+            (true, true) => {
+                words.push((
+                    &text[whitespace_start..word_start],
+                    &text[word_start..offset],
+                ));
+                whitespace_start = offset;
+                in_word = false;
+            }
+            (false, false) => {
+                word_start = offset;
+                in_word = true;
+            }
+            _ => {}
+        }
+        offset += len;
+    }
+    if in_word {
+        words.push((&text[whitespace_start..word_start], &text[word_start..]));
+    }
+
+    words
+}
+
+/// Truncates the text to at most `width` columns, ending it with `…` if it was too wide.
+///
+/// The escape sequences are kept, the ones after the end as well: the styles are reset and the
+/// hyperlinks closed like in the original text.
+///
+/// # Examples
+///
+/// ```
+/// use crossterm::text;
+///
+/// assert_eq!(text::truncate_with_ellipsis("Hello world", 8), "Hello w…");
+/// assert_eq!(text::truncate_with_ellipsis("Hello", 8), "Hello");
+/// ```
+pub fn truncate_with_ellipsis(text: &str, width: usize) -> String {
+    if self::width(text) <= width {
+        return text.to_string();
+    }
+
+    let available = width.saturating_sub(1);
+    let mut truncated = String::with_capacity(text.len());
+    let mut truncated_width = 0;
+    let mut ended = false;
+
+    for segment in segments(text) {
+        match segment {
+            Segment::Escape(escape) => truncated.push_str(escape),
+            Segment::Char(c) if !ended => {
+                let segment_width = segment.width();
+                if truncated_width + segment_width > available {
+                    if width > 0 {
+                        truncated.push(ELLIPSIS);
+                    }
+                    ended = true;
+                } else {
+                    truncated.push(c);
+                    truncated_width += segment_width;
+                }
+            }
+            Segment::Char(_) => {}
+        }
+    }
+
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{truncate_with_ellipsis, width, wrap};
+
+    #[test]
+    fn test_width() {
+        assert_eq!(width("abc"), 3);
+        assert_eq!(width("日本語"), 6);
+        // e + combining acute accent
+        assert_eq!(width("e\u{301}"), 1);
+        assert_eq!(width("\x1B[38;5;9mred\x1B[0m"), 3);
+        assert_eq!(
+            width("\x1B]8;;https://example.com\x1B\\link\x1B]8;;\x07"),
+            4
+        );
+    }
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(wrap("The quick brown fox", 10), ["The quick", "brown fox"]);
+        assert_eq!(wrap("a  b\nc", 10), ["a  b", "c"]);
+        assert_eq!(wrap("abcdefgh ij", 3), ["abc", "def", "gh", "ij"]);
+        assert_eq!(wrap("", 10), [""]);
+        // The wide characters aren't split
+        assert_eq!(wrap("日本語", 5), ["日本", "語"]);
+        assert_eq!(
+            wrap("\x1B[1mbold text\x1B[0m here", 9),
+            ["\x1B[1mbold text\x1B[0m", "here"]
+        );
+    }
+
+    #[test]
+    fn test_truncate_with_ellipsis() {
+        assert_eq!(truncate_with_ellipsis("Hello world", 5), "Hell…");
+        assert_eq!(truncate_with_ellipsis("Hello", 5), "Hello");
+        assert_eq!(truncate_with_ellipsis("日本語", 4), "日…");
+        assert_eq!(truncate_with_ellipsis("Hello", 0), "");
+        assert_eq!(
+            truncate_with_ellipsis("\x1B[1mHello\x1B[0m", 3),
+            "\x1B[1mHe…\x1B[0m"
+        );
+        assert_eq!(
+            truncate_with_ellipsis("e\u{301}e\u{301}e\u{301}", 2),
+            "e\u{301}…"
+        );
+    }
+}
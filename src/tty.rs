@@ -2,7 +2,13 @@
 //! something is a terminal teletype or not.
 //! This module defines the IsTty trait and the is_tty method to
 //! return true if the item represents a terminal.
+//!
+//! It also defines the [`Tty`](trait.Tty.html) trait, for the terminals which aren't the terminal
+//! of this process: a serial port, a memory console, the debug console of a firmware... The
+//! commands, the styling and the input parser work with them as they do with the terminal, see
+//! [`DumbDevice`](struct.DumbDevice.html).
 
+use std::io;
 #[cfg(unix)]
 use std::os::unix::io::AsRawFd;
 #[cfg(windows)]
@@ -11,6 +17,18 @@ use std::os::windows::io::AsRawHandle;
 #[cfg(windows)]
 use winapi::um::consoleapi::GetConsoleMode;
 
+use crate::{
+    command::write_command_ansi,
+    event::{Event, EventParser},
+    Command, Result,
+};
+
+/// The size of a device which wasn't given one, the size of a VT100.
+const DEFAULT_SIZE: (u16, u16) = (80, 24);
+
+/// The number of bytes read from a device at once.
+const READ_BUFFER_SIZE: usize = 1024;
+
 /// Adds the `is_tty` method to types that might represent a terminal
 ///
 /// ```rust
@@ -34,6 +52,15 @@ impl<S: AsRawFd> IsTty for S {
     }
 }
 
+/// In the browser, nothing is a terminal teletype, the terminal is connected with the
+/// [`wasm`](../wasm/index.html) module.
+#[cfg(target_arch = "wasm32")]
+impl<S> IsTty for S {
+    fn is_tty(&self) -> bool {
+        false
+    }
+}
+
 /// On windows, `GetConsoleMode` will return true if we are in a terminal.
 /// Otherwise false.
 #[cfg(windows)]
@@ -44,3 +71,295 @@ impl<S: AsRawHandle> IsTty for S {
         ok == 1
     }
 }
+
+/// A terminal device speaking VT100 over a byte stream, like a serial port.
+///
+/// The terminal of this process is driven with the functions of the
+/// [`terminal`](../terminal/index.html) module, a device answers the same questions through this
+/// trait. The commands are written with [`queue_command`](#method.queue_command) or
+/// [`execute_command`](#method.execute_command).
+pub trait Tty: io::Read + io::Write {
+    /// Switches the device to the raw mode, see
+    /// [`terminal::enable_raw_mode`](../terminal/fn.enable_raw_mode.html).
+    fn enable_raw_mode(&mut self) -> Result<()>;
+
+    /// Switches the device back to the mode it was in before the raw mode.
+    fn disable_raw_mode(&mut self) -> Result<()>;
+
+    /// Returns whether the raw mode is enabled.
+    fn is_raw_mode_enabled(&self) -> bool;
+
+    /// Returns the size of the device `(columns, rows)`.
+    fn size(&mut self) -> Result<(u16, u16)>;
+
+    /// Queues the command, it's written when the device is flushed.
+    ///
+    /// Unlike [`queue`](../trait.QueueableCommand.html#tymethod.queue), the command is always
+    /// written as an escape sequence: the WinAPI calls used on old Windows versions would change
+    /// the console of this process, not the device. With the `validate` feature, the command is
+    /// checked against the size of the device.
+    fn queue_command(&mut self, command: impl Command) -> Result<&mut Self>
+    where
+        Self: Sized,
+    {
+        #[cfg(feature = "validate")]
+        command.validate(self.size()?)?;
+
+        write_command_ansi(self, command)?;
+        Ok(self)
+    }
+
+    /// Writes the command and flushes the device, see [`queue_command`](#method.queue_command).
+    fn execute_command(&mut self, command: impl Command) -> Result<&mut Self>
+    where
+        Self: Sized,
+    {
+        self.queue_command(command)?;
+        self.flush()?;
+        Ok(self)
+    }
+}
+
+/// A device which only carries bytes, like most serial lines and memory consoles.
+///
+/// There's no line discipline to switch: the raw mode is only tracked, the bytes always go
+/// through as they are. The size can't be asked either, it's the one given to
+/// [`set_size`](#method.set_size), 80x24 by default.
+///
+/// The input is parsed into events by [`read_event`](#method.read_event). The bytes read directly
+/// with `io::Read` don't go through the parser.
+///
+/// # Examples
+///
+/// A memory console, the input is given at once and the output is collected:
+///
+/// ```
+/// use crossterm::{
+///     cursor::MoveTo,
+///     event::{Event, KeyCode},
+///     style::Print,
+///     tty::{DumbDevice, Tty},
+/// };
+///
+/// let input: &[u8] = b"\x1B[A";
+/// let mut device = DumbDevice::new(input, Vec::new());
+///
+/// device
+///     .execute_command(MoveTo(0, 0))
+///     .unwrap()
+///     .execute_command(Print("ready"))
+///     .unwrap();
+/// assert_eq!(device.read_event().unwrap(), Event::Key(KeyCode::Up.into()));
+///
+/// let (_, output) = device.into_inner();
+/// assert_eq!(output, b"\x1B[1;1Hready");
+/// ```
+#[derive(Debug)]
+pub struct DumbDevice<R, W> {
+    reader: R,
+    writer: W,
+    size: (u16, u16),
+    raw_mode: bool,
+    parser: EventParser,
+}
+
+impl<R: io::Read, W: io::Write> DumbDevice<R, W> {
+    /// Creates a device reading its input from `reader` and writing its output to `writer`.
+    ///
+    /// A serial port handle which is both is given twice, its `try_clone` for example.
+    pub fn new(reader: R, writer: W) -> DumbDevice<R, W> {
+        DumbDevice {
+            reader,
+            writer,
+            size: DEFAULT_SIZE,
+            raw_mode: false,
+            parser: EventParser::new(),
+        }
+    }
+
+    /// Sets the size of the device, returned by [`size`](trait.Tty.html#tymethod.size).
+    pub fn set_size(&mut self, columns: u16, rows: u16) {
+        self.size = (columns, rows);
+    }
+
+    /// Sets whether the 8-bit C1 controls of the device are recognized, see
+    /// [`EventParser::set_c1_controls`](../event/struct.EventParser.html#method.set_c1_controls).
+    pub fn set_c1_controls(&mut self, enabled: bool) {
+        self.parser.set_c1_controls(enabled);
+    }
+
+    /// Reads the next event, waits until there's one.
+    ///
+    /// An escape sequence can arrive in several reads on a slow line, an `ESC` is kept until the
+    /// next bytes. It's the Esc key if the reader fails with `io::ErrorKind::TimedOut` or
+    /// `io::ErrorKind::WouldBlock` first, give the reader a timeout like the
+    /// [escape timeout](../event/fn.set_escape_timeout.html) for a lone Esc key to be read. The
+    /// error is returned if there was no event pending.
+    ///
+    /// Fails with `io::ErrorKind::BrokenPipe` at the end of the input.
+    pub fn read_event(&mut self) -> Result<Event> {
+        let mut buffer = [0u8; READ_BUFFER_SIZE];
+
+        loop {
+            if let Some(event) = self.parser.next() {
+                return Ok(event);
+            }
+
+            match self.reader.read(&mut buffer) {
+                Ok(0) => {
+                    self.parser.flush();
+                    return self.parser.next().ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::BrokenPipe, "The device was closed")
+                    });
+                }
+                Ok(count) => self.parser.advance(&buffer[..count], true),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e)
+                    if e.kind() == io::ErrorKind::TimedOut
+                        || e.kind() == io::ErrorKind::WouldBlock =>
+                {
+                    self.parser.flush();
+                    return self.parser.next().ok_or(e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Returns the reader and the writer.
+    pub fn into_inner(self) -> (R, W) {
+        (self.reader, self.writer)
+    }
+}
+
+impl<R: io::Read, W> io::Read for DumbDevice<R, W> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+
+impl<R, W: io::Write> io::Write for DumbDevice<R, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl<R: io::Read, W: io::Write> Tty for DumbDevice<R, W> {
+    fn enable_raw_mode(&mut self) -> Result<()> {
+        self.raw_mode = true;
+        Ok(())
+    }
+
+    fn disable_raw_mode(&mut self) -> Result<()> {
+        self.raw_mode = false;
+        Ok(())
+    }
+
+    fn is_raw_mode_enabled(&self) -> bool {
+        self.raw_mode
+    }
+
+    fn size(&mut self) -> Result<(u16, u16)> {
+        Ok(self.size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::VecDeque, io};
+
+    use super::{DumbDevice, Tty};
+    use crate::{
+        event::{Event, KeyCode, KeyEvent, KeyModifiers},
+        style::{Color, Print, SetForegroundColor},
+    };
+
+    /// A reader returning the given reads one after the other, then the end of the input.
+    struct Reads(VecDeque<io::Result<&'static [u8]>>);
+
+    impl io::Read for Reads {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.0.pop_front() {
+                Some(Ok(bytes)) => {
+                    buf[..bytes.len()].copy_from_slice(bytes);
+                    Ok(bytes.len())
+                }
+                Some(Err(e)) => Err(e),
+                None => Ok(0),
+            }
+        }
+    }
+
+    fn key(code: KeyCode) -> Event {
+        Event::Key(code.into())
+    }
+
+    #[test]
+    fn test_commands_are_written_as_escape_sequences() {
+        let input: &[u8] = b"";
+        let mut device = DumbDevice::new(input, Vec::new());
+
+        device
+            .queue_command(SetForegroundColor(Color::Red))
+            .unwrap()
+            .execute_command(Print("a"))
+            .unwrap();
+
+        assert_eq!(device.into_inner().1, b"\x1B[38;5;9ma");
+    }
+
+    #[test]
+    fn test_read_events_until_the_end_of_the_input() {
+        let reads = Reads(vec![Ok(&b"a\x1B"[..]), Ok(b"[B\x03"), Ok(b"\x1B")].into());
+        let mut device = DumbDevice::new(reads, io::sink());
+
+        assert_eq!(device.read_event().unwrap(), key(KeyCode::Char('a')));
+        // The sequence split between two reads
+        assert_eq!(device.read_event().unwrap(), key(KeyCode::Down));
+        assert_eq!(
+            device.read_event().unwrap(),
+            Event::Key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL))
+        );
+        // The `ESC` at the end of the input
+        assert_eq!(device.read_event().unwrap(), key(KeyCode::Esc));
+        assert_eq!(
+            device.read_event().unwrap_err().kind(),
+            io::ErrorKind::BrokenPipe
+        );
+    }
+
+    #[test]
+    fn test_esc_key_is_read_when_the_reader_times_out() {
+        let reads = Reads(
+            vec![
+                Ok(&b"\x1B"[..]),
+                Err(io::ErrorKind::TimedOut.into()),
+                Err(io::ErrorKind::TimedOut.into()),
+            ]
+            .into(),
+        );
+        let mut device = DumbDevice::new(reads, io::sink());
+
+        assert_eq!(device.read_event().unwrap(), key(KeyCode::Esc));
+        assert_eq!(
+            device.read_event().unwrap_err().kind(),
+            io::ErrorKind::TimedOut
+        );
+    }
+
+    #[test]
+    fn test_raw_mode_and_size_are_tracked() {
+        let mut device = DumbDevice::new(io::empty(), io::sink());
+        assert_eq!(device.size().unwrap(), (80, 24));
+        assert!(!device.is_raw_mode_enabled());
+
+        device.set_size(132, 43);
+        device.enable_raw_mode().unwrap();
+        assert_eq!(device.size().unwrap(), (132, 43));
+        assert!(device.is_raw_mode_enabled());
+    }
+}
@@ -0,0 +1,162 @@
+//! # WebAssembly
+//!
+//! The `wasm` module connects crossterm to a terminal running in the browser, like
+//! [xterm.js](https://xtermjs.org/). There's no terminal device in the browser, the JavaScript
+//! side of the application carries the bytes between the terminal and crossterm:
+//!
+//! * The input of the terminal is given to [`push_input`](fn.push_input.html), it's parsed into
+//!   events like on UNIX.
+//! * The commands are written to [`output`](fn.output.html), which hands the bytes to the
+//!   function given to [`set_output`](fn.set_output.html).
+//! * The size of the terminal is given to [`set_size`](fn.set_size.html), it produces the resize
+//!   events.
+//!
+//! The browser runs the application and delivers the input on the same thread, nothing can wait
+//! for the input: [`poll`](../event/fn.poll.html) returns `false` at once when no input is
+//! pending, and [`read`](../event/fn.read.html) fails with `io::ErrorKind::WouldBlock`. The
+//! application reads the events when it's called back with new input. For the same reason,
+//! nothing that waits for a reply of the terminal is available, like
+//! [`cursor::position`](../cursor/fn.position.html).
+//!
+//! The `event-stream` feature isn't supported.
+//!
+//! Only available on `wasm32`.
+//!
+//! ## Examples
+//!
+//! With `wasm-bindgen`, the JavaScript side calls `attach` with the xterm.js `Terminal`:
+//!
+//! ```ignore
+//! use std::time::Duration;
+//!
+//! use crossterm::{event, execute, style::Print, wasm};
+//! use wasm_bindgen::prelude::*;
+//!
+//! #[wasm_bindgen]
+//! extern "C" {
+//!     type Terminal;
+//!
+//!     #[wasm_bindgen(method)]
+//!     fn write(this: &Terminal, data: &[u8]);
+//! }
+//!
+//! #[wasm_bindgen]
+//! pub fn attach(terminal: Terminal, columns: u16, rows: u16) {
+//!     wasm::set_output(move |bytes| terminal.write(bytes));
+//!     wasm::set_size(columns, rows);
+//!     execute!(wasm::output(), Print("Hello from crossterm\r\n")).unwrap();
+//! }
+//!
+//! /// Called by `terminal.onData`.
+//! #[wasm_bindgen]
+//! pub fn on_data(data: &str) {
+//!     wasm::push_input(data.as_bytes());
+//!
+//!     while event::poll(Duration::from_secs(0)).unwrap() {
+//!         let event = event::read().unwrap();
+//!         execute!(wasm::output(), Print(format!("{:?}\r\n", event))).unwrap();
+//!     }
+//! }
+//! ```
+
+use std::io;
+
+use parking_lot::Mutex;
+
+/// Writes the output to the terminal.
+type OutputFn = Box<dyn FnMut(&[u8]) + Send>;
+
+static OUTPUT: Mutex<Option<OutputFn>> = parking_lot::const_mutex(None);
+/// The input which wasn't read by the event source yet.
+static INPUT: Mutex<Vec<u8>> = parking_lot::const_mutex(Vec::new());
+static SIZE: Mutex<TerminalSize> = parking_lot::const_mutex(TerminalSize {
+    current: None,
+    resized: false,
+});
+
+struct TerminalSize {
+    current: Option<(u16, u16)>,
+    /// Whether the size changed since the event source produced the last resize event.
+    resized: bool,
+}
+
+/// Sets the function writing the output to the terminal, `Terminal.write` of xterm.js for
+/// example.
+///
+/// The bytes are ANSI escape sequences and UTF-8 text, they can be split anywhere.
+pub fn set_output(write: impl FnMut(&[u8]) + Send + 'static) {
+    *OUTPUT.lock() = Some(Box::new(write));
+}
+
+/// Gives the input of the terminal to crossterm, the data of `Terminal.onData` of xterm.js for
+/// example.
+///
+/// The input is parsed when the events are polled. The escape sequences can't be split between
+/// two calls, a lone `ESC` at the end is the Esc key.
+pub fn push_input(bytes: &[u8]) {
+    INPUT.lock().extend_from_slice(bytes);
+}
+
+/// Sets the size of the terminal `(columns, rows)`, from `Terminal.onResize` of xterm.js for
+/// example.
+///
+/// It's returned by [`terminal::size`](../terminal/fn.size.html), a resize event is produced
+/// when it changes.
+pub fn set_size(columns: u16, rows: u16) {
+    let mut size = SIZE.lock();
+    if size.current != Some((columns, rows)) {
+        size.resized = size.current.is_some();
+        size.current = Some((columns, rows));
+    }
+}
+
+/// A writer writing to the terminal with the function given to
+/// [`set_output`](fn.set_output.html), returned by [`output`](fn.output.html).
+///
+/// Writing fails with `io::ErrorKind::NotConnected` if no function was set.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Output;
+
+/// Returns a writer writing to the terminal, the `stdout()` of the browser.
+pub fn output() -> Output {
+    Output
+}
+
+impl io::Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match OUTPUT.lock().as_mut() {
+            Some(write) => {
+                write(buf);
+                Ok(buf.len())
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::NotConnected,
+                "No output was set with `crossterm::wasm::set_output`",
+            )),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Returns the input pushed since the last call.
+pub(crate) fn take_input() -> Vec<u8> {
+    std::mem::take(&mut *INPUT.lock())
+}
+
+/// Returns the new size if it changed since the last call.
+pub(crate) fn take_resize() -> Option<(u16, u16)> {
+    let mut size = SIZE.lock();
+    if std::mem::replace(&mut size.resized, false) {
+        size.current
+    } else {
+        None
+    }
+}
+
+/// Returns the size set with `set_size`.
+pub(crate) fn size() -> Option<(u16, u16)> {
+    SIZE.lock().current
+}